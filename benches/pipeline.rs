@@ -0,0 +1,114 @@
+mod two_pass;
+
+use bytes::BytesMut;
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use simple_redis::{serve, Backend, BulkString, RespArray, RespEncode, RespFrame, RespParser};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::runtime::Runtime;
+
+const COMMANDS: usize = 10_000;
+// what a socket read hands to the codec at a time
+const READ_SIZE: usize = 4096;
+
+fn command(args: impl IntoIterator<Item = String>) -> Vec<u8> {
+    RespArray::new(
+        args.into_iter()
+            .map(|a| BulkString::new(a).into())
+            .collect::<Vec<RespFrame>>(),
+    )
+    .encode()
+}
+
+// writes the whole batch at once and reads until every reply came back, the writes run on their
+// own task so neither side stalls on a full socket buffer
+async fn round_trip(stream: &mut TcpStream, requests: &[u8], reply_len: usize) {
+    let (mut reader, mut writer) = stream.split();
+    let write = async {
+        writer.write_all(requests).await.unwrap();
+    };
+    let read = async {
+        let mut buf = vec![0u8; 64 * 1024];
+        let mut received = 0;
+        while received < reply_len {
+            let n = reader.read(&mut buf).await.unwrap();
+            assert!(n > 0, "server closed the connection");
+            received += n;
+        }
+        assert_eq!(received, reply_len);
+    };
+    tokio::join!(write, read);
+}
+
+// the requests of a batch as the server decodes them, read after read
+fn decode_batch(requests: &[u8], mut decode: impl FnMut(&mut BytesMut) -> Option<RespFrame>) {
+    let mut buf = BytesMut::with_capacity(READ_SIZE);
+    let mut frames = 0;
+    for chunk in requests.chunks(READ_SIZE) {
+        buf.extend_from_slice(chunk);
+        while let Some(frame) = decode(&mut buf) {
+            black_box(frame);
+            frames += 1;
+        }
+    }
+    assert_eq!(frames, COMMANDS);
+}
+
+fn bench_pipeline(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let mut stream = rt.block_on(async {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve(listener, Backend::new()));
+        TcpStream::connect(addr).await.unwrap()
+    });
+
+    let mut group = c.benchmark_group("pipeline");
+    group.throughput(Throughput::Elements(COMMANDS as u64));
+
+    let sets = (0..COMMANDS)
+        .flat_map(|i| command(["SET".into(), format!("key:{}", i), format!("value:{}", i)]))
+        .collect::<Vec<u8>>();
+    let ok_len = b"+OK\r\n".len() * COMMANDS;
+    group.bench_function("10k_set", |b| {
+        b.iter(|| rt.block_on(round_trip(&mut stream, &sets, ok_len)))
+    });
+
+    let gets = (0..COMMANDS)
+        .flat_map(|i| command(["GET".into(), format!("key:{}", i)]))
+        .collect::<Vec<u8>>();
+    let values_len = (0..COMMANDS)
+        .map(|i| BulkString::new(format!("value:{}", i)).encode().len())
+        .sum();
+    group.bench_function("10k_get", |b| {
+        b.iter(|| rt.block_on(round_trip(&mut stream, &gets, values_len)))
+    });
+
+    let pings = command(["PING".into()]).repeat(COMMANDS);
+    let pong_len = b"+PONG\r\n".len() * COMMANDS;
+    group.bench_function("10k_ping", |b| {
+        b.iter(|| rt.block_on(round_trip(&mut stream, &pings, pong_len)))
+    });
+
+    group.finish();
+
+    // the share of the round trips spent decoding the requests, against the two pass decoder
+    // RespParser replaced as the baseline
+    let mut group = c.benchmark_group("pipeline_decode");
+    group.throughput(Throughput::Elements(COMMANDS as u64));
+    for (name, requests) in [("10k_set", &sets), ("10k_get", &gets), ("10k_ping", &pings)] {
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                let mut parser = RespParser::default();
+                decode_batch(requests, |buf| parser.parse(buf).unwrap())
+            })
+        });
+        group.bench_function(format!("{}_two_pass", name), |b| {
+            b.iter(|| decode_batch(requests, |buf| two_pass::decode(buf).unwrap()))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_pipeline);
+criterion_main!(benches);
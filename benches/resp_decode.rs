@@ -0,0 +1,88 @@
+mod two_pass;
+
+use bytes::BytesMut;
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use simple_redis::{BulkString, RespArray, RespEncode, RespFrame, RespParser};
+
+// what a socket read hands to the codec at a time
+const READ_SIZE: usize = 4096;
+
+fn command(args: impl IntoIterator<Item = String>) -> Vec<u8> {
+    RespArray::new(
+        args.into_iter()
+            .map(|a| BulkString::new(a).into())
+            .collect::<Vec<RespFrame>>(),
+    )
+    .encode()
+}
+
+// feeds the data in socket sized reads, decoding after every read like the codec does
+fn decode_stream(data: &[u8]) -> usize {
+    let mut parser = RespParser::default();
+    decode_stream_with(data, |buf| parser.parse(buf).unwrap())
+}
+
+// the same with the two pass decoder RespParser replaced, the baseline
+fn decode_stream_two_pass(data: &[u8]) -> usize {
+    decode_stream_with(data, |buf| two_pass::decode(buf).unwrap())
+}
+
+fn decode_stream_with(
+    data: &[u8],
+    mut decode: impl FnMut(&mut BytesMut) -> Option<RespFrame>,
+) -> usize {
+    let mut buf = BytesMut::with_capacity(READ_SIZE);
+    let mut frames = 0;
+    for chunk in data.chunks(READ_SIZE) {
+        buf.extend_from_slice(chunk);
+        while let Some(frame) = decode(&mut buf) {
+            black_box(frame);
+            frames += 1;
+        }
+    }
+    frames
+}
+
+fn bench_decode(c: &mut Criterion) {
+    let mut group = c.benchmark_group("decode");
+
+    // a pipelined batch of small commands
+    let pipeline = (0..10_000)
+        .flat_map(|i| command(["SET".into(), format!("key:{}", i), format!("value:{}", i)]))
+        .collect::<Vec<u8>>();
+    group.throughput(Throughput::Bytes(pipeline.len() as u64));
+    group.bench_function("pipeline_10k_set", |b| {
+        b.iter(|| assert_eq!(decode_stream(&pipeline), 10_000))
+    });
+    group.bench_function("pipeline_10k_set_two_pass", |b| {
+        b.iter(|| assert_eq!(decode_stream_two_pass(&pipeline), 10_000))
+    });
+
+    // a single command with many arguments, spread over many reads
+    let args = ["RPUSH".to_string(), "list".to_string()]
+        .into_iter()
+        .chain((0..50_000).map(|i| format!("element:{}", i)));
+    let wide = command(args);
+    group.throughput(Throughput::Bytes(wide.len() as u64));
+    group.bench_function("rpush_50k_args", |b| {
+        b.iter(|| assert_eq!(decode_stream(&wide), 1))
+    });
+    group.bench_function("rpush_50k_args_two_pass", |b| {
+        b.iter(|| assert_eq!(decode_stream_two_pass(&wide), 1))
+    });
+
+    // a large value, its bytes end up in the frame without being copied
+    let large = command(["SET".into(), "key".into(), "x".repeat(4 << 20)]);
+    group.throughput(Throughput::Bytes(large.len() as u64));
+    group.bench_function("set_4mb_value", |b| {
+        b.iter(|| assert_eq!(decode_stream(&large), 1))
+    });
+    group.bench_function("set_4mb_value_two_pass", |b| {
+        b.iter(|| assert_eq!(decode_stream_two_pass(&large), 1))
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_decode);
+criterion_main!(benches);
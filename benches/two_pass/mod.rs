@@ -0,0 +1,91 @@
+// the decoder RespParser replaced, kept as the baseline of the benches: a frame is walked once to
+// find out whether it is complete, calc_total_length, then a second time to decode it, and both
+// walks start over from the beginning of the frame on every read; bulk strings are copied out of
+// the buffer. Only what the benches send is supported, arrays of bulk strings
+use bytes::{Buf, BytesMut};
+use simple_redis::{BulkString, RespArray, RespFrame};
+
+const CRLF_LEN: usize = 2;
+
+#[derive(Debug)]
+pub enum Error {
+    NotComplete,
+    Invalid,
+}
+
+// decodes the frame at the start of the buffer, Ok(None) until it is complete
+pub fn decode(buf: &mut BytesMut) -> Result<Option<RespFrame>, Error> {
+    match decode_frame(buf) {
+        Ok(frame) => Ok(Some(frame)),
+        Err(Error::NotComplete) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+fn decode_frame(buf: &mut BytesMut) -> Result<RespFrame, Error> {
+    match buf.first() {
+        Some(b'*') => {
+            let (end, len) = parse_length(buf, "*")?;
+            let total_len = calc_total_length(buf, end, len)?;
+            if buf.len() < total_len {
+                return Err(Error::NotComplete);
+            }
+            buf.advance(end + CRLF_LEN);
+            let mut frames = Vec::with_capacity(len);
+            for _ in 0..len {
+                frames.push(decode_frame(buf)?);
+            }
+            Ok(RespArray::new(frames).into())
+        }
+        Some(b'$') => {
+            let (end, len) = parse_length(buf, "$")?;
+            if buf[end + CRLF_LEN..].len() < len + CRLF_LEN {
+                return Err(Error::NotComplete);
+            }
+            buf.advance(end + CRLF_LEN);
+            let data = buf.split_to(len + CRLF_LEN);
+            Ok(BulkString::new(data[..len].to_vec()).into())
+        }
+        Some(_) => Err(Error::Invalid),
+        None => Err(Error::NotComplete),
+    }
+}
+
+fn expect_length(buf: &[u8]) -> Result<usize, Error> {
+    match buf.first() {
+        Some(b'*') => {
+            let (end, len) = parse_length(buf, "*")?;
+            calc_total_length(buf, end, len)
+        }
+        Some(b'$') => {
+            let (end, len) = parse_length(buf, "$")?;
+            Ok(end + len + CRLF_LEN + CRLF_LEN)
+        }
+        Some(_) => Err(Error::Invalid),
+        None => Err(Error::NotComplete),
+    }
+}
+
+fn calc_total_length(buf: &[u8], end: usize, len: usize) -> Result<usize, Error> {
+    let mut total = end + CRLF_LEN;
+    let mut data = buf.get(total..).ok_or(Error::NotComplete)?;
+    for _ in 0..len {
+        let len = expect_length(data)?;
+        data = data.get(len..).ok_or(Error::NotComplete)?;
+        total += len;
+    }
+    Ok(total)
+}
+
+fn parse_length(buf: &[u8], prefix: &str) -> Result<(usize, usize), Error> {
+    if buf.len() < 3 {
+        return Err(Error::NotComplete);
+    }
+    let end = find_crlf(buf).ok_or(Error::NotComplete)?;
+    let s = String::from_utf8_lossy(&buf[prefix.len()..end]);
+    Ok((end, s.parse().map_err(|_| Error::Invalid)?))
+}
+
+fn find_crlf(buf: &[u8]) -> Option<usize> {
+    (1..buf.len() - 1).find(|&i| buf[i] == b'\r' && buf[i + 1] == b'\n')
+}
@@ -0,0 +1,381 @@
+use crate::backend::snapshot::KeyspaceSnapshot;
+use crate::backend::{Backend, BackendError, RedisValue};
+use crate::config::AppendFsync;
+use crate::resp::{BulkString, RespArray, RespEncode, RespFrame};
+use bytes::Bytes;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+use tracing::{info, warn};
+
+// how often the log is flushed to the disk with appendfsync everysec
+const AOF_FSYNC_INTERVAL: Duration = Duration::from_secs(1);
+// upper bound of elements per command in a rewritten log, so no command gets huge
+const AOF_REWRITE_ITEMS_PER_CMD: usize = 64;
+
+#[derive(Debug, Default)]
+pub struct AofState {
+    // None until the log is opened at startup
+    writer: Mutex<Option<AofWriter>>,
+    // writes hold it from execution until they are logged and fed to the replicas, so both see
+    // them in the order they ran; a rewrite holds it to snapshot the keyspace, so every write is
+    // either in the snapshot or in the rewrite buffer
+    barrier: Mutex<()>,
+    rewrite_in_progress: AtomicBool,
+    // the offset of the replication stream fsynced by the last fsync
+    fsynced_offset: AtomicU64,
+}
+
+#[derive(Debug)]
+struct AofWriter {
+    file: File,
+    // writes since the last fsync
+    pending_fsync: bool,
+    // commands logged while a rewrite runs, appended to the new log once it is complete
+    rewrite_buf: Option<Vec<u8>>,
+}
+
+fn open_append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+impl Backend {
+    pub fn open_aof(&self) -> Result<(), BackendError> {
+        let file = open_append(&self.config.aof_path()).map_err(|e| {
+            BackendError::Persistence(format!("can't open the append-only file: {}", e))
+        })?;
+        *self.aof.writer.lock().unwrap() = Some(AofWriter {
+            file,
+            pending_fsync: false,
+            rewrite_buf: None,
+        });
+        Ok(())
+    }
+
+    // one write at a time, also held by the snapshots that must not miss nor repeat any of them
+    pub(crate) fn write_barrier(&self) -> MutexGuard<'_, ()> {
+        self.aof.barrier.lock().unwrap()
+    }
+
+    // appends a command to the log, a no-op until the log is opened
+    pub fn feed_aof(&self, command: RespArray) {
+        self.append_aof(&command.encode());
+    }
+
+    pub(crate) fn append_aof(&self, encoded: &[u8]) {
+        let mut writer = self.aof.writer.lock().unwrap();
+        let Some(writer) = writer.as_mut() else {
+            return;
+        };
+        if let Some(buf) = writer.rewrite_buf.as_mut() {
+            buf.extend_from_slice(encoded);
+        }
+        let ret = writer.file.write_all(encoded).and_then(|_| {
+            if self.config.appendfsync == AppendFsync::Always {
+                writer.file.sync_data()
+            } else {
+                writer.pending_fsync = true;
+                Ok(())
+            }
+        });
+        if let Err(e) = ret {
+            warn!(
+                "[Simple-redis-server]error writing the append-only file: {}",
+                e
+            );
+        }
+    }
+
+    pub fn fsync_aof(&self) {
+        // the writes up to this offset of the stream were appended to the log before it
+        let offset = self.master_repl_offset();
+        let mut writer = self.aof.writer.lock().unwrap();
+        let Some(writer) = writer.as_mut() else {
+            return;
+        };
+        if writer.pending_fsync {
+            if let Err(e) = writer.file.sync_data() {
+                warn!(
+                    "[Simple-redis-server]error syncing the append-only file: {}",
+                    e
+                );
+                return;
+            }
+            writer.pending_fsync = false;
+        }
+        self.aof.fsynced_offset.store(offset, Ordering::Release);
+        self.notify_progress();
+    }
+
+    // the offset of the replication stream up to which the writes are on the disk, 0 without a
+    // log. With appendfsync always they are as soon as they are logged, with no it is up to the
+    // operating system so they count once written
+    pub fn aof_fsynced_offset(&self) -> u64 {
+        if self.aof.writer.lock().unwrap().is_none() {
+            return 0;
+        }
+        match self.config.appendfsync {
+            AppendFsync::Always | AppendFsync::No => self.master_repl_offset(),
+            AppendFsync::EverySec => self.aof.fsynced_offset.load(Ordering::Acquire),
+        }
+    }
+
+    // replaces the log with the shortest command sequence rebuilding the current keyspace
+    pub fn rewrite_aof(&self) -> Result<(), BackendError> {
+        if self.aof.rewrite_in_progress.swap(true, Ordering::AcqRel) {
+            return Err(BackendError::RewriteInProgress);
+        }
+        let snapshot = self.start_rewrite();
+        let ret = self.finish_rewrite(snapshot);
+        self.aof.rewrite_in_progress.store(false, Ordering::Release);
+        ret
+    }
+
+    // snapshots the keyspace synchronously and writes the new log on a blocking thread
+    pub fn bgrewrite_aof(&self) -> Result<(), BackendError> {
+        if self.aof.rewrite_in_progress.swap(true, Ordering::AcqRel) {
+            return Err(BackendError::RewriteInProgress);
+        }
+        let snapshot = self.start_rewrite();
+        let backend = self.clone();
+        tokio::task::spawn_blocking(move || {
+            if let Err(e) = backend.finish_rewrite(snapshot) {
+                warn!("[Simple-redis-server]background AOF rewrite failed: {}", e);
+            }
+            backend
+                .aof
+                .rewrite_in_progress
+                .store(false, Ordering::Release);
+        });
+        Ok(())
+    }
+
+    pub fn aof_rewrite_in_progress(&self) -> bool {
+        self.aof.rewrite_in_progress.load(Ordering::Acquire)
+    }
+
+    fn start_rewrite(&self) -> KeyspaceSnapshot {
+        let _barrier = self.write_barrier();
+        if let Some(writer) = self.aof.writer.lock().unwrap().as_mut() {
+            writer.rewrite_buf = Some(Vec::new());
+        }
+        self.clone_keyspace()
+    }
+
+    fn finish_rewrite(&self, snapshot: KeyspaceSnapshot) -> Result<(), BackendError> {
+        let tmp = self
+            .config
+            .dir
+            .join(format!("temp-rewriteaof-{}.aof", std::process::id()));
+        if let Err(e) = self.install_rewrite(&tmp, snapshot) {
+            if let Some(writer) = self.aof.writer.lock().unwrap().as_mut() {
+                writer.rewrite_buf = None;
+            }
+            let _ = fs::remove_file(&tmp);
+            return Err(BackendError::Persistence(format!(
+                "failed rewriting the append-only file: {}",
+                e
+            )));
+        }
+        info!("[Simple-redis-server]append-only file rewritten");
+        Ok(())
+    }
+
+    fn install_rewrite(&self, tmp: &Path, snapshot: KeyspaceSnapshot) -> io::Result<()> {
+        let path = self.config.aof_path();
+        let mut file = File::create(tmp)?;
+        for library in snapshot.libraries {
+            let load = [Bytes::from("FUNCTION"), Bytes::from("LOAD")];
+            let code = Bytes::from(library.code.clone());
+            file.write_all(&command(load.into_iter().chain([code])).encode())?;
+        }
+        for (key, value, expire) in snapshot.keys {
+            for command in rewrite_commands(key, value, expire) {
+                file.write_all(&command.encode())?;
+            }
+        }
+        // from here on the log is blocked until the new file replaced the old one
+        let mut writer = self.aof.writer.lock().unwrap();
+        if let Some(buf) = writer.as_mut().and_then(|w| w.rewrite_buf.take()) {
+            file.write_all(&buf)?;
+        }
+        file.sync_all()?;
+        fs::rename(tmp, &path)?;
+        if let Some(writer) = writer.as_mut() {
+            writer.file = open_append(&path)?;
+            writer.pending_fsync = false;
+        }
+        Ok(())
+    }
+}
+
+fn command(args: impl IntoIterator<Item = Bytes>) -> RespArray {
+    RespArray::new(
+        args.into_iter()
+            .map(|a| BulkString::new(a.to_vec()).into())
+            .collect::<Vec<RespFrame>>(),
+    )
+}
+
+// splits the elements of a key over as many commands as needed
+fn chunked(name: &'static str, key: &str, items: Vec<Bytes>, per_item: usize) -> Vec<RespArray> {
+    items
+        .chunks(AOF_REWRITE_ITEMS_PER_CMD * per_item)
+        .map(|chunk| {
+            let head = [Bytes::from(name), Bytes::copy_from_slice(key.as_bytes())];
+            command(head.into_iter().chain(chunk.iter().cloned()))
+        })
+        .collect()
+}
+
+fn rewrite_commands(key: String, value: RedisValue, expire: Option<u64>) -> Vec<RespArray> {
+    let mut commands = match value {
+        RedisValue::String(s) => vec![command([
+            Bytes::from("SET"),
+            Bytes::from(key.clone()),
+            s.to_bytes(),
+        ])],
+        RedisValue::List(list) => chunked("RPUSH", &key, list.into_iter().collect(), 1),
+        RedisValue::Set(set) => {
+            let members = set.into_iter().map(Bytes::from).collect();
+            chunked("SADD", &key, members, 1)
+        }
+        RedisValue::Hash(hmap) => {
+            let pairs = hmap
+                .into_iter()
+                .flat_map(|(field, value)| [Bytes::from(field), value])
+                .collect();
+            chunked("HSET", &key, pairs, 2)
+        }
+        RedisValue::ZSet(zset) => {
+            let pairs = zset
+                .iter()
+                .flat_map(|(member, score)| {
+                    [Bytes::from(score.to_string()), Bytes::from(member.clone())]
+                })
+                .collect();
+            chunked("ZADD", &key, pairs, 2)
+        }
+    };
+    if let Some(at) = expire {
+        commands.push(command([
+            Bytes::from("PEXPIREAT"),
+            Bytes::from(key),
+            Bytes::from(at.to_string()),
+        ]));
+    }
+    commands
+}
+
+pub async fn aof_fsync_cycle(backend: Backend) {
+    let mut interval = tokio::time::interval(AOF_FSYNC_INTERVAL);
+    loop {
+        interval.tick().await;
+        backend.fsync_aof();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::backend::{ListEnd, ZAddOptions};
+    use crate::config::Config;
+
+    fn temp_backend(name: &str) -> Backend {
+        let dir =
+            std::env::temp_dir().join(format!("simple-redis-aof-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        Backend::with_config(Config {
+            dir,
+            appendonly: true,
+            ..Config::default()
+        })
+    }
+
+    fn set_command(key: &str, value: &str) -> RespArray {
+        command(["SET", key, value].map(|s| Bytes::copy_from_slice(s.as_bytes())))
+    }
+
+    #[test]
+    fn test_feed_aof() -> anyhow::Result<()> {
+        let backend = temp_backend("feed");
+        // nothing is logged before the file is opened
+        backend.feed_aof(set_command("a", "1"));
+        backend.open_aof()?;
+        backend.feed_aof(set_command("b", "2"));
+        backend.fsync_aof();
+
+        let data = fs::read(backend.config.aof_path())?;
+        assert_eq!(data, b"*3\r\n$3\r\nSET\r\n$1\r\nb\r\n$1\r\n2\r\n");
+        fs::remove_dir_all(&backend.config.dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_rewrite_aof() -> anyhow::Result<()> {
+        let backend = temp_backend("rewrite");
+        backend.open_aof()?;
+        backend.feed_aof(set_command("a", "1"));
+        backend.feed_aof(set_command("a", "2"));
+        backend.set("a".to_string(), "2");
+        let items = (0..100).map(|i| Bytes::from(i.to_string())).collect();
+        backend.list_push("list", items, ListEnd::Right, false)?;
+        backend.zadd("zset", vec![(1.5, "m".to_string())], ZAddOptions::default())?;
+        backend.expire_at("zset", 4_102_444_800_000);
+        let code = "#!lua name=lib\nredis.register_function('f', function() end)";
+        backend.function_load(crate::backend::parse_library(code).unwrap(), false)?;
+
+        backend.rewrite_aof()?;
+        backend.feed_aof(set_command("b", "1"));
+        let data = String::from_utf8(fs::read(backend.config.aof_path())?)?;
+        // the libraries are loaded before the keys, like in a dump
+        assert!(data.starts_with("*3\r\n$8\r\nFUNCTION\r\n$4\r\nLOAD\r\n"));
+        assert_eq!(data.matches("SET").count(), 2);
+        // 100 elements need two RPUSH commands
+        assert_eq!(data.matches("RPUSH").count(), 2);
+        assert!(data.contains("$3\r\n1.5\r\n$1\r\nm\r\n"));
+        assert!(data.contains("PEXPIREAT\r\n$4\r\nzset\r\n$13\r\n4102444800000\r\n"));
+        assert!(data.ends_with("$1\r\nb\r\n$1\r\n1\r\n"));
+        fs::remove_dir_all(&backend.config.dir)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_bgrewrite_aof_keeps_concurrent_writes() -> anyhow::Result<()> {
+        let backend = temp_backend("bgrewrite");
+        backend.open_aof()?;
+        backend.set("a".to_string(), "1");
+        backend.bgrewrite_aof()?;
+        backend.feed_aof(set_command("b", "2"));
+        while backend.aof_rewrite_in_progress() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let data = String::from_utf8(fs::read(backend.config.aof_path())?)?;
+        assert!(data.contains("$1\r\na\r\n$1\r\n1\r\n"));
+        assert!(data.contains("$1\r\nb\r\n$1\r\n2\r\n"));
+        fs::remove_dir_all(&backend.config.dir)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_blocking_pop_logged_under_barrier() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let cloned = backend.clone();
+        let handle = tokio::spawn(async move {
+            let keys = vec!["list".to_string()];
+            let pop = || cloned.list_pop("list", 1, ListEnd::Left).ok().flatten();
+            // no snapshot can run between the pop and its logging
+            let held = |_: &Vec<Bytes>| assert!(cloned.aof.barrier.try_lock().is_err());
+            cloned.block_on(&keys, Duration::ZERO, pop, held).await
+        });
+        while !backend.waiters.contains_key("list") {
+            tokio::task::yield_now().await;
+        }
+        backend.list_push("list", vec![Bytes::from("a")], ListEnd::Left, false)?;
+        assert_eq!(handle.await?, Some(vec![Bytes::from("a")]));
+        Ok(())
+    }
+}
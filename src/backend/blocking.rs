@@ -0,0 +1,174 @@
+use crate::backend::{Backend, RedisValue};
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+use tokio::time::Instant;
+
+// a client parked on one or more keys, shared by the wait queues of all those keys
+#[derive(Debug)]
+pub struct Waiter {
+    id: u64,
+    notify: Notify,
+}
+
+// unregisters the waiter from every queue when the blocking call ends, times out or is cancelled
+struct WaiterGuard<'a> {
+    backend: &'a Backend,
+    waiter: Arc<Waiter>,
+    keys: &'a [String],
+}
+
+impl Backend {
+    // runs `attempt` until it returns Some, parking the caller between tries until one of the
+    // keys is signaled; returns None if the timeout elapses first, a zero timeout blocks forever.
+    // `on_ready` gets the result of the successful try before the write barrier is released, so
+    // what was popped is logged in order with the other writes
+    pub async fn block_on<T>(
+        &self,
+        keys: &[String],
+        timeout: Duration,
+        mut attempt: impl FnMut() -> Option<T>,
+        on_ready: impl FnOnce(&T),
+    ) -> Option<T> {
+        // every try runs like a write command of its own, never in the middle of a transaction
+        let mut on_ready = Some(on_ready);
+        let mut attempt = async || {
+            let _guard = self.command_guard().await;
+            let _barrier = self.write_barrier();
+            let ret = attempt()?;
+            if let Some(on_ready) = on_ready.take() {
+                on_ready(&ret);
+            }
+            Some(ret)
+        };
+        if let Some(ret) = attempt().await {
+            return Some(ret);
+        }
+        // register before the second attempt, so a push in between is never missed
+        let guard = self.register_waiter(keys);
+        if let Some(ret) = attempt().await {
+            return Some(ret);
+        }
+        let deadline = (!timeout.is_zero()).then(|| Instant::now() + timeout);
+        loop {
+            match deadline {
+                Some(deadline) => {
+                    tokio::time::timeout_at(deadline, guard.waiter.notify.notified())
+                        .await
+                        .ok()?;
+                }
+                None => guard.waiter.notify.notified().await,
+            }
+            if let Some(ret) = attempt().await {
+                return Some(ret);
+            }
+        }
+    }
+
+    // wakes up the longest waiting client blocked on the key, if any
+    pub(crate) fn signal_key_ready(&self, key: &str) {
+        if let Some(queue) = self.waiters.get(key) {
+            if let Some(waiter) = queue.front() {
+                waiter.notify.notify_one();
+            }
+        }
+    }
+
+    fn register_waiter<'a>(&'a self, keys: &'a [String]) -> WaiterGuard<'a> {
+        let waiter = Arc::new(Waiter {
+            id: self.next_waiter_id.fetch_add(1, Ordering::Relaxed),
+            notify: Notify::new(),
+        });
+        for key in keys {
+            self.waiters
+                .entry(key.clone())
+                .or_default()
+                .push_back(waiter.clone());
+        }
+        WaiterGuard {
+            backend: self,
+            waiter,
+            keys,
+        }
+    }
+
+    fn unregister_waiter(&self, id: u64, keys: &[String]) {
+        for key in keys {
+            self.waiters.remove_if_mut(key, |_, queue| {
+                queue.retain(|w| w.id != id);
+                queue.is_empty()
+            });
+            // the element that woke us up may still be there, pass it on to the next client
+            let ready = matches!(self.db.get(key).as_deref(), Some(RedisValue::List(_)));
+            if ready {
+                self.signal_key_ready(key);
+            }
+        }
+    }
+}
+
+impl Drop for WaiterGuard<'_> {
+    fn drop(&mut self) {
+        self.backend.unregister_waiter(self.waiter.id, self.keys);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::backend::ListEnd;
+    use bytes::Bytes;
+
+    fn pop(backend: &Backend, key: &str) -> Option<Bytes> {
+        backend
+            .list_pop(key, 1, ListEnd::Left)
+            .ok()
+            .flatten()
+            .and_then(|mut v| v.pop())
+    }
+
+    #[tokio::test]
+    async fn test_block_on_timeout() {
+        let backend = Backend::new();
+        let keys = vec!["list".to_string()];
+        let ret = backend
+            .block_on(
+                &keys,
+                Duration::from_millis(20),
+                || pop(&backend, "list"),
+                |_| {},
+            )
+            .await;
+        assert_eq!(ret, None);
+        assert!(backend.waiters.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_block_on_fifo_wakeup() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let mut handles = vec![];
+        for i in 0..3 {
+            let cloned = backend.clone();
+            handles.push(tokio::spawn(async move {
+                let keys = vec!["list".to_string()];
+                cloned
+                    .block_on(&keys, Duration::ZERO, || pop(&cloned, "list"), |_| {})
+                    .await
+            }));
+            // make sure the clients are parked in order
+            while backend.waiters.get("list").map(|q| q.len()).unwrap_or(0) <= i {
+                tokio::task::yield_now().await;
+            }
+        }
+
+        let values = ["a", "b", "c"].map(Bytes::from).to_vec();
+        backend.list_push("list", values, ListEnd::Right, false)?;
+        for (expected, handle) in ["a", "b", "c"].into_iter().zip(handles) {
+            assert_eq!(handle.await?, Some(Bytes::from(expected)));
+        }
+        assert!(backend.waiters.is_empty());
+        assert!(!backend.exists("list"));
+        Ok(())
+    }
+}
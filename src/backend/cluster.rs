@@ -0,0 +1,622 @@
+use crate::backend::expire::now_ms;
+use crate::backend::replication::new_replid;
+use crate::backend::slot::{key_hash_slot, CLUSTER_SLOTS};
+use crate::backend::{Backend, BackendError};
+use crate::config::Config;
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+
+#[derive(Debug)]
+pub struct ClusterState {
+    inner: RwLock<ClusterView>,
+}
+
+// what this node knows of the cluster
+#[derive(Debug)]
+struct ClusterView {
+    myself: String,
+    // the highest epoch seen in the cluster
+    current_epoch: u64,
+    nodes: BTreeMap<String, ClusterNode>,
+    // the owner of every slot, None while unassigned
+    slots: Vec<Option<String>>,
+    // slots of this node moving to another node, and slots moving here from another node
+    migrating: BTreeMap<u16, String>,
+    importing: BTreeMap<u16, String>,
+    // the addresses given to CLUSTER MEET, until the node there answers
+    meet: Vec<NodeAddr>,
+}
+
+// where a node takes clients and talks to the other nodes
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NodeAddr {
+    pub ip: String,
+    pub port: u16,
+    pub bus_port: u16,
+}
+
+#[derive(Debug, Clone)]
+struct ClusterNode {
+    addr: NodeAddr,
+    // the version of the slots the node claims, the claim with the highest epoch wins
+    config_epoch: u64,
+    // unix milliseconds of the oldest ping not answered yet, and of the last pong
+    ping_sent: u64,
+    pong_received: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClusterMessageKind {
+    // asks the receiver to add the sender to its cluster
+    Meet,
+    Ping,
+    Pong,
+}
+
+// what a node sends over the bus: itself, the slots it owns and the other nodes it knows
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClusterMessage {
+    pub kind: ClusterMessageKind,
+    pub sender: String,
+    pub addr: NodeAddr,
+    pub config_epoch: u64,
+    pub current_epoch: u64,
+    pub slots: Vec<(u16, u16)>,
+    pub gossip: Vec<(String, NodeAddr)>,
+}
+
+// a node as CLUSTER NODES and SHARDS report it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClusterNodeInfo {
+    pub id: String,
+    pub addr: NodeAddr,
+    pub myself: bool,
+    pub config_epoch: u64,
+    pub ping_sent: u64,
+    pub pong_received: u64,
+    // it did not answer a ping within the node timeout
+    pub failing: bool,
+    pub slots: Vec<(u16, u16)>,
+}
+
+// CLUSTER SETSLOT slot IMPORTING | MIGRATING | NODE node-id | STABLE
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SlotState {
+    Importing(String),
+    Migrating(String),
+    Node(String),
+    Stable,
+}
+
+// why this node does not serve a request itself
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClusterRedirect {
+    CrossSlot,
+    // the slot and the "ip:port" of the node owning it
+    Moved(u16, String),
+    // the slot is migrating, the keys not found here are on the target
+    Ask(u16, String),
+    // no node owns the slot
+    Down,
+}
+
+impl ClusterState {
+    pub fn new(config: &Config) -> Self {
+        let myself = new_replid();
+        let node = ClusterNode {
+            addr: NodeAddr {
+                ip: config.bind.clone(),
+                port: config.port,
+                bus_port: config.cluster_bus_port(),
+            },
+            config_epoch: 0,
+            ping_sent: 0,
+            pong_received: 0,
+        };
+        let view = ClusterView {
+            nodes: BTreeMap::from([(myself.clone(), node)]),
+            myself,
+            current_epoch: 0,
+            slots: vec![None; CLUSTER_SLOTS as usize],
+            migrating: BTreeMap::new(),
+            importing: BTreeMap::new(),
+            meet: Vec::new(),
+        };
+        Self {
+            inner: RwLock::new(view),
+        }
+    }
+}
+
+impl Default for ClusterState {
+    fn default() -> Self {
+        Self::new(&Config::default())
+    }
+}
+
+impl ClusterView {
+    fn slot_ranges(&self, id: &str) -> Vec<(u16, u16)> {
+        let mut ranges: Vec<(u16, u16)> = Vec::new();
+        for slot in 0..CLUSTER_SLOTS {
+            if self.slots[slot as usize].as_deref() != Some(id) {
+                continue;
+            }
+            match ranges.last_mut() {
+                Some((_, end)) if *end + 1 == slot => *end = slot,
+                _ => ranges.push((slot, slot)),
+            }
+        }
+        ranges
+    }
+
+    fn client_addr(&self, id: &str) -> String {
+        self.nodes
+            .get(id)
+            .map(|node| format!("{}:{}", node.addr.ip, node.addr.port))
+            .unwrap_or_default()
+    }
+
+    fn my_epoch(&self) -> u64 {
+        self.nodes[&self.myself].config_epoch
+    }
+
+    // takes a config epoch no other node has, so the slots claimed with it win
+    fn bump_epoch(&mut self) {
+        self.current_epoch += 1;
+        let epoch = self.current_epoch;
+        let myself = self.myself.clone();
+        if let Some(node) = self.nodes.get_mut(&myself) {
+            node.config_epoch = epoch;
+        }
+    }
+
+    fn check_slot_owner(&self, slot: u16) -> Result<(), BackendError> {
+        match self.slots[slot as usize].as_deref() {
+            Some(owner) if owner == self.myself => Ok(()),
+            _ => Err(BackendError::Cluster(format!(
+                "I'm not the owner of hash slot {}",
+                slot
+            ))),
+        }
+    }
+
+    fn check_known(&self, id: &str) -> Result<(), BackendError> {
+        match self.nodes.contains_key(id) {
+            true => Ok(()),
+            false => Err(BackendError::Cluster(format!(
+                "I don't know about node {}",
+                id
+            ))),
+        }
+    }
+}
+
+impl Backend {
+    pub fn cluster_enabled(&self) -> bool {
+        self.config.cluster_enabled
+    }
+
+    pub fn cluster_myid(&self) -> String {
+        self.cluster.inner.read().unwrap().myself.clone()
+    }
+
+    // the current epoch of the cluster and the config epoch of this node
+    pub fn cluster_epochs(&self) -> (u64, u64) {
+        let view = self.cluster.inner.read().unwrap();
+        (view.current_epoch, view.my_epoch())
+    }
+
+    // checks that this node serves the keys of a request: they all hash to one slot, which is
+    // ours or, after ASKING, being imported here
+    pub fn cluster_route(&self, keys: &[&[u8]], asking: bool) -> Result<(), ClusterRedirect> {
+        let mut slots = keys.iter().map(|key| key_hash_slot(key));
+        let Some(slot) = slots.next() else {
+            return Ok(());
+        };
+        if slots.any(|other| other != slot) {
+            return Err(ClusterRedirect::CrossSlot);
+        }
+        let view = self.cluster.inner.read().unwrap();
+        let owner = view.slots[slot as usize]
+            .as_deref()
+            .ok_or(ClusterRedirect::Down)?;
+        if owner != view.myself {
+            if asking && view.importing.contains_key(&slot) {
+                return Ok(());
+            }
+            return Err(ClusterRedirect::Moved(slot, view.client_addr(owner)));
+        }
+        let Some(target) = view.migrating.get(&slot) else {
+            return Ok(());
+        };
+        let target = view.client_addr(target);
+        drop(view);
+        // the keys missing here may have been moved to the target already
+        match keys
+            .iter()
+            .all(|key| self.exists(&String::from_utf8_lossy(key)))
+        {
+            true => Ok(()),
+            false => Err(ClusterRedirect::Ask(slot, target)),
+        }
+    }
+
+    // the node at `addr` is contacted on the bus and joins the cluster once it answers
+    pub fn cluster_meet(&self, addr: NodeAddr) {
+        let mut view = self.cluster.inner.write().unwrap();
+        if !view.meet.contains(&addr) {
+            view.meet.push(addr);
+        }
+    }
+
+    pub fn cluster_add_slots(&self, slots: &[u16]) -> Result<(), BackendError> {
+        let mut view = self.cluster.inner.write().unwrap();
+        if let Some(slot) = slots
+            .iter()
+            .find(|&&slot| view.slots[slot as usize].is_some())
+        {
+            return Err(BackendError::Cluster(format!(
+                "Slot {} is already busy",
+                slot
+            )));
+        }
+        let myself = view.myself.clone();
+        for &slot in slots {
+            view.slots[slot as usize] = Some(myself.clone());
+            view.importing.remove(&slot);
+        }
+        Ok(())
+    }
+
+    pub fn cluster_del_slots(&self, slots: &[u16]) -> Result<(), BackendError> {
+        let mut view = self.cluster.inner.write().unwrap();
+        if let Some(slot) = slots
+            .iter()
+            .find(|&&slot| view.slots[slot as usize].is_none())
+        {
+            return Err(BackendError::Cluster(format!(
+                "Slot {} is already unassigned",
+                slot
+            )));
+        }
+        for &slot in slots {
+            view.slots[slot as usize] = None;
+            view.migrating.remove(&slot);
+            view.importing.remove(&slot);
+        }
+        Ok(())
+    }
+
+    // moves a slot between nodes: the target imports it, the source migrates it, and both are
+    // told the new owner once the keys moved
+    pub fn cluster_set_slot(&self, slot: u16, state: SlotState) -> Result<(), BackendError> {
+        // the count is taken first, the keyspace is not read under the view lock
+        let keys = match state {
+            SlotState::Node(_) => self.keys_in_slot(slot).len(),
+            _ => 0,
+        };
+        let mut view = self.cluster.inner.write().unwrap();
+        match state {
+            SlotState::Migrating(id) => {
+                view.check_slot_owner(slot)?;
+                view.check_known(&id)?;
+                view.migrating.insert(slot, id);
+            }
+            SlotState::Importing(id) => {
+                if view.slots[slot as usize].as_deref() == Some(view.myself.as_str()) {
+                    return Err(BackendError::Cluster(format!(
+                        "I'm already the owner of hash slot {}",
+                        slot
+                    )));
+                }
+                view.check_known(&id)?;
+                view.importing.insert(slot, id);
+            }
+            SlotState::Stable => {
+                view.migrating.remove(&slot);
+                view.importing.remove(&slot);
+            }
+            SlotState::Node(id) => {
+                view.check_known(&id)?;
+                let owned = view.slots[slot as usize].as_deref() == Some(view.myself.as_str());
+                if owned && id != view.myself && keys > 0 {
+                    return Err(BackendError::Cluster(format!(
+                        "Can't assign hashslot {} to a different node while I still hold keys \
+                         for this hash slot.",
+                        slot
+                    )));
+                }
+                view.migrating.remove(&slot);
+                // the claim of the new owner has to win over the one of the old owner
+                if view.importing.remove(&slot).is_some() && id == view.myself {
+                    view.bump_epoch();
+                }
+                view.slots[slot as usize] = Some(id);
+            }
+        }
+        Ok(())
+    }
+
+    // the owner of every range of slots, in slot order
+    pub fn cluster_slot_owners(&self) -> Vec<(u16, u16, String)> {
+        let view = self.cluster.inner.read().unwrap();
+        let mut ranges: Vec<(u16, u16, String)> = Vec::new();
+        for (slot, owner) in view.slots.iter().enumerate() {
+            let Some(owner) = owner else {
+                continue;
+            };
+            let slot = slot as u16;
+            match ranges.last_mut() {
+                Some((_, end, id)) if *end + 1 == slot && id == owner => *end = slot,
+                _ => ranges.push((slot, slot, owner.clone())),
+            }
+        }
+        ranges
+    }
+
+    pub fn cluster_nodes(&self) -> Vec<ClusterNodeInfo> {
+        let view = self.cluster.inner.read().unwrap();
+        let now = now_ms();
+        view.nodes
+            .iter()
+            .map(|(id, node)| ClusterNodeInfo {
+                id: id.clone(),
+                addr: node.addr.clone(),
+                myself: *id == view.myself,
+                config_epoch: node.config_epoch,
+                ping_sent: node.ping_sent,
+                pong_received: node.pong_received,
+                failing: node.ping_sent > 0
+                    && now.saturating_sub(node.ping_sent) > self.config.cluster_node_timeout,
+                slots: view.slot_ranges(id),
+            })
+            .collect()
+    }
+
+    // the slots this node migrates and imports, with the node on the other side
+    pub fn cluster_migrations(&self) -> (BTreeMap<u16, String>, BTreeMap<u16, String>) {
+        let view = self.cluster.inner.read().unwrap();
+        (view.migrating.clone(), view.importing.clone())
+    }
+
+    // the keys hashing to `slot`, a full scan since there is no index by slot
+    pub fn keys_in_slot(&self, slot: u16) -> Vec<String> {
+        let now = now_ms();
+        let mut keys = self
+            .db
+            .iter()
+            .map(|entry| entry.key().clone())
+            .filter(|key| key_hash_slot(key.as_bytes()) == slot)
+            .filter(|key| self.expires.get(key).is_none_or(|at| *at.value() > now))
+            .collect::<Vec<String>>();
+        keys.sort();
+        keys
+    }
+
+    // the nodes to keep a bus link to: the known ones and the ones given to CLUSTER MEET
+    pub fn cluster_peers(&self) -> Vec<(NodeAddr, bool)> {
+        let view = self.cluster.inner.read().unwrap();
+        let nodes = view
+            .nodes
+            .iter()
+            .filter(|(id, _)| **id != view.myself)
+            .map(|(_, node)| (node.addr.clone(), false));
+        let meet = view.meet.iter().map(|addr| (addr.clone(), true));
+        nodes.chain(meet).collect()
+    }
+
+    // a ping went out to the node at `addr`, it is failing if no pong comes back in time
+    pub fn cluster_ping_sent(&self, addr: &NodeAddr) {
+        let mut view = self.cluster.inner.write().unwrap();
+        let now = now_ms();
+        for node in view.nodes.values_mut() {
+            if node.addr == *addr && node.ping_sent == 0 {
+                node.ping_sent = now;
+            }
+        }
+    }
+
+    pub fn cluster_message(&self, kind: ClusterMessageKind) -> ClusterMessage {
+        let view = self.cluster.inner.read().unwrap();
+        let gossip = view
+            .nodes
+            .iter()
+            .filter(|(id, _)| **id != view.myself)
+            .map(|(id, node)| (id.clone(), node.addr.clone()))
+            .collect();
+        ClusterMessage {
+            kind,
+            sender: view.myself.clone(),
+            addr: view.nodes[&view.myself].addr.clone(),
+            config_epoch: view.my_epoch(),
+            current_epoch: view.current_epoch,
+            slots: view.slot_ranges(&view.myself),
+            gossip,
+        }
+    }
+
+    // updates the view with what a node sent. `link` is the address this node connected to,
+    // None when the sender connected here: an unknown sender is only added when it sent MEET or
+    // answered on a link this node opened
+    pub fn cluster_receive(&self, message: &ClusterMessage, link: Option<&NodeAddr>) {
+        let mut view = self.cluster.inner.write().unwrap();
+        if message.sender == view.myself {
+            return;
+        }
+        if let Some(link) = link {
+            view.meet.retain(|addr| addr != link);
+        }
+        if !view.nodes.contains_key(&message.sender)
+            && link.is_none()
+            && message.kind != ClusterMessageKind::Meet
+        {
+            return;
+        }
+        view.current_epoch = view.current_epoch.max(message.current_epoch);
+        let node = view
+            .nodes
+            .entry(message.sender.clone())
+            .or_insert_with(|| ClusterNode {
+                addr: message.addr.clone(),
+                config_epoch: 0,
+                ping_sent: 0,
+                pong_received: 0,
+            });
+        node.addr = message.addr.clone();
+        node.config_epoch = message.config_epoch;
+        if message.kind == ClusterMessageKind::Pong {
+            node.ping_sent = 0;
+            node.pong_received = now_ms();
+        }
+
+        for &(start, end) in &message.slots {
+            for slot in start..=end {
+                let claimed = match view.slots[slot as usize].as_deref() {
+                    None => true,
+                    Some(owner) if owner == message.sender => false,
+                    Some(owner) => view
+                        .nodes
+                        .get(owner)
+                        .is_none_or(|owner| owner.config_epoch < message.config_epoch),
+                };
+                if claimed {
+                    view.slots[slot as usize] = Some(message.sender.clone());
+                    view.migrating.remove(&slot);
+                    view.importing.remove(&slot);
+                }
+            }
+        }
+
+        // two nodes with the same config epoch can't tell whose claim is newer, the one with the
+        // smaller id moves on to a new epoch
+        if message.config_epoch == view.my_epoch() && view.myself < message.sender {
+            view.bump_epoch();
+        }
+
+        for (id, addr) in &message.gossip {
+            if *id != view.myself && !view.nodes.contains_key(id) {
+                view.nodes.insert(
+                    id.clone(),
+                    ClusterNode {
+                        addr: addr.clone(),
+                        config_epoch: 0,
+                        ping_sent: 0,
+                        pong_received: 0,
+                    },
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn addr(port: u16) -> NodeAddr {
+        NodeAddr {
+            ip: "127.0.0.1".to_string(),
+            port,
+            bus_port: port + 10000,
+        }
+    }
+
+    fn message(sender: &str, config_epoch: u64, slots: Vec<(u16, u16)>) -> ClusterMessage {
+        ClusterMessage {
+            kind: ClusterMessageKind::Ping,
+            sender: sender.to_string(),
+            addr: addr(7001),
+            config_epoch,
+            current_epoch: config_epoch,
+            slots,
+            gossip: vec![("c".repeat(40), addr(7002))],
+        }
+    }
+
+    #[test]
+    fn test_cluster_view() -> Result<(), BackendError> {
+        let backend = Backend::new();
+        let myself = backend.cluster_myid();
+        let other = "b".repeat(40);
+        backend.cluster_add_slots(&[0, 1, 2])?;
+        assert!(backend.cluster_add_slots(&[2]).is_err());
+        assert_eq!(backend.cluster_slot_owners(), vec![(0, 2, myself.clone())]);
+
+        // an unknown node is only heard once it sent MEET
+        backend.cluster_receive(&message(&other, 0, vec![(3, 5)]), None);
+        assert_eq!(backend.cluster_nodes().len(), 1);
+        let mut meet = message(&other, 0, vec![(3, 5)]);
+        meet.kind = ClusterMessageKind::Meet;
+        backend.cluster_receive(&meet, None);
+        assert_eq!(backend.cluster_nodes().len(), 3);
+        assert_eq!(
+            backend.cluster_slot_owners(),
+            vec![(0, 2, myself.clone()), (3, 5, other.clone())]
+        );
+
+        assert_eq!(
+            backend.cluster_route(&[b"foo"], false),
+            Err(ClusterRedirect::Down)
+        );
+        assert_eq!(
+            backend.cluster_route(&[b"{a}1", b"{b}2"], false),
+            Err(ClusterRedirect::CrossSlot)
+        );
+        backend.cluster_add_slots(&[key_hash_slot(b"foo")])?;
+        assert_eq!(backend.cluster_route(&[b"foo", b"{foo}2"], false), Ok(()));
+
+        // a claim with a higher config epoch takes the slot over
+        let slot = key_hash_slot(b"foo");
+        backend.cluster_receive(&message(&other, 5, vec![(slot, slot)]), None);
+        assert_eq!(
+            backend.cluster_route(&[b"foo"], false),
+            Err(ClusterRedirect::Moved(slot, "127.0.0.1:7001".to_string()))
+        );
+        // a stale one does not
+        backend.cluster_receive(&message(&other, 0, vec![(0, 0)]), None);
+        assert_eq!(backend.cluster_slot_owners()[0], (0, 2, myself));
+        Ok(())
+    }
+
+    #[test]
+    fn test_slot_migration() -> Result<(), BackendError> {
+        let backend = Backend::new();
+        // a smaller id than ours, so this node never bumps its epoch on a collision
+        let other = "0".repeat(40);
+        let mut meet = message(&other, 0, vec![]);
+        meet.kind = ClusterMessageKind::Meet;
+        backend.cluster_receive(&meet, None);
+        let slot = key_hash_slot(b"foo");
+        backend.cluster_add_slots(&[slot])?;
+        backend.set("foo".to_string(), "1");
+
+        assert!(backend
+            .cluster_set_slot(slot, SlotState::Migrating("x".repeat(40)))
+            .is_err());
+        backend.cluster_set_slot(slot, SlotState::Migrating(other.clone()))?;
+        assert_eq!(backend.cluster_route(&[b"foo"], false), Ok(()));
+        assert_eq!(
+            backend.cluster_route(&[b"foo", b"{foo}2"], false),
+            Err(ClusterRedirect::Ask(slot, "127.0.0.1:7001".to_string()))
+        );
+        assert!(backend
+            .cluster_set_slot(slot, SlotState::Node(other.clone()))
+            .is_err());
+        backend.remove("foo");
+        backend.cluster_set_slot(slot, SlotState::Node(other.clone()))?;
+        assert!(backend.cluster_migrations().0.is_empty());
+
+        // the slot is served here again only for the clients sent by ASK
+        backend.cluster_set_slot(slot, SlotState::Importing(other))?;
+        assert!(matches!(
+            backend.cluster_route(&[b"foo"], false),
+            Err(ClusterRedirect::Moved(..))
+        ));
+        assert_eq!(backend.cluster_route(&[b"foo"], true), Ok(()));
+        let myself = backend.cluster_myid();
+        backend.cluster_set_slot(slot, SlotState::Node(myself))?;
+        assert_eq!(backend.cluster_epochs(), (1, 1));
+        assert_eq!(backend.cluster_route(&[b"foo"], false), Ok(()));
+        Ok(())
+    }
+}
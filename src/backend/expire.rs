@@ -0,0 +1,160 @@
+use crate::backend::Backend;
+use crate::resp::{BulkString, RespArray};
+use dashmap::mapref::entry::Entry;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::info;
+
+// how often the background task looks for expired keys
+const ACTIVE_EXPIRE_INTERVAL: Duration = Duration::from_millis(100);
+// upper bound of keys evicted by one cycle, so a huge batch of deadlines cannot stall the shards
+const ACTIVE_EXPIRE_BUDGET: usize = 1024;
+
+pub fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+impl Backend {
+    // lazy expiration: every access to a key goes through here first
+    pub(crate) fn expire_if_needed(&self, key: &str) -> bool {
+        let now = now_ms();
+        let due = self
+            .expires
+            .get(key)
+            .map(|at| *at.value() <= now)
+            .unwrap_or(false);
+        if !due {
+            return false;
+        }
+        // the deadline is checked again while the shard of the key is held, a write that
+        // replaced the value or moved the deadline since the first look keeps its key
+        let expired = match self.db.entry(key.to_string()) {
+            Entry::Occupied(entry) => {
+                let expired = self.expires.remove_if(key, |_, at| *at <= now).is_some();
+                if expired {
+                    entry.remove();
+                }
+                expired
+            }
+            Entry::Vacant(_) => self.expires.remove_if(key, |_, at| *at <= now).is_some(),
+        };
+        if expired {
+            self.touch_key(key);
+        }
+        expired
+    }
+
+    // sets the absolute deadline of an existing key, returns false if the key does not exist
+    pub fn expire_at(&self, key: &str, at: u64) -> bool {
+        if !self.exists(key) {
+            return false;
+        }
+        if at <= now_ms() {
+            self.remove(key);
+        } else {
+            self.expires.insert(key.to_string(), at);
+            self.touch_key(key);
+        }
+        true
+    }
+
+    // None: key does not exist, Some(None): key has no deadline, Some(Some(at)): deadline
+    pub fn expire_time(&self, key: &str) -> Option<Option<u64>> {
+        if !self.exists(key) {
+            return None;
+        }
+        Some(self.expires.get(key).map(|at| *at.value()))
+    }
+
+    pub fn persist(&self, key: &str) -> bool {
+        self.expire_if_needed(key);
+        let persisted = self.expires.remove(key).is_some();
+        if persisted {
+            self.touch_key(key);
+        }
+        persisted
+    }
+
+    // active expiration: evicts keys whose deadline passed even if nobody touches them
+    pub fn active_expire(&self) -> usize {
+        let now = now_ms();
+        let expired = self
+            .expires
+            .iter()
+            .filter(|v| *v.value() <= now)
+            .take(ACTIVE_EXPIRE_BUDGET)
+            .map(|v| v.key().clone())
+            .collect::<Vec<String>>();
+        // the caller holds the write barrier, every eviction reaches the log and the replicas
+        // as a DEL in the order it happened
+        let mut evicted = 0;
+        for key in expired {
+            if self.expire_if_needed(&key) {
+                self.mark_dirty();
+                self.propagate(RespArray::new([
+                    BulkString::new("DEL").into(),
+                    BulkString::new(key).into(),
+                ]));
+                evicted += 1;
+            }
+        }
+        evicted
+    }
+}
+
+pub async fn active_expire_cycle(backend: Backend) {
+    let mut interval = tokio::time::interval(ACTIVE_EXPIRE_INTERVAL);
+    loop {
+        interval.tick().await;
+        // a replica waits for the DEL of its master, evicting on its own would move its offset
+        if backend.is_replica() {
+            continue;
+        }
+        let n = {
+            let _guard = backend.command_guard().await;
+            let _barrier = backend.write_barrier();
+            backend.active_expire()
+        };
+        if n > 0 {
+            info!("[Simple-redis-server]active expire evicted {} keys", n);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_lazy_expire() {
+        let backend = Backend::new();
+        backend.set("k".to_string(), "v");
+        assert!(backend.expire_at("k", now_ms() + 10_000));
+        assert!(backend.exists("k"));
+
+        backend.expires.insert("k".to_string(), now_ms() - 1);
+        assert!(!backend.exists("k"));
+        assert_eq!(backend.expire_time("k"), None);
+        assert!(!backend.expire_at("k", now_ms() + 10_000));
+    }
+
+    #[test]
+    fn test_active_expire() {
+        let backend = Backend::new();
+        for i in 0..10 {
+            let key = format!("key{}", i);
+            backend.set(key.clone(), i.to_string());
+            if i % 2 == 0 {
+                backend.expires.insert(key, now_ms() - 1);
+            }
+        }
+        let offset = backend.master_repl_offset();
+        assert_eq!(backend.active_expire(), 5);
+        assert!(backend.master_repl_offset() > offset);
+        assert!(!backend.exists("key0"));
+        assert!(backend.exists("key1"));
+        assert_eq!(backend.expires.len(), 0);
+    }
+}
@@ -0,0 +1,423 @@
+use crate::backend::glob::string_match;
+use crate::backend::{Backend, BackendError};
+use mlua::{Lua, LuaOptions, StdLib, Table, Value};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, MutexGuard, RwLock};
+
+// the flags a function can declare, `no-writes` is the one enforced by FCALL_RO
+const FUNCTION_FLAGS: [&str; 5] = [
+    "no-writes",
+    "allow-oom",
+    "allow-stale",
+    "no-cluster",
+    "allow-cross-slot-keys",
+];
+
+// the registry table of a library interpreter with the callbacks it registered, by name
+const CALLBACKS: &str = "callbacks";
+
+// the libraries loaded with FUNCTION LOAD, by name
+#[derive(Debug, Default)]
+pub struct FunctionState {
+    libraries: RwLock<BTreeMap<String, Arc<FunctionLibrary>>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct FunctionLibrary {
+    pub name: String,
+    // the source as it was loaded, shebang line included
+    pub code: String,
+    pub functions: Vec<FunctionInfo>,
+    // the interpreter the code ran in when the library was loaded, FCALL calls the callbacks it
+    // registered there
+    vm: Arc<Mutex<Lua>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionInfo {
+    pub name: String,
+    pub description: Option<String>,
+    pub flags: Vec<String>,
+}
+
+// what FUNCTION RESTORE does with the libraries already loaded
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RestorePolicy {
+    // fails if a library of the payload exists
+    Append,
+    // replaces the libraries of the payload
+    Replace,
+    // drops every library first
+    Flush,
+}
+
+impl FunctionLibrary {
+    pub fn function(&self, name: &str) -> Option<&FunctionInfo> {
+        self.functions.iter().find(|f| f.name == name)
+    }
+
+    pub fn vm(&self) -> MutexGuard<'_, Lua> {
+        self.vm.lock().unwrap()
+    }
+}
+
+// the callback registered under the name in the interpreter of a library
+pub fn library_callback<'lua>(lua: &'lua Lua, name: &str) -> mlua::Result<mlua::Function<'lua>> {
+    lua.named_registry_value::<Table>(CALLBACKS)?.get(name)
+}
+
+// like redis, no access to the files or the process from a script
+pub fn lua_sandbox() -> mlua::Result<Lua> {
+    let lua = Lua::new_with(
+        StdLib::TABLE | StdLib::STRING | StdLib::MATH,
+        LuaOptions::default(),
+    )?;
+    let globals = lua.globals();
+    globals.set("loadfile", Value::Nil)?;
+    globals.set("dofile", Value::Nil)?;
+    drop(globals);
+    Ok(lua)
+}
+
+// the same library whatever the interpreter it was loaded in
+impl PartialEq for FunctionLibrary {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && self.code == other.code && self.functions == other.functions
+    }
+}
+
+impl FunctionInfo {
+    pub fn is_read_only(&self) -> bool {
+        self.flags.iter().any(|flag| flag == "no-writes")
+    }
+}
+
+impl Backend {
+    pub fn function_load(
+        &self,
+        library: FunctionLibrary,
+        replace: bool,
+    ) -> Result<(), BackendError> {
+        let mut libraries = self.functions.libraries.write().unwrap();
+        if libraries.contains_key(&library.name) && !replace {
+            return Err(BackendError::Function(format!(
+                "Library '{}' already exists",
+                library.name
+            )));
+        }
+        add_library(&mut libraries, library)
+    }
+
+    pub fn function_delete(&self, name: &str) -> bool {
+        self.functions
+            .libraries
+            .write()
+            .unwrap()
+            .remove(name)
+            .is_some()
+    }
+
+    pub fn function_flush(&self) {
+        self.functions.libraries.write().unwrap().clear();
+    }
+
+    // every library, sorted by name
+    pub fn function_libraries(&self) -> Vec<Arc<FunctionLibrary>> {
+        self.functions
+            .libraries
+            .read()
+            .unwrap()
+            .values()
+            .cloned()
+            .collect()
+    }
+
+    // the libraries whose name matches the glob pattern, all of them without one
+    pub fn function_list(&self, pattern: Option<&str>) -> Vec<Arc<FunctionLibrary>> {
+        self.function_libraries()
+            .into_iter()
+            .filter(|library| {
+                pattern
+                    .is_none_or(|pattern| string_match(pattern.as_bytes(), library.name.as_bytes()))
+            })
+            .collect()
+    }
+
+    // the library registering the function
+    pub fn function_library(&self, function: &str) -> Option<Arc<FunctionLibrary>> {
+        self.functions
+            .libraries
+            .read()
+            .unwrap()
+            .values()
+            .find(|library| library.function(function).is_some())
+            .cloned()
+    }
+
+    // loads all the libraries or none of them
+    pub fn function_restore(
+        &self,
+        restored: Vec<FunctionLibrary>,
+        policy: RestorePolicy,
+    ) -> Result<(), BackendError> {
+        let mut libraries = self.functions.libraries.write().unwrap();
+        let mut updated = match policy {
+            RestorePolicy::Flush => BTreeMap::new(),
+            _ => libraries.clone(),
+        };
+        for library in restored {
+            if policy == RestorePolicy::Append && updated.contains_key(&library.name) {
+                return Err(BackendError::Function(format!(
+                    "Library {} already exists",
+                    library.name
+                )));
+            }
+            add_library(&mut updated, library)?;
+        }
+        *libraries = updated;
+        Ok(())
+    }
+}
+
+// a function name is unique across the libraries
+fn add_library(
+    libraries: &mut BTreeMap<String, Arc<FunctionLibrary>>,
+    library: FunctionLibrary,
+) -> Result<(), BackendError> {
+    let taken = libraries
+        .values()
+        .filter(|other| other.name != library.name)
+        .flat_map(|other| other.functions.iter())
+        .find(|f| library.function(&f.name).is_some());
+    if let Some(f) = taken {
+        return Err(BackendError::Function(format!(
+            "Function {} already exists",
+            f.name
+        )));
+    }
+    libraries.insert(library.name.clone(), Arc::new(library));
+    Ok(())
+}
+
+// reads the metadata of the shebang line and runs the code to collect the functions it
+// registers, the interpreter is kept for FCALL; only redis.register_function is available while
+// a library loads
+pub fn parse_library(code: &str) -> Result<FunctionLibrary, String> {
+    let shebang = code
+        .lines()
+        .next()
+        .and_then(|line| line.strip_prefix("#!"))
+        .ok_or("Missing library metadata")?;
+    let mut parts = shebang.split_whitespace();
+    let engine = parts.next().unwrap_or_default();
+    if !engine.eq_ignore_ascii_case("lua") {
+        return Err(format!("Engine '{}' not found", engine));
+    }
+    let mut name = None;
+    for part in parts {
+        match part.strip_prefix("name=") {
+            Some(value) => name = Some(value.to_string()),
+            None => return Err(format!("Invalid metadata value given: {}", part)),
+        }
+    }
+    let name = name.ok_or("Library name was not given")?;
+    if !valid_name(&name) {
+        return Err(
+            "Library names can only contain letters, numbers, or underscores(_) and \
+                    must be at least one character long"
+                .to_string(),
+        );
+    }
+    let (vm, functions) = registered_functions(&body(code))?;
+    if functions.is_empty() {
+        return Err("No functions registered".to_string());
+    }
+    Ok(FunctionLibrary {
+        name,
+        code: code.to_string(),
+        functions,
+        vm: Arc::new(Mutex::new(vm)),
+    })
+}
+
+fn registered_functions(body: &str) -> Result<(Lua, Vec<FunctionInfo>), String> {
+    let run = || -> mlua::Result<Result<(Lua, Vec<FunctionInfo>), String>> {
+        let lua = lua_sandbox()?;
+        lua.set_named_registry_value(CALLBACKS, lua.create_table()?)?;
+        let functions = Arc::new(Mutex::new(Ok(Vec::new())));
+        let registered = functions.clone();
+        let redis = lua.create_table()?;
+        redis.set(
+            "register_function",
+            lua.create_function(move |lua, args: mlua::Variadic<Value>| {
+                let mut registered = registered.lock().unwrap();
+                if let Ok(functions) = registered.as_mut() {
+                    match register(functions, args) {
+                        Ok((name, callback)) => {
+                            lua.named_registry_value::<Table>(CALLBACKS)?
+                                .set(name, callback)?;
+                        }
+                        Err(e) => *registered = Err(e),
+                    }
+                }
+                Ok(())
+            })?,
+        )?;
+        lua.globals().set("redis", redis)?;
+        if let Err(e) = lua.load(body).set_name("@user_function").exec() {
+            return Ok(Err(format!("Error registering functions: {}", e)));
+        }
+        lua.globals().set("redis", Value::Nil)?;
+        let functions = std::mem::replace(&mut *functions.lock().unwrap(), Ok(Vec::new()));
+        Ok(functions.map(|functions| (lua, functions)))
+    };
+    run().map_err(|e| format!("Error registering functions: {}", e))?
+}
+
+// redis.register_function(name, callback) or redis.register_function{function_name=...,
+// callback=..., flags=..., description=...}
+fn register<'lua>(
+    functions: &mut Vec<FunctionInfo>,
+    args: mlua::Variadic<Value<'lua>>,
+) -> Result<(String, Value<'lua>), String> {
+    let mut args = args.into_iter();
+    let (name, callback, flags, description) = match (args.next(), args.next()) {
+        (Some(Value::String(name)), Some(callback)) => (Value::String(name), callback, None, None),
+        (Some(Value::Table(named)), None) => (
+            named.get("function_name").unwrap_or(Value::Nil),
+            named.get("callback").unwrap_or(Value::Nil),
+            named.get::<_, Option<mlua::Table>>("flags").ok().flatten(),
+            named.get::<_, Option<String>>("description").ok().flatten(),
+        ),
+        _ => return Err("wrong number of arguments to redis.register_function".to_string()),
+    };
+    let Value::String(name) = name else {
+        return Err(
+            "function_name argument given to redis.register_function must be a string".to_string(),
+        );
+    };
+    let name = name.to_string_lossy().into_owned();
+    if !matches!(callback, Value::Function(_)) {
+        return Err(
+            "callback argument given to redis.register_function must be a function".to_string(),
+        );
+    }
+    if !valid_name(&name) {
+        return Err(
+            "Function names can only contain letters, numbers, or underscores(_) and \
+                    must be at least one character long"
+                .to_string(),
+        );
+    }
+    if functions.iter().any(|f| f.name == name) {
+        return Err("Function already exists in the library".to_string());
+    }
+    let flags = match flags {
+        Some(flags) => flags
+            .sequence_values::<String>()
+            .map(|flag| match flag {
+                Ok(flag) if FUNCTION_FLAGS.contains(&flag.as_str()) => Ok(flag),
+                _ => Err("unknown flag given".to_string()),
+            })
+            .collect::<Result<Vec<String>, String>>()?,
+        None => Vec::new(),
+    };
+    functions.push(FunctionInfo {
+        name: name.clone(),
+        description,
+        flags,
+    });
+    Ok((name, callback))
+}
+
+// the source as Lua compiles it, the shebang line turned into a comment so the line numbers of
+// the errors still match
+fn body(code: &str) -> String {
+    format!("--{}", code)
+}
+
+fn valid_name(name: &str) -> bool {
+    !name.is_empty() && name.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_')
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const LIBRARY: &str = "#!lua name=mylib
+redis.register_function('first', function(keys, args) return 1 end)
+redis.register_function{
+    function_name = 'second',
+    callback = function(keys, args) return 2 end,
+    flags = {'no-writes'},
+}";
+
+    #[test]
+    fn test_parse_library() {
+        let library = parse_library(LIBRARY).unwrap();
+        assert_eq!(library.name, "mylib");
+        assert_eq!(library.functions.len(), 2);
+        assert!(!library.function("first").unwrap().is_read_only());
+        assert!(library.function("second").unwrap().is_read_only());
+
+        assert_eq!(
+            parse_library("return 1").unwrap_err(),
+            "Missing library metadata"
+        );
+        assert_eq!(
+            parse_library("#!js name=x\n").unwrap_err(),
+            "Engine 'js' not found"
+        );
+        assert_eq!(
+            parse_library("#!lua name=x\nlocal a = 1").unwrap_err(),
+            "No functions registered"
+        );
+        assert_eq!(
+            parse_library(
+                "#!lua name=x\nredis.register_function{function_name='f', \
+                 callback=function() end, flags={'bad'}}"
+            )
+            .unwrap_err(),
+            "unknown flag given"
+        );
+        let error = parse_library("#!lua name=x\nredis.call('PING')").unwrap_err();
+        assert!(error.starts_with("Error registering functions: "));
+    }
+
+    #[test]
+    fn test_function_registry() {
+        let backend = Backend::new();
+        let library = parse_library(LIBRARY).unwrap();
+        backend.function_load(library.clone(), false).unwrap();
+        assert!(backend.function_load(library.clone(), false).is_err());
+        backend.function_load(library.clone(), true).unwrap();
+        assert_eq!(
+            backend.function_library("second").map(|l| l.name.clone()),
+            Some("mylib".to_string())
+        );
+
+        // function names are unique across libraries
+        let other =
+            parse_library("#!lua name=other\nredis.register_function('first', function() end)")
+                .unwrap();
+        assert_eq!(
+            backend
+                .function_load(other.clone(), false)
+                .unwrap_err()
+                .to_string(),
+            "ERR Function first already exists"
+        );
+        assert!(backend
+            .function_restore(vec![other.clone()], RestorePolicy::Replace)
+            .is_err());
+        backend
+            .function_restore(vec![other], RestorePolicy::Flush)
+            .unwrap();
+        assert_eq!(backend.function_libraries().len(), 1);
+        assert!(backend.function_library("second").is_none());
+
+        assert!(backend.function_delete("other"));
+        assert!(!backend.function_delete("other"));
+        assert!(backend.function_libraries().is_empty());
+    }
+}
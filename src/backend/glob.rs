@@ -0,0 +1,109 @@
+// glob-style matching with the rules of redis patterns: `*`, `?`, `[...]` classes with `^`
+// negation and `a-z` ranges, and `\` to escape the next character
+pub fn string_match(pattern: &[u8], string: &[u8]) -> bool {
+    let (mut p, mut s) = (0, 0);
+    // where to resume after the last star when the rest fails to match
+    let mut star: Option<(usize, usize)> = None;
+    while s < string.len() {
+        let step = match pattern.get(p) {
+            Some(b'*') => {
+                star = Some((p, s));
+                p += 1;
+                continue;
+            }
+            Some(b'?') => Some(1),
+            Some(b'[') => {
+                let (matched, len) = match_class(&pattern[p..], string[s]);
+                matched.then_some(len)
+            }
+            Some(b'\\') if p + 1 < pattern.len() => (pattern[p + 1] == string[s]).then_some(2),
+            Some(&c) => (c == string[s]).then_some(1),
+            None => None,
+        };
+        match (step, star) {
+            (Some(len), _) => {
+                p += len;
+                s += 1;
+            }
+            // the star swallows one more character and the match starts over after it
+            (None, Some((star_p, star_s))) => {
+                star = Some((star_p, star_s + 1));
+                p = star_p + 1;
+                s = star_s + 1;
+            }
+            (None, None) => return false,
+        }
+    }
+    pattern[p..].iter().all(|&c| c == b'*')
+}
+
+// matches a character against the class at the start of the pattern, returns whether it matched
+// and the length of the class; an unterminated class runs to the end of the pattern
+fn match_class(pattern: &[u8], c: u8) -> (bool, usize) {
+    let mut i = 1;
+    let negated = pattern.get(i) == Some(&b'^');
+    if negated {
+        i += 1;
+    }
+    let mut matched = false;
+    while i < pattern.len() && pattern[i] != b']' {
+        if pattern[i] == b'\\' && i + 1 < pattern.len() {
+            i += 1;
+            matched |= pattern[i] == c;
+        } else if i + 2 < pattern.len() && pattern[i + 1] == b'-' {
+            let (start, end) = (
+                pattern[i].min(pattern[i + 2]),
+                pattern[i].max(pattern[i + 2]),
+            );
+            matched |= (start..=end).contains(&c);
+            i += 2;
+        } else {
+            matched |= pattern[i] == c;
+        }
+        i += 1;
+    }
+    (matched != negated, (i + 1).min(pattern.len()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_string_match() {
+        let cases: &[(&str, &str, bool)] = &[
+            ("*", "", true),
+            ("*", "anything", true),
+            ("news.*", "news.tech", true),
+            ("news.*", "news", false),
+            ("h?llo", "hello", true),
+            ("h?llo", "hllo", false),
+            ("h*llo", "heeeello", true),
+            ("h*llo", "hellox", false),
+            ("*a*b*c", "xaxbxbxc", true),
+            ("*a*b*c", "xaxbxbx", false),
+            ("h[ae]llo", "hallo", true),
+            ("h[ae]llo", "hillo", false),
+            ("h[^e]llo", "hallo", true),
+            ("h[^e]llo", "hello", false),
+            ("h[a-b]llo", "hbllo", true),
+            ("h[b-a]llo", "hbllo", true),
+            ("h[a-b]llo", "hcllo", false),
+            ("h\\*llo", "h*llo", true),
+            ("h\\*llo", "hello", false),
+            ("h[\\]]llo", "h]llo", true),
+            ("h[ab", "hb", true),
+            ("", "", true),
+            ("", "a", false),
+        ];
+        for (pattern, string, expected) in cases {
+            assert_eq!(
+                string_match(pattern.as_bytes(), string.as_bytes()),
+                *expected,
+                "{} against {}",
+                pattern,
+                string
+            );
+        }
+    }
+}
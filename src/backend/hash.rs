@@ -0,0 +1,231 @@
+use crate::backend::{Backend, BackendError, RedisValue};
+use bytes::Bytes;
+use dashmap::mapref::entry::Entry;
+use rand::seq::IteratorRandom;
+use rand::Rng;
+use std::collections::HashMap;
+
+impl Backend {
+    // read access to a hash, Ok(None) if the key does not exist
+    fn with_hash<T>(
+        &self,
+        key: &str,
+        f: impl FnOnce(&HashMap<String, Bytes>) -> T,
+    ) -> Result<Option<T>, BackendError> {
+        self.expire_if_needed(key);
+        match self.db.get(key).as_deref() {
+            Some(RedisValue::Hash(hmap)) => Ok(Some(f(hmap))),
+            Some(_) => Err(BackendError::WrongType),
+            None => Ok(None),
+        }
+    }
+
+    // write access to a hash, the key is created if `create` is set and removed once it is empty
+    fn with_hash_mut<T>(
+        &self,
+        key: &str,
+        create: bool,
+        f: impl FnOnce(&mut HashMap<String, Bytes>) -> Result<T, BackendError>,
+    ) -> Result<Option<T>, BackendError> {
+        self.expire_if_needed(key);
+        match self.db.entry(key.to_string()) {
+            Entry::Occupied(mut e) => {
+                let (ret, empty) = match e.get_mut() {
+                    RedisValue::Hash(hmap) => (f(hmap)?, hmap.is_empty()),
+                    _ => return Err(BackendError::WrongType),
+                };
+                if empty {
+                    e.remove();
+                    self.expires.remove(key);
+                }
+                self.touch_key(key);
+                Ok(Some(ret))
+            }
+            Entry::Vacant(e) if create => {
+                let mut hmap = HashMap::new();
+                let ret = f(&mut hmap)?;
+                if !hmap.is_empty() {
+                    e.insert(RedisValue::Hash(hmap));
+                    self.touch_key(key);
+                }
+                Ok(Some(ret))
+            }
+            Entry::Vacant(_) => Ok(None),
+        }
+    }
+
+    pub fn hget(&self, key: &str, field: &str) -> Result<Option<Bytes>, BackendError> {
+        Ok(self
+            .with_hash(key, |hmap| hmap.get(field).cloned())?
+            .flatten())
+    }
+
+    // returns the number of fields that were added, updated fields are not counted
+    pub fn hset(&self, key: &str, fields: Vec<(String, Bytes)>) -> Result<usize, BackendError> {
+        let ret = self.with_hash_mut(key, true, |hmap| {
+            Ok(fields
+                .into_iter()
+                .filter(|(field, value)| hmap.insert(field.clone(), value.clone()).is_none())
+                .count())
+        })?;
+        Ok(ret.unwrap_or(0))
+    }
+
+    // sets the field only if it does not exist yet, returns whether it was set
+    pub fn hsetnx(&self, key: &str, field: String, value: Bytes) -> Result<bool, BackendError> {
+        let ret = self.with_hash_mut(key, true, |hmap| match hmap.entry(field) {
+            std::collections::hash_map::Entry::Occupied(_) => Ok(false),
+            std::collections::hash_map::Entry::Vacant(e) => {
+                e.insert(value);
+                Ok(true)
+            }
+        })?;
+        Ok(ret.unwrap_or(false))
+    }
+
+    pub fn hdel(&self, key: &str, fields: &[String]) -> Result<usize, BackendError> {
+        let ret = self.with_hash_mut(key, false, |hmap| {
+            Ok(fields.iter().filter(|f| hmap.remove(*f).is_some()).count())
+        })?;
+        Ok(ret.unwrap_or(0))
+    }
+
+    pub fn hexists(&self, key: &str, field: &str) -> Result<bool, BackendError> {
+        Ok(self
+            .with_hash(key, |hmap| hmap.contains_key(field))?
+            .unwrap_or(false))
+    }
+
+    pub fn hlen(&self, key: &str) -> Result<usize, BackendError> {
+        Ok(self.with_hash(key, |hmap| hmap.len())?.unwrap_or(0))
+    }
+
+    pub fn hstrlen(&self, key: &str, field: &str) -> Result<usize, BackendError> {
+        let ret = self.with_hash(key, |hmap| hmap.get(field).map(|v| v.len()))?;
+        Ok(ret.flatten().unwrap_or(0))
+    }
+
+    pub fn hgetall(&self, key: &str) -> Result<Option<HashMap<String, Bytes>>, BackendError> {
+        self.with_hash(key, |hmap| hmap.clone())
+    }
+
+    // a missing field counts as 0, returns the new value
+    pub fn hincrby(&self, key: &str, field: &str, delta: i64) -> Result<i64, BackendError> {
+        let ret = self.with_hash_mut(key, true, |hmap| {
+            let current = match hmap.get(field) {
+                Some(v) => std::str::from_utf8(v)
+                    .ok()
+                    .and_then(|s| s.parse::<i64>().ok())
+                    .ok_or(BackendError::HashNotInteger)?,
+                None => 0,
+            };
+            let value = current.checked_add(delta).ok_or(BackendError::Overflow)?;
+            hmap.insert(field.to_string(), Bytes::from(value.to_string()));
+            Ok(value)
+        })?;
+        Ok(ret.unwrap_or_default())
+    }
+
+    // a missing field counts as 0, returns the new value
+    pub fn hincrbyfloat(&self, key: &str, field: &str, delta: f64) -> Result<f64, BackendError> {
+        let ret = self.with_hash_mut(key, true, |hmap| {
+            let current = match hmap.get(field) {
+                Some(v) => std::str::from_utf8(v)
+                    .ok()
+                    .and_then(|s| s.parse::<f64>().ok())
+                    .filter(|f| f.is_finite())
+                    .ok_or(BackendError::HashNotFloat)?,
+                None => 0.0,
+            };
+            let value = current + delta;
+            if !value.is_finite() {
+                return Err(BackendError::NanOrInfinity);
+            }
+            hmap.insert(field.to_string(), Bytes::from(value.to_string()));
+            Ok(value)
+        })?;
+        Ok(ret.unwrap_or_default())
+    }
+
+    // a positive count returns distinct fields, a negative count may return the same field
+    // several times and always returns |count| fields
+    pub fn hrandfield(&self, key: &str, count: i64) -> Result<Vec<(String, Bytes)>, BackendError> {
+        let ret = self.with_hash(key, |hmap| {
+            let mut rng = rand::thread_rng();
+            let fields = hmap.iter().map(|(f, v)| (f.clone(), v.clone()));
+            if count >= 0 {
+                return fields.choose_multiple(&mut rng, count as usize);
+            }
+            let fields = fields.collect::<Vec<(String, Bytes)>>();
+            (0..count.unsigned_abs())
+                .map(|_| fields[rng.gen_range(0..fields.len())].clone())
+                .collect()
+        })?;
+        Ok(ret.unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn fields(v: &[(&str, &'static str)]) -> Vec<(String, Bytes)> {
+        v.iter()
+            .map(|(f, v)| (f.to_string(), Bytes::from_static(v.as_bytes())))
+            .collect()
+    }
+
+    #[test]
+    fn test_hash_set_del() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        assert_eq!(backend.hset("h", fields(&[("a", "1"), ("b", "2")]))?, 2);
+        assert_eq!(backend.hset("h", fields(&[("a", "3"), ("c", "4")]))?, 1);
+        assert_eq!(backend.hget("h", "a")?, Some(Bytes::from("3")));
+        assert!(!backend.hsetnx("h", "a".to_string(), Bytes::from("5"))?);
+        assert!(backend.hsetnx("h", "d".to_string(), Bytes::from("5"))?);
+        assert_eq!(backend.hlen("h")?, 4);
+        assert_eq!(backend.hstrlen("h", "d")?, 1);
+        let all = ["a", "b", "c", "d", "x"].map(String::from);
+        assert_eq!(backend.hdel("h", &all)?, 4);
+        assert!(!backend.exists("h"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_hash_incr() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        assert_eq!(backend.hincrby("h", "n", 5)?, 5);
+        assert_eq!(backend.hincrby("h", "n", -7)?, -2);
+        assert_eq!(
+            backend.hincrby("h", "n", i64::MIN),
+            Err(BackendError::Overflow)
+        );
+        assert_eq!(backend.hincrbyfloat("h", "n", 0.5)?, -1.5);
+        assert_eq!(backend.hget("h", "n")?, Some(Bytes::from("-1.5")));
+        assert_eq!(
+            backend.hincrby("h", "n", 1),
+            Err(BackendError::HashNotInteger)
+        );
+        backend.hset("h", fields(&[("s", "abc")]))?;
+        assert_eq!(
+            backend.hincrbyfloat("h", "s", 1.0),
+            Err(BackendError::HashNotFloat)
+        );
+        assert_eq!(
+            backend.hincrbyfloat("h", "n", f64::INFINITY),
+            Err(BackendError::NanOrInfinity)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_hrandfield() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        backend.hset("h", fields(&[("a", "1"), ("b", "2")]))?;
+        assert_eq!(backend.hrandfield("h", 5)?.len(), 2);
+        let ret = backend.hrandfield("h", -3)?;
+        assert_eq!(ret.len(), 3);
+        assert!(ret.iter().all(|(f, _)| f == "a" || f == "b"));
+        Ok(())
+    }
+}
@@ -0,0 +1,359 @@
+use crate::backend::{Backend, BackendError, RedisValue};
+use bytes::Bytes;
+use dashmap::mapref::entry::Entry;
+use std::collections::VecDeque;
+
+// the end of a list an element is pushed to or popped from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListEnd {
+    Left,
+    Right,
+}
+
+impl Backend {
+    // read access to a list, Ok(None) if the key does not exist
+    fn with_list<T>(
+        &self,
+        key: &str,
+        f: impl FnOnce(&VecDeque<Bytes>) -> T,
+    ) -> Result<Option<T>, BackendError> {
+        self.expire_if_needed(key);
+        match self.db.get(key).as_deref() {
+            Some(RedisValue::List(list)) => Ok(Some(f(list))),
+            Some(_) => Err(BackendError::WrongType),
+            None => Ok(None),
+        }
+    }
+
+    // write access to a list, the key is created if `create` is set and removed once it is empty
+    fn with_list_mut<T>(
+        &self,
+        key: &str,
+        create: bool,
+        f: impl FnOnce(&mut VecDeque<Bytes>) -> T,
+    ) -> Result<Option<T>, BackendError> {
+        self.expire_if_needed(key);
+        match self.db.entry(key.to_string()) {
+            Entry::Occupied(mut e) => {
+                let (ret, empty) = match e.get_mut() {
+                    RedisValue::List(list) => (f(list), list.is_empty()),
+                    _ => return Err(BackendError::WrongType),
+                };
+                if empty {
+                    e.remove();
+                    self.expires.remove(key);
+                }
+                self.touch_key(key);
+                Ok(Some(ret))
+            }
+            Entry::Vacant(e) if create => {
+                let mut list = VecDeque::new();
+                let ret = f(&mut list);
+                if !list.is_empty() {
+                    e.insert(RedisValue::List(list));
+                    self.touch_key(key);
+                }
+                Ok(Some(ret))
+            }
+            Entry::Vacant(_) => Ok(None),
+        }
+    }
+
+    // returns the length of the list after the push, 0 if `only_existing` and the key is missing
+    pub fn list_push(
+        &self,
+        key: &str,
+        values: Vec<Bytes>,
+        end: ListEnd,
+        only_existing: bool,
+    ) -> Result<usize, BackendError> {
+        let len = self.with_list_mut(key, !only_existing, |list| {
+            for value in values {
+                match end {
+                    ListEnd::Left => list.push_front(value),
+                    ListEnd::Right => list.push_back(value),
+                }
+            }
+            list.len()
+        })?;
+        if len.is_some() {
+            self.signal_key_ready(key);
+        }
+        Ok(len.unwrap_or(0))
+    }
+
+    // pops at most `count` elements, Ok(None) if the key does not exist
+    pub fn list_pop(
+        &self,
+        key: &str,
+        count: usize,
+        end: ListEnd,
+    ) -> Result<Option<Vec<Bytes>>, BackendError> {
+        self.with_list_mut(key, false, |list| {
+            let count = count.min(list.len());
+            match end {
+                ListEnd::Left => list.drain(..count).collect(),
+                ListEnd::Right => list.drain(list.len() - count..).rev().collect(),
+            }
+        })
+    }
+
+    pub fn llen(&self, key: &str) -> Result<usize, BackendError> {
+        Ok(self.with_list(key, |list| list.len())?.unwrap_or(0))
+    }
+
+    pub fn lrange(&self, key: &str, start: i64, stop: i64) -> Result<Vec<Bytes>, BackendError> {
+        let ret = self.with_list(key, |list| match normalize_range(start, stop, list.len()) {
+            Some((start, stop)) => list.range(start..=stop).cloned().collect(),
+            None => vec![],
+        })?;
+        Ok(ret.unwrap_or_default())
+    }
+
+    pub fn lindex(&self, key: &str, index: i64) -> Result<Option<Bytes>, BackendError> {
+        let ret = self.with_list(key, |list| {
+            normalize_index(index, list.len()).and_then(|i| list.get(i).cloned())
+        })?;
+        Ok(ret.flatten())
+    }
+
+    pub fn lset(&self, key: &str, index: i64, value: Bytes) -> Result<(), BackendError> {
+        let ret = self.with_list_mut(key, false, |list| {
+            match normalize_index(index, list.len()).and_then(|i| list.get_mut(i)) {
+                Some(v) => {
+                    *v = value;
+                    Ok(())
+                }
+                None => Err(BackendError::IndexOutOfRange),
+            }
+        })?;
+        ret.unwrap_or(Err(BackendError::NoSuchKey))
+    }
+
+    // returns the length of the list after the insert, -1 if the pivot is missing, 0 if the key is missing
+    pub fn linsert(
+        &self,
+        key: &str,
+        before: bool,
+        pivot: &[u8],
+        value: Bytes,
+    ) -> Result<i64, BackendError> {
+        let ret = self.with_list_mut(key, false, |list| {
+            match list.iter().position(|v| v.as_ref() == pivot) {
+                Some(i) => {
+                    let i = if before { i } else { i + 1 };
+                    list.insert(i, value);
+                    list.len() as i64
+                }
+                None => -1,
+            }
+        })?;
+        Ok(ret.unwrap_or(0))
+    }
+
+    // count > 0 removes from head to tail, count < 0 from tail to head, 0 removes all
+    pub fn lrem(&self, key: &str, count: i64, value: &[u8]) -> Result<usize, BackendError> {
+        let ret = self.with_list_mut(key, false, |list| {
+            let limit = match count {
+                0 => usize::MAX,
+                n => n.unsigned_abs() as usize,
+            };
+            let positions: Vec<usize> = if count < 0 {
+                let mut positions = list
+                    .iter()
+                    .enumerate()
+                    .rev()
+                    .filter(|(_, v)| v.as_ref() == value)
+                    .map(|(i, _)| i)
+                    .take(limit)
+                    .collect::<Vec<usize>>();
+                positions.reverse();
+                positions
+            } else {
+                list.iter()
+                    .enumerate()
+                    .filter(|(_, v)| v.as_ref() == value)
+                    .map(|(i, _)| i)
+                    .take(limit)
+                    .collect()
+            };
+            for i in positions.iter().rev() {
+                list.remove(*i);
+            }
+            positions.len()
+        })?;
+        Ok(ret.unwrap_or(0))
+    }
+
+    pub fn ltrim(&self, key: &str, start: i64, stop: i64) -> Result<(), BackendError> {
+        self.with_list_mut(key, false, |list| {
+            match normalize_range(start, stop, list.len()) {
+                Some((start, stop)) => {
+                    list.truncate(stop + 1);
+                    list.drain(..start);
+                }
+                None => list.clear(),
+            }
+        })?;
+        Ok(())
+    }
+
+    // atomically pops an element from src and pushes it to dst, Ok(None) if src does not exist
+    pub fn lmove(
+        &self,
+        src: &str,
+        dst: &str,
+        from: ListEnd,
+        to: ListEnd,
+    ) -> Result<Option<Bytes>, BackendError> {
+        // make sure the destination can hold the element before popping it
+        self.with_list(dst, |_| ())?;
+        let value = match self.list_pop(src, 1, from)?.and_then(|mut v| v.pop()) {
+            Some(value) => value,
+            None => return Ok(None),
+        };
+        if let Err(e) = self.list_push(dst, vec![value.clone()], to, false) {
+            self.list_push(src, vec![value], from, false)?;
+            return Err(e);
+        }
+        Ok(Some(value))
+    }
+}
+
+fn normalize_index(index: i64, len: usize) -> Option<usize> {
+    let index = if index < 0 { index + len as i64 } else { index };
+    if index < 0 || index >= len as i64 {
+        None
+    } else {
+        Some(index as usize)
+    }
+}
+
+// converts redis start/stop indexes to an inclusive range, None if the range is empty
+fn normalize_range(start: i64, stop: i64, len: usize) -> Option<(usize, usize)> {
+    let len = len as i64;
+    let start = if start < 0 {
+        (start + len).max(0)
+    } else {
+        start
+    };
+    let stop = if stop < 0 {
+        stop + len
+    } else {
+        stop.min(len - 1)
+    };
+    if start > stop || start >= len {
+        None
+    } else {
+        Some((start as usize, stop as usize))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn values(v: &[&'static str]) -> Vec<Bytes> {
+        v.iter().map(|s| Bytes::from_static(s.as_bytes())).collect()
+    }
+
+    #[test]
+    fn test_normalize_range() {
+        assert_eq!(normalize_range(0, -1, 3), Some((0, 2)));
+        assert_eq!(normalize_range(-100, 100, 3), Some((0, 2)));
+        assert_eq!(normalize_range(-2, -1, 3), Some((1, 2)));
+        assert_eq!(normalize_range(2, 1, 3), None);
+        assert_eq!(normalize_range(5, 10, 3), None);
+        assert_eq!(normalize_range(0, -1, 0), None);
+    }
+
+    #[test]
+    fn test_list_push_pop() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        assert_eq!(
+            backend.list_push("l", values(&["a"]), ListEnd::Left, true)?,
+            0
+        );
+        assert!(!backend.exists("l"));
+        assert_eq!(
+            backend.list_push("l", values(&["a", "b"]), ListEnd::Left, false)?,
+            2
+        );
+        assert_eq!(
+            backend.list_push("l", values(&["c"]), ListEnd::Right, false)?,
+            3
+        );
+        assert_eq!(backend.lrange("l", 0, -1)?, values(&["b", "a", "c"]));
+        assert_eq!(
+            backend.list_pop("l", 2, ListEnd::Right)?,
+            Some(values(&["c", "a"]))
+        );
+        assert_eq!(
+            backend.list_pop("l", 5, ListEnd::Left)?,
+            Some(values(&["b"]))
+        );
+        assert!(!backend.exists("l"));
+        assert_eq!(backend.list_pop("l", 1, ListEnd::Left)?, None);
+
+        backend.set("s".to_string(), "v");
+        assert_eq!(
+            backend.list_push("s", values(&["a"]), ListEnd::Left, false),
+            Err(BackendError::WrongType)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_list_modify() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        backend.list_push(
+            "l",
+            values(&["a", "b", "a", "c", "a"]),
+            ListEnd::Right,
+            false,
+        )?;
+        assert_eq!(backend.lrem("l", -2, b"a")?, 2);
+        assert_eq!(backend.lrange("l", 0, -1)?, values(&["a", "b", "c"]));
+        assert_eq!(backend.linsert("l", true, b"b", Bytes::from("x"))?, 4);
+        assert_eq!(backend.linsert("l", false, b"z", Bytes::from("x"))?, -1);
+        assert_eq!(backend.lindex("l", -3)?, Some(Bytes::from("x")));
+        backend.lset("l", -1, Bytes::from("d"))?;
+        assert_eq!(
+            backend.lset("l", 10, Bytes::from("d")),
+            Err(BackendError::IndexOutOfRange)
+        );
+        backend.ltrim("l", 1, -2)?;
+        assert_eq!(backend.lrange("l", 0, -1)?, values(&["x", "b"]));
+        backend.ltrim("l", 5, 10)?;
+        assert!(!backend.exists("l"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_lmove() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        backend.list_push("src", values(&["a", "b", "c"]), ListEnd::Right, false)?;
+        assert_eq!(
+            backend.lmove("src", "src", ListEnd::Left, ListEnd::Right)?,
+            Some(Bytes::from("a"))
+        );
+        assert_eq!(backend.lrange("src", 0, -1)?, values(&["b", "c", "a"]));
+        assert_eq!(
+            backend.lmove("src", "dst", ListEnd::Right, ListEnd::Left)?,
+            Some(Bytes::from("a"))
+        );
+        assert_eq!(backend.lrange("dst", 0, -1)?, values(&["a"]));
+        assert_eq!(
+            backend.lmove("none", "dst", ListEnd::Right, ListEnd::Left)?,
+            None
+        );
+
+        backend.set("s".to_string(), "v");
+        assert_eq!(
+            backend.lmove("src", "s", ListEnd::Right, ListEnd::Left),
+            Err(BackendError::WrongType)
+        );
+        assert_eq!(backend.llen("src")?, 2);
+        Ok(())
+    }
+}
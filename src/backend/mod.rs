@@ -1,16 +1,81 @@
-use crate::resp::RespFrame;
-use dashmap::{DashMap, DashSet};
+mod aof;
+mod blocking;
+mod cluster;
+mod expire;
+mod function;
+mod glob;
+mod hash;
+mod list;
+mod pubsub;
+mod rdb;
+mod replication;
+mod script;
+mod set;
+mod slot;
+mod snapshot;
+mod string;
+mod transaction;
+mod value;
+mod zset;
+
+pub use crate::backend::aof::aof_fsync_cycle;
+use crate::backend::aof::AofState;
+use crate::backend::blocking::Waiter;
+use crate::backend::cluster::ClusterState;
+pub use crate::backend::cluster::{
+    ClusterMessage, ClusterMessageKind, ClusterNodeInfo, ClusterRedirect, NodeAddr, SlotState,
+};
+pub use crate::backend::expire::{active_expire_cycle, now_ms};
+use crate::backend::function::FunctionState;
+pub use crate::backend::function::{
+    library_callback, lua_sandbox, parse_library, FunctionLibrary, RestorePolicy,
+};
+pub use crate::backend::list::ListEnd;
+use crate::backend::pubsub::PubSubState;
+pub use crate::backend::pubsub::Subscriber;
+pub use crate::backend::rdb::load_function_dump;
+use crate::backend::replication::ReplicationState;
+pub use crate::backend::replication::{
+    replication_cycle, PsyncReply, ReplicaSender, ReplicaStream,
+};
+pub use crate::backend::script::sha1_hex;
+use crate::backend::script::ScriptState;
+pub use crate::backend::set::SetOp;
+pub use crate::backend::slot::{key_hash_slot, CLUSTER_SLOTS};
+pub use crate::backend::snapshot::save_cycle;
+use crate::backend::snapshot::SnapshotState;
+pub use crate::backend::string::{SetCondition, SetExpiry, StringValue};
+use crate::backend::transaction::TransactionState;
+pub use crate::backend::value::{BackendError, RedisValue};
+pub use crate::backend::zset::{LexBound, ScoreBound, SortedSet, ZAddOptions, ZRangeBy};
+use crate::config::Config;
+use dashmap::DashMap;
+use std::collections::VecDeque;
 use std::ops::Deref;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
 #[derive(Clone, Debug)]
 pub struct Backend(Arc<BackendInner>);
 
-#[derive(Clone, Debug)]
+#[derive(Debug)]
 pub struct BackendInner {
-    map: DashMap<String, RespFrame>,
-    hmap: DashMap<String, DashMap<String, RespFrame>>,
-    set: DashMap<String, DashSet<String>>,
+    db: DashMap<String, RedisValue>,
+    // absolute deadline of a key, in unix milliseconds
+    expires: DashMap<String, u64>,
+    // clients blocked on a key, in the order they started waiting
+    waiters: DashMap<String, VecDeque<Arc<Waiter>>>,
+    pubsub: PubSubState,
+    transaction: TransactionState,
+    scripts: ScriptState,
+    functions: FunctionState,
+    replication: ReplicationState,
+    cluster: ClusterState,
+    next_waiter_id: AtomicU64,
+    next_client_id: AtomicU64,
+    config: Config,
+    snapshot: SnapshotState,
+    aof: AofState,
 }
 
 impl Deref for Backend {
@@ -29,9 +94,20 @@ impl Default for Backend {
 impl Default for BackendInner {
     fn default() -> Self {
         Self {
-            map: DashMap::new(),
-            hmap: DashMap::new(),
-            set: DashMap::new(),
+            db: DashMap::new(),
+            expires: DashMap::new(),
+            waiters: DashMap::new(),
+            pubsub: PubSubState::default(),
+            transaction: TransactionState::default(),
+            scripts: ScriptState::default(),
+            functions: FunctionState::default(),
+            replication: ReplicationState::default(),
+            cluster: ClusterState::default(),
+            next_waiter_id: AtomicU64::new(0),
+            next_client_id: AtomicU64::new(1),
+            config: Config::default(),
+            snapshot: SnapshotState::default(),
+            aof: AofState::default(),
         }
     }
 }
@@ -40,50 +116,139 @@ impl Backend {
     pub fn new() -> Self {
         Self::default()
     }
-    pub fn get(&self, key: &str) -> Option<RespFrame> {
-        self.map.get(key).map(|v| v.value().clone())
+
+    pub fn with_config(config: Config) -> Self {
+        Self(Arc::new(BackendInner {
+            cluster: ClusterState::new(&config),
+            config,
+            ..BackendInner::default()
+        }))
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    pub fn next_client_id(&self) -> u64 {
+        self.next_client_id.fetch_add(1, Ordering::Relaxed)
     }
 
-    pub fn set(&self, key: String, value: RespFrame) {
-        self.map.insert(key, value);
+    pub fn exists(&self, key: &str) -> bool {
+        self.expire_if_needed(key);
+        self.db.contains_key(key)
     }
 
-    pub fn hget(&self, key: &str, field: &str) -> Option<RespFrame> {
-        self.hmap
-            .get(key)
-            .and_then(|v| v.get(field).map(|v| v.value().clone()))
+    pub fn remove(&self, key: &str) -> bool {
+        self.expires.remove(key);
+        let removed = self.db.remove(key).is_some();
+        if removed {
+            self.touch_key(key);
+        }
+        removed
     }
 
-    pub fn hset(&self, key: String, field: String, value: RespFrame) {
-        let hmap = self.hmap.entry(key).or_default();
-        hmap.insert(field, value);
+    pub fn key_type(&self, key: &str) -> Option<&'static str> {
+        self.expire_if_needed(key);
+        self.db.get(key).map(|v| v.type_name())
     }
 
-    pub fn hgetall(&self, key: &str) -> Option<DashMap<String, RespFrame>> {
-        self.hmap.get(key).map(|v| v.clone())
+    // moves the value and the ttl of src to dst, returns false if dst exists and nx is set
+    pub fn rename(&self, src: &str, dst: &str, nx: bool) -> Result<bool, BackendError> {
+        if !self.exists(src) {
+            return Err(BackendError::NoSuchKey);
+        }
+        if src == dst {
+            return Ok(!nx);
+        }
+        if nx && self.exists(dst) {
+            return Ok(false);
+        }
+        let expire = self.expires.remove(src).map(|(_, at)| at);
+        let (_, value) = self.db.remove(src).ok_or(BackendError::NoSuchKey)?;
+        self.touch_key(src);
+        self.write(dst.to_string(), value, expire);
+        Ok(true)
     }
 
-    pub fn sismember(&self, key: &str, value: &str) -> bool {
-        self.set
-            .get(key)
-            .and_then(|v| v.get(value).map(|_| true))
-            .unwrap_or(false)
+    // copies the value and the ttl of src to dst, returns false if nothing was copied
+    pub fn copy(&self, src: &str, dst: &str, replace: bool) -> bool {
+        if src == dst || (!replace && self.exists(dst)) {
+            return false;
+        }
+        self.expire_if_needed(src);
+        let value = match self.db.get(src) {
+            Some(v) => v.value().clone(),
+            None => return false,
+        };
+        let expire = self.expires.get(src).map(|at| *at.value());
+        self.write(dst.to_string(), value, expire);
+        true
     }
-    pub fn insert_set(&self, key: String, values: Vec<String>) {
-        let set = self.set.get_mut(&key);
-        match set {
-            Some(set) => {
-                for value in values {
-                    (*set).insert(value);
-                }
+
+    fn write(&self, key: String, value: RedisValue, expire: Option<u64>) {
+        match expire {
+            Some(at) => {
+                self.expires.insert(key.clone(), at);
             }
             None => {
-                let new_set = DashSet::new();
-                for value in values {
-                    new_set.insert(value);
-                }
-                self.set.insert(key.to_string(), new_set);
+                self.expires.remove(&key);
             }
         }
+        let is_list = matches!(value, RedisValue::List(_));
+        self.db.insert(key.clone(), value);
+        self.touch_key(&key);
+        if is_list {
+            self.signal_key_ready(&key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bytes::Bytes;
+
+    #[test]
+    fn test_unified_keyspace() {
+        let backend = Backend::new();
+        backend.set("k".to_string(), "v");
+        assert_eq!(backend.key_type("k"), Some("string"));
+        assert_eq!(
+            backend.hset("k", vec![("f".to_string(), Bytes::from("v"))]),
+            Err(BackendError::WrongType)
+        );
+        assert_eq!(backend.sismember("k", "v"), Err(BackendError::WrongType));
+
+        assert!(backend.remove("k"));
+        assert_eq!(backend.key_type("k"), None);
+        backend
+            .insert_set("k".to_string(), vec!["v".to_string()])
+            .unwrap();
+        assert_eq!(backend.key_type("k"), Some("set"));
+        assert_eq!(backend.get("k"), Err(BackendError::WrongType));
+    }
+
+    #[test]
+    fn test_rename_and_copy() {
+        let backend = Backend::new();
+        assert_eq!(
+            backend.rename("a", "b", false),
+            Err(BackendError::NoSuchKey)
+        );
+
+        backend.set("a".to_string(), "1");
+        backend.expire_at("a", now_ms() + 10_000);
+        backend.set("b".to_string(), "2");
+        assert_eq!(backend.rename("a", "b", true), Ok(false));
+        assert_eq!(backend.rename("a", "b", false), Ok(true));
+        assert!(!backend.exists("a"));
+        assert_eq!(backend.get("b"), Ok(Some(Bytes::from("1"))));
+        assert!(matches!(backend.expire_time("b"), Some(Some(_))));
+
+        assert!(!backend.copy("b", "b", true));
+        assert!(backend.copy("b", "c", false));
+        assert!(!backend.copy("b", "c", false));
+        assert_eq!(backend.get("c"), Ok(Some(Bytes::from("1"))));
+        assert!(matches!(backend.expire_time("c"), Some(Some(_))));
     }
 }
@@ -0,0 +1,296 @@
+use crate::backend::glob::string_match;
+use crate::backend::Backend;
+use crate::resp::{BulkString, RespFrame, RespPush};
+use bytes::Bytes;
+use dashmap::DashMap;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::mpsc::{channel, Receiver, Sender};
+use tokio::sync::Notify;
+
+// the queue of a connection, messages are written out between its requests; a connection that
+// lets it fill up is told to close rather than having it grow without end
+#[derive(Debug, Clone)]
+pub struct Subscriber {
+    sender: Sender<RespFrame>,
+    overflow: Arc<Notify>,
+}
+
+// subscribers of every channel and pattern, by client id; sharded channels are a namespace of
+// their own, apart from the classic ones
+#[derive(Debug, Default)]
+pub struct PubSubState {
+    channels: DashMap<Bytes, HashMap<u64, Subscriber>>,
+    patterns: DashMap<Bytes, HashMap<u64, Subscriber>>,
+    shard_channels: DashMap<Bytes, HashMap<u64, Subscriber>>,
+}
+
+impl Subscriber {
+    pub fn new(limit: usize) -> (Self, Receiver<RespFrame>) {
+        let (sender, messages) = channel(limit.max(1));
+        let overflow = Arc::new(Notify::new());
+        (Self { sender, overflow }, messages)
+    }
+
+    // false when the message was not queued, the connection is gone or too far behind
+    pub fn send(&self, message: RespFrame) -> bool {
+        match self.sender.try_send(message) {
+            Ok(()) => true,
+            Err(TrySendError::Full(_)) => {
+                self.overflow.notify_one();
+                false
+            }
+            Err(TrySendError::Closed(_)) => false,
+        }
+    }
+
+    // resolves once a message did not fit in the queue
+    pub async fn overflowed(&self) {
+        self.overflow.notified().await
+    }
+}
+
+impl Backend {
+    pub fn subscribe(&self, channel: Bytes, client_id: u64, subscriber: &Subscriber) {
+        add_subscriber(&self.pubsub.channels, channel, client_id, subscriber);
+    }
+
+    pub fn unsubscribe(&self, channel: &[u8], client_id: u64) {
+        remove_subscriber(&self.pubsub.channels, channel, client_id);
+    }
+
+    pub fn psubscribe(&self, pattern: Bytes, client_id: u64, subscriber: &Subscriber) {
+        add_subscriber(&self.pubsub.patterns, pattern, client_id, subscriber);
+    }
+
+    pub fn punsubscribe(&self, pattern: &[u8], client_id: u64) {
+        remove_subscriber(&self.pubsub.patterns, pattern, client_id);
+    }
+
+    pub fn ssubscribe(&self, channel: Bytes, client_id: u64, subscriber: &Subscriber) {
+        add_subscriber(&self.pubsub.shard_channels, channel, client_id, subscriber);
+    }
+
+    pub fn sunsubscribe(&self, channel: &[u8], client_id: u64) {
+        remove_subscriber(&self.pubsub.shard_channels, channel, client_id);
+    }
+
+    // queues the message to the subscribers of the channel and of every matching pattern,
+    // returns how many clients received it
+    pub fn publish(&self, channel: Bytes, message: Bytes) -> usize {
+        let mut receivers = 0;
+        if let Some(subscribers) = self.pubsub.channels.get(&channel) {
+            let push = RespPush::new([
+                BulkString::new("message").into(),
+                BulkString::from(channel.clone()).into(),
+                BulkString::from(message.clone()).into(),
+            ]);
+            for subscriber in subscribers.values() {
+                if subscriber.send(push.clone().into()) {
+                    receivers += 1;
+                }
+            }
+        }
+        for entry in self.pubsub.patterns.iter() {
+            if !string_match(entry.key(), &channel) {
+                continue;
+            }
+            let push = RespPush::new([
+                BulkString::new("pmessage").into(),
+                BulkString::from(entry.key().clone()).into(),
+                BulkString::from(channel.clone()).into(),
+                BulkString::from(message.clone()).into(),
+            ]);
+            for subscriber in entry.value().values() {
+                if subscriber.send(push.clone().into()) {
+                    receivers += 1;
+                }
+            }
+        }
+        receivers
+    }
+
+    // sharded channels are not matched by patterns
+    pub fn spublish(&self, channel: Bytes, message: Bytes) -> usize {
+        let Some(subscribers) = self.pubsub.shard_channels.get(&channel) else {
+            return 0;
+        };
+        let push = RespPush::new([
+            BulkString::new("smessage").into(),
+            BulkString::from(channel.clone()).into(),
+            BulkString::from(message).into(),
+        ]);
+        subscribers
+            .values()
+            .filter(|subscriber| subscriber.send(push.clone().into()))
+            .count()
+    }
+
+    // channels with at least one subscriber, optionally only those matching the pattern
+    pub fn pubsub_channels(&self, pattern: Option<&[u8]>) -> Vec<Bytes> {
+        active_names(&self.pubsub.channels, pattern)
+    }
+
+    pub fn pubsub_numsub(&self, channel: &[u8]) -> usize {
+        subscriber_count(&self.pubsub.channels, channel)
+    }
+
+    pub fn pubsub_shardchannels(&self, pattern: Option<&[u8]>) -> Vec<Bytes> {
+        active_names(&self.pubsub.shard_channels, pattern)
+    }
+
+    pub fn pubsub_shardnumsub(&self, channel: &[u8]) -> usize {
+        subscriber_count(&self.pubsub.shard_channels, channel)
+    }
+
+    // number of distinct patterns subscribed to by any client
+    pub fn pubsub_numpat(&self) -> usize {
+        self.pubsub.patterns.len()
+    }
+}
+
+fn active_names(
+    registry: &DashMap<Bytes, HashMap<u64, Subscriber>>,
+    pattern: Option<&[u8]>,
+) -> Vec<Bytes> {
+    registry
+        .iter()
+        .map(|entry| entry.key().clone())
+        .filter(|name| pattern.is_none_or(|pattern| string_match(pattern, name)))
+        .collect()
+}
+
+fn subscriber_count(registry: &DashMap<Bytes, HashMap<u64, Subscriber>>, name: &[u8]) -> usize {
+    registry
+        .get(name)
+        .map_or(0, |subscribers| subscribers.len())
+}
+
+fn add_subscriber(
+    registry: &DashMap<Bytes, HashMap<u64, Subscriber>>,
+    name: Bytes,
+    client_id: u64,
+    subscriber: &Subscriber,
+) {
+    registry
+        .entry(name)
+        .or_default()
+        .insert(client_id, subscriber.clone());
+}
+
+// drops the entry of a channel or pattern once its last subscriber leaves
+fn remove_subscriber(
+    registry: &DashMap<Bytes, HashMap<u64, Subscriber>>,
+    name: &[u8],
+    client_id: u64,
+) {
+    if let Some(mut subscribers) = registry.get_mut(name) {
+        subscribers.remove(&client_id);
+    }
+    registry.remove_if(name, |_, subscribers| subscribers.is_empty());
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_publish() {
+        let backend = Backend::new();
+        let (tx1, mut rx1) = Subscriber::new(16);
+        let (tx2, mut rx2) = Subscriber::new(16);
+        backend.subscribe(Bytes::from("news"), 1, &tx1);
+        backend.psubscribe(Bytes::from("n*"), 2, &tx2);
+        backend.psubscribe(Bytes::from("x*"), 2, &tx2);
+
+        assert_eq!(backend.publish(Bytes::from("news"), Bytes::from("hi")), 2);
+        assert_eq!(
+            rx1.try_recv().unwrap(),
+            RespPush::new([
+                BulkString::new("message").into(),
+                BulkString::new("news").into(),
+                BulkString::new("hi").into(),
+            ])
+            .into()
+        );
+        assert_eq!(
+            rx2.try_recv().unwrap(),
+            RespPush::new([
+                BulkString::new("pmessage").into(),
+                BulkString::new("n*").into(),
+                BulkString::new("news").into(),
+                BulkString::new("hi").into(),
+            ])
+            .into()
+        );
+        assert!(rx2.try_recv().is_err());
+
+        assert_eq!(backend.pubsub_channels(None), vec![Bytes::from("news")]);
+        assert!(backend.pubsub_channels(Some(b"x*")).is_empty());
+        assert_eq!(backend.pubsub_numsub(b"news"), 1);
+        assert_eq!(backend.pubsub_numpat(), 2);
+
+        backend.unsubscribe(b"news", 1);
+        backend.punsubscribe(b"n*", 2);
+        assert_eq!(backend.publish(Bytes::from("news"), Bytes::from("hi")), 0);
+        assert_eq!(backend.pubsub_numsub(b"news"), 0);
+        assert!(backend.pubsub_channels(None).is_empty());
+        assert_eq!(backend.pubsub_numpat(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_subscriber_overflow() {
+        let backend = Backend::new();
+        let (subscriber, mut messages) = Subscriber::new(2);
+        backend.subscribe(Bytes::from("news"), 1, &subscriber);
+        assert_eq!(backend.publish(Bytes::from("news"), Bytes::from("1")), 1);
+        assert_eq!(backend.publish(Bytes::from("news"), Bytes::from("2")), 1);
+        // the queue is full, the message is not delivered and the connection is told to close
+        assert_eq!(backend.publish(Bytes::from("news"), Bytes::from("3")), 0);
+        let overflowed = tokio::time::timeout(Duration::from_secs(1), subscriber.overflowed());
+        assert!(overflowed.await.is_ok());
+        assert!(messages.try_recv().is_ok());
+    }
+
+    #[test]
+    fn test_spublish() {
+        let backend = Backend::new();
+        let (tx1, mut rx1) = Subscriber::new(16);
+        let (tx2, mut rx2) = Subscriber::new(16);
+        backend.ssubscribe(Bytes::from("orders"), 1, &tx1);
+        backend.subscribe(Bytes::from("orders"), 2, &tx2);
+        backend.psubscribe(Bytes::from("*"), 2, &tx2);
+
+        // the namespaces are apart, a sharded message only reaches sharded subscribers
+        assert_eq!(
+            backend.spublish(Bytes::from("orders"), Bytes::from("hi")),
+            1
+        );
+        assert_eq!(
+            rx1.try_recv().unwrap(),
+            RespPush::new([
+                BulkString::new("smessage").into(),
+                BulkString::new("orders").into(),
+                BulkString::new("hi").into(),
+            ])
+            .into()
+        );
+        assert!(rx2.try_recv().is_err());
+        assert_eq!(backend.publish(Bytes::from("orders"), Bytes::from("hi")), 2);
+        assert!(rx1.try_recv().is_err());
+
+        assert_eq!(
+            backend.pubsub_shardchannels(Some(b"ord*")),
+            vec![Bytes::from("orders")]
+        );
+        assert_eq!(backend.pubsub_shardnumsub(b"orders"), 1);
+        backend.sunsubscribe(b"orders", 1);
+        assert_eq!(backend.pubsub_shardnumsub(b"orders"), 0);
+        assert_eq!(
+            backend.spublish(Bytes::from("orders"), Bytes::from("hi")),
+            0
+        );
+    }
+}
@@ -0,0 +1,995 @@
+use crate::backend::snapshot::KeyspaceSnapshot;
+use crate::backend::{
+    now_ms, parse_library, Backend, FunctionLibrary, RedisValue, RestorePolicy, SortedSet,
+    StringValue,
+};
+use bytes::Bytes;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+// the redis dump format, see https://rdb.fnordig.de/file_format.html
+const RDB_MAGIC: &[u8] = b"REDIS";
+// 10 added the function libraries
+const RDB_VERSION: u32 = 10;
+// 11 is what redis 7.2 writes, its only additions are encodings that are read below
+const RDB_MAX_LOAD_VERSION: u32 = 11;
+
+const RDB_TYPE_STRING: u8 = 0;
+const RDB_TYPE_LIST: u8 = 1;
+const RDB_TYPE_SET: u8 = 2;
+const RDB_TYPE_ZSET: u8 = 3;
+const RDB_TYPE_HASH: u8 = 4;
+const RDB_TYPE_ZSET_2: u8 = 5;
+const RDB_TYPE_MODULE: u8 = 6;
+const RDB_TYPE_MODULE_2: u8 = 7;
+// the compact encodings redis uses for small values, one blob per value or per list node
+const RDB_TYPE_HASH_ZIPMAP: u8 = 9;
+const RDB_TYPE_LIST_ZIPLIST: u8 = 10;
+const RDB_TYPE_SET_INTSET: u8 = 11;
+const RDB_TYPE_ZSET_ZIPLIST: u8 = 12;
+const RDB_TYPE_HASH_ZIPLIST: u8 = 13;
+const RDB_TYPE_LIST_QUICKLIST: u8 = 14;
+const RDB_TYPE_STREAM_LISTPACKS: u8 = 15;
+const RDB_TYPE_HASH_LISTPACK: u8 = 16;
+const RDB_TYPE_ZSET_LISTPACK: u8 = 17;
+const RDB_TYPE_LIST_QUICKLIST_2: u8 = 18;
+const RDB_TYPE_STREAM_LISTPACKS_2: u8 = 19;
+const RDB_TYPE_SET_LISTPACK: u8 = 20;
+const RDB_TYPE_STREAM_LISTPACKS_3: u8 = 21;
+
+// a node of a quicklist 2 holding a single large element rather than a listpack
+const QUICKLIST_NODE_PLAIN: u64 = 1;
+
+const RDB_OPCODE_FUNCTION2: u8 = 245;
+const RDB_OPCODE_MODULE_AUX: u8 = 247;
+const RDB_OPCODE_IDLE: u8 = 248;
+const RDB_OPCODE_FREQ: u8 = 249;
+const RDB_OPCODE_AUX: u8 = 250;
+const RDB_OPCODE_RESIZEDB: u8 = 251;
+const RDB_OPCODE_EXPIRETIME_MS: u8 = 252;
+const RDB_OPCODE_EXPIRETIME: u8 = 253;
+const RDB_OPCODE_SELECTDB: u8 = 254;
+const RDB_OPCODE_EOF: u8 = 255;
+
+// the two high bits of a length byte select its encoding
+const RDB_6BITLEN: u8 = 0;
+const RDB_14BITLEN: u8 = 1;
+const RDB_32BITLEN: u8 = 0x80;
+const RDB_64BITLEN: u8 = 0x81;
+const RDB_ENCVAL: u8 = 3;
+
+const RDB_ENC_INT8: u64 = 0;
+const RDB_ENC_INT16: u64 = 1;
+const RDB_ENC_INT32: u64 = 2;
+const RDB_ENC_LZF: u64 = 3;
+
+#[derive(Error, Debug)]
+pub enum RdbError {
+    #[error("wrong signature trying to load DB from file")]
+    InvalidHeader,
+    #[error("can't handle RDB format version {0}")]
+    UnsupportedVersion(u32),
+    #[error("unexpected end of file reading RDB")]
+    UnexpectedEof,
+    #[error("unknown RDB encoding type {0}")]
+    UnsupportedType(u8),
+    #[error("can't load {0}, they are not supported")]
+    UnsupportedValue(&'static str),
+    #[error("invalid string encoding")]
+    InvalidEncoding,
+    #[error("only database 0 is supported, found database {0}")]
+    UnsupportedDb(u64),
+    #[error("string is not valid utf-8")]
+    InvalidUtf8,
+    // keys are kept as strings, a dump of a server that stored binary keys can't be loaded
+    #[error("key {0:?} is not valid utf-8, only utf-8 keys can be loaded")]
+    NonUtf8Key(Bytes),
+    #[error("wrong RDB checksum")]
+    ChecksumMismatch,
+    #[error("failed loading the function library: {0}")]
+    InvalidFunction(String),
+}
+
+// serializes a snapshot of the keyspace
+pub(crate) fn encode_rdb(snapshot: &KeyspaceSnapshot) -> Vec<u8> {
+    let mut w = RdbWriter::default();
+    w.buf.extend_from_slice(RDB_MAGIC);
+    w.buf
+        .extend_from_slice(format!("{:04}", RDB_VERSION).as_bytes());
+    w.aux("redis-ver", env!("CARGO_PKG_VERSION").as_bytes());
+    w.aux("redis-bits", b"64");
+    let ctime = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    w.aux("ctime", ctime.to_string().as_bytes());
+    w.functions(&snapshot.libraries);
+
+    w.buf.push(RDB_OPCODE_SELECTDB);
+    w.length(0);
+    w.buf.push(RDB_OPCODE_RESIZEDB);
+    w.length(snapshot.keys.len() as u64);
+    let expires = snapshot.keys.iter().filter(|(_, _, at)| at.is_some());
+    w.length(expires.count() as u64);
+
+    for (key, value, expire) in &snapshot.keys {
+        if let Some(at) = expire {
+            w.buf.push(RDB_OPCODE_EXPIRETIME_MS);
+            w.buf.extend_from_slice(&at.to_le_bytes());
+        }
+        w.object(key, value);
+    }
+
+    w.buf.push(RDB_OPCODE_EOF);
+    let checksum = crc64(0, &w.buf);
+    w.buf.extend_from_slice(&checksum.to_le_bytes());
+    w.buf
+}
+
+impl Backend {
+    // serializes the keyspace, keys that already expired are left out
+    pub fn dump_rdb(&self) -> Vec<u8> {
+        let snapshot = {
+            let _barrier = self.write_barrier();
+            self.clone_keyspace()
+        };
+        encode_rdb(&snapshot)
+    }
+
+    // replaces the keyspace with the content of a dump, returns the number of keys loaded
+    pub fn load_rdb(&self, data: &[u8]) -> Result<usize, RdbError> {
+        let mut r = RdbReader { data, pos: 0 };
+        if r.bytes(RDB_MAGIC.len())? != RDB_MAGIC {
+            return Err(RdbError::InvalidHeader);
+        }
+        let version = std::str::from_utf8(r.bytes(4)?)
+            .ok()
+            .and_then(|v| v.parse::<u32>().ok())
+            .ok_or(RdbError::InvalidHeader)?;
+        if !(1..=RDB_MAX_LOAD_VERSION).contains(&version) {
+            return Err(RdbError::UnsupportedVersion(version));
+        }
+
+        let mut entries = Vec::new();
+        let mut libraries = Vec::new();
+        let mut expire = None;
+        loop {
+            let opcode = r.u8()?;
+            match opcode {
+                RDB_OPCODE_EOF => break,
+                RDB_OPCODE_FUNCTION2 => libraries.push(r.library()?),
+                RDB_OPCODE_MODULE_AUX => return Err(RdbError::UnsupportedValue("modules")),
+                RDB_OPCODE_AUX => {
+                    r.string()?;
+                    r.string()?;
+                }
+                RDB_OPCODE_SELECTDB => {
+                    let db = r.length()?;
+                    if db != 0 {
+                        return Err(RdbError::UnsupportedDb(db));
+                    }
+                }
+                RDB_OPCODE_RESIZEDB => {
+                    r.length()?;
+                    r.length()?;
+                }
+                RDB_OPCODE_EXPIRETIME_MS => {
+                    expire = Some(u64::from_le_bytes(r.array()?));
+                }
+                RDB_OPCODE_EXPIRETIME => {
+                    expire = Some(u32::from_le_bytes(r.array()?) as u64 * 1000);
+                }
+                RDB_OPCODE_IDLE => {
+                    r.length()?;
+                }
+                RDB_OPCODE_FREQ => {
+                    r.u8()?;
+                }
+                value_type => {
+                    let key = r.string()?;
+                    let key =
+                        String::from_utf8(key.to_vec()).map_err(|_| RdbError::NonUtf8Key(key))?;
+                    let value = r.object(value_type)?;
+                    entries.push((key, value, expire.take()));
+                }
+            }
+        }
+        if version >= 5 {
+            let end = r.pos;
+            let expected = u64::from_le_bytes(r.array()?);
+            // a zero checksum means the file was saved with checksums disabled
+            if expected != 0 && expected != crc64(0, &data[..end]) {
+                return Err(RdbError::ChecksumMismatch);
+            }
+        }
+
+        self.function_restore(libraries, RestorePolicy::Flush)
+            .map_err(|e| RdbError::InvalidFunction(e.to_string()))?;
+        self.db.clear();
+        self.expires.clear();
+        let now = now_ms();
+        let mut loaded = 0;
+        for (key, value, expire) in entries {
+            match expire {
+                Some(at) if at <= now => continue,
+                Some(at) => {
+                    self.expires.insert(key.clone(), at);
+                }
+                None => {}
+            }
+            self.db.insert(key, value);
+            loaded += 1;
+        }
+        // the whole dataset changed under the clients watching keys or blocked on them
+        self.touch_all_keys();
+        let blocked = self
+            .waiters
+            .iter()
+            .map(|entry| entry.key().clone())
+            .collect::<Vec<String>>();
+        for key in blocked.iter().filter(|key| self.db.contains_key(*key)) {
+            self.signal_key_ready(key);
+        }
+        Ok(loaded)
+    }
+
+    // the payload of FUNCTION DUMP: the libraries the way a dump stores them, followed by the
+    // format version and a checksum
+    pub fn dump_functions(&self) -> Vec<u8> {
+        let mut w = RdbWriter::default();
+        w.functions(&self.function_libraries());
+        w.buf.extend_from_slice(&(RDB_VERSION as u16).to_le_bytes());
+        let checksum = crc64(0, &w.buf);
+        w.buf.extend_from_slice(&checksum.to_le_bytes());
+        w.buf
+    }
+}
+
+// reads back the libraries of a FUNCTION DUMP payload
+pub fn load_function_dump(data: &[u8]) -> Result<Vec<FunctionLibrary>, RdbError> {
+    let body_len = data.len().checked_sub(10).ok_or(RdbError::UnexpectedEof)?;
+    let version = u16::from_le_bytes([data[body_len], data[body_len + 1]]) as u32;
+    if version > RDB_VERSION {
+        return Err(RdbError::UnsupportedVersion(version));
+    }
+    let mut checksum = [0u8; 8];
+    checksum.copy_from_slice(&data[body_len + 2..]);
+    if u64::from_le_bytes(checksum) != crc64(0, &data[..body_len + 2]) {
+        return Err(RdbError::ChecksumMismatch);
+    }
+    let mut r = RdbReader {
+        data: &data[..body_len],
+        pos: 0,
+    };
+    let mut libraries = Vec::new();
+    while r.pos < body_len {
+        match r.u8()? {
+            RDB_OPCODE_FUNCTION2 => libraries.push(r.library()?),
+            opcode => return Err(RdbError::UnsupportedType(opcode)),
+        }
+    }
+    Ok(libraries)
+}
+
+#[derive(Default)]
+struct RdbWriter {
+    buf: Vec<u8>,
+}
+
+impl RdbWriter {
+    fn length(&mut self, len: u64) {
+        if len < 1 << 6 {
+            self.buf.push((RDB_6BITLEN << 6) | len as u8);
+        } else if len < 1 << 14 {
+            self.buf.push((RDB_14BITLEN << 6) | (len >> 8) as u8);
+            self.buf.push(len as u8);
+        } else if len <= u32::MAX as u64 {
+            self.buf.push(RDB_32BITLEN);
+            self.buf.extend_from_slice(&(len as u32).to_be_bytes());
+        } else {
+            self.buf.push(RDB_64BITLEN);
+            self.buf.extend_from_slice(&len.to_be_bytes());
+        }
+    }
+
+    fn string(&mut self, s: &[u8]) {
+        self.length(s.len() as u64);
+        self.buf.extend_from_slice(s);
+    }
+
+    // integers that fit in 32 bits use the compact integer encoding
+    fn int_string(&mut self, i: i64) {
+        let special = (RDB_ENCVAL << 6) as u64;
+        if let Ok(i) = i8::try_from(i) {
+            self.buf.push((special | RDB_ENC_INT8) as u8);
+            self.buf.extend_from_slice(&i.to_le_bytes());
+        } else if let Ok(i) = i16::try_from(i) {
+            self.buf.push((special | RDB_ENC_INT16) as u8);
+            self.buf.extend_from_slice(&i.to_le_bytes());
+        } else if let Ok(i) = i32::try_from(i) {
+            self.buf.push((special | RDB_ENC_INT32) as u8);
+            self.buf.extend_from_slice(&i.to_le_bytes());
+        } else {
+            self.string(i.to_string().as_bytes());
+        }
+    }
+
+    // only the code of a library is stored, loading it registers its functions again
+    fn functions(&mut self, libraries: &[Arc<FunctionLibrary>]) {
+        for library in libraries {
+            self.buf.push(RDB_OPCODE_FUNCTION2);
+            self.string(library.code.as_bytes());
+        }
+    }
+
+    fn aux(&mut self, key: &str, value: &[u8]) {
+        self.buf.push(RDB_OPCODE_AUX);
+        self.string(key.as_bytes());
+        self.string(value);
+    }
+
+    fn object(&mut self, key: &str, value: &RedisValue) {
+        match value {
+            RedisValue::String(s) => {
+                self.buf.push(RDB_TYPE_STRING);
+                self.string(key.as_bytes());
+                match s {
+                    StringValue::Int(i) => self.int_string(*i),
+                    StringValue::Raw(b) => self.string(b),
+                }
+            }
+            RedisValue::List(list) => {
+                self.buf.push(RDB_TYPE_LIST);
+                self.string(key.as_bytes());
+                self.length(list.len() as u64);
+                for item in list {
+                    self.string(item);
+                }
+            }
+            RedisValue::Set(set) => {
+                self.buf.push(RDB_TYPE_SET);
+                self.string(key.as_bytes());
+                self.length(set.len() as u64);
+                for member in set {
+                    self.string(member.as_bytes());
+                }
+            }
+            RedisValue::Hash(hmap) => {
+                self.buf.push(RDB_TYPE_HASH);
+                self.string(key.as_bytes());
+                self.length(hmap.len() as u64);
+                for (field, value) in hmap {
+                    self.string(field.as_bytes());
+                    self.string(value);
+                }
+            }
+            RedisValue::ZSet(zset) => {
+                self.buf.push(RDB_TYPE_ZSET_2);
+                self.string(key.as_bytes());
+                self.length(zset.len() as u64);
+                // reversed, so loading inserts every member at the head like redis does
+                for (member, score) in zset.iter().rev() {
+                    self.string(member.as_bytes());
+                    self.buf.extend_from_slice(&score.to_le_bytes());
+                }
+            }
+        }
+    }
+}
+
+struct RdbReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> RdbReader<'a> {
+    fn bytes(&mut self, n: usize) -> Result<&'a [u8], RdbError> {
+        let end = self.pos.checked_add(n).ok_or(RdbError::UnexpectedEof)?;
+        let data = self
+            .data
+            .get(self.pos..end)
+            .ok_or(RdbError::UnexpectedEof)?;
+        self.pos = end;
+        Ok(data)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], RdbError> {
+        let mut buf = [0u8; N];
+        buf.copy_from_slice(self.bytes(N)?);
+        Ok(buf)
+    }
+
+    fn u8(&mut self) -> Result<u8, RdbError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    // returns the length, or the special encoding of the string that follows
+    fn length_or_encoding(&mut self) -> Result<(u64, bool), RdbError> {
+        let first = self.u8()?;
+        match first >> 6 {
+            RDB_6BITLEN => Ok(((first & 0x3f) as u64, false)),
+            RDB_14BITLEN => Ok(((((first & 0x3f) as u64) << 8) | self.u8()? as u64, false)),
+            RDB_ENCVAL => Ok(((first & 0x3f) as u64, true)),
+            _ => match first {
+                RDB_32BITLEN => Ok((u32::from_be_bytes(self.array()?) as u64, false)),
+                RDB_64BITLEN => Ok((u64::from_be_bytes(self.array()?), false)),
+                _ => Err(RdbError::InvalidEncoding),
+            },
+        }
+    }
+
+    fn length(&mut self) -> Result<u64, RdbError> {
+        match self.length_or_encoding()? {
+            (len, false) => Ok(len),
+            (_, true) => Err(RdbError::InvalidEncoding),
+        }
+    }
+
+    fn string(&mut self) -> Result<Bytes, RdbError> {
+        let (len, encoded) = self.length_or_encoding()?;
+        if !encoded {
+            return Ok(Bytes::copy_from_slice(self.bytes(len as usize)?));
+        }
+        let int = match len {
+            RDB_ENC_INT8 => i8::from_le_bytes(self.array()?) as i64,
+            RDB_ENC_INT16 => i16::from_le_bytes(self.array()?) as i64,
+            RDB_ENC_INT32 => i32::from_le_bytes(self.array()?) as i64,
+            RDB_ENC_LZF => {
+                let compressed_len = self.length()? as usize;
+                let len = self.length()? as usize;
+                let compressed = self.bytes(compressed_len)?;
+                return lzf_decompress(compressed, len).map(Bytes::from);
+            }
+            _ => return Err(RdbError::InvalidEncoding),
+        };
+        Ok(Bytes::from(int.to_string()))
+    }
+
+    fn utf8_string(&mut self) -> Result<String, RdbError> {
+        String::from_utf8(self.string()?.to_vec()).map_err(|_| RdbError::InvalidUtf8)
+    }
+
+    fn library(&mut self) -> Result<FunctionLibrary, RdbError> {
+        parse_library(&self.utf8_string()?).map_err(RdbError::InvalidFunction)
+    }
+
+    // the score of the old zset type, a length prefixed ascii number
+    fn string_double(&mut self) -> Result<f64, RdbError> {
+        match self.u8()? {
+            253 => Ok(f64::NAN),
+            254 => Ok(f64::INFINITY),
+            255 => Ok(f64::NEG_INFINITY),
+            len => std::str::from_utf8(self.bytes(len as usize)?)
+                .ok()
+                .and_then(|s| s.parse::<f64>().ok())
+                .ok_or(RdbError::InvalidEncoding),
+        }
+    }
+
+    fn object(&mut self, value_type: u8) -> Result<RedisValue, RdbError> {
+        match value_type {
+            RDB_TYPE_STRING => Ok(RedisValue::String(StringValue::from(self.string()?))),
+            RDB_TYPE_LIST => {
+                let len = self.length()?;
+                let mut list = VecDeque::new();
+                for _ in 0..len {
+                    list.push_back(self.string()?);
+                }
+                Ok(RedisValue::List(list))
+            }
+            RDB_TYPE_SET => {
+                let len = self.length()?;
+                let mut set = HashSet::new();
+                for _ in 0..len {
+                    set.insert(self.utf8_string()?);
+                }
+                Ok(RedisValue::Set(set))
+            }
+            RDB_TYPE_HASH => {
+                let len = self.length()?;
+                let mut hmap = HashMap::new();
+                for _ in 0..len {
+                    let field = self.utf8_string()?;
+                    hmap.insert(field, self.string()?);
+                }
+                Ok(RedisValue::Hash(hmap))
+            }
+            RDB_TYPE_ZSET | RDB_TYPE_ZSET_2 => {
+                let len = self.length()?;
+                let mut zset = SortedSet::default();
+                for _ in 0..len {
+                    let member = self.utf8_string()?;
+                    let score = if value_type == RDB_TYPE_ZSET {
+                        self.string_double()?
+                    } else {
+                        f64::from_le_bytes(self.array()?)
+                    };
+                    zset.insert(member, score);
+                }
+                Ok(RedisValue::ZSet(zset))
+            }
+            RDB_TYPE_LIST_ZIPLIST => Ok(RedisValue::List(ziplist_entries(&self.string()?)?.into())),
+            RDB_TYPE_LIST_QUICKLIST | RDB_TYPE_LIST_QUICKLIST_2 => {
+                let nodes = self.length()?;
+                let mut list = VecDeque::new();
+                for _ in 0..nodes {
+                    if value_type == RDB_TYPE_LIST_QUICKLIST {
+                        list.extend(ziplist_entries(&self.string()?)?);
+                    } else if self.length()? == QUICKLIST_NODE_PLAIN {
+                        list.push_back(self.string()?);
+                    } else {
+                        list.extend(listpack_entries(&self.string()?)?);
+                    }
+                }
+                Ok(RedisValue::List(list))
+            }
+            RDB_TYPE_SET_INTSET | RDB_TYPE_SET_LISTPACK => {
+                let blob = self.string()?;
+                let members = if value_type == RDB_TYPE_SET_INTSET {
+                    intset_members(&blob)?
+                } else {
+                    listpack_entries(&blob)?
+                };
+                let set = members
+                    .into_iter()
+                    .map(into_utf8)
+                    .collect::<Result<HashSet<String>, RdbError>>()?;
+                Ok(RedisValue::Set(set))
+            }
+            RDB_TYPE_HASH_ZIPLIST | RDB_TYPE_HASH_LISTPACK => {
+                let blob = self.string()?;
+                let entries = if value_type == RDB_TYPE_HASH_ZIPLIST {
+                    ziplist_entries(&blob)?
+                } else {
+                    listpack_entries(&blob)?
+                };
+                let mut hmap = HashMap::new();
+                for (field, value) in pairs(entries)? {
+                    hmap.insert(into_utf8(field)?, value);
+                }
+                Ok(RedisValue::Hash(hmap))
+            }
+            RDB_TYPE_ZSET_ZIPLIST | RDB_TYPE_ZSET_LISTPACK => {
+                let blob = self.string()?;
+                let entries = if value_type == RDB_TYPE_ZSET_ZIPLIST {
+                    ziplist_entries(&blob)?
+                } else {
+                    listpack_entries(&blob)?
+                };
+                let mut zset = SortedSet::default();
+                for (member, score) in pairs(entries)? {
+                    let score = std::str::from_utf8(&score)
+                        .ok()
+                        .and_then(|s| s.parse::<f64>().ok())
+                        .ok_or(RdbError::InvalidEncoding)?;
+                    zset.insert(into_utf8(member)?, score);
+                }
+                Ok(RedisValue::ZSet(zset))
+            }
+            RDB_TYPE_STREAM_LISTPACKS
+            | RDB_TYPE_STREAM_LISTPACKS_2
+            | RDB_TYPE_STREAM_LISTPACKS_3 => Err(RdbError::UnsupportedValue("streams")),
+            RDB_TYPE_MODULE | RDB_TYPE_MODULE_2 => Err(RdbError::UnsupportedValue("modules")),
+            // only written by redis before 2.6
+            RDB_TYPE_HASH_ZIPMAP => Err(RdbError::UnsupportedValue("zipmap encoded hashes")),
+            _ => Err(RdbError::UnsupportedType(value_type)),
+        }
+    }
+}
+
+fn into_utf8(data: Bytes) -> Result<String, RdbError> {
+    String::from_utf8(data.to_vec()).map_err(|_| RdbError::InvalidUtf8)
+}
+
+fn int_entry(i: i64) -> Bytes {
+    Bytes::from(i.to_string())
+}
+
+// the field and value, or member and score, pairs of a flattened hash or zset
+fn pairs(entries: Vec<Bytes>) -> Result<impl Iterator<Item = (Bytes, Bytes)>, RdbError> {
+    if !entries.len().is_multiple_of(2) {
+        return Err(RdbError::InvalidEncoding);
+    }
+    let mut entries = entries.into_iter();
+    Ok(std::iter::from_fn(move || {
+        Some((entries.next()?, entries.next()?))
+    }))
+}
+
+// a ziplist: a 10 bytes header, then entries made of the length of the previous entry, an
+// encoding and the data, up to a 0xff byte
+fn ziplist_entries(blob: &[u8]) -> Result<Vec<Bytes>, RdbError> {
+    let mut r = RdbReader {
+        data: blob,
+        pos: 10,
+    };
+    let mut entries = Vec::new();
+    loop {
+        match r.u8()? {
+            0xff => break,
+            0xfe => {
+                r.bytes(4)?;
+            }
+            _ => {}
+        }
+        let encoding = r.u8()?;
+        let entry = match encoding >> 6 {
+            0 => Bytes::copy_from_slice(r.bytes((encoding & 0x3f) as usize)?),
+            1 => {
+                let len = (((encoding & 0x3f) as usize) << 8) | r.u8()? as usize;
+                Bytes::copy_from_slice(r.bytes(len)?)
+            }
+            2 => {
+                let len = u32::from_be_bytes(r.array()?) as usize;
+                Bytes::copy_from_slice(r.bytes(len)?)
+            }
+            _ => int_entry(match encoding {
+                0xc0 => i16::from_le_bytes(r.array()?) as i64,
+                0xd0 => i32::from_le_bytes(r.array()?) as i64,
+                0xe0 => i64::from_le_bytes(r.array()?),
+                0xf0 => i24(r.array()?),
+                0xfe => i8::from_le_bytes(r.array()?) as i64,
+                // 0 to 12 stored in the encoding itself
+                0xf1..=0xfd => (encoding & 0x0f) as i64 - 1,
+                _ => return Err(RdbError::InvalidEncoding),
+            }),
+        };
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+// a listpack: a 6 bytes header, then entries made of an encoding, the data and the length of
+// both backwards, up to a 0xff byte
+fn listpack_entries(blob: &[u8]) -> Result<Vec<Bytes>, RdbError> {
+    let mut r = RdbReader { data: blob, pos: 6 };
+    let mut entries = Vec::new();
+    loop {
+        let start = r.pos;
+        let encoding = r.u8()?;
+        let entry = if encoding & 0x80 == 0 {
+            int_entry(encoding as i64)
+        } else if encoding & 0xc0 == 0x80 {
+            Bytes::copy_from_slice(r.bytes((encoding & 0x3f) as usize)?)
+        } else if encoding & 0xe0 == 0xc0 {
+            // a 13 bits signed integer
+            let i = (((encoding & 0x1f) as i64) << 8) | r.u8()? as i64;
+            int_entry(if i >= 1 << 12 { i - (1 << 13) } else { i })
+        } else if encoding & 0xf0 == 0xe0 {
+            let len = (((encoding & 0x0f) as usize) << 8) | r.u8()? as usize;
+            Bytes::copy_from_slice(r.bytes(len)?)
+        } else {
+            match encoding {
+                0xf0 => {
+                    let len = u32::from_le_bytes(r.array()?) as usize;
+                    Bytes::copy_from_slice(r.bytes(len)?)
+                }
+                0xf1 => int_entry(i16::from_le_bytes(r.array()?) as i64),
+                0xf2 => int_entry(i24(r.array()?)),
+                0xf3 => int_entry(i32::from_le_bytes(r.array()?) as i64),
+                0xf4 => int_entry(i64::from_le_bytes(r.array()?)),
+                0xff => break,
+                _ => return Err(RdbError::InvalidEncoding),
+            }
+        };
+        // the backwards length takes 7 bits per byte
+        let backlen = match r.pos - start {
+            0..=127 => 1,
+            128..=16383 => 2,
+            16384..=2097151 => 3,
+            2097152..=268435455 => 4,
+            _ => 5,
+        };
+        r.bytes(backlen)?;
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+// an intset: the width of the integers, their count and the sorted integers
+fn intset_members(blob: &[u8]) -> Result<Vec<Bytes>, RdbError> {
+    let mut r = RdbReader { data: blob, pos: 0 };
+    let width = u32::from_le_bytes(r.array()?);
+    let len = u32::from_le_bytes(r.array()?);
+    (0..len)
+        .map(|_| {
+            let i = match width {
+                2 => i16::from_le_bytes(r.array()?) as i64,
+                4 => i32::from_le_bytes(r.array()?) as i64,
+                8 => i64::from_le_bytes(r.array()?),
+                _ => return Err(RdbError::InvalidEncoding),
+            };
+            Ok(int_entry(i))
+        })
+        .collect()
+}
+
+fn i24(bytes: [u8; 3]) -> i64 {
+    (i32::from_le_bytes([0, bytes[0], bytes[1], bytes[2]]) >> 8) as i64
+}
+
+// lzf as used by redis for compressed strings
+fn lzf_decompress(input: &[u8], len: usize) -> Result<Vec<u8>, RdbError> {
+    // 3 bytes of back reference expand to 264 at most, a larger length is not allocated for
+    if len > input.len().saturating_mul(88) {
+        return Err(RdbError::InvalidEncoding);
+    }
+    let mut out = Vec::with_capacity(len);
+    let mut i = 0;
+    while i < input.len() {
+        let ctrl = input[i] as usize;
+        i += 1;
+        if ctrl < 32 {
+            // a run of ctrl + 1 literal bytes
+            let literal = input
+                .get(i..i + ctrl + 1)
+                .ok_or(RdbError::InvalidEncoding)?;
+            out.extend_from_slice(literal);
+            i += ctrl + 1;
+        } else {
+            // a back reference into the output
+            let mut n = ctrl >> 5;
+            if n == 7 {
+                n += *input.get(i).ok_or(RdbError::InvalidEncoding)? as usize;
+                i += 1;
+            }
+            let offset =
+                ((ctrl & 0x1f) << 8) + *input.get(i).ok_or(RdbError::InvalidEncoding)? as usize + 1;
+            i += 1;
+            let start = out
+                .len()
+                .checked_sub(offset)
+                .ok_or(RdbError::InvalidEncoding)?;
+            for j in 0..n + 2 {
+                out.push(out[start + j]);
+            }
+        }
+    }
+    if out.len() != len {
+        return Err(RdbError::InvalidEncoding);
+    }
+    Ok(out)
+}
+
+// crc-64-jones, the checksum redis appends to the dump
+pub(crate) fn crc64(mut crc: u64, data: &[u8]) -> u64 {
+    const POLY: u64 = 0x95ac_9329_ac4b_c9b5;
+    lazy_static::lazy_static! {
+        static ref TABLE: [u64; 256] = {
+            let mut table = [0u64; 256];
+            for (i, entry) in table.iter_mut().enumerate() {
+                let mut crc = i as u64;
+                for _ in 0..8 {
+                    crc = if crc & 1 == 1 { (crc >> 1) ^ POLY } else { crc >> 1 };
+                }
+                *entry = crc;
+            }
+            table
+        };
+    }
+    for b in data {
+        crc = TABLE[((crc ^ *b as u64) & 0xff) as usize] ^ (crc >> 8);
+    }
+    crc
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::backend::{ListEnd, ZAddOptions};
+
+    #[test]
+    fn test_crc64() {
+        assert_eq!(crc64(0, b"123456789"), 0xe9c6d914c4b8d9ca);
+    }
+
+    #[test]
+    fn test_lzf_decompress() -> anyhow::Result<()> {
+        // "aaaaaaaaaa": one literal 'a' and a back reference of length 9 at distance 1
+        let compressed = [0x00, b'a', 0xe0, 0x00, 0x00];
+        assert_eq!(lzf_decompress(&compressed, 10)?, b"aaaaaaaaaa".to_vec());
+        assert!(lzf_decompress(&compressed, 11).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_length_encoding() -> anyhow::Result<()> {
+        for len in [0, 63, 64, 16383, 16384, u32::MAX as u64, u64::MAX] {
+            let mut w = RdbWriter::default();
+            w.length(len);
+            let mut r = RdbReader {
+                data: &w.buf,
+                pos: 0,
+            };
+            assert_eq!(r.length()?, len);
+            assert_eq!(r.pos, w.buf.len());
+        }
+        for i in [0, -1, 127, -129, 40000, -2147483648, 1 << 40] {
+            let mut w = RdbWriter::default();
+            w.int_string(i);
+            let mut r = RdbReader {
+                data: &w.buf,
+                pos: 0,
+            };
+            assert_eq!(r.string()?, Bytes::from(i.to_string()));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_dump_and_load() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        backend.set("string".to_string(), "hello");
+        backend.set("int".to_string(), "-42");
+        backend.set("big".to_string(), "x".repeat(20000));
+        backend.list_push(
+            "list",
+            vec![Bytes::from("a"), Bytes::from("b")],
+            ListEnd::Right,
+            false,
+        )?;
+        backend.insert_set("set".to_string(), vec!["m".to_string()])?;
+        backend.hset("hash", vec![("f".to_string(), Bytes::from("v"))])?;
+        let members = vec![(1.5, "one".to_string()), (f64::INFINITY, "inf".to_string())];
+        backend.zadd("zset", members, ZAddOptions::default())?;
+        let at = now_ms() + 100_000;
+        backend.expire_at("string", at);
+        backend.set("expired".to_string(), "v");
+        backend.expires.insert("expired".to_string(), now_ms() - 1);
+
+        let data = backend.dump_rdb();
+        assert!(data.starts_with(b"REDIS0010"));
+
+        let loaded = Backend::new();
+        loaded.set("stale".to_string(), "v");
+        assert_eq!(loaded.load_rdb(&data)?, 7);
+        assert!(!loaded.exists("stale"));
+        assert!(!loaded.exists("expired"));
+        assert_eq!(loaded.get("string")?, Some(Bytes::from("hello")));
+        assert_eq!(loaded.expire_time("string"), Some(Some(at)));
+        assert_eq!(loaded.get("int")?, Some(Bytes::from("-42")));
+        assert_eq!(loaded.strlen("big")?, 20000);
+        assert_eq!(
+            loaded.lrange("list", 0, -1)?,
+            backend.lrange("list", 0, -1)?
+        );
+        assert!(loaded.sismember("set", "m")?);
+        assert_eq!(loaded.hget("hash", "f")?, Some(Bytes::from("v")));
+        assert_eq!(loaded.zscore("zset", "inf")?, Some(f64::INFINITY));
+        assert_eq!(loaded.zrank("zset", "one", false)?, Some((0, 1.5)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_dump_functions() -> anyhow::Result<()> {
+        let code = "#!lua name=lib\nredis.register_function('f', function() return 1 end)";
+        let backend = Backend::new();
+        backend.function_load(parse_library(code).unwrap(), false)?;
+
+        let loaded = Backend::new();
+        loaded.load_rdb(&backend.dump_rdb())?;
+        assert!(loaded.function_library("f").is_some());
+
+        let payload = backend.dump_functions();
+        let libraries = load_function_dump(&payload)?;
+        assert_eq!(libraries.len(), 1);
+        assert_eq!(libraries[0].code, code);
+        let mut corrupted = payload.clone();
+        corrupted[3] ^= 0xff;
+        assert!(load_function_dump(&corrupted).is_err());
+        assert!(load_function_dump(b"short").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_compact_encodings() -> anyhow::Result<()> {
+        // the way redis 7 stores small values
+        let hash_listpack = [
+            0x18, 0, 0, 0, 6, 0, 0x81, b'f', 2, 0x81, b'v', 2, 0x81, b'n', 2, 5, 1, 0x81, b'm', 2,
+            0xdf, 0xfe, 3, 0xff,
+        ];
+        let list_ziplist = [
+            0x16, 0, 0, 0, 0x10, 0, 0, 0, 4, 0, 0, 1, b'a', 3, 0xc0, 0xe8, 3, 4, 0xf8, 2, 0xf0,
+            0x90, 0xee, 0xfe, 0xff,
+        ];
+        let set_intset = [2, 0, 0, 0, 3, 0, 0, 0, 1, 0, 2, 0, 3, 0];
+        let set_listpack = [12, 0, 0, 0, 2, 0, 0x81, b'm', 2, 12, 1, 0xff];
+        let zset_listpack = [
+            20, 0, 0, 0, 4, 0, 0x81, b'a', 2, 1, 1, 0x81, b'b', 2, 0x83, b'1', b'.', b'5', 4, 0xff,
+        ];
+        let node_listpack = [13, 0, 0, 0, 2, 0, 0x81, b'x', 2, 0x81, b'y', 2, 0xff];
+
+        let mut w = RdbWriter::default();
+        w.buf.extend_from_slice(b"REDIS0011");
+        let blobs: [(u8, &str, &[u8]); 5] = [
+            (RDB_TYPE_HASH_LISTPACK, "hash", &hash_listpack),
+            (RDB_TYPE_LIST_ZIPLIST, "list", &list_ziplist),
+            (RDB_TYPE_SET_INTSET, "ints", &set_intset),
+            (RDB_TYPE_SET_LISTPACK, "set", &set_listpack),
+            (RDB_TYPE_ZSET_LISTPACK, "zset", &zset_listpack),
+        ];
+        for (value_type, key, blob) in blobs {
+            w.buf.push(value_type);
+            w.string(key.as_bytes());
+            w.string(blob);
+        }
+        // a packed node then a plain one
+        w.buf.push(RDB_TYPE_LIST_QUICKLIST_2);
+        w.string(b"quicklist");
+        w.length(2);
+        w.length(2);
+        w.string(&node_listpack);
+        w.length(QUICKLIST_NODE_PLAIN);
+        w.string(b"zzz");
+        w.buf.push(RDB_OPCODE_EOF);
+        // checksums disabled
+        w.buf.extend_from_slice(&[0; 8]);
+
+        let backend = Backend::new();
+        assert_eq!(backend.load_rdb(&w.buf)?, 6);
+        assert_eq!(backend.hget("hash", "f")?, Some(Bytes::from("v")));
+        assert_eq!(backend.hget("hash", "n")?, Some(Bytes::from("5")));
+        assert_eq!(backend.hget("hash", "m")?, Some(Bytes::from("-2")));
+        let list = ["a", "1000", "7", "-70000"].map(Bytes::from).to_vec();
+        assert_eq!(backend.lrange("list", 0, -1)?, list);
+        assert!(backend.sismember("ints", "3")?);
+        assert!(backend.sismember("set", "12")?);
+        assert_eq!(backend.zscore("zset", "b")?, Some(1.5));
+        let quicklist = ["x", "y", "zzz"].map(Bytes::from).to_vec();
+        assert_eq!(backend.lrange("quicklist", 0, -1)?, quicklist);
+
+        // streams have no counterpart here
+        let mut w = RdbWriter::default();
+        w.buf.extend_from_slice(b"REDIS0011");
+        w.buf.push(RDB_TYPE_STREAM_LISTPACKS_2);
+        w.string(b"stream");
+        assert!(matches!(
+            backend.load_rdb(&w.buf),
+            Err(RdbError::UnsupportedValue("streams"))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_corrupted() {
+        let backend = Backend::new();
+        backend.set("k".to_string(), "v");
+        let mut data = backend.dump_rdb();
+        let loaded = Backend::new();
+        assert!(matches!(
+            loaded.load_rdb(&data[..data.len() - 3]),
+            Err(RdbError::UnexpectedEof)
+        ));
+        let n = data.len();
+        data[n - 12] ^= 0xff;
+        assert!(loaded.load_rdb(&data).is_err());
+        assert!(matches!(
+            loaded.load_rdb(b"NOTREDIS"),
+            Err(RdbError::InvalidHeader)
+        ));
+        // a string key "k\xff" with the value "v", without checksum
+        let mut binary_key = b"REDIS0009\x00\x02k\xff\x01v\xff".to_vec();
+        binary_key.extend_from_slice(&[0; 8]);
+        assert!(matches!(
+            loaded.load_rdb(&binary_key),
+            Err(RdbError::NonUtf8Key(key)) if key == Bytes::from_static(b"k\xff")
+        ));
+    }
+
+    #[test]
+    fn test_load_touches_watched_keys() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        backend.set("k".to_string(), "v");
+        let data = backend.dump_rdb();
+
+        let loaded = Backend::new();
+        let kept = loaded.watch("k");
+        let gone = loaded.watch("other");
+        loaded.load_rdb(&data)?;
+        assert!(loaded.watched_key_changed("k", kept));
+        assert!(loaded.watched_key_changed("other", gone));
+        Ok(())
+    }
+}
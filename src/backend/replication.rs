@@ -0,0 +1,550 @@
+use crate::backend::snapshot::KeyspaceSnapshot;
+use crate::backend::Backend;
+use crate::resp::{BulkString, RespArray, RespEncode};
+use bytes::Bytes;
+use rand::Rng;
+use std::collections::VecDeque;
+use std::pin::pin;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use tokio::sync::Notify;
+use tokio::task::AbortHandle;
+use tracing::info;
+
+// how often a master with replicas pings them, so they see the link is alive
+const REPL_PING_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Default)]
+pub struct ReplicationState {
+    inner: Mutex<Replication>,
+    // the task streaming from the master, set while the server is a replica
+    link: Mutex<Option<AbortHandle>>,
+    full_syncs: AtomicU64,
+    partial_syncs: AtomicU64,
+    // woken on every ack of a replica and fsync of the log, for WAIT and WAITAOF
+    progress: Notify,
+}
+
+#[derive(Debug)]
+struct Replication {
+    // host and port of the master, None on a master
+    master: Option<(String, u16)>,
+    // the history the dataset comes from, and how many bytes of it went into the dataset
+    replid: String,
+    offset: u64,
+    // the history before the last promotion and the offset up to which it is ours, so the
+    // replicas of the old master can resume from this one
+    previous: Option<(String, u64)>,
+    // the last bytes of the stream, the last one is at `offset`
+    backlog: VecDeque<u8>,
+    replicas: Vec<Replica>,
+}
+
+#[derive(Debug)]
+struct Replica {
+    // the id of its connection
+    id: u64,
+    // the stream goes through it to the connection of the replica
+    sender: ReplicaSender,
+    // the offsets the replica reported as processed, and as fsynced to its log
+    ack: u64,
+    aof_ack: u64,
+}
+
+// the stream on its way to the connection of a replica; a replica that lets more than `limit`
+// bytes pile up is dropped, it resumes with a PSYNC once it reconnected
+#[derive(Debug, Clone)]
+pub struct ReplicaSender {
+    sender: UnboundedSender<Bytes>,
+    queue: Arc<ReplicaQueue>,
+    limit: usize,
+}
+
+#[derive(Debug)]
+pub struct ReplicaStream {
+    receiver: UnboundedReceiver<Bytes>,
+    queue: Arc<ReplicaQueue>,
+}
+
+#[derive(Debug, Default)]
+struct ReplicaQueue {
+    // bytes sent and not received yet
+    queued: AtomicUsize,
+    dropped: Notify,
+}
+
+// how a master answers the PSYNC of a replica
+#[derive(Debug, PartialEq)]
+pub enum PsyncReply {
+    // the whole dataset as of the registration, the stream goes on from `offset`; it is encoded
+    // by the caller, once the writes resumed
+    FullResync {
+        replid: String,
+        offset: u64,
+        snapshot: KeyspaceSnapshot,
+    },
+    // the part of the stream the replica missed
+    Continue {
+        replid: String,
+        backlog: Bytes,
+    },
+}
+
+impl Default for Replication {
+    fn default() -> Self {
+        Self {
+            master: None,
+            replid: new_replid(),
+            offset: 0,
+            previous: None,
+            backlog: VecDeque::new(),
+            replicas: Vec::new(),
+        }
+    }
+}
+
+// 40 random hex characters, like redis run ids
+pub(super) fn new_replid() -> String {
+    let mut rng = rand::thread_rng();
+    (0..40)
+        .map(|_| char::from_digit(rng.gen_range(0..16), 16).unwrap())
+        .collect()
+}
+
+impl ReplicaSender {
+    pub fn new(limit: usize) -> (Self, ReplicaStream) {
+        let (sender, receiver) = unbounded_channel();
+        let queue = Arc::new(ReplicaQueue::default());
+        let stream = ReplicaStream {
+            receiver,
+            queue: queue.clone(),
+        };
+        (
+            Self {
+                sender,
+                queue,
+                limit,
+            },
+            stream,
+        )
+    }
+
+    // false when the replica is gone or too far behind, it is dropped then
+    fn send(&self, data: &Bytes) -> bool {
+        let queued = self.queue.queued.fetch_add(data.len(), Ordering::Relaxed) + data.len();
+        if queued > self.limit {
+            self.queue.dropped.notify_one();
+            return false;
+        }
+        self.sender.send(data.clone()).is_ok()
+    }
+}
+
+impl ReplicaStream {
+    pub async fn recv(&mut self) -> Option<Bytes> {
+        let data = self.receiver.recv().await?;
+        self.queue.queued.fetch_sub(data.len(), Ordering::Relaxed);
+        Some(data)
+    }
+
+    pub fn try_recv(&mut self) -> Option<Bytes> {
+        let data = self.receiver.try_recv().ok()?;
+        self.queue.queued.fetch_sub(data.len(), Ordering::Relaxed);
+        Some(data)
+    }
+
+    // resolves once the replica fell too far behind, whatever is still queued is not sent
+    pub async fn dropped(&self) {
+        self.queue.dropped.notified().await
+    }
+}
+
+impl Replication {
+    // the backlog from `offset` on, if this server has the history `replid` up to there
+    fn backlog_from(&self, replid: &str, offset: i64) -> Option<Bytes> {
+        let known = replid == self.replid
+            || self
+                .previous
+                .as_ref()
+                .is_some_and(|(previous, end)| previous == replid && offset <= *end as i64);
+        let start = self.offset + 1 - self.backlog.len() as u64;
+        if !known || offset < start as i64 || offset > self.offset as i64 + 1 {
+            return None;
+        }
+        let skip = (offset as u64 - start) as usize;
+        Some(self.backlog.iter().skip(skip).copied().collect())
+    }
+}
+
+impl Backend {
+    // a write applied to the dataset: appended to the log and streamed to the replicas, under the
+    // write barrier taken before it ran, so the log and the replicas both get the execution order
+    pub fn propagate(&self, command: RespArray) {
+        let encoded = command.encode();
+        self.append_aof(&encoded);
+        self.feed_replication(&encoded);
+    }
+
+    // appends to the replication stream, the replicas that went away are dropped; the caller holds
+    // the write barrier so the stream is not reordered against the writes
+    pub fn feed_replication(&self, data: &[u8]) {
+        let mut repl = self.replication.inner.lock().unwrap();
+        repl.offset += data.len() as u64;
+        repl.backlog.extend(data);
+        let excess = repl
+            .backlog
+            .len()
+            .saturating_sub(self.config.repl_backlog_size);
+        repl.backlog.drain(..excess);
+        if !repl.replicas.is_empty() {
+            let data = Bytes::copy_from_slice(data);
+            repl.replicas.retain(|replica| {
+                let sent = replica.sender.send(&data);
+                if !sent {
+                    info!("[Simple-redis-server]replica {} dropped", replica.id);
+                }
+                sent
+            });
+        }
+    }
+
+    // answers the PSYNC of a replica asking for the stream from `offset` on, and registers it for
+    // the rest of the stream
+    pub fn psync(&self, id: u64, replid: &str, offset: i64, sender: ReplicaSender) -> PsyncReply {
+        // no write may land between what is sent and the registration
+        let _barrier = self.write_barrier();
+        let mut repl = self.replication.inner.lock().unwrap();
+        let reply = match repl.backlog_from(replid, offset) {
+            Some(backlog) => {
+                self.replication
+                    .partial_syncs
+                    .fetch_add(1, Ordering::Relaxed);
+                PsyncReply::Continue {
+                    replid: repl.replid.clone(),
+                    backlog,
+                }
+            }
+            None => {
+                self.replication.full_syncs.fetch_add(1, Ordering::Relaxed);
+                PsyncReply::FullResync {
+                    replid: repl.replid.clone(),
+                    offset: repl.offset,
+                    snapshot: self.clone_keyspace(),
+                }
+            }
+        };
+        repl.replicas.push(Replica {
+            id,
+            sender,
+            ack: 0,
+            aof_ack: 0,
+        });
+        reply
+    }
+
+    pub fn replica_ack(&self, id: u64, offset: u64, fsynced: u64) {
+        let mut repl = self.replication.inner.lock().unwrap();
+        if let Some(replica) = repl.replicas.iter_mut().find(|replica| replica.id == id) {
+            replica.ack = offset;
+            replica.aof_ack = fsynced;
+        }
+        drop(repl);
+        self.replication.progress.notify_waiters();
+    }
+
+    // the replicas that processed the stream up to `offset`, or fsynced it to their log
+    pub fn replicas_acked(&self, offset: u64, fsynced: bool) -> usize {
+        let repl = self.replication.inner.lock().unwrap();
+        repl.replicas
+            .iter()
+            .filter(|replica| {
+                let ack = if fsynced {
+                    replica.aof_ack
+                } else {
+                    replica.ack
+                };
+                ack >= offset
+            })
+            .count()
+    }
+
+    // has the replicas ack right away rather than on their next periodic ack
+    pub fn request_acks(&self) {
+        let getack = RespArray::new(
+            ["REPLCONF", "GETACK", "*"]
+                .map(|arg| BulkString::new(arg).into())
+                .to_vec(),
+        );
+        let _barrier = self.write_barrier();
+        if self.connected_replicas() > 0 {
+            self.feed_replication(&getack.encode());
+        }
+    }
+
+    // waits until `reached` holds or the timeout passes, it is checked again on every ack and
+    // fsync, None waits forever
+    pub async fn wait_progress(&self, timeout: Option<Duration>, reached: impl Fn() -> bool) {
+        let deadline = timeout.map(|timeout| tokio::time::Instant::now() + timeout);
+        loop {
+            // registered before checking, so no wakeup is missed in between
+            let mut notified = pin!(self.replication.progress.notified());
+            notified.as_mut().enable();
+            if reached() {
+                return;
+            }
+            match deadline {
+                Some(deadline) => {
+                    if tokio::time::timeout_at(deadline, notified).await.is_err() {
+                        return;
+                    }
+                }
+                None => notified.await,
+            }
+        }
+    }
+
+    pub(crate) fn notify_progress(&self) {
+        self.replication.progress.notify_waiters();
+    }
+
+    pub fn remove_replica(&self, id: u64) {
+        let mut repl = self.replication.inner.lock().unwrap();
+        repl.replicas.retain(|replica| replica.id != id);
+    }
+
+    pub fn connected_replicas(&self) -> usize {
+        self.replication.inner.lock().unwrap().replicas.len()
+    }
+
+    // the number of full and partial resyncs served
+    pub fn sync_stats(&self) -> (u64, u64) {
+        (
+            self.replication.full_syncs.load(Ordering::Relaxed),
+            self.replication.partial_syncs.load(Ordering::Relaxed),
+        )
+    }
+
+    pub fn master(&self) -> Option<(String, u16)> {
+        self.replication.inner.lock().unwrap().master.clone()
+    }
+
+    pub fn is_replica(&self) -> bool {
+        self.replication.inner.lock().unwrap().master.is_some()
+    }
+
+    pub fn master_repl_offset(&self) -> u64 {
+        self.replication.inner.lock().unwrap().offset
+    }
+
+    // the history of the dataset and the offset reached in it
+    pub fn replication_offset(&self) -> (String, u64) {
+        let repl = self.replication.inner.lock().unwrap();
+        (repl.replid.clone(), repl.offset)
+    }
+
+    // makes the server a replica of `master`, with the link started by `start`, or a master when
+    // None. The replicas of the server are disconnected when it starts following a master, they
+    // resync from it once connected again
+    pub(crate) fn set_master(
+        &self,
+        master: Option<(String, u16)>,
+        start: impl FnOnce(String, u16) -> AbortHandle,
+    ) {
+        let mut link = self.replication.link.lock().unwrap();
+        if let Some(link) = link.take() {
+            link.abort();
+        }
+        let mut repl = self.replication.inner.lock().unwrap();
+        match &master {
+            Some(_) => repl.replicas.clear(),
+            // a promoted replica starts a history of its own, continuing the one of its master
+            None if repl.master.is_some() => {
+                let replid = std::mem::replace(&mut repl.replid, new_replid());
+                repl.previous = Some((replid, repl.offset + 1));
+            }
+            None => {}
+        }
+        repl.master = master.clone();
+        *link = master.map(|(host, port)| start(host, port));
+    }
+
+    // the replica loaded the dataset of its master, the stream goes on from `offset`
+    pub(crate) fn replica_full_sync(&self, replid: String, offset: u64) {
+        let mut repl = self.replication.inner.lock().unwrap();
+        repl.replid = replid;
+        repl.offset = offset;
+        repl.previous = None;
+        repl.backlog.clear();
+        // their dataset is not the one of this server anymore
+        repl.replicas.clear();
+    }
+
+    // the master resumed the stream, under a new id if it was promoted since
+    pub(crate) fn replica_continue(&self, replid: &str) {
+        let mut repl = self.replication.inner.lock().unwrap();
+        if repl.replid != replid {
+            let previous = std::mem::replace(&mut repl.replid, replid.to_string());
+            repl.previous = Some((previous, repl.offset + 1));
+        }
+    }
+}
+
+pub async fn replication_cycle(backend: Backend) {
+    let mut interval = tokio::time::interval(REPL_PING_INTERVAL);
+    let ping = RespArray::new([BulkString::new("PING").into()]).encode();
+    loop {
+        interval.tick().await;
+        // a replica forwards the pings of its master instead
+        if backend.connected_replicas() > 0 && !backend.is_replica() {
+            let _barrier = backend.write_barrier();
+            backend.feed_replication(&ping);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn set_command(key: &str, value: &str) -> RespArray {
+        RespArray::new(
+            ["SET", key, value]
+                .map(|arg| BulkString::new(arg).into())
+                .to_vec(),
+        )
+    }
+
+    #[test]
+    fn test_psync() {
+        let backend = Backend::new();
+        let (replid, _) = backend.replication_offset();
+        let (sender, mut stream) = ReplicaSender::new(1024);
+        match backend.psync(1, "?", -1, sender.clone()) {
+            PsyncReply::FullResync { offset, .. } => assert_eq!(offset, 0),
+            reply => panic!("unexpected reply {:?}", reply),
+        }
+        let encoded = set_command("a", "1").encode();
+        backend.propagate(set_command("a", "1"));
+        assert_eq!(stream.try_recv().unwrap(), Bytes::from(encoded.clone()));
+        assert_eq!(backend.replication_offset().1, encoded.len() as u64);
+
+        // a replica that has the first bytes only gets the rest
+        assert_eq!(
+            backend.psync(2, &replid, 5, sender.clone()),
+            PsyncReply::Continue {
+                replid: replid.clone(),
+                backlog: Bytes::copy_from_slice(&encoded[4..]),
+            }
+        );
+        assert_eq!(
+            backend.psync(3, &replid, encoded.len() as i64 + 1, sender.clone()),
+            PsyncReply::Continue {
+                replid: replid.clone(),
+                backlog: Bytes::new(),
+            }
+        );
+        assert!(matches!(
+            backend.psync(4, "other", 1, sender.clone()),
+            PsyncReply::FullResync { .. }
+        ));
+        assert_eq!(backend.sync_stats(), (2, 2));
+        assert_eq!(backend.connected_replicas(), 4);
+        backend.remove_replica(4);
+        assert_eq!(backend.connected_replicas(), 3);
+    }
+
+    #[test]
+    fn test_full_resync_snapshot() {
+        let backend = Backend::new();
+        backend.set("a".to_string(), "1");
+        let (sender, mut stream) = ReplicaSender::new(1024);
+        let PsyncReply::FullResync { snapshot, .. } = backend.psync(1, "?", -1, sender) else {
+            panic!("expected a full resync");
+        };
+        // a write after the registration is in the stream, not in the snapshot
+        backend.set("b".to_string(), "1");
+        backend.propagate(set_command("b", "1"));
+        let keys = snapshot.keys.iter().map(|(key, ..)| key.as_str());
+        assert_eq!(keys.collect::<Vec<_>>(), ["a"]);
+        assert_eq!(
+            stream.try_recv().unwrap(),
+            Bytes::from(set_command("b", "1").encode())
+        );
+    }
+
+    #[test]
+    fn test_backlog_ring() {
+        let backend = Backend::with_config(crate::config::Config {
+            repl_backlog_size: 16,
+            ..Default::default()
+        });
+        let (replid, _) = backend.replication_offset();
+        backend.feed_replication(b"0123456789");
+        backend.feed_replication(b"abcdefghij");
+        let (sender, _stream) = ReplicaSender::new(1024);
+        // the first bytes were dropped out of the backlog
+        assert!(matches!(
+            backend.psync(1, &replid, 1, sender.clone()),
+            PsyncReply::FullResync { .. }
+        ));
+        assert_eq!(
+            backend.psync(2, &replid, 5, sender),
+            PsyncReply::Continue {
+                replid,
+                backlog: Bytes::from("456789abcdefghij"),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_lagging_replica_dropped() {
+        let backend = Backend::new();
+        let (sender, stream) = ReplicaSender::new(16);
+        backend.psync(1, "?", -1, sender);
+        backend.feed_replication(b"0123456789");
+        assert_eq!(backend.connected_replicas(), 1);
+        // nothing was read yet, the next write goes over the limit
+        backend.feed_replication(b"abcdefghij");
+        assert_eq!(backend.connected_replicas(), 0);
+        let dropped = tokio::time::timeout(Duration::from_secs(1), stream.dropped());
+        assert!(dropped.await.is_ok());
+
+        // what the connection takes out of the queue is no longer counted
+        let (sender, mut stream) = ReplicaSender::new(16);
+        backend.psync(2, "?", -1, sender);
+        backend.feed_replication(b"0123456789");
+        assert_eq!(stream.recv().await, Some(Bytes::from("0123456789")));
+        backend.feed_replication(b"abcdefghij");
+        assert_eq!(backend.connected_replicas(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_promotion_keeps_history() {
+        let backend = Backend::new();
+        let link = tokio::spawn(std::future::pending::<()>());
+        backend.set_master(Some(("127.0.0.1".to_string(), 1)), |_, _| {
+            link.abort_handle()
+        });
+        assert!(backend.is_replica());
+        backend.replica_full_sync("a".repeat(40), 100);
+        backend.feed_replication(b"PING");
+
+        backend.set_master(None, |_, _| unreachable!());
+        assert!(!backend.is_replica());
+        assert!(link.await.unwrap_err().is_cancelled());
+        let (replid, offset) = backend.replication_offset();
+        assert_ne!(replid, "a".repeat(40));
+        assert_eq!(offset, 104);
+        // the other replicas of the old master resume from here
+        let (sender, _stream) = ReplicaSender::new(1024);
+        assert_eq!(
+            backend.psync(1, &"a".repeat(40), 103, sender),
+            PsyncReply::Continue {
+                replid,
+                backlog: Bytes::from("NG"),
+            }
+        );
+    }
+}
@@ -0,0 +1,189 @@
+use crate::backend::{Backend, BackendError};
+use dashmap::DashMap;
+use mlua::Lua;
+use sha1::{Digest, Sha1};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+// the bodies of the scripts run or loaded so far, by the hex SHA1 of the body
+#[derive(Debug, Default)]
+pub struct ScriptState {
+    scripts: DashMap<String, String>,
+    // the interpreter the scripts run in, with the compiled scripts; None until the first EVAL
+    // and after a flush
+    vm: Mutex<Option<Lua>>,
+    running: Mutex<Option<RunningScript>>,
+    // whether `running` is Some, checked before every command
+    active: AtomicBool,
+}
+
+#[derive(Debug)]
+struct RunningScript {
+    started: Instant,
+    // a function of a library rather than a script of EVAL
+    function: bool,
+    // a script that wrote can't be killed, that would leave half of its writes
+    wrote: bool,
+    killed: bool,
+}
+
+// marks a script as running until it is dropped
+pub struct ScriptGuard<'a> {
+    backend: &'a Backend,
+}
+
+impl Backend {
+    // caches the script and returns its SHA1, the name EVALSHA runs it by
+    pub fn script_load(&self, body: &str) -> String {
+        let sha = sha1_hex(body.as_bytes());
+        self.scripts
+            .scripts
+            .entry(sha.clone())
+            .or_insert_with(|| body.to_string());
+        sha
+    }
+
+    // the SHA1 is matched case insensitively, like redis does
+    pub fn script_get(&self, sha: &str) -> Option<String> {
+        self.scripts
+            .scripts
+            .get(&sha.to_ascii_lowercase())
+            .map(|body| body.clone())
+    }
+
+    pub fn script_exists(&self, sha: &str) -> bool {
+        self.scripts.scripts.contains_key(&sha.to_ascii_lowercase())
+    }
+
+    pub fn script_flush(&self) {
+        self.scripts.scripts.clear();
+        *self.scripts.vm.lock().unwrap() = None;
+    }
+
+    pub(crate) fn script_vm(&self) -> MutexGuard<'_, Option<Lua>> {
+        self.scripts.vm.lock().unwrap()
+    }
+
+    pub fn start_script(&self, function: bool) -> ScriptGuard<'_> {
+        *self.scripts.running.lock().unwrap() = Some(RunningScript {
+            started: Instant::now(),
+            function,
+            wrote: false,
+            killed: false,
+        });
+        self.scripts.active.store(true, Ordering::Release);
+        ScriptGuard { backend: self }
+    }
+
+    pub fn script_wrote(&self) {
+        if let Some(running) = self.scripts.running.lock().unwrap().as_mut() {
+            running.wrote = true;
+        }
+    }
+
+    pub fn script_killed(&self) -> bool {
+        self.scripts
+            .running
+            .lock()
+            .unwrap()
+            .as_ref()
+            .is_some_and(|running| running.killed)
+    }
+
+    // Some(true) for a function, once a script ran for longer than busy-reply-threshold
+    pub fn busy_script(&self) -> Option<bool> {
+        let threshold = Duration::from_millis(self.config.busy_reply_threshold);
+        self.scripts
+            .running
+            .lock()
+            .unwrap()
+            .as_ref()
+            .filter(|running| running.started.elapsed() >= threshold)
+            .map(|running| running.function)
+    }
+
+    pub fn script_running(&self) -> bool {
+        self.scripts.active.load(Ordering::Acquire)
+    }
+
+    // stops the running script at its next check, SCRIPT KILL stops scripts and FUNCTION KILL
+    // functions
+    pub fn script_kill(&self, function: bool) -> Result<(), BackendError> {
+        let mut running = self.scripts.running.lock().unwrap();
+        match running.as_mut() {
+            Some(running) if running.function == function => {
+                if running.wrote {
+                    return Err(BackendError::Unkillable);
+                }
+                running.killed = true;
+                Ok(())
+            }
+            _ => Err(BackendError::NotBusy),
+        }
+    }
+}
+
+impl Drop for ScriptGuard<'_> {
+    fn drop(&mut self) {
+        *self.backend.scripts.running.lock().unwrap() = None;
+        self.backend.scripts.active.store(false, Ordering::Release);
+    }
+}
+
+pub fn sha1_hex(data: &[u8]) -> String {
+    format!("{:x}", Sha1::digest(data))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn test_script_cache() {
+        let backend = Backend::new();
+        let sha = backend.script_load("return 1");
+        assert_eq!(sha, "e0e1f9fabfc9d4800c877a703b823ac0578ff8db");
+        assert_eq!(
+            backend.script_get(&sha.to_uppercase()).as_deref(),
+            Some("return 1")
+        );
+        assert!(backend.script_exists(&sha));
+        backend.script_flush();
+        assert!(!backend.script_exists(&sha));
+        assert_eq!(backend.script_get(&sha), None);
+    }
+
+    #[test]
+    fn test_script_kill() {
+        let backend = Backend::with_config(Config {
+            busy_reply_threshold: 0,
+            ..Config::default()
+        });
+        assert!(matches!(
+            backend.script_kill(false),
+            Err(BackendError::NotBusy)
+        ));
+        assert_eq!(backend.busy_script(), None);
+        {
+            let _running = backend.start_script(false);
+            assert!(backend.script_running());
+            // functions are killed by FUNCTION KILL
+            assert!(matches!(
+                backend.script_kill(true),
+                Err(BackendError::NotBusy)
+            ));
+            backend.script_kill(false).unwrap();
+            assert!(backend.script_killed());
+        }
+        assert!(!backend.script_running());
+        let _running = backend.start_script(false);
+        backend.script_wrote();
+        assert!(matches!(
+            backend.script_kill(false),
+            Err(BackendError::Unkillable)
+        ));
+        assert_eq!(backend.busy_script(), Some(false));
+    }
+}
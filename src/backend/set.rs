@@ -0,0 +1,225 @@
+use crate::backend::{Backend, BackendError, RedisValue};
+use dashmap::mapref::entry::Entry;
+use rand::seq::IteratorRandom;
+use rand::Rng;
+use std::collections::HashSet;
+
+// how the sets of several keys are combined
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetOp {
+    Inter,
+    Union,
+    Diff,
+}
+
+impl Backend {
+    // read access to a set, Ok(None) if the key does not exist
+    fn with_set<T>(
+        &self,
+        key: &str,
+        f: impl FnOnce(&HashSet<String>) -> T,
+    ) -> Result<Option<T>, BackendError> {
+        self.expire_if_needed(key);
+        match self.db.get(key).as_deref() {
+            Some(RedisValue::Set(set)) => Ok(Some(f(set))),
+            Some(_) => Err(BackendError::WrongType),
+            None => Ok(None),
+        }
+    }
+
+    // write access to an existing set, the key is removed once the set is empty
+    fn with_set_mut<T>(
+        &self,
+        key: &str,
+        f: impl FnOnce(&mut HashSet<String>) -> T,
+    ) -> Result<Option<T>, BackendError> {
+        self.expire_if_needed(key);
+        match self.db.entry(key.to_string()) {
+            Entry::Occupied(mut e) => {
+                let (ret, empty) = match e.get_mut() {
+                    RedisValue::Set(set) => (f(set), set.is_empty()),
+                    _ => return Err(BackendError::WrongType),
+                };
+                if empty {
+                    e.remove();
+                    self.expires.remove(key);
+                }
+                self.touch_key(key);
+                Ok(Some(ret))
+            }
+            Entry::Vacant(_) => Ok(None),
+        }
+    }
+
+    pub fn sismember(&self, key: &str, value: &str) -> Result<bool, BackendError> {
+        Ok(self
+            .with_set(key, |set| set.contains(value))?
+            .unwrap_or(false))
+    }
+
+    // returns the number of members that were not already in the set
+    pub fn insert_set(&self, key: String, values: Vec<String>) -> Result<usize, BackendError> {
+        self.expire_if_needed(&key);
+        self.touch_key(&key);
+        let mut entry = self
+            .db
+            .entry(key)
+            .or_insert_with(|| RedisValue::Set(HashSet::new()));
+        match entry.value_mut() {
+            RedisValue::Set(set) => {
+                Ok(values.into_iter().filter(|v| set.insert(v.clone())).count())
+            }
+            _ => Err(BackendError::WrongType),
+        }
+    }
+
+    pub fn srem(&self, key: &str, values: &[String]) -> Result<usize, BackendError> {
+        let ret = self.with_set_mut(key, |set| values.iter().filter(|v| set.remove(*v)).count())?;
+        Ok(ret.unwrap_or(0))
+    }
+
+    pub fn smembers(&self, key: &str) -> Result<Vec<String>, BackendError> {
+        let ret = self.with_set(key, |set| set.iter().cloned().collect())?;
+        Ok(ret.unwrap_or_default())
+    }
+
+    pub fn scard(&self, key: &str) -> Result<usize, BackendError> {
+        Ok(self.with_set(key, |set| set.len())?.unwrap_or(0))
+    }
+
+    // removes and returns at most `count` random members, Ok(None) if the key does not exist
+    pub fn spop(&self, key: &str, count: usize) -> Result<Option<Vec<String>>, BackendError> {
+        self.with_set_mut(key, |set| {
+            let popped = set
+                .iter()
+                .cloned()
+                .choose_multiple(&mut rand::thread_rng(), count);
+            for member in &popped {
+                set.remove(member);
+            }
+            popped
+        })
+    }
+
+    // a positive count returns distinct members, a negative count may return the same member
+    // several times and always returns |count| members
+    pub fn srandmember(&self, key: &str, count: i64) -> Result<Vec<String>, BackendError> {
+        let ret = self.with_set(key, |set| {
+            let mut rng = rand::thread_rng();
+            if count >= 0 {
+                return set
+                    .iter()
+                    .cloned()
+                    .choose_multiple(&mut rng, count as usize);
+            }
+            let members = set.iter().collect::<Vec<&String>>();
+            (0..count.unsigned_abs())
+                .map(|_| members[rng.gen_range(0..members.len())].clone())
+                .collect()
+        })?;
+        Ok(ret.unwrap_or_default())
+    }
+
+    // combines the sets of all keys, a missing key is an empty set
+    pub fn set_combine(&self, keys: &[String], op: SetOp) -> Result<HashSet<String>, BackendError> {
+        let mut sets = Vec::with_capacity(keys.len());
+        for key in keys {
+            sets.push(self.with_set(key, |set| set.clone())?.unwrap_or_default());
+        }
+        let mut sets = sets.into_iter();
+        let mut ret = sets.next().unwrap_or_default();
+        for set in sets {
+            match op {
+                SetOp::Inter => ret.retain(|v| set.contains(v)),
+                SetOp::Union => ret.extend(set),
+                SetOp::Diff => ret.retain(|v| !set.contains(v)),
+            }
+        }
+        Ok(ret)
+    }
+
+    // like set_combine, but overwrites dst with the result and returns its size
+    pub fn set_combine_store(
+        &self,
+        dst: &str,
+        keys: &[String],
+        op: SetOp,
+    ) -> Result<usize, BackendError> {
+        let set = self.set_combine(keys, op)?;
+        let len = set.len();
+        if set.is_empty() {
+            self.remove(dst);
+        } else {
+            self.write(dst.to_string(), RedisValue::Set(set), None);
+        }
+        Ok(len)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn members(v: &[&str]) -> Vec<String> {
+        v.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_set_add_remove() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        assert_eq!(
+            backend.insert_set("s".to_string(), members(&["a", "b", "a"]))?,
+            2
+        );
+        assert_eq!(
+            backend.insert_set("s".to_string(), members(&["b", "c"]))?,
+            1
+        );
+        assert_eq!(backend.scard("s")?, 3);
+        assert_eq!(backend.srem("s", &members(&["a", "x"]))?, 1);
+
+        let popped = backend.spop("s", 5)?.unwrap_or_default();
+        assert_eq!(popped.len(), 2);
+        assert!(!backend.exists("s"));
+        assert_eq!(backend.spop("s", 1)?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_srandmember() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        backend.insert_set("s".to_string(), members(&["a", "b"]))?;
+        assert_eq!(backend.srandmember("s", 5)?.len(), 2);
+        let ret = backend.srandmember("s", -5)?;
+        assert_eq!(ret.len(), 5);
+        assert!(ret.iter().all(|v| v == "a" || v == "b"));
+        assert_eq!(backend.scard("s")?, 2);
+        assert!(backend.srandmember("none", -5)?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_combine() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        backend.insert_set("s1".to_string(), members(&["a", "b", "c"]))?;
+        backend.insert_set("s2".to_string(), members(&["c", "d"]))?;
+        let keys = members(&["s1", "s2"]);
+        let expected = |v: &[&str]| members(v).into_iter().collect::<HashSet<String>>();
+        assert_eq!(backend.set_combine(&keys, SetOp::Inter)?, expected(&["c"]));
+        assert_eq!(
+            backend.set_combine(&keys, SetOp::Union)?,
+            expected(&["a", "b", "c", "d"])
+        );
+        assert_eq!(
+            backend.set_combine(&keys, SetOp::Diff)?,
+            expected(&["a", "b"])
+        );
+
+        assert_eq!(backend.set_combine_store("s1", &keys, SetOp::Diff)?, 2);
+        assert_eq!(backend.scard("s1")?, 2);
+        let keys = members(&["s1", "none"]);
+        assert_eq!(backend.set_combine_store("s1", &keys, SetOp::Inter)?, 0);
+        assert!(!backend.exists("s1"));
+        Ok(())
+    }
+}
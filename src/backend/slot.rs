@@ -0,0 +1,53 @@
+// the key space of a cluster is split in 16384 slots
+pub const CLUSTER_SLOTS: u16 = 16384;
+
+// CRC16-CCITT (XMODEM), the checksum redis cluster hashes keys with
+pub fn crc16(data: &[u8]) -> u16 {
+    data.iter().fold(0u16, |crc, &byte| {
+        let mut crc = crc ^ ((byte as u16) << 8);
+        for _ in 0..8 {
+            crc = match crc & 0x8000 {
+                0 => crc << 1,
+                _ => (crc << 1) ^ 0x1021,
+            };
+        }
+        crc
+    })
+}
+
+// only the part between the first `{` and the following `}` is hashed when it is not empty, so
+// related keys can be forced into the same slot
+pub fn key_hash_slot(key: &[u8]) -> u16 {
+    let hashed = key
+        .iter()
+        .position(|&b| b == b'{')
+        .and_then(|start| {
+            let tag = &key[start + 1..];
+            tag.iter().position(|&b| b == b'}').map(|end| &tag[..end])
+        })
+        .filter(|tag| !tag.is_empty())
+        .unwrap_or(key);
+    crc16(hashed) & (CLUSTER_SLOTS - 1)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_key_hash_slot() {
+        assert_eq!(crc16(b"123456789"), 0x31c3);
+        assert_eq!(key_hash_slot(b"foo"), 12182);
+        assert_eq!(key_hash_slot(b"hello"), 866);
+        assert_eq!(key_hash_slot(b""), 0);
+        assert_eq!(key_hash_slot(b"{foo}bar"), 12182);
+        assert_eq!(key_hash_slot(b"bar{foo}{zap}"), 12182);
+        assert_eq!(
+            key_hash_slot(b"{user1000}.following"),
+            key_hash_slot(b"{user1000}.followers")
+        );
+        // an empty tag or an unclosed brace hashes the whole key
+        assert_eq!(key_hash_slot(b"foo{}{bar}"), crc16(b"foo{}{bar}") & 16383);
+        assert_eq!(key_hash_slot(b"foo{bar"), crc16(b"foo{bar") & 16383);
+    }
+}
@@ -0,0 +1,280 @@
+use crate::backend::rdb::encode_rdb;
+use crate::backend::{now_ms, Backend, BackendError, FunctionLibrary, RedisValue};
+use std::fs;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{info, warn};
+
+// how often the background task checks the save points
+const SAVE_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug)]
+pub struct SnapshotState {
+    // writes since the last successful save
+    dirty: AtomicU64,
+    // unix seconds of the last successful save, or of the startup
+    last_save: AtomicU64,
+    bgsave_in_progress: AtomicBool,
+}
+
+impl Default for SnapshotState {
+    fn default() -> Self {
+        Self {
+            dirty: AtomicU64::new(0),
+            last_save: AtomicU64::new(now_secs()),
+            bgsave_in_progress: AtomicBool::new(false),
+        }
+    }
+}
+
+pub type KeySnapshot = (String, RedisValue, Option<u64>);
+
+// the libraries and the live keys at one point in time, serialized once the writes resumed
+#[derive(Debug, PartialEq)]
+pub struct KeyspaceSnapshot {
+    pub libraries: Vec<Arc<FunctionLibrary>>,
+    pub keys: Vec<KeySnapshot>,
+}
+
+impl KeyspaceSnapshot {
+    // the RDB dump of the snapshot, slow on a large keyspace so kept off the runtime threads
+    pub fn encode(&self) -> Vec<u8> {
+        encode_rdb(self)
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+impl Backend {
+    pub fn mark_dirty(&self) {
+        self.snapshot.dirty.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn last_save(&self) -> u64 {
+        self.snapshot.last_save.load(Ordering::Relaxed)
+    }
+
+    // clones the keyspace, keys that already expired are left out; the caller holds the write
+    // barrier, so a write is either entirely in the snapshot or not at all
+    pub(crate) fn clone_keyspace(&self) -> KeyspaceSnapshot {
+        let now = now_ms();
+        let keys = self
+            .db
+            .iter()
+            .filter_map(|entry| {
+                let expire = self.expires.get(entry.key()).map(|at| *at.value());
+                match expire {
+                    Some(at) if at <= now => None,
+                    _ => Some((entry.key().clone(), entry.value().clone(), expire)),
+                }
+            })
+            .collect();
+        KeyspaceSnapshot {
+            libraries: self.function_libraries(),
+            keys,
+        }
+    }
+
+    pub fn save(&self) -> Result<(), BackendError> {
+        let (dirty, snapshot) = self.snapshot_for_save();
+        self.write_snapshot(dirty, &snapshot)
+    }
+
+    // snapshots right away and saves on a blocking thread, at most one background save runs at a
+    // time
+    pub fn bgsave(&self) -> Result<(), BackendError> {
+        if self
+            .snapshot
+            .bgsave_in_progress
+            .swap(true, Ordering::AcqRel)
+        {
+            return Err(BackendError::SaveInProgress);
+        }
+        let (dirty, snapshot) = self.snapshot_for_save();
+        let backend = self.clone();
+        tokio::task::spawn_blocking(move || {
+            if let Err(e) = backend.write_snapshot(dirty, &snapshot) {
+                warn!("[Simple-redis-server]background save failed: {}", e);
+            }
+            backend
+                .snapshot
+                .bgsave_in_progress
+                .store(false, Ordering::Release);
+        });
+        Ok(())
+    }
+
+    // the writes counted so far are those in the snapshot
+    fn snapshot_for_save(&self) -> (u64, KeyspaceSnapshot) {
+        let _barrier = self.write_barrier();
+        let dirty = self.snapshot.dirty.load(Ordering::Relaxed);
+        (dirty, self.clone_keyspace())
+    }
+
+    // writes the dump next to the target and renames it, so a crash never leaves a partial file
+    fn write_snapshot(&self, dirty: u64, snapshot: &KeyspaceSnapshot) -> Result<(), BackendError> {
+        let data = snapshot.encode();
+        let path = self.config.rdb_path();
+        let tmp = self
+            .config
+            .dir
+            .join(format!("temp-{}.rdb", std::process::id()));
+        fs::write(&tmp, data)
+            .and_then(|_| fs::rename(&tmp, &path))
+            .map_err(|e| {
+                let _ = fs::remove_file(&tmp);
+                BackendError::Persistence(format!("failed saving the DB: {}", e))
+            })?;
+        // writes that came after the snapshot stay counted for the next save
+        self.snapshot.dirty.fetch_sub(dirty, Ordering::Relaxed);
+        self.snapshot.last_save.store(now_secs(), Ordering::Relaxed);
+        info!("[Simple-redis-server]DB saved on disk");
+        Ok(())
+    }
+
+    pub fn bgsave_in_progress(&self) -> bool {
+        self.snapshot.bgsave_in_progress.load(Ordering::Acquire)
+    }
+
+    // loads the dump of the configured path, a missing file is an empty keyspace
+    pub fn load_snapshot(&self) -> anyhow::Result<usize> {
+        let path = self.config.rdb_path();
+        let data = match fs::read(&path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e.into()),
+        };
+        let loaded = self.load_rdb(&data)?;
+        self.snapshot.dirty.store(0, Ordering::Relaxed);
+        Ok(loaded)
+    }
+
+    // true once any save point has seen enough changes in enough time
+    fn should_save(&self) -> bool {
+        let dirty = self.snapshot.dirty.load(Ordering::Relaxed);
+        let elapsed = now_secs().saturating_sub(self.last_save());
+        self.config
+            .save
+            .iter()
+            .any(|p| dirty >= p.changes && dirty > 0 && elapsed >= p.seconds)
+    }
+}
+
+pub async fn save_cycle(backend: Backend) {
+    let mut interval = tokio::time::interval(SAVE_CHECK_INTERVAL);
+    loop {
+        interval.tick().await;
+        if backend.should_save() && !backend.bgsave_in_progress() {
+            info!("[Simple-redis-server]save point reached, saving");
+            let _ = backend.bgsave();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::config::{Config, SavePoint};
+    use bytes::Bytes;
+    use std::path::PathBuf;
+
+    fn temp_config(name: &str) -> Config {
+        let dir =
+            std::env::temp_dir().join(format!("simple-redis-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        Config {
+            dir,
+            save: vec![SavePoint {
+                seconds: 0,
+                changes: 2,
+            }],
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn test_save_and_load_snapshot() -> anyhow::Result<()> {
+        let config = temp_config("save");
+        let backend = Backend::with_config(config.clone());
+        assert_eq!(backend.load_snapshot()?, 0);
+
+        backend.set("k".to_string(), "v");
+        backend.mark_dirty();
+        assert!(!backend.should_save());
+        backend.mark_dirty();
+        assert!(backend.should_save());
+        backend.save()?;
+        assert!(!backend.should_save());
+
+        let loaded = Backend::with_config(config.clone());
+        assert_eq!(loaded.load_snapshot()?, 1);
+        assert_eq!(loaded.get("k")?, Some(Bytes::from("v")));
+
+        fs::remove_dir_all(&config.dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_save_to_missing_dir() {
+        let config = Config {
+            dir: PathBuf::from("/nonexistent/simple-redis"),
+            ..Config::default()
+        };
+        let backend = Backend::with_config(config);
+        assert!(matches!(backend.save(), Err(BackendError::Persistence(_))));
+    }
+
+    #[tokio::test]
+    async fn test_bgsave() -> anyhow::Result<()> {
+        let config = temp_config("bgsave");
+        let backend = Backend::with_config(config.clone());
+        backend.set("k".to_string(), "v");
+        backend.bgsave()?;
+        while backend.bgsave_in_progress() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(config.rdb_path().exists());
+
+        fs::remove_dir_all(&config.dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_save_is_point_in_time() -> anyhow::Result<()> {
+        let config = temp_config("point-in-time");
+        let backend = Backend::with_config(config.clone());
+        backend.set("a".to_string(), "v");
+        let stop = Arc::new(AtomicBool::new(false));
+        let writer = {
+            let (backend, stop) = (backend.clone(), stop.clone());
+            std::thread::spawn(move || {
+                // the key moves back and forth, a snapshot must see it under exactly one name
+                while !stop.load(Ordering::Relaxed) {
+                    let _barrier = backend.write_barrier();
+                    let (src, dst) = if backend.exists("a") {
+                        ("a", "b")
+                    } else {
+                        ("b", "a")
+                    };
+                    backend.rename(src, dst, false).unwrap();
+                }
+            })
+        };
+        for _ in 0..20 {
+            backend.save()?;
+            let loaded = Backend::with_config(config.clone());
+            assert_eq!(loaded.load_snapshot()?, 1);
+        }
+        stop.store(true, Ordering::Relaxed);
+        writer.join().unwrap();
+
+        fs::remove_dir_all(&config.dir)?;
+        Ok(())
+    }
+}
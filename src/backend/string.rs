@@ -0,0 +1,452 @@
+use crate::backend::{Backend, BackendError, RedisValue};
+use bytes::{Bytes, BytesMut};
+use dashmap::mapref::entry::Entry;
+
+// condition of SET: NX only sets a missing key, XX only overwrites an existing key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetCondition {
+    Always,
+    NotExists,
+    Exists,
+}
+
+// what SET does with the time to live of the key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetExpiry {
+    Clear,
+    Keep,
+    At(u64),
+}
+
+// a string value, strings that are the canonical form of an i64 are kept as integers
+// so counters do not have to be parsed on every INCR
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StringValue {
+    Raw(Bytes),
+    Int(i64),
+}
+
+impl StringValue {
+    pub fn to_bytes(&self) -> Bytes {
+        match self {
+            StringValue::Raw(b) => b.clone(),
+            StringValue::Int(i) => Bytes::from(i.to_string()),
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        match self {
+            StringValue::Raw(b) => b.len(),
+            StringValue::Int(i) => i.to_string().len(),
+        }
+    }
+
+    fn to_integer(&self) -> Result<i64, BackendError> {
+        match self {
+            StringValue::Int(i) => Ok(*i),
+            StringValue::Raw(b) => std::str::from_utf8(b)
+                .ok()
+                .and_then(|s| s.parse::<i64>().ok())
+                .ok_or(BackendError::NotInteger),
+        }
+    }
+
+    fn to_float(&self) -> Result<f64, BackendError> {
+        match self {
+            StringValue::Int(i) => Ok(*i as f64),
+            StringValue::Raw(b) => std::str::from_utf8(b)
+                .ok()
+                .and_then(|s| s.parse::<f64>().ok())
+                .filter(|f| f.is_finite())
+                .ok_or(BackendError::NotFloat),
+        }
+    }
+}
+
+impl From<Bytes> for StringValue {
+    fn from(b: Bytes) -> Self {
+        // only canonical integers, "007" or "+1" must read back exactly as written
+        let int = std::str::from_utf8(&b)
+            .ok()
+            .filter(|s| s.len() <= 20)
+            .and_then(|s| s.parse::<i64>().ok())
+            .filter(|i| i.to_string().as_bytes() == b.as_ref());
+        match int {
+            Some(i) => StringValue::Int(i),
+            None => StringValue::Raw(b),
+        }
+    }
+}
+
+impl Backend {
+    pub fn get(&self, key: &str) -> Result<Option<Bytes>, BackendError> {
+        self.expire_if_needed(key);
+        match self.db.get(key).as_deref() {
+            Some(RedisValue::String(v)) => Ok(Some(v.to_bytes())),
+            Some(_) => Err(BackendError::WrongType),
+            None => Ok(None),
+        }
+    }
+
+    // SET overwrites the key whatever type it was holding
+    pub fn set(&self, key: String, value: impl Into<Bytes>) {
+        self.expires.remove(&key);
+        self.touch_key(&key);
+        self.db
+            .insert(key, RedisValue::String(StringValue::from(value.into())));
+    }
+
+    // returns whether the value was written, and the previous string value of the key
+    // when `get` is set, the previous value must be a string
+    pub fn set_with(
+        &self,
+        key: String,
+        value: Bytes,
+        condition: SetCondition,
+        expiry: SetExpiry,
+        get: bool,
+    ) -> Result<(bool, Option<Bytes>), BackendError> {
+        self.expire_if_needed(&key);
+        let entry = self.db.entry(key.clone());
+        let old = match &entry {
+            Entry::Occupied(e) => match e.get() {
+                RedisValue::String(v) => Some(Some(v.to_bytes())),
+                _ if get => return Err(BackendError::WrongType),
+                _ => Some(None),
+            },
+            Entry::Vacant(_) => None,
+        };
+        let applied = match condition {
+            SetCondition::Always => true,
+            SetCondition::NotExists => old.is_none(),
+            SetCondition::Exists => old.is_some(),
+        };
+        let old = old.flatten();
+        if !applied {
+            return Ok((false, old));
+        }
+        entry.insert(RedisValue::String(StringValue::from(value)));
+        self.touch_key(&key);
+        self.apply_expiry(key, expiry);
+        Ok((true, old))
+    }
+
+    // sets all the pairs, or none of them if `nx` is set and one of the keys exists
+    pub fn mset(&self, pairs: Vec<(String, Bytes)>, nx: bool) -> bool {
+        if nx && pairs.iter().any(|(key, _)| self.exists(key)) {
+            return false;
+        }
+        for (key, value) in pairs {
+            self.set(key, value);
+        }
+        true
+    }
+
+    // returns the value of the key and deletes it
+    pub fn getdel(&self, key: &str) -> Result<Option<Bytes>, BackendError> {
+        self.expire_if_needed(key);
+        match self.db.entry(key.to_string()) {
+            Entry::Occupied(e) => match e.get() {
+                RedisValue::String(v) => {
+                    let value = v.to_bytes();
+                    e.remove();
+                    self.expires.remove(key);
+                    self.touch_key(key);
+                    Ok(Some(value))
+                }
+                _ => Err(BackendError::WrongType),
+            },
+            Entry::Vacant(_) => Ok(None),
+        }
+    }
+
+    // returns the value of the key and updates its ttl, `Keep` leaves the ttl untouched
+    pub fn getex(&self, key: &str, expiry: SetExpiry) -> Result<Option<Bytes>, BackendError> {
+        let value = self.get(key)?;
+        if value.is_some() {
+            match expiry {
+                SetExpiry::At(at) => {
+                    self.expire_at(key, at);
+                }
+                SetExpiry::Clear => {
+                    self.persist(key);
+                }
+                SetExpiry::Keep => {}
+            }
+        }
+        Ok(value)
+    }
+
+    // write access to a string, a missing key is passed as None and stays missing if f returns None
+    fn with_string_mut<T>(
+        &self,
+        key: &str,
+        f: impl FnOnce(Option<&StringValue>) -> Result<(Option<StringValue>, T), BackendError>,
+    ) -> Result<T, BackendError> {
+        self.expire_if_needed(key);
+        match self.db.entry(key.to_string()) {
+            Entry::Occupied(mut e) => {
+                let current = match e.get() {
+                    RedisValue::String(v) => v,
+                    _ => return Err(BackendError::WrongType),
+                };
+                let (value, ret) = f(Some(current))?;
+                if let Some(value) = value {
+                    e.insert(RedisValue::String(value));
+                    self.touch_key(key);
+                }
+                Ok(ret)
+            }
+            Entry::Vacant(e) => {
+                let (value, ret) = f(None)?;
+                if let Some(value) = value {
+                    e.insert(RedisValue::String(value));
+                    self.touch_key(key);
+                }
+                Ok(ret)
+            }
+        }
+    }
+
+    // returns the length of the string after the append
+    pub fn append(&self, key: &str, value: &[u8]) -> Result<usize, BackendError> {
+        self.with_string_mut(key, |current| {
+            let current = current.map(|v| v.to_bytes()).unwrap_or_default();
+            if current.len() + value.len() > self.config.proto_max_bulk_len {
+                return Err(BackendError::StringTooLong);
+            }
+            let mut buf = BytesMut::with_capacity(current.len() + value.len());
+            buf.extend_from_slice(&current);
+            buf.extend_from_slice(value);
+            let len = buf.len();
+            Ok((Some(StringValue::from(buf.freeze())), len))
+        })
+    }
+
+    pub fn strlen(&self, key: &str) -> Result<usize, BackendError> {
+        self.expire_if_needed(key);
+        match self.db.get(key).as_deref() {
+            Some(RedisValue::String(v)) => Ok(v.len()),
+            Some(_) => Err(BackendError::WrongType),
+            None => Ok(0),
+        }
+    }
+
+    // a missing key counts as 0, returns the new value
+    pub fn incr_by(&self, key: &str, delta: i64) -> Result<i64, BackendError> {
+        self.with_string_mut(key, |current| {
+            let current = match current {
+                Some(v) => v.to_integer()?,
+                None => 0,
+            };
+            let value = current.checked_add(delta).ok_or(BackendError::Overflow)?;
+            Ok((Some(StringValue::Int(value)), value))
+        })
+    }
+
+    // a missing key counts as 0, returns the new value
+    pub fn incr_by_float(&self, key: &str, delta: f64) -> Result<f64, BackendError> {
+        self.with_string_mut(key, |current| {
+            let current = match current {
+                Some(v) => v.to_float()?,
+                None => 0.0,
+            };
+            let value = current + delta;
+            if !value.is_finite() {
+                return Err(BackendError::NanOrInfinity);
+            }
+            let stored = StringValue::from(Bytes::from(value.to_string()));
+            Ok((Some(stored), value))
+        })
+    }
+
+    // start and end are inclusive and may be negative, out of range parts are ignored
+    pub fn getrange(&self, key: &str, start: i64, end: i64) -> Result<Bytes, BackendError> {
+        let value = self.get(key)?.unwrap_or_default();
+        let len = value.len() as i64;
+        let start = if start < 0 {
+            (start + len).max(0)
+        } else {
+            start
+        };
+        let end = if end < 0 { end + len } else { end.min(len - 1) };
+        if start > end || start >= len {
+            return Ok(Bytes::new());
+        }
+        Ok(value.slice(start as usize..=end as usize))
+    }
+
+    // overwrites part of the string, padding it with zero bytes if needed, returns the new length
+    pub fn setrange(&self, key: &str, offset: usize, value: &[u8]) -> Result<usize, BackendError> {
+        self.with_string_mut(key, |current| {
+            let current = current.map(|v| v.to_bytes());
+            if value.is_empty() {
+                // nothing to write, a missing key is not created
+                let len = current.map(|v| v.len()).unwrap_or(0);
+                return Ok((None, len));
+            }
+            if offset + value.len() > self.config.proto_max_bulk_len {
+                return Err(BackendError::StringTooLong);
+            }
+            let current = current.unwrap_or_default();
+            let mut buf = BytesMut::from(current.as_ref());
+            if buf.len() < offset + value.len() {
+                buf.resize(offset + value.len(), 0);
+            }
+            buf[offset..offset + value.len()].copy_from_slice(value);
+            let len = buf.len();
+            Ok((Some(StringValue::from(buf.freeze())), len))
+        })
+    }
+
+    fn apply_expiry(&self, key: String, expiry: SetExpiry) {
+        match expiry {
+            SetExpiry::Clear => {
+                self.expires.remove(&key);
+            }
+            SetExpiry::Keep => {}
+            SetExpiry::At(at) => {
+                self.expires.insert(key, at);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_string_value_encoding() {
+        assert_eq!(StringValue::from(Bytes::from("-42")), StringValue::Int(-42));
+        assert_eq!(
+            StringValue::from(Bytes::from("007")),
+            StringValue::Raw(Bytes::from("007"))
+        );
+        assert_eq!(
+            StringValue::from(Bytes::from("+1")),
+            StringValue::Raw(Bytes::from("+1"))
+        );
+        assert_eq!(StringValue::Int(-42).to_bytes(), Bytes::from("-42"));
+        assert_eq!(StringValue::Int(-42).len(), 3);
+    }
+
+    #[test]
+    fn test_set_with_condition() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let value = Bytes::from("v1");
+        let (applied, old) = backend.set_with(
+            "k".to_string(),
+            value.clone(),
+            SetCondition::Exists,
+            SetExpiry::Clear,
+            false,
+        )?;
+        assert!(!applied);
+        assert_eq!(old, None);
+        assert_eq!(backend.get("k")?, None);
+
+        let (applied, _) = backend.set_with(
+            "k".to_string(),
+            value.clone(),
+            SetCondition::NotExists,
+            SetExpiry::Clear,
+            false,
+        )?;
+        assert!(applied);
+
+        let (applied, old) = backend.set_with(
+            "k".to_string(),
+            Bytes::from("v2"),
+            SetCondition::NotExists,
+            SetExpiry::Clear,
+            true,
+        )?;
+        assert!(!applied);
+        assert_eq!(old, Some(value));
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_with_get_wrong_type() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        backend.insert_set("k".to_string(), vec!["a".to_string()])?;
+        let ret = backend.set_with(
+            "k".to_string(),
+            Bytes::from("v"),
+            SetCondition::Always,
+            SetExpiry::Clear,
+            true,
+        );
+        assert_eq!(ret, Err(BackendError::WrongType));
+
+        let (applied, old) = backend.set_with(
+            "k".to_string(),
+            Bytes::from("v"),
+            SetCondition::Exists,
+            SetExpiry::Clear,
+            false,
+        )?;
+        assert!(applied);
+        assert_eq!(old, None);
+        assert_eq!(backend.key_type("k"), Some("string"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_incr() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        assert_eq!(backend.incr_by("n", 10)?, 10);
+        assert_eq!(backend.incr_by("n", -11)?, -1);
+        assert_eq!(backend.get("n")?, Some(Bytes::from("-1")));
+        backend.set("n".to_string(), i64::MAX.to_string());
+        assert_eq!(backend.incr_by("n", 1), Err(BackendError::Overflow));
+        backend.set("n".to_string(), " 1");
+        assert_eq!(backend.incr_by("n", 1), Err(BackendError::NotInteger));
+
+        backend.set("f".to_string(), "10.50");
+        assert_eq!(backend.incr_by_float("f", 0.1)?, 10.6);
+        assert_eq!(backend.get("f")?, Some(Bytes::from("10.6")));
+        assert_eq!(backend.incr_by_float("f", -0.6)?, 10.0);
+        assert_eq!(backend.incr_by("f", 1)?, 11);
+        assert_eq!(
+            backend.incr_by_float("f", f64::INFINITY),
+            Err(BackendError::NanOrInfinity)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_string_ranges() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        assert_eq!(backend.append("s", b"Hello")?, 5);
+        assert_eq!(backend.append("s", b" World")?, 11);
+        assert_eq!(backend.getrange("s", -5, -1)?, Bytes::from("World"));
+        assert_eq!(backend.getrange("s", 0, 100)?, Bytes::from("Hello World"));
+        assert_eq!(backend.getrange("s", 5, 1)?, Bytes::new());
+
+        assert_eq!(backend.setrange("s", 6, b"Redis")?, 11);
+        assert_eq!(backend.get("s")?, Some(Bytes::from("Hello Redis")));
+        assert_eq!(backend.setrange("p", 2, b"x")?, 3);
+        assert_eq!(backend.get("p")?, Some(Bytes::from_static(b"\0\0x")));
+        assert_eq!(backend.setrange("none", 2, b"")?, 0);
+        assert!(!backend.exists("none"));
+        assert_eq!(backend.strlen("p")?, 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_mset_getdel() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let pairs = vec![
+            ("a".to_string(), Bytes::from("1")),
+            ("b".to_string(), Bytes::from("2")),
+        ];
+        assert!(backend.mset(pairs.clone(), true));
+        assert!(!backend.mset(pairs, true));
+        assert_eq!(backend.getdel("a")?, Some(Bytes::from("1")));
+        assert!(!backend.exists("a"));
+        assert_eq!(backend.getdel("a")?, None);
+        Ok(())
+    }
+}
@@ -0,0 +1,104 @@
+use crate::backend::Backend;
+use dashmap::DashMap;
+use std::sync::Arc;
+use tokio::sync::{OwnedRwLockWriteGuard, RwLock, RwLockReadGuard};
+
+#[derive(Debug, Default)]
+pub struct TransactionState {
+    // every command of a client runs holding it shared, EXEC holds it exclusively so nothing
+    // interleaves with the queued commands
+    lock: Arc<RwLock<()>>,
+    // version counters of the keys some client is watching, only those are tracked
+    watched: DashMap<String, WatchedKey>,
+}
+
+#[derive(Debug, Default)]
+struct WatchedKey {
+    version: u64,
+    watchers: usize,
+}
+
+impl Backend {
+    // the lock is fair, a command waits behind an EXEC or a script that asked for it first
+    pub(crate) async fn command_guard(&self) -> RwLockReadGuard<'_, ()> {
+        self.transaction.lock.read().await
+    }
+
+    // owned, so a script can carry it to the thread it runs on
+    pub(crate) async fn exclusive_guard(&self) -> OwnedRwLockWriteGuard<()> {
+        self.transaction.lock.clone().write_owned().await
+    }
+
+    // starts tracking the key for a client, returns the version to compare against on EXEC
+    pub fn watch(&self, key: &str) -> u64 {
+        // a key already expired must not count as changed once it is evicted
+        self.expire_if_needed(key);
+        let mut watched = self.transaction.watched.entry(key.to_string()).or_default();
+        watched.watchers += 1;
+        watched.version
+    }
+
+    pub fn unwatch(&self, key: &str) {
+        if let Some(mut watched) = self.transaction.watched.get_mut(key) {
+            watched.watchers -= 1;
+        }
+        self.transaction
+            .watched
+            .remove_if(key, |_, watched| watched.watchers == 0);
+    }
+
+    // whether the key was written since it was watched at `version`, a key that expired in
+    // between counts as written
+    pub fn watched_key_changed(&self, key: &str, version: u64) -> bool {
+        self.expire_if_needed(key);
+        self.transaction
+            .watched
+            .get(key)
+            .is_none_or(|watched| watched.version != version)
+    }
+
+    // called on every write to a key, even one leaving the value as it was
+    pub(crate) fn touch_key(&self, key: &str) {
+        if let Some(mut watched) = self.transaction.watched.get_mut(key) {
+            watched.version += 1;
+        }
+    }
+
+    // the whole dataset was replaced, every watched key counts as written
+    pub(crate) fn touch_all_keys(&self) {
+        for mut watched in self.transaction.watched.iter_mut() {
+            watched.version += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::backend::now_ms;
+
+    #[test]
+    fn test_watched_key_versions() {
+        let backend = Backend::new();
+        let version = backend.watch("k");
+        assert!(!backend.watched_key_changed("k", version));
+        backend.set("other".to_string(), "v");
+        assert!(!backend.watched_key_changed("k", version));
+        backend.set("k".to_string(), "v");
+        assert!(backend.watched_key_changed("k", version));
+
+        let version = backend.watch("k");
+        assert!(!backend.watched_key_changed("k", version));
+        backend.expire_at("k", now_ms() + 10_000);
+        assert!(backend.watched_key_changed("k", version));
+
+        let version = backend.watch("k");
+        backend.remove("k");
+        assert!(backend.watched_key_changed("k", version));
+
+        backend.unwatch("k");
+        backend.unwatch("k");
+        backend.unwatch("k");
+        assert!(backend.transaction.watched.is_empty());
+    }
+}
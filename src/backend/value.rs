@@ -0,0 +1,73 @@
+use crate::backend::{SortedSet, StringValue};
+use crate::resp::{RespFrame, SimpleError};
+use bytes::Bytes;
+use std::collections::{HashMap, HashSet, VecDeque};
+use thiserror::Error;
+
+// the value stored under a key, every key holds exactly one type
+#[derive(Debug, Clone, PartialEq)]
+pub enum RedisValue {
+    String(StringValue),
+    Hash(HashMap<String, Bytes>),
+    Set(HashSet<String>),
+    List(VecDeque<Bytes>),
+    ZSet(SortedSet),
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum BackendError {
+    #[error("WRONGTYPE Operation against a key holding the wrong kind of value")]
+    WrongType,
+    #[error("ERR no such key")]
+    NoSuchKey,
+    #[error("ERR index out of range")]
+    IndexOutOfRange,
+    #[error("ERR value is not an integer or out of range")]
+    NotInteger,
+    #[error("ERR value is not a valid float")]
+    NotFloat,
+    #[error("ERR string exceeds maximum allowed size (proto-max-bulk-len)")]
+    StringTooLong,
+    #[error("ERR hash value is not an integer")]
+    HashNotInteger,
+    #[error("ERR hash value is not a float")]
+    HashNotFloat,
+    #[error("ERR increment or decrement would overflow")]
+    Overflow,
+    #[error("ERR increment would produce NaN or Infinity")]
+    NanOrInfinity,
+    #[error("ERR resulting score is not a number (NaN)")]
+    NotANumber,
+    #[error("ERR Background save already in progress")]
+    SaveInProgress,
+    #[error("ERR Background append only file rewriting already in progress")]
+    RewriteInProgress,
+    #[error("ERR {0}")]
+    Persistence(String),
+    #[error("ERR {0}")]
+    Function(String),
+    #[error("ERR {0}")]
+    Cluster(String),
+    #[error("NOTBUSY No scripts in execution right now.")]
+    NotBusy,
+    #[error("UNKILLABLE Sorry the script already executed write commands against the dataset. You can either wait the script termination or kill the server in a hard way using the SHUTDOWN NOSAVE command.")]
+    Unkillable,
+}
+
+impl RedisValue {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            RedisValue::String(_) => "string",
+            RedisValue::Hash(_) => "hash",
+            RedisValue::Set(_) => "set",
+            RedisValue::List(_) => "list",
+            RedisValue::ZSet(_) => "zset",
+        }
+    }
+}
+
+impl From<BackendError> for RespFrame {
+    fn from(e: BackendError) -> Self {
+        SimpleError::new(e.to_string()).into()
+    }
+}
@@ -0,0 +1,463 @@
+use crate::backend::{Backend, BackendError, RedisValue};
+use dashmap::mapref::entry::Entry;
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashMap};
+use std::ops::Bound;
+
+// members ordered by (score, member), with a member -> score index for O(1) lookups
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SortedSet {
+    scores: HashMap<String, f64>,
+    ordered: BTreeSet<(Score, String)>,
+}
+
+// f64 with a total order, so it can be used as a BTreeSet key
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Score(f64);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ZAddOptions {
+    pub nx: bool,
+    pub xx: bool,
+    pub gt: bool,
+    pub lt: bool,
+    pub ch: bool,
+    pub incr: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ZAddOutcome {
+    pub added: usize,
+    pub updated: usize,
+    // the new score of the member in INCR mode, None if the update was skipped
+    pub score: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScoreBound {
+    pub value: f64,
+    pub exclusive: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LexBound {
+    NegInf,
+    PosInf,
+    Inclusive(String),
+    Exclusive(String),
+}
+
+// the selection of a ZRANGE, indexes are inclusive and may be negative
+#[derive(Debug, Clone, PartialEq)]
+pub enum ZRangeBy {
+    Rank(i64, i64),
+    Score(ScoreBound, ScoreBound),
+    Lex(LexBound, LexBound),
+}
+
+impl Eq for Score {}
+
+impl PartialOrd for Score {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Score {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+impl ScoreBound {
+    fn above_min(&self, score: f64) -> bool {
+        if self.exclusive {
+            score > self.value
+        } else {
+            score >= self.value
+        }
+    }
+
+    fn below_max(&self, score: f64) -> bool {
+        if self.exclusive {
+            score < self.value
+        } else {
+            score <= self.value
+        }
+    }
+}
+
+impl LexBound {
+    fn above_min(&self, member: &str) -> bool {
+        match self {
+            LexBound::NegInf => true,
+            LexBound::PosInf => false,
+            LexBound::Inclusive(s) => member >= s.as_str(),
+            LexBound::Exclusive(s) => member > s.as_str(),
+        }
+    }
+
+    fn below_max(&self, member: &str) -> bool {
+        match self {
+            LexBound::NegInf => false,
+            LexBound::PosInf => true,
+            LexBound::Inclusive(s) => member <= s.as_str(),
+            LexBound::Exclusive(s) => member < s.as_str(),
+        }
+    }
+}
+
+impl SortedSet {
+    pub fn len(&self) -> usize {
+        self.scores.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scores.is_empty()
+    }
+
+    pub fn score(&self, member: &str) -> Option<f64> {
+        self.scores.get(member).copied()
+    }
+
+    // returns true if the member is new
+    pub fn insert(&mut self, member: String, score: f64) -> bool {
+        // -0.0 and 0.0 must be the same score
+        let score = score + 0.0;
+        let old = self.scores.insert(member.clone(), score);
+        if let Some(old) = old {
+            self.ordered.remove(&(Score(old), member.clone()));
+        }
+        self.ordered.insert((Score(score), member));
+        old.is_none()
+    }
+
+    pub fn remove(&mut self, member: &str) -> bool {
+        match self.scores.remove(member) {
+            Some(score) => {
+                self.ordered.remove(&(Score(score), member.to_string()));
+                true
+            }
+            None => false,
+        }
+    }
+
+    // 0-based position of the member in ascending order
+    pub fn rank(&self, member: &str) -> Option<usize> {
+        let score = self.score(member)?;
+        let upper = (Score(score), member.to_string());
+        Some(
+            self.ordered
+                .range((Bound::Unbounded, Bound::Excluded(&upper)))
+                .count(),
+        )
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (&String, f64)> {
+        self.ordered.iter().map(|(score, member)| (member, score.0))
+    }
+
+    // members in the selected range, `limit` is (offset, count) and a negative count means all
+    pub fn range(
+        &self,
+        by: &ZRangeBy,
+        rev: bool,
+        limit: Option<(usize, i64)>,
+    ) -> Vec<(String, f64)> {
+        let iter: Box<dyn Iterator<Item = (&String, f64)>> = match by {
+            ZRangeBy::Rank(start, stop) => {
+                let len = self.len() as i64;
+                let start = if *start < 0 {
+                    (start + len).max(0)
+                } else {
+                    *start
+                };
+                let stop = if *stop < 0 {
+                    stop + len
+                } else {
+                    (*stop).min(len - 1)
+                };
+                if start > stop || start >= len {
+                    return vec![];
+                }
+                let (skip, take) = (start as usize, (stop - start + 1) as usize);
+                if rev {
+                    Box::new(self.iter().rev().skip(skip).take(take))
+                } else {
+                    Box::new(self.iter().skip(skip).take(take))
+                }
+            }
+            ZRangeBy::Score(min, max) => {
+                let (min, max) = (*min, *max);
+                let in_range = move |(_, score): &(&String, f64)| {
+                    min.above_min(*score) && max.below_max(*score)
+                };
+                if rev {
+                    Box::new(self.iter().rev().filter(in_range))
+                } else {
+                    Box::new(self.iter().filter(in_range))
+                }
+            }
+            ZRangeBy::Lex(min, max) => {
+                let in_range =
+                    |(member, _): &(&String, f64)| min.above_min(member) && max.below_max(member);
+                if rev {
+                    Box::new(self.iter().rev().filter(in_range))
+                } else {
+                    Box::new(self.iter().filter(in_range))
+                }
+            }
+        };
+        let (offset, count) = limit.unwrap_or((0, -1));
+        let count = if count < 0 {
+            usize::MAX
+        } else {
+            count as usize
+        };
+        iter.skip(offset)
+            .take(count)
+            .map(|(member, score)| (member.clone(), score))
+            .collect()
+    }
+}
+
+impl Backend {
+    // read access to a sorted set, Ok(None) if the key does not exist
+    fn with_zset<T>(
+        &self,
+        key: &str,
+        f: impl FnOnce(&SortedSet) -> T,
+    ) -> Result<Option<T>, BackendError> {
+        self.expire_if_needed(key);
+        match self.db.get(key).as_deref() {
+            Some(RedisValue::ZSet(zset)) => Ok(Some(f(zset))),
+            Some(_) => Err(BackendError::WrongType),
+            None => Ok(None),
+        }
+    }
+
+    // write access to a sorted set, the key is created if `create` is set and removed once empty
+    fn with_zset_mut<T>(
+        &self,
+        key: &str,
+        create: bool,
+        f: impl FnOnce(&mut SortedSet) -> Result<T, BackendError>,
+    ) -> Result<Option<T>, BackendError> {
+        self.expire_if_needed(key);
+        match self.db.entry(key.to_string()) {
+            Entry::Occupied(mut e) => {
+                let (ret, empty) = match e.get_mut() {
+                    RedisValue::ZSet(zset) => (f(zset)?, zset.is_empty()),
+                    _ => return Err(BackendError::WrongType),
+                };
+                if empty {
+                    e.remove();
+                    self.expires.remove(key);
+                }
+                self.touch_key(key);
+                Ok(Some(ret))
+            }
+            Entry::Vacant(e) if create => {
+                let mut zset = SortedSet::default();
+                let ret = f(&mut zset)?;
+                if !zset.is_empty() {
+                    e.insert(RedisValue::ZSet(zset));
+                    self.touch_key(key);
+                }
+                Ok(Some(ret))
+            }
+            Entry::Vacant(_) => Ok(None),
+        }
+    }
+
+    pub fn zadd(
+        &self,
+        key: &str,
+        members: Vec<(f64, String)>,
+        options: ZAddOptions,
+    ) -> Result<ZAddOutcome, BackendError> {
+        let ret = self.with_zset_mut(key, !options.xx, |zset| {
+            let mut outcome = ZAddOutcome::default();
+            for (score, member) in members {
+                let current = zset.score(&member);
+                let score = match (options.incr, current) {
+                    (true, Some(current)) => current + score,
+                    _ => score,
+                };
+                if score.is_nan() {
+                    return Err(BackendError::NotANumber);
+                }
+                let skip = match current {
+                    Some(current) => {
+                        options.nx
+                            || (options.gt && score <= current)
+                            || (options.lt && score >= current)
+                    }
+                    None => options.xx,
+                };
+                if skip {
+                    continue;
+                }
+                outcome.score = Some(score);
+                match current {
+                    Some(current) if current != score => {
+                        zset.insert(member, score);
+                        outcome.updated += 1;
+                    }
+                    Some(_) => {}
+                    None => {
+                        zset.insert(member, score);
+                        outcome.added += 1;
+                    }
+                }
+            }
+            Ok(outcome)
+        })?;
+        Ok(ret.unwrap_or_default())
+    }
+
+    pub fn zrem(&self, key: &str, members: &[String]) -> Result<usize, BackendError> {
+        let ret = self.with_zset_mut(key, false, |zset| {
+            Ok(members.iter().filter(|m| zset.remove(m)).count())
+        })?;
+        Ok(ret.unwrap_or(0))
+    }
+
+    pub fn zcard(&self, key: &str) -> Result<usize, BackendError> {
+        Ok(self.with_zset(key, |zset| zset.len())?.unwrap_or(0))
+    }
+
+    pub fn zscore(&self, key: &str, member: &str) -> Result<Option<f64>, BackendError> {
+        Ok(self.with_zset(key, |zset| zset.score(member))?.flatten())
+    }
+
+    // rank of the member and its score, counted from the highest score if `rev` is set
+    pub fn zrank(
+        &self,
+        key: &str,
+        member: &str,
+        rev: bool,
+    ) -> Result<Option<(usize, f64)>, BackendError> {
+        let ret = self.with_zset(key, |zset| {
+            let rank = zset.rank(member)?;
+            let rank = if rev { zset.len() - 1 - rank } else { rank };
+            Some((rank, zset.score(member)?))
+        })?;
+        Ok(ret.flatten())
+    }
+
+    pub fn zrange(
+        &self,
+        key: &str,
+        by: &ZRangeBy,
+        rev: bool,
+        limit: Option<(usize, i64)>,
+    ) -> Result<Vec<(String, f64)>, BackendError> {
+        let ret = self.with_zset(key, |zset| zset.range(by, rev, limit))?;
+        Ok(ret.unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn zset(members: &[(f64, &str)]) -> SortedSet {
+        let mut zset = SortedSet::default();
+        for (score, member) in members {
+            zset.insert(member.to_string(), *score);
+        }
+        zset
+    }
+
+    fn members(range: Vec<(String, f64)>) -> Vec<String> {
+        range.into_iter().map(|(m, _)| m).collect()
+    }
+
+    #[test]
+    fn test_sorted_set_order() {
+        let mut zset = zset(&[(2.0, "b"), (1.0, "c"), (2.0, "a"), (f64::NEG_INFINITY, "d")]);
+        let order = zset.iter().map(|(m, _)| m.as_str()).collect::<Vec<_>>();
+        assert_eq!(order, vec!["d", "c", "a", "b"]);
+        assert_eq!(zset.rank("a"), Some(2));
+        assert!(!zset.insert("c".to_string(), 3.0));
+        assert_eq!(zset.rank("c"), Some(3));
+        assert!(zset.remove("c"));
+        assert_eq!(zset.rank("c"), None);
+        assert_eq!(zset.len(), 3);
+    }
+
+    #[test]
+    fn test_sorted_set_range() {
+        let zset = zset(&[(1.0, "a"), (2.0, "b"), (3.0, "c"), (4.0, "d")]);
+        let range = zset.range(&ZRangeBy::Rank(-2, -1), false, None);
+        assert_eq!(members(range), vec!["c", "d"]);
+        let range = zset.range(&ZRangeBy::Rank(0, 1), true, None);
+        assert_eq!(members(range), vec!["d", "c"]);
+
+        let min = ScoreBound {
+            value: 1.0,
+            exclusive: true,
+        };
+        let max = ScoreBound {
+            value: f64::INFINITY,
+            exclusive: false,
+        };
+        let range = zset.range(&ZRangeBy::Score(min, max), false, Some((1, 1)));
+        assert_eq!(members(range), vec!["c"]);
+        let range = zset.range(&ZRangeBy::Score(min, max), true, None);
+        assert_eq!(members(range), vec!["d", "c", "b"]);
+
+        let by = ZRangeBy::Lex(LexBound::Inclusive("b".to_string()), LexBound::PosInf);
+        let range = zset.range(&by, false, Some((0, -1)));
+        assert_eq!(members(range), vec!["b", "c", "d"]);
+    }
+
+    #[test]
+    fn test_zadd_options() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let members = vec![(1.0, "a".to_string()), (2.0, "b".to_string())];
+        let outcome = backend.zadd("z", members, ZAddOptions::default())?;
+        assert_eq!(outcome.added, 2);
+
+        let options = ZAddOptions {
+            gt: true,
+            ch: true,
+            ..Default::default()
+        };
+        let members = vec![(0.5, "a".to_string()), (3.0, "b".to_string())];
+        let outcome = backend.zadd("z", members, options)?;
+        assert_eq!((outcome.added, outcome.updated), (0, 1));
+        assert_eq!(backend.zscore("z", "a")?, Some(1.0));
+        assert_eq!(backend.zscore("z", "b")?, Some(3.0));
+
+        let options = ZAddOptions {
+            incr: true,
+            ..Default::default()
+        };
+        let outcome = backend.zadd("z", vec![(2.5, "a".to_string())], options)?;
+        assert_eq!(outcome.score, Some(3.5));
+        assert_eq!(backend.zrank("z", "a", false)?, Some((1, 3.5)));
+        assert_eq!(backend.zrank("z", "a", true)?, Some((0, 3.5)));
+
+        let options = ZAddOptions {
+            xx: true,
+            ..Default::default()
+        };
+        let outcome = backend.zadd("none", vec![(1.0, "a".to_string())], options)?;
+        assert_eq!(outcome.added, 0);
+        assert!(!backend.exists("none"));
+
+        let options = ZAddOptions {
+            incr: true,
+            ..Default::default()
+        };
+        backend.zadd("inf", vec![(f64::INFINITY, "a".to_string())], options)?;
+        let ret = backend.zadd("inf", vec![(f64::NEG_INFINITY, "a".to_string())], options);
+        assert_eq!(ret, Err(BackendError::NotANumber));
+
+        assert_eq!(backend.zrem("z", &["a".to_string(), "b".to_string()])?, 2);
+        assert!(!backend.exists("z"));
+        Ok(())
+    }
+}
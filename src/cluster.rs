@@ -0,0 +1,404 @@
+use crate::backend::{ClusterMessage, ClusterMessageKind, NodeAddr};
+use crate::resp::{BulkString, RespArray, RespEncode, RespFrame, RespParser};
+use crate::Backend;
+use anyhow::{anyhow, bail, Result};
+use bytes::BytesMut;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+// how often every other node is pinged, and the links to new nodes are opened
+const CLUSTER_PING_INTERVAL: Duration = Duration::from_millis(100);
+// how long a node that could not be reached is left alone before connecting again
+const CLUSTER_RETRY_INTERVAL: Duration = Duration::from_secs(1);
+
+// one end of a bus connection, the messages are RESP arrays:
+// kind sender ip port bus-port config-epoch current-epoch slots [id ip port bus-port ...]
+struct BusLink {
+    stream: TcpStream,
+    buf: BytesMut,
+    parser: RespParser,
+    // the address of the other end, for a node announcing an unspecified one
+    peer_ip: String,
+}
+
+impl BusLink {
+    fn new(stream: TcpStream, backend: &Backend) -> Result<Self> {
+        let peer_ip = stream.peer_addr()?.ip().to_string();
+        Ok(BusLink {
+            stream,
+            buf: BytesMut::new(),
+            parser: RespParser::with_limits(backend.config().resp_limits()),
+            peer_ip,
+        })
+    }
+
+    async fn send(&mut self, message: &ClusterMessage) -> Result<()> {
+        self.stream
+            .write_all(&encode_message(message).encode())
+            .await?;
+        Ok(())
+    }
+
+    async fn read(&mut self) -> Result<ClusterMessage> {
+        loop {
+            if let Some(frame) = self.parser.parse(&mut self.buf)? {
+                let mut message = decode_message(frame)?;
+                if message
+                    .addr
+                    .ip
+                    .parse::<IpAddr>()
+                    .is_ok_and(|ip| ip.is_unspecified())
+                {
+                    message.addr.ip = self.peer_ip.clone();
+                }
+                return Ok(message);
+            }
+            if self.stream.read_buf(&mut self.buf).await? == 0 {
+                bail!("connection closed by the node");
+            }
+        }
+    }
+}
+
+fn encode_message(message: &ClusterMessage) -> RespArray {
+    let kind = match message.kind {
+        ClusterMessageKind::Meet => "MEET",
+        ClusterMessageKind::Ping => "PING",
+        ClusterMessageKind::Pong => "PONG",
+    };
+    let slots = message
+        .slots
+        .iter()
+        .map(|(start, end)| format!("{}-{}", start, end))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let mut args = vec![
+        kind.to_string(),
+        message.sender.clone(),
+        message.addr.ip.clone(),
+        message.addr.port.to_string(),
+        message.addr.bus_port.to_string(),
+        message.config_epoch.to_string(),
+        message.current_epoch.to_string(),
+        slots,
+    ];
+    for (id, addr) in &message.gossip {
+        args.extend([
+            id.clone(),
+            addr.ip.clone(),
+            addr.port.to_string(),
+            addr.bus_port.to_string(),
+        ]);
+    }
+    RespArray::new(
+        args.into_iter()
+            .map(|arg| BulkString::new(arg).into())
+            .collect::<Vec<RespFrame>>(),
+    )
+}
+
+fn decode_message(frame: RespFrame) -> Result<ClusterMessage> {
+    let invalid = || anyhow!("invalid cluster message");
+    let RespFrame::Array(array) = frame else {
+        return Err(invalid());
+    };
+    let args = array
+        .iter()
+        .map(|arg| match arg {
+            RespFrame::BulkString(arg) => Ok(String::from_utf8_lossy(arg).into_owned()),
+            _ => Err(invalid()),
+        })
+        .collect::<Result<Vec<String>>>()?;
+    if args.len() < 8 || (args.len() - 8) % 4 != 0 {
+        return Err(invalid());
+    }
+    let kind = match args[0].as_str() {
+        "MEET" => ClusterMessageKind::Meet,
+        "PING" => ClusterMessageKind::Ping,
+        "PONG" => ClusterMessageKind::Pong,
+        _ => return Err(invalid()),
+    };
+    let addr = |fields: &[String]| -> Result<NodeAddr> {
+        Ok(NodeAddr {
+            ip: fields[0].clone(),
+            port: fields[1].parse()?,
+            bus_port: fields[2].parse()?,
+        })
+    };
+    let slots = args[7]
+        .split_whitespace()
+        .map(|range| {
+            let (start, end) = range.split_once('-').ok_or_else(invalid)?;
+            Ok((start.parse()?, end.parse()?))
+        })
+        .collect::<Result<Vec<(u16, u16)>>>()?;
+    let gossip = args[8..]
+        .chunks(4)
+        .map(|node| Ok((node[0].clone(), addr(&node[1..])?)))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(ClusterMessage {
+        kind,
+        sender: args[1].clone(),
+        addr: addr(&args[2..5])?,
+        config_epoch: args[5].parse()?,
+        current_epoch: args[6].parse()?,
+        slots,
+        gossip,
+    })
+}
+
+// takes the links other nodes open to this one, every PING and MEET is answered with a PONG
+pub async fn cluster_bus(listener: TcpListener, backend: Backend) -> Result<()> {
+    loop {
+        let (stream, raddr) = listener.accept().await?;
+        let backend = backend.clone();
+        tokio::spawn(async move {
+            if let Err(e) = serve_bus_link(stream, &backend).await {
+                info!(
+                    "[Simple-redis-server]cluster link from {} closed: {}",
+                    raddr, e
+                );
+            }
+        });
+    }
+}
+
+async fn serve_bus_link(stream: TcpStream, backend: &Backend) -> Result<()> {
+    let mut link = BusLink::new(stream, backend)?;
+    loop {
+        let message = link.read().await?;
+        backend.cluster_receive(&message, None);
+        if message.kind != ClusterMessageKind::Pong {
+            link.send(&backend.cluster_message(ClusterMessageKind::Pong))
+                .await?;
+        }
+    }
+}
+
+// keeps a link open to every node of the cluster and to the ones given to CLUSTER MEET
+pub async fn cluster_cycle(backend: Backend) {
+    let mut links: HashMap<(String, u16), JoinHandle<()>> = HashMap::new();
+    let mut interval = tokio::time::interval(CLUSTER_PING_INTERVAL);
+    loop {
+        interval.tick().await;
+        links.retain(|_, link| !link.is_finished());
+        for (addr, meet) in backend.cluster_peers() {
+            links
+                .entry((addr.ip.clone(), addr.bus_port))
+                .or_insert_with(|| tokio::spawn(node_link(backend.clone(), addr, meet)));
+        }
+    }
+}
+
+async fn node_link(backend: Backend, addr: NodeAddr, meet: bool) {
+    if let Err(e) = ping_node(&backend, &addr, meet).await {
+        warn!(
+            "[Simple-redis-server]cluster link to {}:{} failed: {}",
+            addr.ip, addr.bus_port, e
+        );
+        tokio::time::sleep(CLUSTER_RETRY_INTERVAL).await;
+    }
+}
+
+// pings the node until the link breaks, the first message is a MEET for a node met by address
+async fn ping_node(backend: &Backend, addr: &NodeAddr, mut meet: bool) -> Result<()> {
+    backend.cluster_ping_sent(addr);
+    let stream = TcpStream::connect((addr.ip.as_str(), addr.bus_port)).await?;
+    let mut link = BusLink::new(stream, backend)?;
+    let timeout = Duration::from_millis(backend.config().cluster_node_timeout);
+    let mut interval = tokio::time::interval(CLUSTER_PING_INTERVAL);
+    loop {
+        interval.tick().await;
+        let kind = match meet {
+            true => ClusterMessageKind::Meet,
+            false => ClusterMessageKind::Ping,
+        };
+        backend.cluster_ping_sent(addr);
+        link.send(&backend.cluster_message(kind)).await?;
+        let pong = tokio::time::timeout(timeout, link.read()).await??;
+        backend.cluster_receive(&pong, Some(addr));
+        meet = false;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::backend::{key_hash_slot, ClusterRedirect};
+    use crate::config::Config;
+    use crate::server::serve;
+    use std::collections::HashSet;
+
+    struct Node {
+        backend: Backend,
+        client: TcpStream,
+        port: u16,
+        bus_port: u16,
+    }
+
+    async fn start_node() -> Result<Node> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let bus = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let backend = Backend::with_config(Config {
+            cluster_enabled: true,
+            port: addr.port(),
+            cluster_port: bus.local_addr()?.port(),
+            ..Config::default()
+        });
+        tokio::spawn(serve(listener, backend.clone()));
+        tokio::spawn(cluster_bus(bus, backend.clone()));
+        tokio::spawn(cluster_cycle(backend.clone()));
+        Ok(Node {
+            client: TcpStream::connect(addr).await?,
+            port: addr.port(),
+            bus_port: backend.config().cluster_bus_port(),
+            backend,
+        })
+    }
+
+    impl Node {
+        // sends inline commands, one per line, and reads until a reply to each was decoded
+        async fn request(&mut self, request: &str) -> Result<String> {
+            self.client.write_all(request.as_bytes()).await?;
+            let (mut parser, mut buf) = (RespParser::default(), BytesMut::new());
+            let mut replies = String::new();
+            for _ in request.lines() {
+                let data = loop {
+                    if let Some((_, data)) = parser.parse_raw(&mut buf)? {
+                        break data;
+                    }
+                    if self.client.read_buf(&mut buf).await? == 0 {
+                        bail!("connection closed by the node");
+                    }
+                };
+                replies.push_str(&String::from_utf8_lossy(&data));
+            }
+            Ok(replies)
+        }
+
+        fn id(&self) -> String {
+            self.backend.cluster_myid()
+        }
+    }
+
+    // polls until the gossip spread what `reached` checks
+    async fn converge(reached: impl Fn() -> bool) -> Result<()> {
+        for _ in 0..200 {
+            if reached() {
+                return Ok(());
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        bail!("the cluster never converged")
+    }
+
+    #[tokio::test]
+    async fn test_cluster() -> Result<()> {
+        let mut a = start_node().await?;
+        let mut b = start_node().await?;
+        let mut c = start_node().await?;
+
+        // a only meets b, it learns about c from the gossip of b
+        let meet =
+            |node: &Node| format!("CLUSTER MEET 127.0.0.1 {} {}\r\n", node.port, node.bus_port);
+        assert_eq!(a.request(&meet(&b)).await?, "+OK\r\n");
+        assert_eq!(b.request(&meet(&c)).await?, "+OK\r\n");
+        a.request("CLUSTER ADDSLOTSRANGE 0 5460\r\n").await?;
+        b.request("CLUSTER ADDSLOTSRANGE 5461 10922\r\n").await?;
+        c.request("CLUSTER ADDSLOTSRANGE 10923 16383\r\n").await?;
+        let owners = vec![
+            (0, 5460, a.id()),
+            (5461, 10922, b.id()),
+            (10923, 16383, c.id()),
+        ];
+        // the config epochs that collided are told apart too, or the epoch a takes over a slot
+        // with could end up below the one of c
+        converge(|| {
+            let epochs = [&a, &b, &c].map(|node| node.backend.cluster_epochs());
+            let latest = epochs.iter().map(|(_, config)| *config).max();
+            let distinct = epochs
+                .iter()
+                .map(|(_, config)| config)
+                .collect::<HashSet<_>>();
+            [&a, &b, &c].iter().all(|node| {
+                node.backend.cluster_nodes().len() == 3
+                    && node.backend.cluster_slot_owners() == owners
+            }) && distinct.len() == 3
+                && epochs.iter().all(|(current, _)| Some(*current) == latest)
+        })
+        .await?;
+        assert!(b
+            .request("CLUSTER INFO\r\n")
+            .await?
+            .contains("cluster_state:ok\r\ncluster_slots_assigned:16384\r\n"));
+
+        // foo hashes to 12182, a slot of c
+        let slot = key_hash_slot(b"foo");
+        assert_eq!(
+            a.request("SET foo 1\r\n").await?,
+            format!("-MOVED {} 127.0.0.1:{}\r\n", slot, c.port)
+        );
+        assert_eq!(c.request("SET foo 1\r\n").await?, "+OK\r\n");
+        assert_eq!(
+            c.request("MSET foo 1 bar 2\r\n").await?,
+            "-CROSSSLOT Keys in request don't hash to the same slot\r\n"
+        );
+        assert_eq!(c.request("MSET foo 1 {foo}x 2\r\n").await?, "+OK\r\n");
+        assert_eq!(
+            c.request(&format!("CLUSTER COUNTKEYSINSLOT {}\r\n", slot))
+                .await?,
+            ":2\r\n"
+        );
+
+        // the slot moves from c to a: the keys not on c anymore are asked to a
+        a.request(&format!(
+            "CLUSTER SETSLOT {} IMPORTING {}\r\n",
+            slot,
+            c.id()
+        ))
+        .await?;
+        c.request(&format!(
+            "CLUSTER SETSLOT {} MIGRATING {}\r\n",
+            slot,
+            a.id()
+        ))
+        .await?;
+        assert_eq!(c.request("DEL {foo}x\r\n").await?, ":1\r\n");
+        assert_eq!(c.request("GET foo\r\n").await?, "$1\r\n1\r\n");
+        let ask = format!("-ASK {} 127.0.0.1:{}\r\n", slot, a.port);
+        assert_eq!(c.request("GET {foo}x\r\n").await?, ask);
+        let moved = format!("-MOVED {} 127.0.0.1:{}\r\n", slot, c.port);
+        assert_eq!(a.request("GET {foo}x\r\n").await?, moved);
+        assert_eq!(
+            a.request("ASKING\r\nSET {foo}x 2\r\n").await?,
+            "+OK\r\n+OK\r\n"
+        );
+        // ASKING only covered the command after it
+        assert_eq!(a.request("GET {foo}x\r\n").await?, moved);
+
+        assert_eq!(c.request("DEL foo\r\n").await?, ":1\r\n");
+        assert_eq!(
+            a.request("ASKING\r\nSET foo 1\r\n").await?,
+            "+OK\r\n+OK\r\n"
+        );
+        let set_slot = format!("CLUSTER SETSLOT {} NODE {}\r\n", slot, a.id());
+        for node in [&mut c, &mut a] {
+            assert_eq!(node.request(&set_slot).await?, "+OK\r\n");
+        }
+        // b hears of the new owner from the gossip of a
+        let new_owner = ClusterRedirect::Moved(slot, format!("127.0.0.1:{}", a.port));
+        converge(|| b.backend.cluster_route(&[b"foo"], false) == Err(new_owner.clone())).await?;
+        assert_eq!(
+            b.request("GET foo\r\n").await?,
+            format!("-MOVED {} 127.0.0.1:{}\r\n", slot, a.port)
+        );
+        assert_eq!(a.request("GET {foo}x\r\n").await?, "$1\r\n2\r\n");
+        Ok(())
+    }
+}
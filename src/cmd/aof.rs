@@ -0,0 +1,366 @@
+use crate::backend::now_ms;
+use crate::cmd::{Command, CommandExecutor};
+use crate::resp::{BulkString, RespArray, RespDecode, RespError, RespFrame};
+use crate::Backend;
+use anyhow::{anyhow, Result};
+use bytes::BytesMut;
+use std::fs::{self, OpenOptions};
+use tracing::warn;
+
+// turns an executed write command into the entry appended to the log, None if it is not logged:
+// relative expires become absolute deadlines, blocking and random pops become what they popped,
+// float increments become the value they resulted in, so replaying never rounds differently
+pub fn aof_entry(frame: RespFrame, reply: &RespFrame) -> Option<RespArray> {
+    let RespFrame::Array(args) = frame else {
+        return None;
+    };
+    if matches!(reply, RespFrame::Error(_)) {
+        return None;
+    }
+    let name = match args.first() {
+        Some(RespFrame::BulkString(name)) => name.to_ascii_lowercase(),
+        _ => return None,
+    };
+    match name.as_slice() {
+        b"expire" | b"pexpire" => {
+            let unit = if name == b"expire" { 1000 } else { 1 };
+            let mut args = args.0;
+            let at = absolute_deadline(args.get(2)?, unit)?;
+            args[0] = BulkString::new("PEXPIREAT").into();
+            args[2] = BulkString::new(at.to_string()).into();
+            Some(RespArray::new(args))
+        }
+        // the options of SET start after the value, those of GETEX after the key
+        b"set" => Some(absolute_set_expiry(args.0, 3)),
+        b"getex" => Some(absolute_set_expiry(args.0, 2)),
+        b"blpop" | b"brpop" => {
+            let RespFrame::Array(popped) = reply else {
+                return None;
+            };
+            let pop = if name == b"blpop" { "LPOP" } else { "RPOP" };
+            Some(RespArray::new([
+                BulkString::new(pop).into(),
+                popped.first()?.clone(),
+            ]))
+        }
+        b"blmove" => {
+            if !matches!(reply, RespFrame::BulkString(_)) {
+                return None;
+            }
+            let mut args = args.0;
+            args.truncate(5);
+            args[0] = BulkString::new("LMOVE").into();
+            Some(RespArray::new(args))
+        }
+        b"blmpop" => {
+            let RespFrame::Array(popped) = reply else {
+                return None;
+            };
+            let count = match popped.get(1)? {
+                RespFrame::Array(values) => values.len(),
+                _ => return None,
+            };
+            let numkeys = bulk_str(args.get(2)?)?.parse::<usize>().ok()?;
+            Some(RespArray::new([
+                BulkString::new("LMPOP").into(),
+                BulkString::new("1").into(),
+                popped.first()?.clone(),
+                args.get(3 + numkeys)?.clone(),
+                BulkString::new("COUNT").into(),
+                BulkString::new(count.to_string()).into(),
+            ]))
+        }
+        b"spop" => {
+            let members = match reply {
+                RespFrame::BulkString(member) => vec![member.clone().into()],
+                RespFrame::Array(members) => members.0.clone(),
+                RespFrame::Set(members) => members.0.clone(),
+                _ => return None,
+            };
+            if members.is_empty() {
+                return None;
+            }
+            let head = [BulkString::new("SREM").into(), args.get(1)?.clone()];
+            Some(RespArray::new(
+                head.into_iter().chain(members).collect::<Vec<_>>(),
+            ))
+        }
+        b"incrbyfloat" => Some(RespArray::new([
+            BulkString::new("SET").into(),
+            args.get(1)?.clone(),
+            reply.clone(),
+            BulkString::new("KEEPTTL").into(),
+        ])),
+        b"hincrbyfloat" => Some(RespArray::new([
+            BulkString::new("HSET").into(),
+            args.get(1)?.clone(),
+            args.get(2)?.clone(),
+            reply.clone(),
+        ])),
+        _ => Some(args),
+    }
+}
+
+// propagates the writes of a transaction or a script between MULTI and EXEC, so a crash never
+// leaves half of them in the log and a replica never applies half of them
+pub fn propagate_transaction(backend: &Backend, entries: Vec<RespArray>) {
+    if entries.is_empty() {
+        return;
+    }
+    backend.propagate(RespArray::new([BulkString::new("MULTI").into()]));
+    for entry in entries {
+        backend.propagate(entry);
+    }
+    backend.propagate(RespArray::new([BulkString::new("EXEC").into()]));
+}
+
+fn bulk_str(frame: &RespFrame) -> Option<&str> {
+    match frame {
+        RespFrame::BulkString(s) => std::str::from_utf8(s).ok(),
+        _ => None,
+    }
+}
+
+// `unit` is the number of milliseconds of the relative time of the frame
+fn absolute_deadline(frame: &RespFrame, unit: i64) -> Option<i64> {
+    let time = bulk_str(frame)?.parse::<i64>().ok()?;
+    Some((now_ms() as i64).saturating_add(time.saturating_mul(unit)))
+}
+
+// replaces EX and PX with PXAT
+fn absolute_set_expiry(mut args: Vec<RespFrame>, options_start: usize) -> RespArray {
+    let mut i = options_start;
+    while i + 1 < args.len() {
+        let unit = match bulk_str(&args[i]).map(|o| o.to_ascii_uppercase()) {
+            Some(o) if o == "EX" => 1000,
+            Some(o) if o == "PX" => 1,
+            _ => {
+                i += 1;
+                continue;
+            }
+        };
+        if let Some(at) = absolute_deadline(&args[i + 1], unit) {
+            args[i] = BulkString::new("PXAT").into();
+            args[i + 1] = BulkString::new(at.to_string()).into();
+        }
+        i += 2;
+    }
+    RespArray::new(args)
+}
+
+// replays the log through the regular command path, returns the number of commands executed;
+// an incomplete command at the end, left by a crash in the middle of a write, is cut off, and so
+// is a transaction whose EXEC never made it to the log
+pub fn load_aof(backend: &Backend) -> Result<usize> {
+    let path = backend.config().aof_path();
+    let data = fs::read(&path)?;
+    let mut buf = BytesMut::from(&data[..]);
+    let mut commands = 0;
+    // offset of the open MULTI and the commands queued since
+    let mut transaction: Option<(usize, Vec<Command>)> = None;
+    let mut valid_len = data.len();
+    let limits = backend.config().resp_limits();
+    while !buf.is_empty() {
+        let offset = data.len() - buf.len();
+        let frame = match RespFrame::decode_with_limits(&mut buf, &limits) {
+            Ok(frame) => frame,
+            Err(RespError::NotComplete) => {
+                warn!(
+                    "[Simple-redis-server]the append-only file is truncated, \
+                     {} trailing bytes are discarded",
+                    buf.len()
+                );
+                valid_len = offset;
+                break;
+            }
+            Err(e) => {
+                return Err(anyhow!(
+                    "bad file format reading the append only file: {}",
+                    e
+                ))
+            }
+        };
+        match command_name(&frame).as_deref() {
+            Some(b"multi") => transaction = Some((offset, Vec::new())),
+            Some(b"exec") => {
+                let Some((_, queued)) = transaction.take() else {
+                    return Err(anyhow!(
+                        "bad file format reading the append only file: EXEC without MULTI"
+                    ));
+                };
+                for cmd in queued {
+                    cmd.execute(backend);
+                    commands += 1;
+                }
+            }
+            _ => {
+                let cmd = Command::try_from(frame)
+                    .map_err(|e| anyhow!("bad file format reading the append only file: {}", e))?;
+                match transaction.as_mut() {
+                    Some((_, queued)) => queued.push(cmd),
+                    None => {
+                        cmd.execute(backend);
+                        commands += 1;
+                    }
+                }
+            }
+        }
+    }
+    if let Some((offset, _)) = transaction {
+        warn!(
+            "[Simple-redis-server]the append-only file ends inside a transaction, \
+             it is discarded"
+        );
+        valid_len = offset;
+    }
+    if valid_len < data.len() {
+        OpenOptions::new()
+            .write(true)
+            .open(&path)?
+            .set_len(valid_len as u64)?;
+    }
+    Ok(commands)
+}
+
+fn command_name(frame: &RespFrame) -> Option<Vec<u8>> {
+    match frame {
+        RespFrame::Array(args) => match args.first() {
+            Some(RespFrame::BulkString(name)) => Some(name.to_ascii_lowercase()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cmd::request;
+    use crate::config::Config;
+    use crate::resp::{RespEncode, RespNull};
+    use bytes::Bytes;
+
+    fn frame(args: &[&str]) -> RespFrame {
+        request(args).into()
+    }
+
+    fn entry_args(entry: RespArray) -> Vec<String> {
+        entry
+            .0
+            .into_iter()
+            .map(|a| match a {
+                RespFrame::BulkString(s) => String::from_utf8(s.0.to_vec()).unwrap(),
+                _ => panic!("unexpected frame {:?}", a),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_aof_entry() {
+        let ok = RespFrame::Integer(1);
+        let entry = aof_entry(frame(&["set", "k", "EX", "ex", "10"]), &ok).unwrap();
+        let args = entry_args(entry);
+        assert_eq!(args[..4], ["set", "k", "EX", "PXAT"]);
+        let at = args[4].parse::<u64>().unwrap();
+        assert!(at > now_ms() + 9_000 && at <= now_ms() + 10_000);
+
+        let args = entry_args(aof_entry(frame(&["expire", "k", "10", "NX"]), &ok).unwrap());
+        assert_eq!(args[0], "PEXPIREAT");
+        assert_eq!(args[3], "NX");
+
+        let popped =
+            RespArray::new([BulkString::new("list").into(), BulkString::new("a").into()]).into();
+        let entry = aof_entry(frame(&["brpop", "other", "list", "0"]), &popped).unwrap();
+        assert_eq!(entry_args(entry), ["RPOP", "list"]);
+        assert!(aof_entry(frame(&["blpop", "list", "1"]), &RespFrame::Null(RespNull)).is_none());
+
+        let popped = RespArray::new([
+            BulkString::new("b").into(),
+            RespArray::new([BulkString::new("x").into(), BulkString::new("y").into()]).into(),
+        ])
+        .into();
+        let entry = aof_entry(
+            frame(&["blmpop", "0", "2", "a", "b", "RIGHT", "COUNT", "5"]),
+            &popped,
+        )
+        .unwrap();
+        assert_eq!(
+            entry_args(entry),
+            ["LMPOP", "1", "b", "RIGHT", "COUNT", "2"]
+        );
+
+        let entry = aof_entry(frame(&["spop", "s"]), &BulkString::new("m").into()).unwrap();
+        assert_eq!(entry_args(entry), ["SREM", "s", "m"]);
+
+        let value = BulkString::new("10.1").into();
+        let entry = aof_entry(frame(&["incrbyfloat", "k", "0.1"]), &value).unwrap();
+        assert_eq!(entry_args(entry), ["SET", "k", "10.1", "KEEPTTL"]);
+        let entry = aof_entry(frame(&["hincrbyfloat", "h", "f", "0.1"]), &value).unwrap();
+        assert_eq!(entry_args(entry), ["HSET", "h", "f", "10.1"]);
+
+        let error = crate::resp::SimpleError::new("ERR").into();
+        assert!(aof_entry(frame(&["incr", "k"]), &error).is_none());
+    }
+
+    #[test]
+    fn test_load_aof() -> Result<()> {
+        let dir =
+            std::env::temp_dir().join(format!("simple-redis-load-aof-{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        let backend = Backend::with_config(Config {
+            dir: dir.clone(),
+            ..Config::default()
+        });
+        let mut data = Vec::new();
+        for args in [
+            &["SET", "k", "v"][..],
+            &["RPUSH", "list", "a", "b"],
+            &["PEXPIREAT", "list", "4102444800000"],
+            &["INCR", "n"],
+        ] {
+            let RespFrame::Array(array) = frame(args) else {
+                unreachable!()
+            };
+            data.extend_from_slice(&array.encode());
+        }
+        let mut complete = data.len();
+        data.extend_from_slice(b"*3\r\n$3\r\nSET\r\n$1\r\nx");
+        fs::write(backend.config().aof_path(), &data)?;
+
+        assert_eq!(load_aof(&backend)?, 4);
+        assert_eq!(backend.get("k")?, Some(Bytes::from("v")));
+        assert_eq!(backend.get("n")?, Some(Bytes::from("1")));
+        assert_eq!(backend.expire_time("list"), Some(Some(4102444800000)));
+        assert!(!backend.exists("x"));
+        // the truncated tail is cut off
+        assert_eq!(fs::read(backend.config().aof_path())?.len(), complete);
+
+        // a transaction is replayed whole or not at all
+        let mut data = Vec::new();
+        for args in [
+            &["MULTI"][..],
+            &["SET", "a", "1"],
+            &["EXEC"],
+            &["MULTI"],
+            &["SET", "b", "1"],
+        ] {
+            let RespFrame::Array(array) = frame(args) else {
+                unreachable!()
+            };
+            data.extend_from_slice(&array.encode());
+            if args == ["EXEC"] {
+                complete = data.len();
+            }
+        }
+        fs::write(backend.config().aof_path(), &data)?;
+        assert_eq!(load_aof(&backend)?, 1);
+        assert!(backend.exists("a"));
+        assert!(!backend.exists("b"));
+        assert_eq!(fs::read(backend.config().aof_path())?.len(), complete);
+
+        fs::write(backend.config().aof_path(), b"garbage")?;
+        assert!(load_aof(&backend).is_err());
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,63 @@
+use crate::cmd::{
+    extract_args, extract_bytes, extract_string, validate_command, CommandError, CommandExecutor,
+};
+use crate::resp::{RespArray, RespFrame};
+use crate::Backend;
+use bytes::Bytes;
+
+// APPEND key value
+// appends the value to the string, a missing key is created, returns the new length
+
+// redis> APPEND mykey "Hello"
+// (integer) 5
+// redis> APPEND mykey " World"
+// (integer) 11
+
+#[derive(Debug)]
+pub struct Append {
+    key: String,
+    value: Bytes,
+}
+
+impl CommandExecutor for Append {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.append(&self.key, &self.value) {
+            Ok(len) => RespFrame::Integer(len as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Append {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "append")?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(Append {
+            key: extract_string(args.next())?,
+            value: extract_bytes(args.next())?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_append_command() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let cmd = Append {
+            key: "mykey".to_string(),
+            value: Bytes::from("Hello"),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(5));
+        let cmd = Append {
+            key: "mykey".to_string(),
+            value: Bytes::from(" World"),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(11));
+        assert_eq!(backend.get("mykey")?, Some(Bytes::from("Hello World")));
+        Ok(())
+    }
+}
@@ -0,0 +1,26 @@
+use crate::cmd::{validate_command, CommandError, CommandExecutor};
+use crate::resp::{RespArray, RespFrame, SimpleString};
+use crate::Backend;
+
+// BGREWRITEAOF
+// rebuilds the append-only file from the current keyspace in the background
+
+#[derive(Debug)]
+pub struct BgRewriteAof;
+
+impl CommandExecutor for BgRewriteAof {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.bgrewrite_aof() {
+            Ok(()) => SimpleString::new("Background append only file rewriting started").into(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for BgRewriteAof {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "bgrewriteaof")?;
+        Ok(BgRewriteAof)
+    }
+}
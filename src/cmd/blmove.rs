@@ -0,0 +1,89 @@
+use crate::backend::ListEnd;
+use crate::cmd::lmove::extract_list_end;
+use crate::cmd::{
+    extract_args, extract_string, extract_timeout, validate_command, CommandError, CommandExecutor,
+};
+use crate::resp::{BulkString, RespArray, RespFrame, RespNull};
+use crate::Backend;
+use std::time::Duration;
+
+// BLMOVE source destination <LEFT | RIGHT> <LEFT | RIGHT> timeout
+// the blocking variant of LMOVE
+
+#[derive(Debug)]
+pub struct BLMove {
+    source: String,
+    destination: String,
+    from: ListEnd,
+    to: ListEnd,
+    timeout: Duration,
+}
+
+impl CommandExecutor for BLMove {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        self.attempt(backend).unwrap_or(RespFrame::Null(RespNull))
+    }
+}
+
+impl BLMove {
+    pub async fn execute_blocking(
+        self,
+        backend: &Backend,
+        on_ready: impl FnOnce(&RespFrame),
+    ) -> RespFrame {
+        let keys = [self.source.clone()];
+        backend
+            .block_on(&keys, self.timeout, || self.attempt(backend), on_ready)
+            .await
+            .unwrap_or(RespFrame::Null(RespNull))
+    }
+
+    fn attempt(&self, backend: &Backend) -> Option<RespFrame> {
+        match backend.lmove(&self.source, &self.destination, self.from, self.to) {
+            Ok(Some(value)) => Some(BulkString::new(value).into()),
+            Ok(None) => None,
+            Err(e) => Some(e.into()),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for BLMove {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "blmove")?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(BLMove {
+            source: extract_string(args.next())?,
+            destination: extract_string(args.next())?,
+            from: extract_list_end(args.next())?,
+            to: extract_list_end(args.next())?,
+            timeout: extract_timeout(args.next())?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bytes::Bytes;
+
+    #[tokio::test]
+    async fn test_blmove_command() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let cmd = BLMove::try_from(RespArray::new([
+            BulkString::new("blmove").into(),
+            BulkString::new("src").into(),
+            BulkString::new("dst").into(),
+            BulkString::new("right").into(),
+            BulkString::new("left").into(),
+            BulkString::new("0").into(),
+        ]))?;
+        let cloned = backend.clone();
+        let handle = tokio::spawn(async move { cmd.execute_blocking(&cloned, |_| {}).await });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        backend.list_push("src", vec![Bytes::from("x")], ListEnd::Left, false)?;
+        assert_eq!(handle.await?, BulkString::new("x").into());
+        assert_eq!(backend.lrange("dst", 0, -1)?, vec![Bytes::from("x")]);
+        Ok(())
+    }
+}
@@ -0,0 +1,184 @@
+use crate::backend::ListEnd;
+use crate::cmd::{
+    extract_args, extract_string, extract_timeout, validate_command, CommandError, CommandExecutor,
+};
+use crate::resp::{BulkString, RespArray, RespFrame, RespNullArray};
+use crate::Backend;
+use std::time::Duration;
+
+// BLPOP key [key ...] timeout
+// BRPOP key [key ...] timeout
+// pops from the first non-empty list, or blocks until an element is pushed to one of the keys
+
+// redis> BLPOP list1 list2 0
+// 1) "list1"
+// 2) "a"
+
+#[derive(Debug)]
+pub struct BLPop {
+    keys: Vec<String>,
+    timeout: Duration,
+}
+
+#[derive(Debug)]
+pub struct BRPop {
+    keys: Vec<String>,
+    timeout: Duration,
+}
+
+// outside of a connection (e.g. in a transaction) blocking commands never block
+impl CommandExecutor for BLPop {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        pop_first(backend, &self.keys, ListEnd::Left).unwrap_or(RespFrame::NullArray(RespNullArray))
+    }
+}
+
+impl CommandExecutor for BRPop {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        pop_first(backend, &self.keys, ListEnd::Right)
+            .unwrap_or(RespFrame::NullArray(RespNullArray))
+    }
+}
+
+impl BLPop {
+    pub async fn execute_blocking(
+        self,
+        backend: &Backend,
+        on_ready: impl FnOnce(&RespFrame),
+    ) -> RespFrame {
+        backend
+            .block_on(
+                &self.keys,
+                self.timeout,
+                || pop_first(backend, &self.keys, ListEnd::Left),
+                on_ready,
+            )
+            .await
+            .unwrap_or(RespFrame::NullArray(RespNullArray))
+    }
+}
+
+impl BRPop {
+    pub async fn execute_blocking(
+        self,
+        backend: &Backend,
+        on_ready: impl FnOnce(&RespFrame),
+    ) -> RespFrame {
+        backend
+            .block_on(
+                &self.keys,
+                self.timeout,
+                || pop_first(backend, &self.keys, ListEnd::Right),
+                on_ready,
+            )
+            .await
+            .unwrap_or(RespFrame::NullArray(RespNullArray))
+    }
+}
+
+// pops an element from the first non-empty list, None if all of them are empty
+fn pop_first(backend: &Backend, keys: &[String], end: ListEnd) -> Option<RespFrame> {
+    for key in keys {
+        match backend.list_pop(key, 1, end) {
+            Ok(Some(mut values)) => {
+                if let Some(value) = values.pop() {
+                    return Some(
+                        RespArray::new([
+                            BulkString::new(key.as_str()).into(),
+                            BulkString::new(value).into(),
+                        ])
+                        .into(),
+                    );
+                }
+            }
+            Ok(None) => {}
+            Err(e) => return Some(e.into()),
+        }
+    }
+    None
+}
+
+fn parse_blocking_pop_args(
+    value: RespArray,
+    name: &'static str,
+) -> Result<(Vec<String>, Duration), CommandError> {
+    validate_command(&value, name)?;
+    let mut args = extract_args(value, 1)?;
+    let timeout = extract_timeout(args.pop())?;
+    let keys = args
+        .into_iter()
+        .map(|arg| extract_string(Some(arg)))
+        .collect::<Result<Vec<String>, CommandError>>()?;
+    Ok((keys, timeout))
+}
+
+impl TryFrom<RespArray> for BLPop {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (keys, timeout) = parse_blocking_pop_args(value, "blpop")?;
+        Ok(BLPop { keys, timeout })
+    }
+}
+
+impl TryFrom<RespArray> for BRPop {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (keys, timeout) = parse_blocking_pop_args(value, "brpop")?;
+        Ok(BRPop { keys, timeout })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::resp::RespDecode;
+    use bytes::{Bytes, BytesMut};
+
+    #[test]
+    fn test_blpop_from_resp_array() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$5\r\nblpop\r\n$1\r\na\r\n$1\r\nb\r\n$3\r\n0.5\r\n");
+
+        let frame = RespArray::decode(&mut buf)?;
+        let result: BLPop = frame.try_into()?;
+        assert_eq!(result.keys, vec!["a".to_string(), "b".to_string()]);
+        assert_eq!(result.timeout, Duration::from_millis(500));
+
+        let ret = BRPop::try_from(RespArray::new([
+            BulkString::new("brpop").into(),
+            BulkString::new("a").into(),
+            BulkString::new("-1").into(),
+        ]));
+        assert!(ret.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_blpop_command() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let cmd = BLPop {
+            keys: vec!["a".to_string(), "b".to_string()],
+            timeout: Duration::from_millis(10),
+        };
+        assert_eq!(
+            cmd.execute_blocking(&backend, |_| {}).await,
+            RespFrame::NullArray(RespNullArray)
+        );
+
+        let cloned = backend.clone();
+        let handle = tokio::spawn(async move {
+            let cmd = BRPop {
+                keys: vec!["a".to_string(), "b".to_string()],
+                timeout: Duration::ZERO,
+            };
+            cmd.execute_blocking(&cloned, |_| {}).await
+        });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        backend.list_push("b", vec![Bytes::from("x")], ListEnd::Left, false)?;
+        assert_eq!(
+            handle.await?,
+            RespArray::new([BulkString::new("b").into(), BulkString::new("x").into()]).into()
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,516 @@
+use crate::backend::{
+    key_hash_slot, BackendError, ClusterNodeInfo, NodeAddr, SlotState, CLUSTER_SLOTS,
+};
+use crate::cmd::{
+    extract_args, extract_bytes, extract_string, validate_command, CommandError, CommandExecutor,
+    RESP_OK,
+};
+use crate::network::ClientState;
+use crate::resp::{BulkString, RespArray, RespFrame, RespMap, SimpleError};
+use crate::Backend;
+use bytes::Bytes;
+
+// CLUSTER INFO | MYID | NODES | SLOTS | SHARDS
+// CLUSTER KEYSLOT key
+// CLUSTER COUNTKEYSINSLOT slot
+// CLUSTER GETKEYSINSLOT slot count
+// CLUSTER MEET ip port [cluster-bus-port]
+// CLUSTER ADDSLOTS slot [slot ...] | ADDSLOTSRANGE start end [start end ...]
+// CLUSTER DELSLOTS slot [slot ...] | DELSLOTSRANGE start end [start end ...]
+// CLUSTER SETSLOT slot IMPORTING node-id | MIGRATING node-id | NODE node-id | STABLE
+// ASKING
+// inspects and builds the cluster: the nodes met here gossip the slots they own over the bus.
+// ASKING lets the next command of the connection into a slot being imported, after an -ASK
+
+// redis> CLUSTER KEYSLOT somekey
+// (integer) 11058
+
+#[derive(Debug)]
+pub enum Cluster {
+    Info,
+    MyId,
+    Nodes,
+    Slots,
+    Shards,
+    KeySlot(Bytes),
+    CountKeysInSlot(u16),
+    GetKeysInSlot { slot: u16, count: usize },
+    Meet(NodeAddr),
+    AddSlots(Vec<u16>),
+    DelSlots(Vec<u16>),
+    SetSlot { slot: u16, state: SlotState },
+}
+
+#[derive(Debug)]
+pub struct Asking;
+
+fn cluster_disabled() -> RespFrame {
+    SimpleError::new("ERR This instance has cluster support disabled").into()
+}
+
+impl CommandExecutor for Cluster {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        if !backend.cluster_enabled() {
+            return cluster_disabled();
+        }
+        let done = |ret: Result<(), BackendError>| match ret {
+            Ok(()) => RESP_OK.clone(),
+            Err(e) => SimpleError::new(e.to_string()).into(),
+        };
+        match self {
+            Cluster::Info => BulkString::new(cluster_info(backend)).into(),
+            Cluster::MyId => BulkString::new(backend.cluster_myid()).into(),
+            Cluster::Nodes => BulkString::new(cluster_nodes(backend)).into(),
+            Cluster::Slots => cluster_slots(backend),
+            Cluster::Shards => cluster_shards(backend),
+            Cluster::KeySlot(key) => RespFrame::Integer(key_hash_slot(&key) as i64),
+            Cluster::CountKeysInSlot(slot) => {
+                RespFrame::Integer(backend.keys_in_slot(slot).len() as i64)
+            }
+            Cluster::GetKeysInSlot { slot, count } => RespArray::new(
+                backend
+                    .keys_in_slot(slot)
+                    .into_iter()
+                    .take(count)
+                    .map(|key| BulkString::new(key).into())
+                    .collect::<Vec<RespFrame>>(),
+            )
+            .into(),
+            Cluster::Meet(addr) => {
+                backend.cluster_meet(addr);
+                RESP_OK.clone()
+            }
+            Cluster::AddSlots(slots) => done(backend.cluster_add_slots(&slots)),
+            Cluster::DelSlots(slots) => done(backend.cluster_del_slots(&slots)),
+            Cluster::SetSlot { slot, state } => done(backend.cluster_set_slot(slot, state)),
+        }
+    }
+}
+
+impl CommandExecutor for Asking {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        self.execute_for(&mut ClientState::default(), backend)
+    }
+}
+
+impl Asking {
+    pub fn execute_for(self, client: &mut ClientState, backend: &Backend) -> RespFrame {
+        if !backend.cluster_enabled() {
+            return cluster_disabled();
+        }
+        client.asking = true;
+        RESP_OK.clone()
+    }
+}
+
+fn cluster_info(backend: &Backend) -> String {
+    let nodes = backend.cluster_nodes();
+    let assigned = nodes
+        .iter()
+        .flat_map(|node| node.slots.iter())
+        .map(|(start, end)| (end - start + 1) as usize)
+        .sum::<usize>();
+    let pfail = nodes
+        .iter()
+        .filter(|node| node.failing)
+        .flat_map(|node| node.slots.iter())
+        .map(|(start, end)| (end - start + 1) as usize)
+        .sum::<usize>();
+    let (current_epoch, my_epoch) = backend.cluster_epochs();
+    let state = match assigned == CLUSTER_SLOTS as usize {
+        true => "ok",
+        false => "fail",
+    };
+    [
+        format!("cluster_state:{}", state),
+        format!("cluster_slots_assigned:{}", assigned),
+        format!("cluster_slots_ok:{}", assigned - pfail),
+        format!("cluster_slots_pfail:{}", pfail),
+        "cluster_slots_fail:0".to_string(),
+        format!("cluster_known_nodes:{}", nodes.len()),
+        format!(
+            "cluster_size:{}",
+            nodes.iter().filter(|node| !node.slots.is_empty()).count()
+        ),
+        format!("cluster_current_epoch:{}", current_epoch),
+        format!("cluster_my_epoch:{}", my_epoch),
+    ]
+    .iter()
+    .map(|line| format!("{}\r\n", line))
+    .collect()
+}
+
+// one line per node: id ip:port@cport flags master ping-sent pong-recv config-epoch link-state
+// slot ...
+fn cluster_nodes(backend: &Backend) -> String {
+    let (migrating, importing) = backend.cluster_migrations();
+    backend
+        .cluster_nodes()
+        .iter()
+        .map(|node| {
+            let mut flags = match node.myself {
+                true => "myself,master".to_string(),
+                false => "master".to_string(),
+            };
+            if node.failing {
+                flags.push_str(",fail?");
+            }
+            let link = match node.myself || (node.pong_received > 0 && !node.failing) {
+                true => "connected",
+                false => "disconnected",
+            };
+            let mut line = format!(
+                "{} {}:{}@{} {} - {} {} {} {}",
+                node.id,
+                node.addr.ip,
+                node.addr.port,
+                node.addr.bus_port,
+                flags,
+                node.ping_sent,
+                node.pong_received,
+                node.config_epoch,
+                link
+            );
+            for (start, end) in &node.slots {
+                match start == end {
+                    true => line.push_str(&format!(" {}", start)),
+                    false => line.push_str(&format!(" {}-{}", start, end)),
+                }
+            }
+            if node.myself {
+                for (slot, id) in &migrating {
+                    line.push_str(&format!(" [{}->-{}]", slot, id));
+                }
+                for (slot, id) in &importing {
+                    line.push_str(&format!(" [{}-<-{}]", slot, id));
+                }
+            }
+            line.push('\n');
+            line
+        })
+        .collect()
+}
+
+fn cluster_slots(backend: &Backend) -> RespFrame {
+    let nodes = backend.cluster_nodes();
+    let ranges = backend
+        .cluster_slot_owners()
+        .into_iter()
+        .filter_map(|(start, end, id)| {
+            let node = nodes.iter().find(|node| node.id == id)?;
+            Some(
+                RespArray::new([
+                    RespFrame::Integer(start as i64),
+                    RespFrame::Integer(end as i64),
+                    RespArray::new([
+                        BulkString::new(node.addr.ip.clone()).into(),
+                        RespFrame::Integer(node.addr.port as i64),
+                        BulkString::new(id).into(),
+                    ])
+                    .into(),
+                ])
+                .into(),
+            )
+        })
+        .collect::<Vec<RespFrame>>();
+    RespArray::new(ranges).into()
+}
+
+// every node is a shard of its own, there are no replicas in the cluster
+fn cluster_shards(backend: &Backend) -> RespFrame {
+    let shards = backend
+        .cluster_nodes()
+        .iter()
+        .map(|node| {
+            let slots = node
+                .slots
+                .iter()
+                .flat_map(|(start, end)| [*start, *end])
+                .map(|slot| RespFrame::Integer(slot as i64))
+                .collect::<Vec<RespFrame>>();
+            let mut shard = RespMap::new();
+            shard.insert("slots".to_string(), RespArray::new(slots).into());
+            shard.insert(
+                "nodes".to_string(),
+                RespArray::new([shard_node(backend, node)]).into(),
+            );
+            shard.into()
+        })
+        .collect::<Vec<RespFrame>>();
+    RespArray::new(shards).into()
+}
+
+fn shard_node(backend: &Backend, node: &ClusterNodeInfo) -> RespFrame {
+    let offset = match node.myself {
+        true => backend.master_repl_offset(),
+        false => 0,
+    };
+    let health = match node.failing {
+        true => "fail",
+        false => "online",
+    };
+    let mut info = RespMap::new();
+    info.insert("id".to_string(), BulkString::new(node.id.clone()).into());
+    info.insert(
+        "port".to_string(),
+        RespFrame::Integer(node.addr.port as i64),
+    );
+    info.insert(
+        "ip".to_string(),
+        BulkString::new(node.addr.ip.clone()).into(),
+    );
+    info.insert(
+        "endpoint".to_string(),
+        BulkString::new(node.addr.ip.clone()).into(),
+    );
+    info.insert("role".to_string(), BulkString::new("master").into());
+    info.insert(
+        "replication-offset".to_string(),
+        RespFrame::Integer(offset as i64),
+    );
+    info.insert("health".to_string(), BulkString::new(health).into());
+    info.into()
+}
+
+fn parse_slot(arg: &Bytes) -> Result<u16, CommandError> {
+    std::str::from_utf8(arg)
+        .ok()
+        .and_then(|slot| slot.parse::<u16>().ok())
+        .filter(|slot| *slot < CLUSTER_SLOTS)
+        .ok_or_else(|| CommandError::InvalidArgument("Invalid or out of range slot".to_string()))
+}
+
+// the slots of ADDSLOTS and DELSLOTS, or the ranges of their RANGE forms
+fn parse_slots(args: &[Bytes], ranges: bool) -> Result<Vec<u16>, CommandError> {
+    let mut slots = Vec::new();
+    if ranges {
+        for range in args.chunks(2) {
+            let (start, end) = (parse_slot(&range[0])?, parse_slot(&range[1])?);
+            if start > end {
+                return Err(CommandError::InvalidArgument(format!(
+                    "start slot number {} is greater than end slot number {}",
+                    start, end
+                )));
+            }
+            slots.extend(start..=end);
+        }
+    } else {
+        for arg in args {
+            slots.push(parse_slot(arg)?);
+        }
+    }
+    let mut seen = vec![false; CLUSTER_SLOTS as usize];
+    for &slot in &slots {
+        if std::mem::replace(&mut seen[slot as usize], true) {
+            return Err(CommandError::InvalidArgument(format!(
+                "Slot {} specified multiple times",
+                slot
+            )));
+        }
+    }
+    Ok(slots)
+}
+
+impl TryFrom<RespArray> for Cluster {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "cluster")?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        let subcommand = extract_string(args.next())?.to_ascii_lowercase();
+        let args = args
+            .map(|arg| extract_bytes(Some(arg)))
+            .collect::<Result<Vec<Bytes>, CommandError>>()?;
+        let wrong_arity = || CommandError::WrongArity(format!("cluster|{}", subcommand));
+        let text = |arg: &Bytes| String::from_utf8_lossy(arg).into_owned();
+        match (subcommand.as_str(), args.as_slice()) {
+            ("info", []) => Ok(Cluster::Info),
+            ("myid", []) => Ok(Cluster::MyId),
+            ("nodes", []) => Ok(Cluster::Nodes),
+            ("slots", []) => Ok(Cluster::Slots),
+            ("shards", []) => Ok(Cluster::Shards),
+            ("keyslot", [key]) => Ok(Cluster::KeySlot(key.clone())),
+            ("countkeysinslot", [slot]) => parse_slot(slot)
+                .map(Cluster::CountKeysInSlot)
+                .map_err(|_| CommandError::InvalidArgument("Invalid slot".to_string())),
+            ("getkeysinslot", [slot, count]) => {
+                let slot = parse_slot(slot).ok();
+                let count = std::str::from_utf8(count)
+                    .ok()
+                    .and_then(|count| count.parse::<usize>().ok());
+                match (slot, count) {
+                    (Some(slot), Some(count)) => Ok(Cluster::GetKeysInSlot { slot, count }),
+                    _ => Err(CommandError::InvalidArgument(
+                        "Invalid slot or number of keys".to_string(),
+                    )),
+                }
+            }
+            ("meet", [ip, port]) | ("meet", [ip, port, _]) => {
+                let parse_port = |arg: &Bytes| text(arg).parse::<u16>().ok();
+                let port = parse_port(port);
+                // the bus port is the client port plus 10000 unless given
+                let bus_port = match args.get(2) {
+                    Some(bus_port) => parse_port(bus_port),
+                    None => port.and_then(|port| port.checked_add(10000)),
+                };
+                match (port, bus_port) {
+                    (Some(port), Some(bus_port)) => Ok(Cluster::Meet(NodeAddr {
+                        ip: text(ip),
+                        port,
+                        bus_port,
+                    })),
+                    _ => Err(CommandError::InvalidArgument(format!(
+                        "Invalid node address specified: {}:{}",
+                        text(ip),
+                        text(&args[1])
+                    ))),
+                }
+            }
+            ("addslots", slots) if !slots.is_empty() => {
+                Ok(Cluster::AddSlots(parse_slots(slots, false)?))
+            }
+            ("delslots", slots) if !slots.is_empty() => {
+                Ok(Cluster::DelSlots(parse_slots(slots, false)?))
+            }
+            ("addslotsrange", ranges) if !ranges.is_empty() && ranges.len() % 2 == 0 => {
+                Ok(Cluster::AddSlots(parse_slots(ranges, true)?))
+            }
+            ("delslotsrange", ranges) if !ranges.is_empty() && ranges.len() % 2 == 0 => {
+                Ok(Cluster::DelSlots(parse_slots(ranges, true)?))
+            }
+            ("setslot", [slot, rest @ ..]) if !rest.is_empty() => {
+                let slot = parse_slot(slot)?;
+                let action = text(&rest[0]).to_ascii_lowercase();
+                let state =
+                    match (action.as_str(), &rest[1..]) {
+                        ("importing", [id]) => SlotState::Importing(text(id)),
+                        ("migrating", [id]) => SlotState::Migrating(text(id)),
+                        ("node", [id]) => SlotState::Node(text(id)),
+                        ("stable", []) => SlotState::Stable,
+                        _ => return Err(CommandError::InvalidArgument(
+                            "Invalid CLUSTER SETSLOT action or number of arguments. Try CLUSTER \
+                             HELP"
+                                .to_string(),
+                        )),
+                    };
+                Ok(Cluster::SetSlot { slot, state })
+            }
+            (
+                "info" | "myid" | "nodes" | "slots" | "shards" | "keyslot" | "countkeysinslot"
+                | "getkeysinslot" | "meet" | "addslots" | "delslots" | "addslotsrange"
+                | "delslotsrange" | "setslot",
+                _,
+            ) => Err(wrong_arity()),
+            _ => Err(CommandError::InvalidArgument(format!(
+                "unknown subcommand '{}'. Try CLUSTER HELP.",
+                subcommand
+            ))),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Asking {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "asking")?;
+        Ok(Asking)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cmd::request;
+    use crate::config::Config;
+    use anyhow::Result;
+
+    fn cluster(backend: &Backend, args: &[&str]) -> Result<RespFrame> {
+        let args = [&["cluster"], args].concat();
+        Ok(Cluster::try_from(request(&args))?.execute(backend))
+    }
+
+    #[test]
+    fn test_cluster_command() -> Result<()> {
+        assert_eq!(cluster(&Backend::new(), &["info"])?, cluster_disabled());
+
+        let backend = Backend::with_config(Config {
+            cluster_enabled: true,
+            port: 7000,
+            ..Config::default()
+        });
+        assert_eq!(
+            cluster(&backend, &["keyslot", "somekey"])?,
+            RespFrame::Integer(11058)
+        );
+        assert_eq!(
+            cluster(&backend, &["keyslot", "{user1}.name"])?,
+            cluster(&backend, &["keyslot", "user1"])?
+        );
+
+        assert_eq!(
+            cluster(&backend, &["addslotsrange", "0", "8191"])?,
+            RESP_OK.clone()
+        );
+        assert_eq!(
+            cluster(&backend, &["addslots", "8191"])?,
+            SimpleError::new("ERR Slot 8191 is already busy").into()
+        );
+        assert!(Cluster::try_from(RespArray::new([
+            BulkString::new("cluster").into(),
+            BulkString::new("addslots").into(),
+            BulkString::new("16384").into(),
+        ]))
+        .is_err());
+        let info = cluster(&backend, &["info"])?;
+        let RespFrame::BulkString(info) = info else {
+            panic!("CLUSTER INFO replies with a bulk string");
+        };
+        let info = String::from_utf8_lossy(&info);
+        assert!(info.starts_with("cluster_state:fail\r\ncluster_slots_assigned:8192\r\n"));
+        assert_eq!(
+            cluster(&backend, &["addslotsrange", "8192", "16383"])?,
+            RESP_OK.clone()
+        );
+
+        let id = backend.cluster_myid();
+        assert_eq!(
+            cluster(&backend, &["nodes"])?,
+            BulkString::new(format!(
+                "{} 127.0.0.1:7000@17000 myself,master - 0 0 0 connected 0-16383\n",
+                id
+            ))
+            .into()
+        );
+        assert_eq!(
+            cluster(&backend, &["slots"])?,
+            RespArray::new([RespArray::new([
+                RespFrame::Integer(0),
+                RespFrame::Integer(16383),
+                RespArray::new([
+                    BulkString::new("127.0.0.1").into(),
+                    RespFrame::Integer(7000),
+                    BulkString::new(id).into(),
+                ])
+                .into(),
+            ])
+            .into()])
+            .into()
+        );
+
+        backend.set("{user1}.name".to_string(), "a");
+        backend.set("{user1}.mail".to_string(), "b");
+        let slot = key_hash_slot(b"user1").to_string();
+        assert_eq!(
+            cluster(&backend, &["countkeysinslot", &slot])?,
+            RespFrame::Integer(2)
+        );
+        assert_eq!(
+            cluster(&backend, &["getkeysinslot", &slot, "1"])?,
+            RespArray::new([BulkString::new("{user1}.mail").into()]).into()
+        );
+        assert_eq!(
+            cluster(&backend, &["setslot", &slot, "migrating", "unknown"])?,
+            SimpleError::new("ERR I don't know about node unknown").into()
+        );
+        Ok(())
+    }
+}
@@ -1,6 +1,18 @@
+use crate::backend::Backend;
 use crate::cmd::sadd::SAdd;
+use crate::cmd::{arity, CommandExecutor};
 use crate::cmd::{
-    CommandError, Echo, Get, HGet, HGetAll, HMGet, HSet, Set, SisMember, Unrecognized,
+    Append, Asking, BLMPop, BLMove, BLPop, BRPop, BgRewriteAof, BgSave, Cluster, CommandError,
+    Copy, Decr, DecrBy, Del, Discard, Echo, Eval, EvalSha, Exec, Exists, Expire, ExpireAt, FCall,
+    FCallRo, Function, Get, GetDel, GetEx, GetRange, HDel, HExists, HGet, HGetAll, HIncrBy,
+    HIncrByFloat, HKeys, HLen, HMGet, HRandField, HSet, HSetNx, HStrLen, HVals, Hello, Incr,
+    IncrBy, IncrByFloat, LIndex, LInsert, LLen, LMPop, LMove, LPop, LPush, LPushX, LRange, LRem,
+    LSet, LTrim, LastSave, MGet, MSet, MSetNx, Multi, PExpire, PExpireAt, PSubscribe, PSync, PTtl,
+    PUnsubscribe, Persist, Ping, PubSub, Publish, RPop, RPush, RPushX, Rename, RenameNx, ReplConf,
+    ReplicaOf, SCard, SDiff, SDiffStore, SInter, SInterStore, SMembers, SPop, SPublish,
+    SRandMember, SRem, SSubscribe, SUnion, SUnionStore, SUnsubscribe, Save, Script, Set, SetRange,
+    SisMember, StrLen, Subscribe, Touch, Ttl, Type, Unlink, Unsubscribe, Unwatch, Wait, WaitAof,
+    Watch, ZAdd, ZCard, ZIncrBy, ZRange, ZRangeByScore, ZRank, ZRem, ZRevRank, ZScore,
 };
 use crate::resp::{RespArray, RespFrame};
 use enum_dispatch::enum_dispatch;
@@ -21,8 +33,374 @@ pub enum Command {
     SAdd(SAdd),
     // SISMEMBER
     SisMember(SisMember),
-    // unrecognized command
-    Unrecognized(Unrecognized),
+    // EXPIRE
+    Expire(Expire),
+    // PEXPIRE
+    PExpire(PExpire),
+    // TTL
+    Ttl(Ttl),
+    // PTTL
+    PTtl(PTtl),
+    // PERSIST
+    Persist(Persist),
+    // DEL
+    Del(Del),
+    // UNLINK
+    Unlink(Unlink),
+    // EXISTS
+    Exists(Exists),
+    // TYPE
+    Type(Type),
+    // RENAME
+    Rename(Rename),
+    // RENAMENX
+    RenameNx(RenameNx),
+    // COPY
+    Copy(Copy),
+    // TOUCH
+    Touch(Touch),
+    // LPUSH
+    LPush(LPush),
+    // RPUSH
+    RPush(RPush),
+    // LPUSHX
+    LPushX(LPushX),
+    // RPUSHX
+    RPushX(RPushX),
+    // LPOP
+    LPop(LPop),
+    // RPOP
+    RPop(RPop),
+    // LLEN
+    LLen(LLen),
+    // LRANGE
+    LRange(LRange),
+    // LINDEX
+    LIndex(LIndex),
+    // LSET
+    LSet(LSet),
+    // LINSERT
+    LInsert(LInsert),
+    // LREM
+    LRem(LRem),
+    // LTRIM
+    LTrim(LTrim),
+    // LMOVE
+    LMove(LMove),
+    // BLPOP
+    BLPop(BLPop),
+    // BRPOP
+    BRPop(BRPop),
+    // BLMOVE
+    BLMove(BLMove),
+    // LMPOP
+    LMPop(LMPop),
+    // BLMPOP
+    BLMPop(BLMPop),
+    // ZADD
+    ZAdd(ZAdd),
+    // ZRANGE
+    ZRange(ZRange),
+    // ZRANGEBYSCORE
+    ZRangeByScore(ZRangeByScore),
+    // ZRANK
+    ZRank(ZRank),
+    // ZREVRANK
+    ZRevRank(ZRevRank),
+    // ZINCRBY
+    ZIncrBy(ZIncrBy),
+    // ZREM
+    ZRem(ZRem),
+    // ZCARD
+    ZCard(ZCard),
+    // ZSCORE
+    ZScore(ZScore),
+    // SREM
+    SRem(SRem),
+    // SMEMBERS
+    SMembers(SMembers),
+    // SCARD
+    SCard(SCard),
+    // SPOP
+    SPop(SPop),
+    // SRANDMEMBER
+    SRandMember(SRandMember),
+    // SINTER
+    SInter(SInter),
+    // SUNION
+    SUnion(SUnion),
+    // SDIFF
+    SDiff(SDiff),
+    // SINTERSTORE
+    SInterStore(SInterStore),
+    // SUNIONSTORE
+    SUnionStore(SUnionStore),
+    // SDIFFSTORE
+    SDiffStore(SDiffStore),
+    // HDEL
+    HDel(HDel),
+    // HEXISTS
+    HExists(HExists),
+    // HLEN
+    HLen(HLen),
+    // HKEYS
+    HKeys(HKeys),
+    // HVALS
+    HVals(HVals),
+    // HINCRBY
+    HIncrBy(HIncrBy),
+    // HINCRBYFLOAT
+    HIncrByFloat(HIncrByFloat),
+    // HSETNX
+    HSetNx(HSetNx),
+    // HSTRLEN
+    HStrLen(HStrLen),
+    // HRANDFIELD
+    HRandField(HRandField),
+    // APPEND
+    Append(Append),
+    // STRLEN
+    StrLen(StrLen),
+    // INCR
+    Incr(Incr),
+    // DECR
+    Decr(Decr),
+    // INCRBY
+    IncrBy(IncrBy),
+    // DECRBY
+    DecrBy(DecrBy),
+    // INCRBYFLOAT
+    IncrByFloat(IncrByFloat),
+    // GETRANGE
+    GetRange(GetRange),
+    // SETRANGE
+    SetRange(SetRange),
+    // MGET
+    MGet(MGet),
+    // MSET
+    MSet(MSet),
+    // MSETNX
+    MSetNx(MSetNx),
+    // GETDEL
+    GetDel(GetDel),
+    // GETEX
+    GetEx(GetEx),
+    // SAVE
+    Save(Save),
+    // BGSAVE
+    BgSave(BgSave),
+    // LASTSAVE
+    LastSave(LastSave),
+    // EXPIREAT
+    ExpireAt(ExpireAt),
+    // PEXPIREAT
+    PExpireAt(PExpireAt),
+    // BGREWRITEAOF
+    BgRewriteAof(BgRewriteAof),
+    // HELLO
+    Hello(Hello),
+    // PING
+    Ping(Ping),
+    // SUBSCRIBE
+    Subscribe(Subscribe),
+    // UNSUBSCRIBE
+    Unsubscribe(Unsubscribe),
+    // PSUBSCRIBE
+    PSubscribe(PSubscribe),
+    // PUNSUBSCRIBE
+    PUnsubscribe(PUnsubscribe),
+    // PUBLISH
+    Publish(Publish),
+    // PUBSUB
+    PubSub(PubSub),
+    // SSUBSCRIBE
+    SSubscribe(SSubscribe),
+    // SUNSUBSCRIBE
+    SUnsubscribe(SUnsubscribe),
+    // SPUBLISH
+    SPublish(SPublish),
+    // MULTI
+    Multi(Multi),
+    // EXEC
+    Exec(Exec),
+    // DISCARD
+    Discard(Discard),
+    // WATCH
+    Watch(Watch),
+    // UNWATCH
+    Unwatch(Unwatch),
+    // EVAL
+    Eval(Eval),
+    // EVALSHA
+    EvalSha(EvalSha),
+    // SCRIPT
+    Script(Script),
+    // FUNCTION
+    Function(Function),
+    // FCALL
+    FCall(FCall),
+    // FCALL_RO
+    FCallRo(FCallRo),
+    // REPLICAOF
+    ReplicaOf(ReplicaOf),
+    // PSYNC
+    PSync(PSync),
+    // REPLCONF
+    ReplConf(ReplConf),
+    // WAIT
+    Wait(Wait),
+    // WAITAOF
+    WaitAof(WaitAof),
+    // CLUSTER
+    Cluster(Cluster),
+    // ASKING
+    Asking(Asking),
+}
+
+impl Command {
+    // blocking commands may park the connection, every other command completes right away;
+    // `on_ready` gets the reply of a blocking command while its write is still under the barrier
+    pub async fn execute_async(
+        self,
+        backend: &Backend,
+        on_ready: impl FnOnce(&RespFrame),
+    ) -> RespFrame {
+        match self {
+            Command::BLPop(cmd) => cmd.execute_blocking(backend, on_ready).await,
+            Command::BRPop(cmd) => cmd.execute_blocking(backend, on_ready).await,
+            Command::BLMove(cmd) => cmd.execute_blocking(backend, on_ready).await,
+            Command::BLMPop(cmd) => cmd.execute_blocking(backend, on_ready).await,
+            cmd => cmd.execute(backend),
+        }
+    }
+
+    // the commands that stop a script, they don't wait for it
+    pub fn is_script_kill(&self) -> bool {
+        matches!(
+            self,
+            Command::Script(Script::Kill) | Command::Function(Function::Kill)
+        )
+    }
+
+    pub fn is_blocking(&self) -> bool {
+        matches!(
+            self,
+            Command::BLPop(_) | Command::BRPop(_) | Command::BLMove(_) | Command::BLMPop(_)
+        )
+    }
+
+    // the commands run right away after MULTI rather than queued
+    pub fn is_transaction_control(&self) -> bool {
+        matches!(
+            self,
+            Command::Multi(_) | Command::Exec(_) | Command::Discard(_) | Command::Watch(_)
+        )
+    }
+
+    // scripts can't manage the connection or start anything that outlives their call
+    pub fn is_allowed_from_script(&self) -> bool {
+        !(self.is_subscription()
+            || self.is_transaction_control()
+            || matches!(
+                self,
+                Command::Unwatch(_)
+                    | Command::Hello(_)
+                    | Command::Eval(_)
+                    | Command::EvalSha(_)
+                    | Command::Script(_)
+                    | Command::Function(_)
+                    | Command::FCall(_)
+                    | Command::FCallRo(_)
+                    | Command::ReplicaOf(_)
+                    | Command::PSync(_)
+                    | Command::ReplConf(_)
+                    | Command::Wait(_)
+                    | Command::WaitAof(_)
+                    | Command::Asking(_)
+            ))
+    }
+
+    // commands changing the subscriptions of the connection, their replies are pushed
+    pub fn is_subscription(&self) -> bool {
+        matches!(
+            self,
+            Command::Subscribe(_)
+                | Command::Unsubscribe(_)
+                | Command::PSubscribe(_)
+                | Command::PUnsubscribe(_)
+                | Command::SSubscribe(_)
+                | Command::SUnsubscribe(_)
+        )
+    }
+
+    // a RESP2 connection with subscriptions can only manage them, any other reply would be
+    // mistaken for a message
+    pub fn allowed_when_subscribed(&self) -> bool {
+        self.is_subscription() || matches!(self, Command::Ping(_))
+    }
+
+    // commands that may modify the keyspace, they count as changes for the save points and are
+    // appended to the log
+    pub fn is_write(&self) -> bool {
+        if let Command::Function(function) = self {
+            return function.is_write();
+        }
+        matches!(
+            self,
+            Command::Set(_)
+                | Command::HSet(_)
+                | Command::SAdd(_)
+                | Command::Expire(_)
+                | Command::PExpire(_)
+                | Command::ExpireAt(_)
+                | Command::PExpireAt(_)
+                | Command::Persist(_)
+                | Command::Del(_)
+                | Command::Unlink(_)
+                | Command::Rename(_)
+                | Command::RenameNx(_)
+                | Command::Copy(_)
+                | Command::LPush(_)
+                | Command::RPush(_)
+                | Command::LPushX(_)
+                | Command::RPushX(_)
+                | Command::LPop(_)
+                | Command::RPop(_)
+                | Command::LSet(_)
+                | Command::LInsert(_)
+                | Command::LRem(_)
+                | Command::LTrim(_)
+                | Command::LMove(_)
+                | Command::BLPop(_)
+                | Command::BRPop(_)
+                | Command::BLMove(_)
+                | Command::LMPop(_)
+                | Command::BLMPop(_)
+                | Command::ZAdd(_)
+                | Command::ZIncrBy(_)
+                | Command::ZRem(_)
+                | Command::SRem(_)
+                | Command::SPop(_)
+                | Command::SInterStore(_)
+                | Command::SUnionStore(_)
+                | Command::SDiffStore(_)
+                | Command::HDel(_)
+                | Command::HIncrBy(_)
+                | Command::HIncrByFloat(_)
+                | Command::HSetNx(_)
+                | Command::Append(_)
+                | Command::Incr(_)
+                | Command::Decr(_)
+                | Command::IncrBy(_)
+                | Command::DecrBy(_)
+                | Command::IncrByFloat(_)
+                | Command::SetRange(_)
+                | Command::MSet(_)
+                | Command::MSetNx(_)
+                | Command::GetDel(_)
+                | Command::GetEx(_)
+        )
+    }
 }
 
 impl TryFrom<RespFrame> for Command {
@@ -41,7 +419,11 @@ impl TryFrom<RespArray> for Command {
     fn try_from(v: RespArray) -> Result<Self, Self::Error> {
         match v.first() {
             Some(RespFrame::BulkString(ref cmd)) => {
-                match cmd.as_ref().to_ascii_lowercase().as_slice() {
+                let name = cmd.as_ref().to_ascii_lowercase();
+                if arity(&name).is_none() {
+                    return Err(unknown_command(&v));
+                }
+                match name.as_slice() {
                     b"get" => Ok(Get::try_from(v)?.into()),
                     b"set" => Ok(Set::try_from(v)?.into()),
                     b"hget" => Ok(HGet::try_from(v)?.into()),
@@ -51,7 +433,118 @@ impl TryFrom<RespArray> for Command {
                     b"hmget" => Ok(HMGet::try_from(v)?.into()),
                     b"sadd" => Ok(SAdd::try_from(v)?.into()),
                     b"sismember" => Ok(SisMember::try_from(v)?.into()),
-                    _ => Ok(Unrecognized.into()),
+                    b"expire" => Ok(Expire::try_from(v)?.into()),
+                    b"pexpire" => Ok(PExpire::try_from(v)?.into()),
+                    b"ttl" => Ok(Ttl::try_from(v)?.into()),
+                    b"pttl" => Ok(PTtl::try_from(v)?.into()),
+                    b"persist" => Ok(Persist::try_from(v)?.into()),
+                    b"del" => Ok(Del::try_from(v)?.into()),
+                    b"unlink" => Ok(Unlink::try_from(v)?.into()),
+                    b"exists" => Ok(Exists::try_from(v)?.into()),
+                    b"type" => Ok(Type::try_from(v)?.into()),
+                    b"rename" => Ok(Rename::try_from(v)?.into()),
+                    b"renamenx" => Ok(RenameNx::try_from(v)?.into()),
+                    b"copy" => Ok(Copy::try_from(v)?.into()),
+                    b"touch" => Ok(Touch::try_from(v)?.into()),
+                    b"lpush" => Ok(LPush::try_from(v)?.into()),
+                    b"rpush" => Ok(RPush::try_from(v)?.into()),
+                    b"lpushx" => Ok(LPushX::try_from(v)?.into()),
+                    b"rpushx" => Ok(RPushX::try_from(v)?.into()),
+                    b"lpop" => Ok(LPop::try_from(v)?.into()),
+                    b"rpop" => Ok(RPop::try_from(v)?.into()),
+                    b"llen" => Ok(LLen::try_from(v)?.into()),
+                    b"lrange" => Ok(LRange::try_from(v)?.into()),
+                    b"lindex" => Ok(LIndex::try_from(v)?.into()),
+                    b"lset" => Ok(LSet::try_from(v)?.into()),
+                    b"linsert" => Ok(LInsert::try_from(v)?.into()),
+                    b"lrem" => Ok(LRem::try_from(v)?.into()),
+                    b"ltrim" => Ok(LTrim::try_from(v)?.into()),
+                    b"lmove" => Ok(LMove::try_from(v)?.into()),
+                    b"blpop" => Ok(BLPop::try_from(v)?.into()),
+                    b"brpop" => Ok(BRPop::try_from(v)?.into()),
+                    b"blmove" => Ok(BLMove::try_from(v)?.into()),
+                    b"lmpop" => Ok(LMPop::try_from(v)?.into()),
+                    b"blmpop" => Ok(BLMPop::try_from(v)?.into()),
+                    b"zadd" => Ok(ZAdd::try_from(v)?.into()),
+                    b"zrange" => Ok(ZRange::try_from(v)?.into()),
+                    b"zrangebyscore" => Ok(ZRangeByScore::try_from(v)?.into()),
+                    b"zrank" => Ok(ZRank::try_from(v)?.into()),
+                    b"zrevrank" => Ok(ZRevRank::try_from(v)?.into()),
+                    b"zincrby" => Ok(ZIncrBy::try_from(v)?.into()),
+                    b"zrem" => Ok(ZRem::try_from(v)?.into()),
+                    b"zcard" => Ok(ZCard::try_from(v)?.into()),
+                    b"zscore" => Ok(ZScore::try_from(v)?.into()),
+                    b"srem" => Ok(SRem::try_from(v)?.into()),
+                    b"smembers" => Ok(SMembers::try_from(v)?.into()),
+                    b"scard" => Ok(SCard::try_from(v)?.into()),
+                    b"spop" => Ok(SPop::try_from(v)?.into()),
+                    b"srandmember" => Ok(SRandMember::try_from(v)?.into()),
+                    b"sinter" => Ok(SInter::try_from(v)?.into()),
+                    b"sunion" => Ok(SUnion::try_from(v)?.into()),
+                    b"sdiff" => Ok(SDiff::try_from(v)?.into()),
+                    b"sinterstore" => Ok(SInterStore::try_from(v)?.into()),
+                    b"sunionstore" => Ok(SUnionStore::try_from(v)?.into()),
+                    b"sdiffstore" => Ok(SDiffStore::try_from(v)?.into()),
+                    b"hdel" => Ok(HDel::try_from(v)?.into()),
+                    b"hexists" => Ok(HExists::try_from(v)?.into()),
+                    b"hlen" => Ok(HLen::try_from(v)?.into()),
+                    b"hkeys" => Ok(HKeys::try_from(v)?.into()),
+                    b"hvals" => Ok(HVals::try_from(v)?.into()),
+                    b"hincrby" => Ok(HIncrBy::try_from(v)?.into()),
+                    b"hincrbyfloat" => Ok(HIncrByFloat::try_from(v)?.into()),
+                    b"hsetnx" => Ok(HSetNx::try_from(v)?.into()),
+                    b"hstrlen" => Ok(HStrLen::try_from(v)?.into()),
+                    b"hrandfield" => Ok(HRandField::try_from(v)?.into()),
+                    b"append" => Ok(Append::try_from(v)?.into()),
+                    b"strlen" => Ok(StrLen::try_from(v)?.into()),
+                    b"incr" => Ok(Incr::try_from(v)?.into()),
+                    b"decr" => Ok(Decr::try_from(v)?.into()),
+                    b"incrby" => Ok(IncrBy::try_from(v)?.into()),
+                    b"decrby" => Ok(DecrBy::try_from(v)?.into()),
+                    b"incrbyfloat" => Ok(IncrByFloat::try_from(v)?.into()),
+                    b"getrange" => Ok(GetRange::try_from(v)?.into()),
+                    b"setrange" => Ok(SetRange::try_from(v)?.into()),
+                    b"mget" => Ok(MGet::try_from(v)?.into()),
+                    b"mset" => Ok(MSet::try_from(v)?.into()),
+                    b"msetnx" => Ok(MSetNx::try_from(v)?.into()),
+                    b"getdel" => Ok(GetDel::try_from(v)?.into()),
+                    b"getex" => Ok(GetEx::try_from(v)?.into()),
+                    b"save" => Ok(Save::try_from(v)?.into()),
+                    b"bgsave" => Ok(BgSave::try_from(v)?.into()),
+                    b"lastsave" => Ok(LastSave::try_from(v)?.into()),
+                    b"expireat" => Ok(ExpireAt::try_from(v)?.into()),
+                    b"pexpireat" => Ok(PExpireAt::try_from(v)?.into()),
+                    b"bgrewriteaof" => Ok(BgRewriteAof::try_from(v)?.into()),
+                    b"hello" => Ok(Hello::try_from(v)?.into()),
+                    b"ping" => Ok(Ping::try_from(v)?.into()),
+                    b"subscribe" => Ok(Subscribe::try_from(v)?.into()),
+                    b"unsubscribe" => Ok(Unsubscribe::try_from(v)?.into()),
+                    b"psubscribe" => Ok(PSubscribe::try_from(v)?.into()),
+                    b"punsubscribe" => Ok(PUnsubscribe::try_from(v)?.into()),
+                    b"publish" => Ok(Publish::try_from(v)?.into()),
+                    b"pubsub" => Ok(PubSub::try_from(v)?.into()),
+                    b"ssubscribe" => Ok(SSubscribe::try_from(v)?.into()),
+                    b"sunsubscribe" => Ok(SUnsubscribe::try_from(v)?.into()),
+                    b"spublish" => Ok(SPublish::try_from(v)?.into()),
+                    b"multi" => Ok(Multi::try_from(v)?.into()),
+                    b"exec" => Ok(Exec::try_from(v)?.into()),
+                    b"discard" => Ok(Discard::try_from(v)?.into()),
+                    b"watch" => Ok(Watch::try_from(v)?.into()),
+                    b"unwatch" => Ok(Unwatch::try_from(v)?.into()),
+                    b"eval" => Ok(Eval::try_from(v)?.into()),
+                    b"evalsha" => Ok(EvalSha::try_from(v)?.into()),
+                    b"script" => Ok(Script::try_from(v)?.into()),
+                    b"function" => Ok(Function::try_from(v)?.into()),
+                    b"fcall" => Ok(FCall::try_from(v)?.into()),
+                    b"fcall_ro" => Ok(FCallRo::try_from(v)?.into()),
+                    b"replicaof" => Ok(ReplicaOf::try_from(v)?.into()),
+                    b"psync" => Ok(PSync::try_from(v)?.into()),
+                    b"replconf" => Ok(ReplConf::try_from(v)?.into()),
+                    b"wait" => Ok(Wait::try_from(v)?.into()),
+                    b"waitaof" => Ok(WaitAof::try_from(v)?.into()),
+                    b"cluster" => Ok(Cluster::try_from(v)?.into()),
+                    b"asking" => Ok(Asking::try_from(v)?.into()),
+                    _ => Err(unknown_command(&v)),
                 }
             }
             _ => Err(CommandError::InvalidCommand(
@@ -60,3 +553,79 @@ impl TryFrom<RespArray> for Command {
         }
     }
 }
+
+// where the keys of a command are among its arguments, like the key specs of redis
+enum KeySpec {
+    // the first key, the last one (negative counts from the end) and the step between them
+    Range(usize, i64, usize),
+    // the argument at this position is the number of keys, they follow it
+    NumKeys(usize),
+}
+
+fn key_spec(name: &[u8]) -> Option<KeySpec> {
+    let spec = match name {
+        b"del" | b"unlink" | b"exists" | b"touch" | b"sinter" | b"sunion" | b"sdiff"
+        | b"sinterstore" | b"sunionstore" | b"sdiffstore" | b"mget" | b"watch" | b"ssubscribe"
+        | b"sunsubscribe" => KeySpec::Range(1, -1, 1),
+        b"rename" | b"renamenx" | b"copy" | b"lmove" | b"blmove" => KeySpec::Range(1, 2, 1),
+        b"mset" | b"msetnx" => KeySpec::Range(1, -1, 2),
+        b"blpop" | b"brpop" => KeySpec::Range(1, -2, 1),
+        b"eval" | b"evalsha" | b"fcall" | b"fcall_ro" | b"blmpop" => KeySpec::NumKeys(2),
+        b"lmpop" => KeySpec::NumKeys(1),
+        b"echo" | b"publish" | b"replicaof" | b"wait" | b"waitaof" | b"save" | b"lastsave"
+        | b"bgrewriteaof" | b"multi" | b"exec" | b"discard" | b"unwatch" | b"bgsave" | b"hello"
+        | b"ping" | b"replconf" | b"unsubscribe" | b"punsubscribe" | b"subscribe"
+        | b"psubscribe" | b"pubsub" | b"script" | b"function" | b"psync" | b"cluster"
+        | b"asking" => return None,
+        // every other command takes a single key first
+        _ => KeySpec::Range(1, 1, 1),
+    };
+    Some(spec)
+}
+
+// the keys a request acts on, which decide the node serving it in a cluster
+pub fn command_keys(v: &RespArray) -> Vec<&[u8]> {
+    let arg = |i: usize| match v.get(i) {
+        Some(RespFrame::BulkString(arg)) => Some(arg.as_ref()),
+        _ => None,
+    };
+    let Some(name) = arg(0) else {
+        return Vec::new();
+    };
+    let positions: Vec<usize> = match key_spec(&name.to_ascii_lowercase()) {
+        None => Vec::new(),
+        Some(KeySpec::Range(first, last, step)) => {
+            let last = match last {
+                last if last < 0 => v.len() as i64 + last,
+                last => last,
+            };
+            (first..(last + 1).max(0) as usize).step_by(step).collect()
+        }
+        Some(KeySpec::NumKeys(at)) => {
+            let count = arg(at)
+                .and_then(|count| std::str::from_utf8(count).ok()?.parse::<usize>().ok())
+                .unwrap_or(0);
+            (at + 1..at + 1 + count).collect()
+        }
+    };
+    positions.into_iter().filter_map(arg).collect()
+}
+
+// redis quotes the first arguments of an unknown command, up to 128 bytes of them
+fn unknown_command(v: &RespArray) -> CommandError {
+    let mut frames = v.iter().map(|frame| match frame {
+        RespFrame::BulkString(s) => String::from_utf8_lossy(s).into_owned(),
+        _ => String::new(),
+    });
+    let name = frames.next().unwrap_or_default();
+    let mut args = String::new();
+    for arg in frames {
+        let remaining = 128usize.saturating_sub(args.len());
+        if remaining == 0 {
+            break;
+        }
+        let arg: String = arg.chars().take(remaining).collect();
+        args.push_str(&format!("'{}' ", arg));
+    }
+    CommandError::UnknownCommand(name.chars().take(128).collect(), args)
+}
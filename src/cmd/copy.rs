@@ -0,0 +1,94 @@
+use crate::cmd::{extract_args, extract_string, validate_command, CommandError, CommandExecutor};
+use crate::resp::{RespArray, RespFrame};
+use crate::Backend;
+
+// COPY source destination [DB destination-db] [REPLACE]
+// only the default database exists, so DB must be 0
+
+#[derive(Debug)]
+pub struct Copy {
+    source: String,
+    destination: String,
+    replace: bool,
+}
+
+impl CommandExecutor for Copy {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.copy(&self.source, &self.destination, self.replace) {
+            true => RespFrame::Integer(1),
+            false => RespFrame::Integer(0),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Copy {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "copy")?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        let source = extract_string(args.next())?;
+        let destination = extract_string(args.next())?;
+        let mut replace = false;
+        while let Some(arg) = args.next() {
+            match extract_string(Some(arg))?.to_ascii_uppercase().as_str() {
+                "REPLACE" => replace = true,
+                "DB" => {
+                    if extract_string(args.next())? != "0" {
+                        return Err(CommandError::InvalidArgument(
+                            "DB index is out of range".to_string(),
+                        ));
+                    }
+                }
+                _ => return Err(CommandError::InvalidArgument("syntax error".to_string())),
+            }
+        }
+        Ok(Copy {
+            source,
+            destination,
+            replace,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::resp::BulkString;
+
+    #[test]
+    fn test_copy_from_resp_array() -> anyhow::Result<()> {
+        let cmd = Copy::try_from(RespArray::new([
+            BulkString::new("copy").into(),
+            BulkString::new("a").into(),
+            BulkString::new("b").into(),
+            BulkString::new("db").into(),
+            BulkString::new("0").into(),
+            BulkString::new("replace").into(),
+        ]))?;
+        assert_eq!(cmd.source, "a");
+        assert_eq!(cmd.destination, "b");
+        assert!(cmd.replace);
+        Ok(())
+    }
+
+    #[test]
+    fn test_copy_command() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        backend.insert_set("a".to_string(), vec!["1".to_string()])?;
+        backend.set("b".to_string(), "2");
+        let cmd = Copy {
+            source: "a".to_string(),
+            destination: "b".to_string(),
+            replace: false,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+        let cmd = Copy {
+            source: "a".to_string(),
+            destination: "b".to_string(),
+            replace: true,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        assert_eq!(backend.sismember("b", "1"), Ok(true));
+        Ok(())
+    }
+}
@@ -0,0 +1,96 @@
+use crate::cmd::{extract_args, extract_string, validate_command, CommandError, CommandExecutor};
+use crate::resp::{RespArray, RespFrame};
+use crate::Backend;
+
+// DEL key [key ...]
+// UNLINK key [key ...]
+// returns the number of keys that were removed
+
+#[derive(Debug)]
+pub struct Del {
+    keys: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct Unlink {
+    keys: Vec<String>,
+}
+
+impl CommandExecutor for Del {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        remove_keys(backend, &self.keys)
+    }
+}
+
+// values are dropped in place, so UNLINK behaves exactly like DEL
+impl CommandExecutor for Unlink {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        remove_keys(backend, &self.keys)
+    }
+}
+
+fn remove_keys(backend: &Backend, keys: &[String]) -> RespFrame {
+    let count = keys
+        .iter()
+        .filter(|key| backend.exists(key) && backend.remove(key))
+        .count();
+    RespFrame::Integer(count as i64)
+}
+
+fn parse_keys(value: RespArray, name: &'static str) -> Result<Vec<String>, CommandError> {
+    validate_command(&value, name)?;
+    extract_args(value, 1)?
+        .into_iter()
+        .map(|arg| extract_string(Some(arg)))
+        .collect()
+}
+
+impl TryFrom<RespArray> for Del {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(Del {
+            keys: parse_keys(value, "del")?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for Unlink {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(Unlink {
+            keys: parse_keys(value, "unlink")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::resp::RespDecode;
+    use bytes::BytesMut;
+
+    #[test]
+    fn test_del_from_resp_array() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$3\r\ndel\r\n$1\r\na\r\n$1\r\nb\r\n");
+
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Del = frame.try_into()?;
+        assert_eq!(result.keys, vec!["a".to_string(), "b".to_string()]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_del_command() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        backend.set("a".to_string(), "1");
+        backend.insert_set("b".to_string(), vec!["1".to_string()])?;
+        let cmd = Del {
+            keys: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(2));
+        assert!(!backend.exists("a"));
+        assert!(!backend.exists("b"));
+        Ok(())
+    }
+}
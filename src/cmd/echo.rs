@@ -16,11 +16,11 @@ impl CommandExecutor for Echo {
 impl TryFrom<RespArray> for Echo {
     type Error = CommandError;
     fn try_from(value: RespArray) -> Result<Self, Self::Error> {
-        validate_command(&value, &["echo"], 1)?;
+        validate_command(&value, "echo")?;
         let mut args = extract_args(value, 1)?.into_iter();
         match args.next() {
             Some(RespFrame::BulkString(key)) => Ok(Echo {
-                value: String::from_utf8(key.0)?,
+                value: String::from_utf8(key.0.to_vec())?,
             }),
             _ => Err(CommandError::InvalidArgument("Invalid key!".to_string())),
         }
@@ -0,0 +1,146 @@
+use crate::cmd::{
+    extract_args, extract_bytes, extract_integer, extract_string, propagate_transaction,
+    run_script, validate_command, CommandError, CommandExecutor,
+};
+use crate::resp::{RespArray, RespFrame, SimpleError};
+use crate::Backend;
+use bytes::Bytes;
+
+// EVAL script numkeys [key [key ...]] [arg [arg ...]]
+// EVALSHA sha1 numkeys [key [key ...]] [arg [arg ...]]
+// runs a Lua script with the keys in KEYS and the other arguments in ARGV, no other client runs
+// a command until it returns. EVALSHA runs a script already cached by EVAL or SCRIPT LOAD
+
+// redis> EVAL "return redis.call('INCRBY', KEYS[1], ARGV[1])" 1 counter 5
+// (integer) 5
+
+#[derive(Debug)]
+pub struct Eval {
+    script: String,
+    keys: Vec<Bytes>,
+    args: Vec<Bytes>,
+}
+
+#[derive(Debug)]
+pub struct EvalSha {
+    sha: String,
+    keys: Vec<Bytes>,
+    args: Vec<Bytes>,
+}
+
+impl CommandExecutor for Eval {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let (reply, entries) = self.run(backend);
+        propagate_transaction(backend, entries);
+        reply
+    }
+}
+
+impl CommandExecutor for EvalSha {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let (reply, entries) = self.run(backend);
+        propagate_transaction(backend, entries);
+        reply
+    }
+}
+
+// the writes of a script are logged rather than the script, so the log replays the same
+// changes whatever the script did with the time or random numbers
+impl Eval {
+    pub fn run(self, backend: &Backend) -> (RespFrame, Vec<RespArray>) {
+        backend.script_load(&self.script);
+        run_script(backend, &self.script, self.keys, self.args)
+    }
+}
+
+impl EvalSha {
+    pub fn run(self, backend: &Backend) -> (RespFrame, Vec<RespArray>) {
+        match backend.script_get(&self.sha) {
+            Some(script) => run_script(backend, &script, self.keys, self.args),
+            None => (
+                SimpleError::new("NOSCRIPT No matching script. Please use EVAL.").into(),
+                Vec::new(),
+            ),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Eval {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (script, keys, args) = parse_script_args(value, "eval")?;
+        Ok(Eval { script, keys, args })
+    }
+}
+
+impl TryFrom<RespArray> for EvalSha {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (sha, keys, args) = parse_script_args(value, "evalsha")?;
+        Ok(EvalSha { sha, keys, args })
+    }
+}
+
+// splits the arguments after the script in the numkeys keys and the rest
+fn parse_script_args(
+    value: RespArray,
+    name: &'static str,
+) -> Result<(String, Vec<Bytes>, Vec<Bytes>), CommandError> {
+    validate_command(&value, name)?;
+    let mut args = extract_args(value, 1)?.into_iter();
+    let script = extract_string(args.next())?;
+    let numkeys = extract_integer(args.next())?;
+    let mut args = args
+        .map(|arg| extract_bytes(Some(arg)))
+        .collect::<Result<Vec<Bytes>, CommandError>>()?;
+    if numkeys < 0 {
+        return Err(CommandError::InvalidArgument(
+            "Number of keys can't be negative".to_string(),
+        ));
+    }
+    if numkeys as usize > args.len() {
+        return Err(CommandError::InvalidArgument(
+            "Number of keys can't be greater than number of args".to_string(),
+        ));
+    }
+    let rest = args.split_off(numkeys as usize);
+    Ok((script, args, rest))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cmd::request;
+    use anyhow::Result;
+
+    #[test]
+    fn test_eval_command() -> Result<()> {
+        let backend = Backend::new();
+        let script = "return redis.call('INCRBY', KEYS[1], ARGV[1])";
+        let cmd = Eval::try_from(request(&["eval", script, "1", "counter", "5"]))?;
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(5));
+
+        // EVAL caches the script for EVALSHA
+        let sha = crate::backend::sha1_hex(script.as_bytes());
+        let cmd = EvalSha::try_from(request(&["evalsha", &sha, "1", "counter", "2"]))?;
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(7));
+
+        let cmd = EvalSha::try_from(request(&["evalsha", "ffff", "0"]))?;
+        assert_eq!(
+            cmd.execute(&backend),
+            SimpleError::new("NOSCRIPT No matching script. Please use EVAL.").into()
+        );
+
+        let ret = Eval::try_from(request(&["eval", script, "2", "counter"]));
+        assert_eq!(
+            ret.unwrap_err().to_string(),
+            "ERR Number of keys can't be greater than number of args"
+        );
+        let ret = Eval::try_from(request(&["eval", script, "-1"]));
+        assert_eq!(
+            ret.unwrap_err().to_string(),
+            "ERR Number of keys can't be negative"
+        );
+        Ok(())
+    }
+}
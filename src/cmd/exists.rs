@@ -0,0 +1,45 @@
+use crate::cmd::{extract_args, extract_string, validate_command, CommandError, CommandExecutor};
+use crate::resp::{RespArray, RespFrame};
+use crate::Backend;
+
+// EXISTS key [key ...]
+// a key mentioned multiple times is counted multiple times
+
+#[derive(Debug)]
+pub struct Exists {
+    keys: Vec<String>,
+}
+
+impl CommandExecutor for Exists {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let count = self.keys.iter().filter(|key| backend.exists(key)).count();
+        RespFrame::Integer(count as i64)
+    }
+}
+
+impl TryFrom<RespArray> for Exists {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "exists")?;
+        let keys = extract_args(value, 1)?
+            .into_iter()
+            .map(|arg| extract_string(Some(arg)))
+            .collect::<Result<Vec<String>, CommandError>>()?;
+        Ok(Exists { keys })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_exists_command() {
+        let backend = Backend::new();
+        backend.set("a".to_string(), "1");
+        let cmd = Exists {
+            keys: vec!["a".to_string(), "a".to_string(), "b".to_string()],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(2));
+    }
+}
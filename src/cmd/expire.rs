@@ -0,0 +1,270 @@
+use crate::backend::now_ms;
+use crate::cmd::{
+    extract_args, extract_integer, extract_string, validate_command, CommandError, CommandExecutor,
+};
+use crate::resp::{RespArray, RespFrame};
+use crate::Backend;
+
+// EXPIRE key seconds [NX | XX | GT | LT]
+// PEXPIRE key milliseconds [NX | XX | GT | LT]
+// EXPIREAT key unix-time-seconds [NX | XX | GT | LT]
+// PEXPIREAT key unix-time-milliseconds [NX | XX | GT | LT]
+
+// redis> SET mykey "Hello"
+// "OK"
+// redis> EXPIRE mykey 10
+// (integer) 1
+// redis> TTL mykey
+// (integer) 10
+
+#[derive(Debug)]
+pub struct Expire {
+    key: String,
+    seconds: i64,
+    condition: ExpireCondition,
+}
+
+#[derive(Debug)]
+pub struct PExpire {
+    key: String,
+    milliseconds: i64,
+    condition: ExpireCondition,
+}
+
+#[derive(Debug)]
+pub struct ExpireAt {
+    key: String,
+    timestamp: i64,
+    condition: ExpireCondition,
+}
+
+#[derive(Debug)]
+pub struct PExpireAt {
+    key: String,
+    timestamp: i64,
+    condition: ExpireCondition,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExpireCondition {
+    Always,
+    // only when the key has no expiry
+    Nx,
+    // only when the key has an expiry
+    Xx,
+    // only when the new expiry is greater than the current one
+    Gt,
+    // only when the new expiry is less than the current one
+    Lt,
+}
+
+impl CommandExecutor for Expire {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let at = (now_ms() as i64).saturating_add(self.seconds.saturating_mul(1000));
+        expire_generic(backend, &self.key, at, self.condition)
+    }
+}
+
+impl CommandExecutor for PExpire {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let at = (now_ms() as i64).saturating_add(self.milliseconds);
+        expire_generic(backend, &self.key, at, self.condition)
+    }
+}
+
+impl CommandExecutor for ExpireAt {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let at = self.timestamp.saturating_mul(1000);
+        expire_generic(backend, &self.key, at, self.condition)
+    }
+}
+
+impl CommandExecutor for PExpireAt {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        expire_generic(backend, &self.key, self.timestamp, self.condition)
+    }
+}
+
+// `at` is the absolute deadline in unix milliseconds, a past deadline deletes the key
+fn expire_generic(backend: &Backend, key: &str, at: i64, condition: ExpireCondition) -> RespFrame {
+    let current = match backend.expire_time(key) {
+        Some(current) => current,
+        None => return RespFrame::Integer(0),
+    };
+    let at = at.max(0) as u64;
+    let allowed = match (condition, current) {
+        (ExpireCondition::Always, _) => true,
+        (ExpireCondition::Nx, current) => current.is_none(),
+        (ExpireCondition::Xx, current) => current.is_some(),
+        // a key without ttl is considered to have an infinite ttl
+        (ExpireCondition::Gt, current) => current.map(|c| at > c).unwrap_or(false),
+        (ExpireCondition::Lt, current) => current.map(|c| at < c).unwrap_or(true),
+    };
+    if allowed && backend.expire_at(key, at) {
+        RespFrame::Integer(1)
+    } else {
+        RespFrame::Integer(0)
+    }
+}
+
+fn parse_expire_args(
+    value: RespArray,
+    name: &'static str,
+) -> Result<(String, i64, ExpireCondition), CommandError> {
+    validate_command(&value, name)?;
+    let mut args = extract_args(value, 1)?.into_iter();
+    let key = extract_string(args.next())?;
+    let time = extract_integer(args.next())?;
+    let condition = match args.next() {
+        None => ExpireCondition::Always,
+        Some(arg) => match extract_string(Some(arg))?.to_ascii_uppercase().as_str() {
+            "NX" => ExpireCondition::Nx,
+            "XX" => ExpireCondition::Xx,
+            "GT" => ExpireCondition::Gt,
+            "LT" => ExpireCondition::Lt,
+            option => {
+                return Err(CommandError::InvalidArgument(format!(
+                    "Unsupported option {}",
+                    option
+                )))
+            }
+        },
+    };
+    if args.next().is_some() {
+        return Err(CommandError::InvalidArgument(
+            "NX and XX, GT or LT options at the same time are not compatible".to_string(),
+        ));
+    }
+    Ok((key, time, condition))
+}
+
+impl TryFrom<RespArray> for Expire {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, seconds, condition) = parse_expire_args(value, "expire")?;
+        Ok(Expire {
+            key,
+            seconds,
+            condition,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for PExpire {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, milliseconds, condition) = parse_expire_args(value, "pexpire")?;
+        Ok(PExpire {
+            key,
+            milliseconds,
+            condition,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ExpireAt {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, timestamp, condition) = parse_expire_args(value, "expireat")?;
+        Ok(ExpireAt {
+            key,
+            timestamp,
+            condition,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for PExpireAt {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, timestamp, condition) = parse_expire_args(value, "pexpireat")?;
+        Ok(PExpireAt {
+            key,
+            timestamp,
+            condition,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::resp::RespDecode;
+    use bytes::BytesMut;
+
+    #[test]
+    fn test_expire_from_resp_array() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$6\r\nexpire\r\n$5\r\nhello\r\n$2\r\n10\r\n$2\r\nGT\r\n");
+
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Expire = frame.try_into()?;
+        assert_eq!(result.key, "hello");
+        assert_eq!(result.seconds, 10);
+        assert_eq!(result.condition, ExpireCondition::Gt);
+        Ok(())
+    }
+
+    #[test]
+    fn test_expire_command() {
+        let backend = Backend::new();
+        let cmd = Expire {
+            key: "hello".to_string(),
+            seconds: 10,
+            condition: ExpireCondition::Always,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+
+        backend.set("hello".to_string(), "world");
+        let cmd = PExpire {
+            key: "hello".to_string(),
+            milliseconds: 10_000,
+            condition: ExpireCondition::Xx,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+
+        let cmd = Expire {
+            key: "hello".to_string(),
+            seconds: 10,
+            condition: ExpireCondition::Nx,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+
+        let cmd = Expire {
+            key: "hello".to_string(),
+            seconds: 5,
+            condition: ExpireCondition::Gt,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+
+        let cmd = Expire {
+            key: "hello".to_string(),
+            seconds: -1,
+            condition: ExpireCondition::Always,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        assert!(!backend.exists("hello"));
+    }
+
+    #[test]
+    fn test_expire_at_command() {
+        let backend = Backend::new();
+        backend.set("hello".to_string(), "world");
+        let at = now_ms() + 10_000;
+        let cmd = PExpireAt {
+            key: "hello".to_string(),
+            timestamp: at as i64,
+            condition: ExpireCondition::Always,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        assert_eq!(backend.expire_time("hello"), Some(Some(at)));
+
+        let cmd = ExpireAt {
+            key: "hello".to_string(),
+            timestamp: 1,
+            condition: ExpireCondition::Always,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        assert!(!backend.exists("hello"));
+    }
+}
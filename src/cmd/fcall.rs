@@ -0,0 +1,196 @@
+use crate::cmd::{
+    extract_args, extract_bytes, extract_integer, extract_string, propagate_transaction,
+    run_function, validate_command, CommandError, CommandExecutor,
+};
+use crate::resp::{RespArray, RespFrame, SimpleError};
+use crate::Backend;
+use bytes::Bytes;
+
+// FCALL function numkeys [key [key ...]] [arg [arg ...]]
+// FCALL_RO function numkeys [key [key ...]] [arg [arg ...]]
+// runs a function of a loaded library, it gets the keys and the arguments as its parameters.
+// FCALL_RO only runs the functions flagged no-writes
+
+// redis> FCALL hello 0
+// "hi"
+
+#[derive(Debug)]
+pub struct FCall {
+    function: String,
+    keys: Vec<Bytes>,
+    args: Vec<Bytes>,
+}
+
+#[derive(Debug)]
+pub struct FCallRo {
+    function: String,
+    keys: Vec<Bytes>,
+    args: Vec<Bytes>,
+}
+
+impl CommandExecutor for FCall {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let (reply, entries) = self.run(backend);
+        propagate_transaction(backend, entries);
+        reply
+    }
+}
+
+impl CommandExecutor for FCallRo {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        // a read-only function has nothing to log
+        self.run(backend).0
+    }
+}
+
+impl FCall {
+    pub fn run(self, backend: &Backend) -> (RespFrame, Vec<RespArray>) {
+        call_function(backend, &self.function, self.keys, self.args, false)
+    }
+}
+
+impl FCallRo {
+    pub fn run(self, backend: &Backend) -> (RespFrame, Vec<RespArray>) {
+        call_function(backend, &self.function, self.keys, self.args, true)
+    }
+}
+
+fn call_function(
+    backend: &Backend,
+    name: &str,
+    keys: Vec<Bytes>,
+    args: Vec<Bytes>,
+    ro_command: bool,
+) -> (RespFrame, Vec<RespArray>) {
+    let Some(library) = backend.function_library(name) else {
+        return (
+            SimpleError::new("ERR Function not found").into(),
+            Vec::new(),
+        );
+    };
+    let read_only = library.function(name).is_some_and(|f| f.is_read_only());
+    if ro_command && !read_only {
+        return (
+            SimpleError::new("ERR Can not execute a script with write flag using *_ro command.")
+                .into(),
+            Vec::new(),
+        );
+    }
+    run_function(backend, &library, name, keys, args, read_only)
+}
+
+impl TryFrom<RespArray> for FCall {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (function, keys, args) = parse_function_args(value, "fcall")?;
+        Ok(FCall {
+            function,
+            keys,
+            args,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for FCallRo {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (function, keys, args) = parse_function_args(value, "fcall_ro")?;
+        Ok(FCallRo {
+            function,
+            keys,
+            args,
+        })
+    }
+}
+
+fn parse_function_args(
+    value: RespArray,
+    name: &'static str,
+) -> Result<(String, Vec<Bytes>, Vec<Bytes>), CommandError> {
+    validate_command(&value, name)?;
+    let mut args = extract_args(value, 1)?.into_iter();
+    let function = extract_string(args.next())?;
+    let numkeys = extract_integer(args.next())?;
+    let mut args = args
+        .map(|arg| extract_bytes(Some(arg)))
+        .collect::<Result<Vec<Bytes>, CommandError>>()?;
+    if numkeys < 0 {
+        return Err(CommandError::InvalidArgument(
+            "Number of keys can't be negative".to_string(),
+        ));
+    }
+    if numkeys as usize > args.len() {
+        return Err(CommandError::InvalidArgument(
+            "Number of keys can't be greater than number of args".to_string(),
+        ));
+    }
+    let rest = args.split_off(numkeys as usize);
+    Ok((function, args, rest))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::backend::parse_library;
+    use crate::cmd::request;
+    use crate::resp::BulkString;
+    use anyhow::Result;
+
+    const LIBRARY: &str = "#!lua name=counters
+redis.register_function('incr_by', function(keys, args)
+    return redis.call('INCRBY', keys[1], args[1])
+end)
+redis.register_function{
+    function_name = 'peek',
+    callback = function(keys) return redis.call('GET', keys[1]) end,
+    flags = {'no-writes'},
+}
+redis.register_function{
+    function_name = 'sneaky',
+    callback = function(keys) return redis.call('SET', keys[1], 'x') end,
+    flags = {'no-writes'},
+}";
+
+    #[test]
+    fn test_fcall_command() -> Result<()> {
+        let backend = Backend::new();
+        backend.function_load(parse_library(LIBRARY).unwrap(), false)?;
+
+        let cmd = FCall::try_from(request(&["fcall", "incr_by", "1", "n", "5"]))?;
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(5));
+        let cmd = FCallRo::try_from(request(&["fcall_ro", "peek", "1", "n"]))?;
+        assert_eq!(cmd.execute(&backend), BulkString::new("5").into());
+
+        let cmd = FCallRo::try_from(request(&["fcall_ro", "incr_by", "1", "n", "5"]))?;
+        assert_eq!(
+            cmd.execute(&backend),
+            SimpleError::new("ERR Can not execute a script with write flag using *_ro command.")
+                .into()
+        );
+        let cmd = FCall::try_from(request(&["fcall", "sneaky", "1", "n"]))?;
+        assert_eq!(
+            cmd.execute(&backend),
+            SimpleError::new("ERR Write commands are not allowed from read-only scripts.").into()
+        );
+        let cmd = FCall::try_from(request(&["fcall", "missing", "0"]))?;
+        assert_eq!(
+            cmd.execute(&backend),
+            SimpleError::new("ERR Function not found").into()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_library_loaded_once() -> Result<()> {
+        let backend = Backend::new();
+        let code = "#!lua name=counter
+local calls = 0
+redis.register_function('count', function() calls = calls + 1 return calls end)";
+        backend.function_load(parse_library(code).unwrap(), false)?;
+        for expected in 1..=3 {
+            let cmd = FCall::try_from(request(&["fcall", "count", "0"]))?;
+            assert_eq!(cmd.execute(&backend), RespFrame::Integer(expected));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,322 @@
+use crate::backend::{load_function_dump, parse_library, FunctionLibrary, RestorePolicy};
+use crate::cmd::{
+    extract_args, extract_bytes, extract_string, validate_command, CommandError, CommandExecutor,
+    RESP_OK,
+};
+use crate::resp::{BulkString, RespArray, RespFrame, RespMap, RespNull, RespSet, SimpleError};
+use crate::Backend;
+use bytes::Bytes;
+
+// FUNCTION LOAD [REPLACE] function-code
+// FUNCTION LIST [LIBRARYNAME library-name-pattern] [WITHCODE]
+// FUNCTION DELETE library-name
+// FUNCTION FLUSH [ASYNC | SYNC]
+// FUNCTION DUMP
+// FUNCTION RESTORE serialized-value [FLUSH | APPEND | REPLACE]
+// FUNCTION KILL
+// manages the libraries of functions FCALL runs, they are saved with the dataset, and stops a
+// function running for too long
+
+// redis> FUNCTION LOAD "#!lua name=mylib\nredis.register_function('hello', function() return 'hi' end)"
+// "mylib"
+
+#[derive(Debug)]
+pub enum Function {
+    Load {
+        code: String,
+        replace: bool,
+    },
+    List {
+        pattern: Option<String>,
+        with_code: bool,
+    },
+    Delete(String),
+    Flush,
+    Dump,
+    Restore {
+        payload: Bytes,
+        policy: RestorePolicy,
+    },
+    Kill,
+}
+
+impl CommandExecutor for Function {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match self {
+            Function::Load { code, replace } => {
+                let library = match parse_library(&code) {
+                    Ok(library) => library,
+                    Err(e) => return SimpleError::new(format!("ERR {}", e)).into(),
+                };
+                let name = library.name.clone();
+                match backend.function_load(library, replace) {
+                    Ok(()) => BulkString::new(name).into(),
+                    Err(e) => SimpleError::new(e.to_string()).into(),
+                }
+            }
+            Function::List { pattern, with_code } => RespArray::new(
+                backend
+                    .function_list(pattern.as_deref())
+                    .iter()
+                    .map(|library| library_info(library, with_code))
+                    .collect::<Vec<RespFrame>>(),
+            )
+            .into(),
+            Function::Delete(name) => match backend.function_delete(&name) {
+                true => RESP_OK.clone(),
+                false => SimpleError::new("ERR Library not found").into(),
+            },
+            Function::Flush => {
+                backend.function_flush();
+                RESP_OK.clone()
+            }
+            Function::Dump => BulkString::new(backend.dump_functions()).into(),
+            Function::Restore { payload, policy } => {
+                let libraries = match load_function_dump(&payload) {
+                    Ok(libraries) => libraries,
+                    Err(_) => {
+                        return SimpleError::new("ERR payload version or checksum are wrong").into()
+                    }
+                };
+                match backend.function_restore(libraries, policy) {
+                    Ok(()) => RESP_OK.clone(),
+                    Err(e) => SimpleError::new(e.to_string()).into(),
+                }
+            }
+            Function::Kill => match backend.script_kill(true) {
+                Ok(()) => RESP_OK.clone(),
+                Err(e) => e.into(),
+            },
+        }
+    }
+}
+
+impl Function {
+    // the subcommands changing the libraries, they are logged and count as changes
+    pub fn is_write(&self) -> bool {
+        matches!(
+            self,
+            Function::Load { .. }
+                | Function::Delete(_)
+                | Function::Flush
+                | Function::Restore { .. }
+        )
+    }
+}
+
+fn library_info(library: &FunctionLibrary, with_code: bool) -> RespFrame {
+    let functions = library
+        .functions
+        .iter()
+        .map(|function| {
+            let mut info = RespMap::new();
+            info.insert(
+                "name".to_string(),
+                BulkString::new(function.name.clone()).into(),
+            );
+            info.insert(
+                "description".to_string(),
+                match &function.description {
+                    Some(description) => BulkString::new(description.clone()).into(),
+                    None => RespFrame::Null(RespNull),
+                },
+            );
+            let flags = function
+                .flags
+                .iter()
+                .map(|flag| BulkString::new(flag.clone()).into())
+                .collect::<Vec<RespFrame>>();
+            info.insert("flags".to_string(), RespSet::new(flags).into());
+            info.into()
+        })
+        .collect::<Vec<RespFrame>>();
+    let mut info = RespMap::new();
+    info.insert(
+        "library_name".to_string(),
+        BulkString::new(library.name.clone()).into(),
+    );
+    info.insert("engine".to_string(), BulkString::new("LUA").into());
+    info.insert("functions".to_string(), RespArray::new(functions).into());
+    if with_code {
+        info.insert(
+            "library_code".to_string(),
+            BulkString::new(library.code.clone()).into(),
+        );
+    }
+    info.into()
+}
+
+impl TryFrom<RespArray> for Function {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "function")?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        let subcommand = extract_string(args.next())?.to_ascii_lowercase();
+        let args = args
+            .map(|arg| extract_bytes(Some(arg)))
+            .collect::<Result<Vec<Bytes>, CommandError>>()?;
+        let wrong_arity = || CommandError::WrongArity(format!("function|{}", subcommand));
+        let syntax_error = || CommandError::InvalidArgument("syntax error".to_string());
+        let option = |arg: &Bytes| String::from_utf8_lossy(arg).to_ascii_lowercase();
+        match subcommand.as_str() {
+            "load" => {
+                let (replace, code) = match args.as_slice() {
+                    [code] => (false, code),
+                    [flag, code] if option(flag) == "replace" => (true, code),
+                    [_, _] => {
+                        return Err(CommandError::InvalidArgument(format!(
+                            "Unknown option given: {}",
+                            String::from_utf8_lossy(&args[0])
+                        )))
+                    }
+                    _ => return Err(wrong_arity()),
+                };
+                let code = String::from_utf8(code.to_vec())?;
+                Ok(Function::Load { code, replace })
+            }
+            "list" => {
+                let (mut pattern, mut with_code) = (None, false);
+                let mut args = args.iter();
+                while let Some(arg) = args.next() {
+                    match option(arg).as_str() {
+                        "withcode" => with_code = true,
+                        "libraryname" => {
+                            let name = args.next().ok_or_else(|| {
+                                CommandError::InvalidArgument(
+                                    "library name argument was not given".to_string(),
+                                )
+                            })?;
+                            pattern = Some(String::from_utf8_lossy(name).into_owned());
+                        }
+                        _ => {
+                            return Err(CommandError::InvalidArgument(format!(
+                                "Unknown argument {}",
+                                String::from_utf8_lossy(arg)
+                            )))
+                        }
+                    }
+                }
+                Ok(Function::List { pattern, with_code })
+            }
+            "delete" => match args.as_slice() {
+                [name] => Ok(Function::Delete(String::from_utf8_lossy(name).into_owned())),
+                _ => Err(wrong_arity()),
+            },
+            // the libraries are dropped at once either way
+            "flush" => match args.as_slice() {
+                [] => Ok(Function::Flush),
+                [mode] if matches!(option(mode).as_str(), "async" | "sync") => Ok(Function::Flush),
+                [_] => Err(syntax_error()),
+                _ => Err(wrong_arity()),
+            },
+            "dump" if args.is_empty() => Ok(Function::Dump),
+            "restore" => {
+                let (payload, policy) = match args.as_slice() {
+                    [payload] => (payload.clone(), RestorePolicy::Append),
+                    [payload, policy] => {
+                        let policy =
+                            match option(policy).as_str() {
+                                "append" => RestorePolicy::Append,
+                                "replace" => RestorePolicy::Replace,
+                                "flush" => RestorePolicy::Flush,
+                                _ => return Err(CommandError::InvalidArgument(
+                                    "Wrong restore policy given, value should be either FLUSH, \
+                                     APPEND or REPLACE."
+                                        .to_string(),
+                                )),
+                            };
+                        (payload.clone(), policy)
+                    }
+                    _ => return Err(wrong_arity()),
+                };
+                Ok(Function::Restore { payload, policy })
+            }
+            "kill" if args.is_empty() => Ok(Function::Kill),
+            "dump" | "kill" => Err(wrong_arity()),
+            _ => Err(CommandError::InvalidArgument(format!(
+                "unknown subcommand '{}'. Try FUNCTION HELP.",
+                subcommand
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cmd::request;
+    use anyhow::Result;
+
+    const LIBRARY: &str =
+        "#!lua name=mylib\nredis.register_function('hello', function() return 'hi' end)";
+
+    fn function(backend: &Backend, args: &[&[u8]]) -> Result<RespFrame> {
+        let mut full = vec![&b"function"[..]];
+        full.extend_from_slice(args);
+        Ok(Function::try_from(request(&full))?.execute(backend))
+    }
+
+    #[test]
+    fn test_function_command() -> Result<()> {
+        let backend = Backend::new();
+        assert_eq!(
+            function(&backend, &[b"load", LIBRARY.as_bytes()])?,
+            BulkString::new("mylib").into()
+        );
+        assert_eq!(
+            function(&backend, &[b"load", LIBRARY.as_bytes()])?,
+            SimpleError::new("ERR Library 'mylib' already exists").into()
+        );
+        assert_eq!(
+            function(&backend, &[b"load", b"replace", LIBRARY.as_bytes()])?,
+            BulkString::new("mylib").into()
+        );
+        assert_eq!(
+            function(&backend, &[b"load", b"return 1"])?,
+            SimpleError::new("ERR Missing library metadata").into()
+        );
+
+        let RespFrame::Array(libraries) = function(&backend, &[b"list", b"withcode"])? else {
+            panic!("expected an array");
+        };
+        let RespFrame::Map(info) = &libraries[0] else {
+            panic!("expected a map");
+        };
+        assert_eq!(info["library_name"], BulkString::new("mylib").into());
+        assert_eq!(info["library_code"], BulkString::new(LIBRARY).into());
+        assert_eq!(
+            function(&backend, &[b"list", b"libraryname", b"other*"])?,
+            RespArray::new(Vec::<RespFrame>::new()).into()
+        );
+
+        // a dump restores the libraries on a server that has none
+        let RespFrame::BulkString(payload) = function(&backend, &[b"dump"])? else {
+            panic!("expected a bulk string");
+        };
+        assert_eq!(
+            function(&backend, &[b"restore", &payload])?,
+            SimpleError::new("ERR Library mylib already exists").into()
+        );
+        assert_eq!(function(&backend, &[b"delete", b"mylib"])?, RESP_OK.clone());
+        assert_eq!(
+            function(&backend, &[b"delete", b"mylib"])?,
+            SimpleError::new("ERR Library not found").into()
+        );
+        assert_eq!(
+            function(&backend, &[b"restore", &payload])?,
+            RESP_OK.clone()
+        );
+        assert!(backend.function_library("hello").is_some());
+        assert_eq!(
+            function(&backend, &[b"restore", b"garbage", b"replace"])?,
+            SimpleError::new("ERR payload version or checksum are wrong").into()
+        );
+        assert_eq!(function(&backend, &[b"flush", b"async"])?, RESP_OK.clone());
+        assert!(backend.function_libraries().is_empty());
+        assert_eq!(
+            function(&backend, &[b"kill"])?,
+            SimpleError::new("NOTBUSY No scripts in execution right now.").into()
+        );
+        Ok(())
+    }
+}
@@ -1,5 +1,5 @@
 use crate::cmd::{extract_args, validate_command, CommandError, CommandExecutor};
-use crate::resp::{RespArray, RespFrame, RespNull};
+use crate::resp::{BulkString, RespArray, RespFrame, RespNull};
 use crate::Backend;
 
 #[derive(Debug)]
@@ -9,18 +9,22 @@ pub struct Get {
 
 impl CommandExecutor for Get {
     fn execute(self, backend: &Backend) -> RespFrame {
-        backend.get(&self.key).unwrap_or(RespFrame::Null(RespNull))
+        match backend.get(&self.key) {
+            Ok(Some(value)) => BulkString::new(value).into(),
+            Ok(None) => RespFrame::Null(RespNull),
+            Err(e) => e.into(),
+        }
     }
 }
 
 impl TryFrom<RespArray> for Get {
     type Error = CommandError;
     fn try_from(value: RespArray) -> Result<Self, Self::Error> {
-        validate_command(&value, &["get"], 1)?;
+        validate_command(&value, "get")?;
         let mut args = extract_args(value, 1)?.into_iter();
         match args.next() {
             Some(RespFrame::BulkString(key)) => Ok(Get {
-                key: String::from_utf8(key.0)?,
+                key: String::from_utf8(key.0.to_vec())?,
             }),
             _ => Err(CommandError::InvalidArgument("Invalid key!".to_string())),
         }
@@ -30,9 +34,10 @@ impl TryFrom<RespArray> for Get {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::backend::SetCondition;
     use crate::cmd::{Set, RESP_OK};
     use crate::resp::RespDecode;
-    use bytes::BytesMut;
+    use bytes::{Bytes, BytesMut};
     #[test]
     fn test_get_from_resp_array() -> anyhow::Result<()> {
         let mut buf = BytesMut::new();
@@ -51,7 +56,10 @@ mod test {
         let backend = Backend::new();
         let cmd = Set {
             key: "hello".to_string(),
-            value: RespFrame::BulkString(b"world".into()),
+            value: Bytes::from("world"),
+            condition: SetCondition::Always,
+            expiration: None,
+            get: false,
         };
         let result = cmd.execute(&backend);
         assert_eq!(result, RESP_OK.clone());
@@ -0,0 +1,52 @@
+use crate::cmd::{extract_args, extract_string, validate_command, CommandError, CommandExecutor};
+use crate::resp::{BulkString, RespArray, RespFrame, RespNull};
+use crate::Backend;
+
+// GETDEL key
+// returns the value of the key and deletes it, nil if the key does not exist
+
+#[derive(Debug)]
+pub struct GetDel {
+    key: String,
+}
+
+impl CommandExecutor for GetDel {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.getdel(&self.key) {
+            Ok(Some(value)) => BulkString::new(value).into(),
+            Ok(None) => RespFrame::Null(RespNull),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for GetDel {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "getdel")?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(GetDel {
+            key: extract_string(args.next())?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_getdel_command() {
+        let backend = Backend::new();
+        backend.set("mykey".to_string(), "Hello");
+        let cmd = GetDel {
+            key: "mykey".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), BulkString::new("Hello").into());
+        assert!(!backend.exists("mykey"));
+        let cmd = GetDel {
+            key: "mykey".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Null(RespNull));
+    }
+}
@@ -0,0 +1,124 @@
+use crate::backend::SetExpiry;
+use crate::cmd::set::Expiration;
+use crate::cmd::{
+    extract_args, extract_integer, extract_string, validate_command, CommandError, CommandExecutor,
+};
+use crate::resp::{BulkString, RespArray, RespFrame, RespNull};
+use crate::Backend;
+
+// GETEX key [EX seconds | PX milliseconds | EXAT unix-time-seconds |
+//   PXAT unix-time-milliseconds | PERSIST]
+// returns the value of the key and updates its ttl, without options the ttl is left untouched
+
+// redis> SET mykey "Hello"
+// "OK"
+// redis> GETEX mykey EX 60
+// "Hello"
+// redis> TTL mykey
+// (integer) 60
+
+#[derive(Debug)]
+pub struct GetEx {
+    key: String,
+    expiration: Option<Expiration>,
+    persist: bool,
+}
+
+impl CommandExecutor for GetEx {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let expiry = match self.expiration {
+            Some(expiration) => expiration.to_expiry(),
+            None if self.persist => SetExpiry::Clear,
+            None => SetExpiry::Keep,
+        };
+        match backend.getex(&self.key, expiry) {
+            Ok(Some(value)) => BulkString::new(value).into(),
+            Ok(None) => RespFrame::Null(RespNull),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for GetEx {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "getex")?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+
+        let mut expiration = None;
+        let mut persist = false;
+        while let Some(arg) = args.next() {
+            let option = extract_string(Some(arg))?.to_ascii_uppercase();
+            match option.as_str() {
+                "PERSIST" if expiration.is_none() && !persist => persist = true,
+                "EX" | "PX" | "EXAT" | "PXAT" if expiration.is_none() && !persist => {
+                    let time = extract_integer(args.next())?;
+                    if time <= 0 {
+                        return Err(CommandError::InvalidArgument(
+                            "invalid expire time in 'getex' command".to_string(),
+                        ));
+                    }
+                    let time = time as u64;
+                    expiration = Some(match option.as_str() {
+                        "EX" => Expiration::Ex(time),
+                        "PX" => Expiration::Px(time),
+                        "EXAT" => Expiration::ExAt(time),
+                        _ => Expiration::PxAt(time),
+                    });
+                }
+                _ => return Err(CommandError::InvalidArgument("syntax error".to_string())),
+            }
+        }
+        Ok(GetEx {
+            key,
+            expiration,
+            persist,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::resp::RespDecode;
+    use bytes::BytesMut;
+
+    #[test]
+    fn test_getex_from_resp_array() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$5\r\ngetex\r\n$5\r\nmykey\r\n$2\r\nex\r\n$2\r\n60\r\n");
+
+        let frame = RespArray::decode(&mut buf)?;
+        let result: GetEx = frame.try_into()?;
+        assert_eq!(result.key, "mykey");
+        assert_eq!(result.expiration, Some(Expiration::Ex(60)));
+
+        buf.extend_from_slice(
+            b"*5\r\n$5\r\ngetex\r\n$5\r\nmykey\r\n$7\r\npersist\r\n$2\r\npx\r\n$2\r\n60\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(GetEx::try_from(frame).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_getex_command() {
+        let backend = Backend::new();
+        backend.set("mykey".to_string(), "Hello");
+        let cmd = GetEx {
+            key: "mykey".to_string(),
+            expiration: Some(Expiration::Ex(60)),
+            persist: false,
+        };
+        assert_eq!(cmd.execute(&backend), BulkString::new("Hello").into());
+        assert!(matches!(backend.expire_time("mykey"), Some(Some(_))));
+        let cmd = GetEx {
+            key: "mykey".to_string(),
+            expiration: None,
+            persist: true,
+        };
+        assert_eq!(cmd.execute(&backend), BulkString::new("Hello").into());
+        assert_eq!(backend.expire_time("mykey"), Some(None));
+    }
+}
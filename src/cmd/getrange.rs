@@ -0,0 +1,65 @@
+use crate::cmd::{
+    extract_args, extract_integer, extract_string, validate_command, CommandError, CommandExecutor,
+};
+use crate::resp::{BulkString, RespArray, RespFrame};
+use crate::Backend;
+
+// GETRANGE key start end
+// returns the substring between start and end, both inclusive, negative offsets count from the end
+
+// redis> SET mykey "This is a string"
+// "OK"
+// redis> GETRANGE mykey -3 -1
+// "ing"
+
+#[derive(Debug)]
+pub struct GetRange {
+    key: String,
+    start: i64,
+    end: i64,
+}
+
+impl CommandExecutor for GetRange {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.getrange(&self.key, self.start, self.end) {
+            Ok(value) => BulkString::new(value).into(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for GetRange {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "getrange")?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(GetRange {
+            key: extract_string(args.next())?,
+            start: extract_integer(args.next())?,
+            end: extract_integer(args.next())?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_getrange_command() {
+        let backend = Backend::new();
+        backend.set("mykey".to_string(), "This is a string");
+        let cmd = GetRange {
+            key: "mykey".to_string(),
+            start: -3,
+            end: -1,
+        };
+        assert_eq!(cmd.execute(&backend), BulkString::new("ing").into());
+        let cmd = GetRange {
+            key: "mykey".to_string(),
+            start: 10,
+            end: 100,
+        };
+        assert_eq!(cmd.execute(&backend), BulkString::new("string").into());
+    }
+}
@@ -0,0 +1,53 @@
+use crate::cmd::{extract_args, extract_string, validate_command, CommandError, CommandExecutor};
+use crate::resp::{RespArray, RespFrame};
+use crate::Backend;
+
+// HDEL key field [field ...]
+// returns the number of fields removed, the key is deleted once the hash is empty
+
+#[derive(Debug)]
+pub struct HDel {
+    key: String,
+    fields: Vec<String>,
+}
+
+impl CommandExecutor for HDel {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.hdel(&self.key, &self.fields) {
+            Ok(removed) => RespFrame::Integer(removed as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for HDel {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "hdel")?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let fields = args
+            .map(|arg| extract_string(Some(arg)))
+            .collect::<Result<Vec<String>, CommandError>>()?;
+        Ok(HDel { key, fields })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bytes::Bytes;
+
+    #[test]
+    fn test_hdel_command() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        backend.hset("myhash", vec![("field1".to_string(), Bytes::from("foo"))])?;
+        let cmd = HDel {
+            key: "myhash".to_string(),
+            fields: vec!["field1".to_string(), "field2".to_string()],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        assert!(!backend.exists("myhash"));
+        Ok(())
+    }
+}
@@ -0,0 +1,191 @@
+use crate::cmd::{extract_args, extract_string, validate_command, CommandError, CommandExecutor};
+use crate::network::ClientState;
+use crate::resp::{BulkString, RespArray, RespFrame, RespMap, RespVersion, SimpleError};
+use crate::Backend;
+
+// HELLO [protover [AUTH username password] [SETNAME clientname]]
+// switches the protocol of the connection and replies with the server properties
+
+// redis> HELLO 3
+// 1# "server" => "redis"
+// 2# "version" => "0.1.0"
+// 3# "proto" => (integer) 3
+// ...
+
+#[derive(Debug)]
+pub struct Hello {
+    protover: Option<i64>,
+    auth: Option<(String, String)>,
+    setname: Option<String>,
+}
+
+// without a connection there is no state to update, the reply is the one of a new client
+impl CommandExecutor for Hello {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        self.execute_for(&mut ClientState::default(), backend)
+    }
+}
+
+impl Hello {
+    pub fn execute_for(self, client: &mut ClientState, _backend: &Backend) -> RespFrame {
+        let protocol = match self.protover {
+            None => client.protocol,
+            Some(2) => RespVersion::Resp2,
+            Some(3) => RespVersion::Resp3,
+            Some(_) => return SimpleError::new("NOPROTO unsupported protocol version").into(),
+        };
+        // no users are configured, so only the default user exists and it needs no password
+        if let Some((username, _)) = &self.auth {
+            if username != "default" {
+                return SimpleError::new(
+                    "WRONGPASS invalid username-password pair or user is disabled.",
+                )
+                .into();
+            }
+        }
+        if let Some(name) = &self.setname {
+            if name.bytes().any(|b| !(b'!'..=b'~').contains(&b)) {
+                return SimpleError::new(
+                    "ERR Client names cannot contain spaces, newlines or special characters.",
+                )
+                .into();
+            }
+        }
+
+        client.protocol = protocol;
+        if let Some(name) = self.setname {
+            client.name = (!name.is_empty()).then_some(name);
+        }
+
+        let mut map = RespMap::new();
+        map.insert("server".to_string(), BulkString::new("redis").into());
+        map.insert(
+            "version".to_string(),
+            BulkString::new(env!("CARGO_PKG_VERSION")).into(),
+        );
+        let proto = match protocol {
+            RespVersion::Resp2 => 2,
+            RespVersion::Resp3 => 3,
+        };
+        map.insert("proto".to_string(), RespFrame::Integer(proto));
+        map.insert("id".to_string(), RespFrame::Integer(client.id as i64));
+        map.insert("mode".to_string(), BulkString::new("standalone").into());
+        map.insert("role".to_string(), BulkString::new("master").into());
+        map.insert("modules".to_string(), RespArray::new([]).into());
+        map.into()
+    }
+}
+
+impl TryFrom<RespArray> for Hello {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "hello")?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        let protover = match args.next() {
+            Some(arg) => Some(extract_string(Some(arg))?.parse::<i64>().map_err(|_| {
+                CommandError::InvalidArgument(
+                    "Protocol version is not an integer or out of range".to_string(),
+                )
+            })?),
+            None => None,
+        };
+
+        let mut auth = None;
+        let mut setname = None;
+        while let Some(arg) = args.next() {
+            let option = extract_string(Some(arg))?;
+            match option.to_ascii_uppercase().as_str() {
+                "AUTH" => {
+                    let (username, password) = (args.next(), args.next());
+                    if password.is_none() {
+                        return Err(CommandError::InvalidArgument(format!(
+                            "Syntax error in HELLO option '{}'",
+                            option
+                        )));
+                    }
+                    auth = Some((extract_string(username)?, extract_string(password)?));
+                }
+                "SETNAME" => match args.next() {
+                    Some(name) => setname = Some(extract_string(Some(name))?),
+                    None => {
+                        return Err(CommandError::InvalidArgument(format!(
+                            "Syntax error in HELLO option '{}'",
+                            option
+                        )))
+                    }
+                },
+                _ => {
+                    return Err(CommandError::InvalidArgument(format!(
+                        "Syntax error in HELLO option '{}'",
+                        option
+                    )))
+                }
+            }
+        }
+        Ok(Hello {
+            protover,
+            auth,
+            setname,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn hello(args: &[&str]) -> Result<Hello, CommandError> {
+        let frames = std::iter::once("hello")
+            .chain(args.iter().copied())
+            .map(|a| BulkString::new(a).into())
+            .collect::<Vec<RespFrame>>();
+        Hello::try_from(RespArray::new(frames))
+    }
+
+    #[test]
+    fn test_hello_from_resp_array() -> anyhow::Result<()> {
+        let cmd = hello(&["3", "auth", "default", "pass", "SETNAME", "conn"])?;
+        assert_eq!(cmd.protover, Some(3));
+        assert_eq!(cmd.auth, Some(("default".to_string(), "pass".to_string())));
+        assert_eq!(cmd.setname, Some("conn".to_string()));
+
+        assert!(hello(&["three"]).is_err());
+        assert!(hello(&["3", "AUTH", "default"]).is_err());
+        assert!(hello(&["3", "SETNAME"]).is_err());
+        assert!(hello(&["3", "FOO"]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_hello_command() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let mut client = ClientState {
+            id: 7,
+            ..ClientState::default()
+        };
+
+        let ret = hello(&[])?.execute_for(&mut client, &backend);
+        let RespFrame::Map(map) = ret else {
+            panic!("expected a map, got {:?}", ret);
+        };
+        assert_eq!(map.get("proto"), Some(&RespFrame::Integer(2)));
+        assert_eq!(map.get("id"), Some(&RespFrame::Integer(7)));
+
+        hello(&["3", "SETNAME", "conn"])?.execute_for(&mut client, &backend);
+        assert_eq!(client.protocol, RespVersion::Resp3);
+        assert_eq!(client.name.as_deref(), Some("conn"));
+
+        let ret = hello(&["4"])?.execute_for(&mut client, &backend);
+        assert_eq!(
+            ret,
+            SimpleError::new("NOPROTO unsupported protocol version").into()
+        );
+        let ret = hello(&["2", "AUTH", "admin", "pass"])?.execute_for(&mut client, &backend);
+        assert!(matches!(ret, RespFrame::Error(_)));
+        let ret = hello(&["2", "SETNAME", "my conn"])?.execute_for(&mut client, &backend);
+        assert!(matches!(ret, RespFrame::Error(_)));
+        // a failed HELLO leaves the connection as it was
+        assert_eq!(client.protocol, RespVersion::Resp3);
+        Ok(())
+    }
+}
@@ -0,0 +1,56 @@
+use crate::cmd::{extract_args, extract_string, validate_command, CommandError, CommandExecutor};
+use crate::resp::{RespArray, RespFrame};
+use crate::Backend;
+
+// HEXISTS key field
+// returns 1 if the hash contains the field, 0 otherwise
+
+#[derive(Debug)]
+pub struct HExists {
+    key: String,
+    field: String,
+}
+
+impl CommandExecutor for HExists {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.hexists(&self.key, &self.field) {
+            Ok(exists) => RespFrame::Integer(exists as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for HExists {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "hexists")?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(HExists {
+            key: extract_string(args.next())?,
+            field: extract_string(args.next())?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bytes::Bytes;
+
+    #[test]
+    fn test_hexists_command() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        backend.hset("myhash", vec![("field1".to_string(), Bytes::from("foo"))])?;
+        let cmd = HExists {
+            key: "myhash".to_string(),
+            field: "field1".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        let cmd = HExists {
+            key: "myhash".to_string(),
+            field: "field2".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+        Ok(())
+    }
+}
@@ -1,5 +1,5 @@
 use crate::cmd::{extract_args, validate_command, CommandError, CommandExecutor};
-use crate::resp::{RespArray, RespFrame, RespNull};
+use crate::resp::{BulkString, RespArray, RespFrame, RespNull};
 use crate::Backend;
 
 #[derive(Debug)]
@@ -10,21 +10,23 @@ pub struct HGet {
 
 impl CommandExecutor for HGet {
     fn execute(self, backend: &Backend) -> RespFrame {
-        backend
-            .hget(&self.key, &self.field)
-            .unwrap_or(RespFrame::Null(RespNull))
+        match backend.hget(&self.key, &self.field) {
+            Ok(Some(value)) => BulkString::new(value).into(),
+            Ok(None) => RespFrame::Null(RespNull),
+            Err(e) => e.into(),
+        }
     }
 }
 
 impl TryFrom<RespArray> for HGet {
     type Error = CommandError;
     fn try_from(value: RespArray) -> Result<Self, Self::Error> {
-        validate_command(&value, &["hget"], 2)?;
+        validate_command(&value, "hget")?;
         let mut args = extract_args(value, 1)?.into_iter();
         match (args.next(), args.next()) {
             (Some(RespFrame::BulkString(key)), Some(RespFrame::BulkString(field))) => Ok(HGet {
-                key: String::from_utf8(key.0)?,
-                field: String::from_utf8(field.0)?,
+                key: String::from_utf8(key.0.to_vec())?,
+                field: String::from_utf8(field.0.to_vec())?,
             }),
             _ => Err(CommandError::InvalidArgument(
                 "Invalid key or field".to_string(),
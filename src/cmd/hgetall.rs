@@ -1,33 +1,26 @@
 use crate::cmd::{extract_args, validate_command, CommandError, CommandExecutor};
-use crate::resp::{BulkString, RespArray, RespFrame};
+use crate::resp::{BulkString, RespArray, RespFrame, RespMap};
 use crate::Backend;
 
+// HGETALL key
+// returns all the fields and values of the hash, as a RESP3 map
+
 #[derive(Debug)]
 pub struct HGetAll {
     key: String,
-    sort: bool,
 }
 
 impl CommandExecutor for HGetAll {
     fn execute(self, backend: &Backend) -> RespFrame {
-        let hmap = backend.hgetall(&self.key);
-        match hmap {
-            Some(hmap) => {
-                let mut data = Vec::with_capacity(hmap.len());
-                for v in hmap.iter() {
-                    let key = v.key().to_owned();
-                    data.push((key, v.value().clone()));
-                }
-                if self.sort {
-                    data.sort_by(|a, b| a.0.cmp(&b.0));
+        match backend.hgetall(&self.key) {
+            Ok(hmap) => {
+                let mut map = RespMap::new();
+                for (field, value) in hmap.unwrap_or_default() {
+                    map.insert(field, BulkString::new(value).into());
                 }
-                let ret = data
-                    .into_iter()
-                    .flat_map(|(k, v)| vec![BulkString::from(k).into(), v])
-                    .collect::<Vec<RespFrame>>();
-                RespArray::new(ret).into()
+                map.into()
             }
-            None => RespArray::new([]).into(),
+            Err(e) => e.into(),
         }
     }
 }
@@ -35,12 +28,11 @@ impl CommandExecutor for HGetAll {
 impl TryFrom<RespArray> for HGetAll {
     type Error = CommandError;
     fn try_from(value: RespArray) -> Result<Self, Self::Error> {
-        validate_command(&value, &["hgetall"], 1)?;
+        validate_command(&value, "hgetall")?;
         let mut args = extract_args(value, 1)?.into_iter();
         match args.next() {
             Some(RespFrame::BulkString(key)) => Ok(HGetAll {
-                key: String::from_utf8(key.0)?,
-                sort: false,
+                key: String::from_utf8(key.0.to_vec())?,
             }),
             _ => Err(CommandError::InvalidArgument("Invalid key".to_string())),
         }
@@ -49,10 +41,10 @@ impl TryFrom<RespArray> for HGetAll {
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::cmd::{HGet, HSet, RESP_OK};
+    use crate::cmd::{HGet, HSet};
     use crate::resp::RespDecode;
     use anyhow::Result;
-    use bytes::BytesMut;
+    use bytes::{Bytes, BytesMut};
 
     #[test]
     fn test_hgetall_from_resp_array() -> Result<()> {
@@ -72,16 +64,14 @@ mod test {
         let backend = Backend::new();
         let cmd = HSet {
             key: "map".to_string(),
-            field: "hello".to_string(),
-            value: RespFrame::BulkString(b"world".into()),
+            fields: vec![("hello".to_string(), Bytes::from("world"))],
         };
         let result = cmd.execute(&backend);
-        assert_eq!(result, RESP_OK.clone());
+        assert_eq!(result, RespFrame::Integer(1));
 
         let cmd = HSet {
             key: "map".to_string(),
-            field: "hello1".to_string(),
-            value: RespFrame::BulkString(b"world1".into()),
+            fields: vec![("hello1".to_string(), Bytes::from("world1"))],
         };
         cmd.execute(&backend);
 
@@ -94,15 +84,11 @@ mod test {
 
         let cmd = HGetAll {
             key: "map".to_string(),
-            sort: true,
         };
         let result = cmd.execute(&backend);
-        let expected = RespArray::new([
-            BulkString::from("hello").into(),
-            BulkString::from("world").into(),
-            BulkString::from("hello1").into(),
-            BulkString::from("world1").into(),
-        ]);
+        let mut expected = RespMap::new();
+        expected.insert("hello".to_string(), BulkString::from("world").into());
+        expected.insert("hello1".to_string(), BulkString::from("world1").into());
 
         assert_eq!(result, expected.into());
         Ok(())
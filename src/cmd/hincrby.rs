@@ -0,0 +1,130 @@
+use crate::cmd::{
+    extract_args, extract_float, extract_integer, extract_string, validate_command, CommandError,
+    CommandExecutor,
+};
+use crate::resp::{BulkString, RespArray, RespFrame};
+use crate::Backend;
+
+// HINCRBY key field increment
+// HINCRBYFLOAT key field increment
+// a missing field is set to 0 before the operation, returns the new value
+// HINCRBYFLOAT replies with the value as a bulk string
+
+// redis> HSET mykey field 10.50
+// (integer) 1
+// redis> HINCRBYFLOAT mykey field 0.1
+// "10.6"
+// redis> HINCRBY mykey other -5
+// (integer) -5
+
+#[derive(Debug)]
+pub struct HIncrBy {
+    key: String,
+    field: String,
+    increment: i64,
+}
+
+#[derive(Debug)]
+pub struct HIncrByFloat {
+    key: String,
+    field: String,
+    increment: f64,
+}
+
+impl CommandExecutor for HIncrBy {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.hincrby(&self.key, &self.field, self.increment) {
+            Ok(value) => RespFrame::Integer(value),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for HIncrByFloat {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.hincrbyfloat(&self.key, &self.field, self.increment) {
+            Ok(value) => BulkString::new(value.to_string()).into(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for HIncrBy {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "hincrby")?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(HIncrBy {
+            key: extract_string(args.next())?,
+            field: extract_string(args.next())?,
+            increment: extract_integer(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for HIncrByFloat {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "hincrbyfloat")?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(HIncrByFloat {
+            key: extract_string(args.next())?,
+            field: extract_string(args.next())?,
+            increment: extract_float(args.next())?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::resp::RespDecode;
+    use bytes::{Bytes, BytesMut};
+
+    #[test]
+    fn test_hincrby_from_resp_array() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$7\r\nhincrby\r\n$5\r\nmykey\r\n$5\r\nfield\r\n$2\r\n-5\r\n");
+
+        let frame = RespArray::decode(&mut buf)?;
+        let result: HIncrBy = frame.try_into()?;
+        assert_eq!(result.key, "mykey");
+        assert_eq!(result.field, "field");
+        assert_eq!(result.increment, -5);
+
+        buf.extend_from_slice(
+            b"*4\r\n$7\r\nhincrby\r\n$5\r\nmykey\r\n$5\r\nfield\r\n$3\r\n1.5\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(HIncrBy::try_from(frame).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_hincrby_commands() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        backend.hset("mykey", vec![("field".to_string(), Bytes::from("10.50"))])?;
+        let cmd = HIncrByFloat {
+            key: "mykey".to_string(),
+            field: "field".to_string(),
+            increment: 0.1,
+        };
+        assert_eq!(cmd.execute(&backend), BulkString::new("10.6").into());
+        let cmd = HIncrBy {
+            key: "mykey".to_string(),
+            field: "field".to_string(),
+            increment: 1,
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            crate::backend::BackendError::HashNotInteger.into()
+        );
+        let cmd = HIncrBy {
+            key: "mykey".to_string(),
+            field: "other".to_string(),
+            increment: -5,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(-5));
+        Ok(())
+    }
+}
@@ -0,0 +1,100 @@
+use crate::cmd::{extract_args, extract_string, validate_command, CommandError, CommandExecutor};
+use crate::resp::{BulkString, RespArray, RespFrame};
+use crate::Backend;
+
+// HKEYS key
+// HVALS key
+// returns all the fields or all the values of the hash, an empty array if the key does not exist
+
+#[derive(Debug)]
+pub struct HKeys {
+    key: String,
+}
+
+#[derive(Debug)]
+pub struct HVals {
+    key: String,
+}
+
+impl CommandExecutor for HKeys {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.hgetall(&self.key) {
+            Ok(hmap) => RespArray::new(
+                hmap.unwrap_or_default()
+                    .into_keys()
+                    .map(|f| BulkString::new(f).into())
+                    .collect::<Vec<RespFrame>>(),
+            )
+            .into(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for HVals {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.hgetall(&self.key) {
+            Ok(hmap) => RespArray::new(
+                hmap.unwrap_or_default()
+                    .into_values()
+                    .map(|v| BulkString::new(v).into())
+                    .collect::<Vec<RespFrame>>(),
+            )
+            .into(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for HKeys {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "hkeys")?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(HKeys {
+            key: extract_string(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for HVals {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "hvals")?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(HVals {
+            key: extract_string(args.next())?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bytes::Bytes;
+
+    #[test]
+    fn test_hkeys_hvals_commands() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        backend.hset("myhash", vec![("field1".to_string(), Bytes::from("Hello"))])?;
+        let cmd = HKeys {
+            key: "myhash".to_string(),
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            RespArray::new([BulkString::new("field1").into()]).into()
+        );
+        let cmd = HVals {
+            key: "myhash".to_string(),
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            RespArray::new([BulkString::new("Hello").into()]).into()
+        );
+        let cmd = HKeys {
+            key: "none".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespArray::new(vec![]).into());
+        Ok(())
+    }
+}
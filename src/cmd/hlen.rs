@@ -0,0 +1,52 @@
+use crate::cmd::{extract_args, extract_string, validate_command, CommandError, CommandExecutor};
+use crate::resp::{RespArray, RespFrame};
+use crate::Backend;
+
+// HLEN key
+// returns the number of fields in the hash, 0 if the key does not exist
+
+#[derive(Debug)]
+pub struct HLen {
+    key: String,
+}
+
+impl CommandExecutor for HLen {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.hlen(&self.key) {
+            Ok(len) => RespFrame::Integer(len as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for HLen {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "hlen")?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(HLen {
+            key: extract_string(args.next())?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bytes::Bytes;
+
+    #[test]
+    fn test_hlen_command() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let fields = vec![
+            ("field1".to_string(), Bytes::from("Hello")),
+            ("field2".to_string(), Bytes::from("World")),
+        ];
+        backend.hset("myhash", fields)?;
+        let cmd = HLen {
+            key: "myhash".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(2));
+        Ok(())
+    }
+}
@@ -1,5 +1,5 @@
 use crate::cmd::{extract_args, validate_command, CommandError, CommandExecutor};
-use crate::resp::{RespArray, RespFrame, RespNull};
+use crate::resp::{BulkString, RespArray, RespFrame, RespNull};
 use crate::Backend;
 
 //HMGET key field [field ...]
@@ -23,18 +23,19 @@ impl CommandExecutor for HMGet {
     fn execute(self, backend: &Backend) -> RespFrame {
         let hmap = backend.hgetall(&self.key);
         match hmap {
-            Some(hmap) => {
+            Ok(Some(hmap)) => {
                 let mut data = Vec::with_capacity(self.fields.len());
                 for field in self.fields.iter() {
                     let value = hmap.get(field);
                     match value {
-                        Some(value) => data.push(value.clone()),
+                        Some(value) => data.push(BulkString::new(value.clone()).into()),
                         None => data.push(RespNull.into()),
                     }
                 }
                 RespArray::new(data).into()
             }
-            None => RespArray::new([]).into(),
+            Ok(None) => RespArray::new(vec![RespNull.into(); self.fields.len()]).into(),
+            Err(e) => e.into(),
         }
     }
 }
@@ -42,13 +43,13 @@ impl CommandExecutor for HMGet {
 impl TryFrom<RespArray> for HMGet {
     type Error = CommandError;
     fn try_from(value: RespArray) -> Result<Self, Self::Error> {
-        validate_command(&value, &["HMGET"], 2)?;
+        validate_command(&value, "HMGET")?;
         let args = extract_args(value, 1)?.into_iter();
         let mut data = Vec::with_capacity(args.len());
         for arg in args {
             match arg {
                 RespFrame::BulkString(s) => {
-                    let s = String::from_utf8(s.0)?;
+                    let s = String::from_utf8(s.0.to_vec())?;
                     data.push(s);
                 }
                 _ => {
@@ -68,27 +69,25 @@ impl TryFrom<RespArray> for HMGet {
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::cmd::{HSet, RESP_OK};
-    use crate::resp::BulkString;
+    use crate::cmd::HSet;
+    use bytes::Bytes;
 
     #[test]
     fn test_hmget_from_resp_array() -> anyhow::Result<()> {
         let backend = Backend::new();
         let cmd = HSet {
             key: "myhash".to_string(),
-            field: "field1".to_string(),
-            value: RespFrame::BulkString(b"hello".into()),
+            fields: vec![("field1".to_string(), Bytes::from("hello"))],
         };
         let result = cmd.execute(&backend);
-        assert_eq!(result, RESP_OK.clone());
+        assert_eq!(result, RespFrame::Integer(1));
 
         let cmd = HSet {
             key: "myhash".to_string(),
-            field: "field2".to_string(),
-            value: RespFrame::BulkString(b"world".into()),
+            fields: vec![("field2".to_string(), Bytes::from("world"))],
         };
         let result = cmd.execute(&backend);
-        assert_eq!(result, RESP_OK.clone());
+        assert_eq!(result, RespFrame::Integer(1));
 
         let cmd = HMGet::try_from(RespArray::new(vec![
             RespFrame::BulkString("HMGET".into()),
@@ -0,0 +1,131 @@
+use crate::cmd::{
+    extract_args, extract_integer, extract_string, validate_command, CommandError, CommandExecutor,
+};
+use crate::resp::{BulkString, RespArray, RespFrame, RespNull};
+use crate::Backend;
+
+// HRANDFIELD key [count [WITHVALUES]]
+// without count a single field is returned, nil if the key does not exist
+// with count an array is returned, a negative count allows repeated fields
+
+// redis> HSET coin heads obverse tails reverse
+// (integer) 2
+// redis> HRANDFIELD coin -3 WITHVALUES
+// 1) "heads"
+// 2) "obverse"
+// 3) "heads"
+// 4) "obverse"
+// 5) "tails"
+// 6) "reverse"
+
+#[derive(Debug)]
+pub struct HRandField {
+    key: String,
+    count: Option<i64>,
+    with_values: bool,
+}
+
+impl CommandExecutor for HRandField {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let fields = match backend.hrandfield(&self.key, self.count.unwrap_or(1)) {
+            Ok(fields) => fields,
+            Err(e) => return e.into(),
+        };
+        if self.count.is_none() {
+            return match fields.into_iter().next() {
+                Some((field, _)) => BulkString::new(field).into(),
+                None => RespFrame::Null(RespNull),
+            };
+        }
+        let mut frames = Vec::with_capacity(fields.len() * if self.with_values { 2 } else { 1 });
+        for (field, value) in fields {
+            frames.push(BulkString::new(field).into());
+            if self.with_values {
+                frames.push(BulkString::new(value).into());
+            }
+        }
+        RespArray::new(frames).into()
+    }
+}
+
+impl TryFrom<RespArray> for HRandField {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "hrandfield")?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let count = match args.next() {
+            Some(arg) => Some(extract_integer(Some(arg))?),
+            None => None,
+        };
+        let with_values = match args.next() {
+            Some(arg) => {
+                if !extract_string(Some(arg))?.eq_ignore_ascii_case("withvalues") {
+                    return Err(CommandError::InvalidArgument("syntax error".to_string()));
+                }
+                true
+            }
+            None => false,
+        };
+        if args.next().is_some() {
+            return Err(CommandError::InvalidArgument("syntax error".to_string()));
+        }
+        Ok(HRandField {
+            key,
+            count,
+            with_values,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::resp::RespDecode;
+    use bytes::{Bytes, BytesMut};
+
+    #[test]
+    fn test_hrandfield_from_resp_array() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*4\r\n$10\r\nhrandfield\r\n$4\r\ncoin\r\n$2\r\n-3\r\n$10\r\nWITHVALUES\r\n",
+        );
+
+        let frame = RespArray::decode(&mut buf)?;
+        let result: HRandField = frame.try_into()?;
+        assert_eq!(result.key, "coin");
+        assert_eq!(result.count, Some(-3));
+        assert!(result.with_values);
+        Ok(())
+    }
+
+    #[test]
+    fn test_hrandfield_command() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        backend.hset("coin", vec![("heads".to_string(), Bytes::from("obverse"))])?;
+        let cmd = HRandField {
+            key: "coin".to_string(),
+            count: None,
+            with_values: false,
+        };
+        assert_eq!(cmd.execute(&backend), BulkString::new("heads").into());
+        let cmd = HRandField {
+            key: "coin".to_string(),
+            count: Some(-2),
+            with_values: true,
+        };
+        let heads: RespFrame = BulkString::new("heads").into();
+        let obverse: RespFrame = BulkString::new("obverse").into();
+        assert_eq!(
+            cmd.execute(&backend),
+            RespArray::new([heads.clone(), obverse.clone(), heads, obverse]).into()
+        );
+        let cmd = HRandField {
+            key: "none".to_string(),
+            count: None,
+            with_values: false,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Null(RespNull));
+        Ok(())
+    }
+}
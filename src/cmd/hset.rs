@@ -1,37 +1,46 @@
-use crate::cmd::{extract_args, validate_command, CommandError, CommandExecutor, RESP_OK};
+use crate::cmd::{
+    extract_args, extract_bytes, extract_string, validate_command, CommandError, CommandExecutor,
+};
 use crate::resp::{RespArray, RespFrame};
 use crate::Backend;
+use bytes::Bytes;
+
+// HSET key field value [field value ...]
+// returns the number of fields that were added
+
+// redis> HSET myhash field1 "Hello" field2 "World"
+// (integer) 2
+// redis> HSET myhash field1 "Hi"
+// (integer) 0
 
 #[derive(Debug)]
 pub struct HSet {
     pub(crate) key: String,
-    pub(crate) field: String,
-    pub(crate) value: RespFrame,
+    pub(crate) fields: Vec<(String, Bytes)>,
 }
 impl CommandExecutor for HSet {
     fn execute(self, backend: &Backend) -> RespFrame {
-        backend.hset(self.key, self.field, self.value);
-        RESP_OK.clone()
+        match backend.hset(&self.key, self.fields) {
+            Ok(added) => RespFrame::Integer(added as i64),
+            Err(e) => e.into(),
+        }
     }
 }
 
 impl TryFrom<RespArray> for HSet {
     type Error = CommandError;
     fn try_from(value: RespArray) -> Result<Self, Self::Error> {
-        validate_command(&value, &["hset"], 3)?;
+        validate_command(&value, "hset")?;
+        if !value.len().is_multiple_of(2) {
+            return Err(CommandError::WrongArity("hset".to_string()));
+        }
         let mut args = extract_args(value, 1)?.into_iter();
-        match (args.next(), args.next(), args.next()) {
-            (Some(RespFrame::BulkString(key)), Some(RespFrame::BulkString(field)), Some(value)) => {
-                Ok(HSet {
-                    key: String::from_utf8(key.0)?,
-                    field: String::from_utf8(field.0)?,
-                    value,
-                })
-            }
-            _ => Err(CommandError::InvalidArgument(
-                "Invalid key, field or value".to_string(),
-            )),
+        let key = extract_string(args.next())?;
+        let mut fields = Vec::with_capacity(args.len() / 2);
+        while let Some(field) = args.next() {
+            fields.push((extract_string(Some(field))?, extract_bytes(args.next())?));
         }
+        Ok(HSet { key, fields })
     }
 }
 
@@ -49,9 +58,37 @@ mod test {
 
         let result: HSet = frame.try_into()?;
         assert_eq!(result.key, "map");
-        assert_eq!(result.field, "hello");
-        assert_eq!(result.value, RespFrame::BulkString(b"world".into()));
+        assert_eq!(
+            result.fields,
+            vec![("hello".to_string(), Bytes::from("world"))]
+        );
 
+        buf.extend_from_slice(
+            b"*5\r\n$4\r\nhset\r\n$3\r\nmap\r\n$1\r\na\r\n$1\r\nb\r\n$1\r\nc\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(HSet::try_from(frame).is_err());
         Ok(())
     }
+
+    #[test]
+    fn test_hset_command() {
+        let backend = Backend::new();
+        let cmd = HSet {
+            key: "map".to_string(),
+            fields: vec![
+                ("a".to_string(), Bytes::from("1")),
+                ("b".to_string(), Bytes::from("2")),
+            ],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(2));
+        let cmd = HSet {
+            key: "map".to_string(),
+            fields: vec![
+                ("a".to_string(), Bytes::from("3")),
+                ("c".to_string(), Bytes::from("4")),
+            ],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+    }
 }
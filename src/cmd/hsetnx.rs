@@ -0,0 +1,62 @@
+use crate::cmd::{
+    extract_args, extract_bytes, extract_string, validate_command, CommandError, CommandExecutor,
+};
+use crate::resp::{RespArray, RespFrame};
+use crate::Backend;
+use bytes::Bytes;
+
+// HSETNX key field value
+// sets the field only if it does not exist yet, returns 1 if the field was set and 0 otherwise
+
+#[derive(Debug)]
+pub struct HSetNx {
+    key: String,
+    field: String,
+    value: Bytes,
+}
+
+impl CommandExecutor for HSetNx {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.hsetnx(&self.key, self.field, self.value) {
+            Ok(set) => RespFrame::Integer(set as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for HSetNx {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "hsetnx")?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(HSetNx {
+            key: extract_string(args.next())?,
+            field: extract_string(args.next())?,
+            value: extract_bytes(args.next())?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_hsetnx_command() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let cmd = HSetNx {
+            key: "myhash".to_string(),
+            field: "field".to_string(),
+            value: Bytes::from("Hello"),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        let cmd = HSetNx {
+            key: "myhash".to_string(),
+            field: "field".to_string(),
+            value: Bytes::from("World"),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+        assert_eq!(backend.hget("myhash", "field")?, Some(Bytes::from("Hello")));
+        Ok(())
+    }
+}
@@ -0,0 +1,59 @@
+use crate::cmd::{extract_args, extract_string, validate_command, CommandError, CommandExecutor};
+use crate::resp::{RespArray, RespFrame};
+use crate::Backend;
+
+// HSTRLEN key field
+// returns the length of the value of the field, 0 if the field or the key does not exist
+
+#[derive(Debug)]
+pub struct HStrLen {
+    key: String,
+    field: String,
+}
+
+impl CommandExecutor for HStrLen {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.hstrlen(&self.key, &self.field) {
+            Ok(len) => RespFrame::Integer(len as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for HStrLen {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "hstrlen")?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(HStrLen {
+            key: extract_string(args.next())?,
+            field: extract_string(args.next())?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bytes::Bytes;
+
+    #[test]
+    fn test_hstrlen_command() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        backend.hset(
+            "myhash",
+            vec![("f1".to_string(), Bytes::from("HelloWorld"))],
+        )?;
+        let cmd = HStrLen {
+            key: "myhash".to_string(),
+            field: "f1".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(10));
+        let cmd = HStrLen {
+            key: "myhash".to_string(),
+            field: "f2".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+        Ok(())
+    }
+}
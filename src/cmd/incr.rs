@@ -0,0 +1,182 @@
+use crate::cmd::{
+    extract_args, extract_integer, extract_string, validate_command, CommandError, CommandExecutor,
+};
+use crate::resp::{RespArray, RespFrame, SimpleError};
+use crate::Backend;
+
+// INCR key
+// DECR key
+// INCRBY key increment
+// DECRBY key decrement
+// a missing key is set to 0 before the operation, returns the new value
+
+// redis> SET mykey "10"
+// "OK"
+// redis> INCR mykey
+// (integer) 11
+// redis> DECRBY mykey 3
+// (integer) 8
+
+#[derive(Debug)]
+pub struct Incr {
+    key: String,
+}
+
+#[derive(Debug)]
+pub struct Decr {
+    key: String,
+}
+
+#[derive(Debug)]
+pub struct IncrBy {
+    key: String,
+    increment: i64,
+}
+
+#[derive(Debug)]
+pub struct DecrBy {
+    key: String,
+    decrement: i64,
+}
+
+impl CommandExecutor for Incr {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        incr_generic(backend, &self.key, 1)
+    }
+}
+
+impl CommandExecutor for Decr {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        incr_generic(backend, &self.key, -1)
+    }
+}
+
+impl CommandExecutor for IncrBy {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        incr_generic(backend, &self.key, self.increment)
+    }
+}
+
+impl CommandExecutor for DecrBy {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match self.decrement.checked_neg() {
+            Some(delta) => incr_generic(backend, &self.key, delta),
+            None => SimpleError::new("ERR decrement would overflow").into(),
+        }
+    }
+}
+
+fn incr_generic(backend: &Backend, key: &str, delta: i64) -> RespFrame {
+    match backend.incr_by(key, delta) {
+        Ok(value) => RespFrame::Integer(value),
+        Err(e) => e.into(),
+    }
+}
+
+fn parse_key(value: RespArray, name: &'static str) -> Result<String, CommandError> {
+    validate_command(&value, name)?;
+    let mut args = extract_args(value, 1)?.into_iter();
+    extract_string(args.next())
+}
+
+fn parse_key_delta(value: RespArray, name: &'static str) -> Result<(String, i64), CommandError> {
+    validate_command(&value, name)?;
+    let mut args = extract_args(value, 1)?.into_iter();
+    Ok((extract_string(args.next())?, extract_integer(args.next())?))
+}
+
+impl TryFrom<RespArray> for Incr {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let key = parse_key(value, "incr")?;
+        Ok(Incr { key })
+    }
+}
+
+impl TryFrom<RespArray> for Decr {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let key = parse_key(value, "decr")?;
+        Ok(Decr { key })
+    }
+}
+
+impl TryFrom<RespArray> for IncrBy {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, increment) = parse_key_delta(value, "incrby")?;
+        Ok(IncrBy { key, increment })
+    }
+}
+
+impl TryFrom<RespArray> for DecrBy {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, decrement) = parse_key_delta(value, "decrby")?;
+        Ok(DecrBy { key, decrement })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::backend::BackendError;
+    use crate::resp::RespDecode;
+    use bytes::BytesMut;
+
+    #[test]
+    fn test_incrby_from_resp_array() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$6\r\nincrby\r\n$5\r\nmykey\r\n$2\r\n-5\r\n");
+
+        let frame = RespArray::decode(&mut buf)?;
+        let result: IncrBy = frame.try_into()?;
+        assert_eq!(result.key, "mykey");
+        assert_eq!(result.increment, -5);
+
+        buf.extend_from_slice(
+            b"*3\r\n$6\r\nincrby\r\n$5\r\nmykey\r\n$20\r\n99999999999999999999\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(IncrBy::try_from(frame).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_incr_commands() {
+        let backend = Backend::new();
+        backend.set("mykey".to_string(), "10");
+        let cmd = Incr {
+            key: "mykey".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(11));
+        let cmd = DecrBy {
+            key: "mykey".to_string(),
+            decrement: 3,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(8));
+        let cmd = Decr {
+            key: "other".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(-1));
+        let cmd = DecrBy {
+            key: "other".to_string(),
+            decrement: i64::MIN,
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            SimpleError::new("ERR decrement would overflow").into()
+        );
+        let cmd = IncrBy {
+            key: "other".to_string(),
+            increment: i64::MIN,
+        };
+        assert_eq!(cmd.execute(&backend), BackendError::Overflow.into());
+
+        backend.set("text".to_string(), "abc");
+        let cmd = Incr {
+            key: "text".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), BackendError::NotInteger.into());
+    }
+}
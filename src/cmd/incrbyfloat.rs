@@ -0,0 +1,61 @@
+use crate::cmd::{
+    extract_args, extract_float, extract_string, validate_command, CommandError, CommandExecutor,
+};
+use crate::resp::{BulkString, RespArray, RespFrame};
+use crate::Backend;
+
+// INCRBYFLOAT key increment
+// a missing key is set to 0 before the operation, returns the new value as a bulk string
+
+// redis> SET mykey 10.50
+// "OK"
+// redis> INCRBYFLOAT mykey 0.1
+// "10.6"
+
+#[derive(Debug)]
+pub struct IncrByFloat {
+    key: String,
+    increment: f64,
+}
+
+impl CommandExecutor for IncrByFloat {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.incr_by_float(&self.key, self.increment) {
+            Ok(value) => BulkString::new(value.to_string()).into(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for IncrByFloat {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "incrbyfloat")?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(IncrByFloat {
+            key: extract_string(args.next())?,
+            increment: extract_float(args.next())?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_incrbyfloat_command() {
+        let backend = Backend::new();
+        backend.set("mykey".to_string(), "10.50");
+        let cmd = IncrByFloat {
+            key: "mykey".to_string(),
+            increment: 0.1,
+        };
+        assert_eq!(cmd.execute(&backend), BulkString::new("10.6").into());
+        let cmd = IncrByFloat {
+            key: "mykey".to_string(),
+            increment: 5.0e3,
+        };
+        assert_eq!(cmd.execute(&backend), BulkString::new("5010.6").into());
+    }
+}
@@ -0,0 +1,50 @@
+use crate::cmd::{extract_args, extract_string, validate_command, CommandError, CommandExecutor};
+use crate::resp::{RespArray, RespFrame, SimpleString};
+use crate::Backend;
+
+// TYPE key
+// returns string, list, set, zset, hash or none
+
+#[derive(Debug)]
+pub struct Type {
+    key: String,
+}
+
+impl CommandExecutor for Type {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let name = backend.key_type(&self.key).unwrap_or("none");
+        SimpleString::new(name).into()
+    }
+}
+
+impl TryFrom<RespArray> for Type {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "type")?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(Type {
+            key: extract_string(args.next())?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bytes::Bytes;
+
+    #[test]
+    fn test_type_command() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        backend.hset("h", vec![("f".to_string(), Bytes::from("v"))])?;
+        let cmd = Type {
+            key: "h".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), SimpleString::new("hash").into());
+        let cmd = Type {
+            key: "none".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), SimpleString::new("none").into());
+        Ok(())
+    }
+}
@@ -0,0 +1,60 @@
+use crate::cmd::{
+    extract_args, extract_integer, extract_string, validate_command, CommandError, CommandExecutor,
+};
+use crate::resp::{BulkString, RespArray, RespFrame, RespNull};
+use crate::Backend;
+
+// LINDEX key index
+
+#[derive(Debug)]
+pub struct LIndex {
+    key: String,
+    index: i64,
+}
+
+impl CommandExecutor for LIndex {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.lindex(&self.key, self.index) {
+            Ok(Some(value)) => BulkString::new(value).into(),
+            Ok(None) => RespFrame::Null(RespNull),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for LIndex {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "lindex")?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(LIndex {
+            key: extract_string(args.next())?,
+            index: extract_integer(args.next())?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::backend::ListEnd;
+    use bytes::Bytes;
+
+    #[test]
+    fn test_lindex_command() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let values = ["a", "b"].map(Bytes::from).to_vec();
+        backend.list_push("mylist", values, ListEnd::Right, false)?;
+        let cmd = LIndex {
+            key: "mylist".to_string(),
+            index: -1,
+        };
+        assert_eq!(cmd.execute(&backend), BulkString::new("b").into());
+        let cmd = LIndex {
+            key: "mylist".to_string(),
+            index: 2,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Null(RespNull));
+        Ok(())
+    }
+}
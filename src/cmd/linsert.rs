@@ -0,0 +1,71 @@
+use crate::cmd::{
+    extract_args, extract_bytes, extract_string, validate_command, CommandError, CommandExecutor,
+};
+use crate::resp::{RespArray, RespFrame};
+use crate::Backend;
+use bytes::Bytes;
+
+// LINSERT key <BEFORE | AFTER> pivot element
+// returns the list length after the insert, -1 when the pivot was not found, 0 if the key is missing
+
+#[derive(Debug)]
+pub struct LInsert {
+    key: String,
+    before: bool,
+    pivot: Bytes,
+    value: Bytes,
+}
+
+impl CommandExecutor for LInsert {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.linsert(&self.key, self.before, &self.pivot, self.value) {
+            Ok(len) => RespFrame::Integer(len),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for LInsert {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "linsert")?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let before = match extract_string(args.next())?.to_ascii_uppercase().as_str() {
+            "BEFORE" => true,
+            "AFTER" => false,
+            _ => return Err(CommandError::InvalidArgument("syntax error".to_string())),
+        };
+        Ok(LInsert {
+            key,
+            before,
+            pivot: extract_bytes(args.next())?,
+            value: extract_bytes(args.next())?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::backend::ListEnd;
+    use crate::resp::BulkString;
+
+    #[test]
+    fn test_linsert_command() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let values = ["Hello", "World"].map(Bytes::from).to_vec();
+        backend.list_push("mylist", values, ListEnd::Right, false)?;
+
+        let cmd = LInsert::try_from(RespArray::new([
+            BulkString::new("linsert").into(),
+            BulkString::new("mylist").into(),
+            BulkString::new("before").into(),
+            BulkString::new("World").into(),
+            BulkString::new("There").into(),
+        ]))?;
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(3));
+        assert_eq!(backend.lindex("mylist", 1)?, Some(Bytes::from("There")));
+        Ok(())
+    }
+}
@@ -0,0 +1,88 @@
+use crate::backend::ListEnd;
+use crate::cmd::{extract_args, extract_string, validate_command, CommandError, CommandExecutor};
+use crate::resp::{BulkString, RespArray, RespFrame, RespNull};
+use crate::Backend;
+
+// LMOVE source destination <LEFT | RIGHT> <LEFT | RIGHT>
+// atomically pops an element from source and pushes it to destination
+
+#[derive(Debug)]
+pub struct LMove {
+    source: String,
+    destination: String,
+    from: ListEnd,
+    to: ListEnd,
+}
+
+impl CommandExecutor for LMove {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.lmove(&self.source, &self.destination, self.from, self.to) {
+            Ok(Some(value)) => BulkString::new(value).into(),
+            Ok(None) => RespFrame::Null(RespNull),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+pub(crate) fn extract_list_end(value: Option<RespFrame>) -> Result<ListEnd, CommandError> {
+    match extract_string(value)?.to_ascii_uppercase().as_str() {
+        "LEFT" => Ok(ListEnd::Left),
+        "RIGHT" => Ok(ListEnd::Right),
+        _ => Err(CommandError::InvalidArgument("syntax error".to_string())),
+    }
+}
+
+impl TryFrom<RespArray> for LMove {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "lmove")?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(LMove {
+            source: extract_string(args.next())?,
+            destination: extract_string(args.next())?,
+            from: extract_list_end(args.next())?,
+            to: extract_list_end(args.next())?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::resp::RespDecode;
+    use bytes::{Bytes, BytesMut};
+
+    #[test]
+    fn test_lmove_from_resp_array() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*5\r\n$5\r\nlmove\r\n$1\r\na\r\n$1\r\nb\r\n$4\r\nleft\r\n$5\r\nRIGHT\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let result: LMove = frame.try_into()?;
+        assert_eq!(result.source, "a");
+        assert_eq!(result.destination, "b");
+        assert_eq!(result.from, ListEnd::Left);
+        assert_eq!(result.to, ListEnd::Right);
+        Ok(())
+    }
+
+    #[test]
+    fn test_lmove_command() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let values = ["one", "two", "three"].map(Bytes::from).to_vec();
+        backend.list_push("mylist", values, ListEnd::Right, false)?;
+        let cmd = LMove {
+            source: "mylist".to_string(),
+            destination: "myotherlist".to_string(),
+            from: ListEnd::Right,
+            to: ListEnd::Left,
+        };
+        assert_eq!(cmd.execute(&backend), BulkString::new("three").into());
+        assert_eq!(
+            backend.lrange("myotherlist", 0, -1)?,
+            vec![Bytes::from("three")]
+        );
+        Ok(())
+    }
+}
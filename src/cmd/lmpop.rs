@@ -0,0 +1,195 @@
+use crate::backend::ListEnd;
+use crate::cmd::lmove::extract_list_end;
+use crate::cmd::{
+    extract_args, extract_integer, extract_string, extract_timeout, validate_command, CommandError,
+    CommandExecutor,
+};
+use crate::resp::{BulkString, RespArray, RespFrame, RespNullArray};
+use crate::Backend;
+use std::time::Duration;
+
+// LMPOP numkeys key [key ...] <LEFT | RIGHT> [COUNT count]
+// BLMPOP timeout numkeys key [key ...] <LEFT | RIGHT> [COUNT count]
+// pops up to count elements from the first non-empty list
+
+// redis> LMPOP 2 non1 mylist LEFT COUNT 10
+// 1) "mylist"
+// 2) 1) "one"
+//    2) "two"
+
+#[derive(Debug)]
+pub struct LMPop {
+    keys: Vec<String>,
+    end: ListEnd,
+    count: usize,
+}
+
+#[derive(Debug)]
+pub struct BLMPop {
+    timeout: Duration,
+    keys: Vec<String>,
+    end: ListEnd,
+    count: usize,
+}
+
+impl CommandExecutor for LMPop {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        mpop(backend, &self.keys, self.end, self.count)
+            .unwrap_or(RespFrame::NullArray(RespNullArray))
+    }
+}
+
+impl CommandExecutor for BLMPop {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        mpop(backend, &self.keys, self.end, self.count)
+            .unwrap_or(RespFrame::NullArray(RespNullArray))
+    }
+}
+
+impl BLMPop {
+    pub async fn execute_blocking(
+        self,
+        backend: &Backend,
+        on_ready: impl FnOnce(&RespFrame),
+    ) -> RespFrame {
+        backend
+            .block_on(
+                &self.keys,
+                self.timeout,
+                || mpop(backend, &self.keys, self.end, self.count),
+                on_ready,
+            )
+            .await
+            .unwrap_or(RespFrame::NullArray(RespNullArray))
+    }
+}
+
+fn mpop(backend: &Backend, keys: &[String], end: ListEnd, count: usize) -> Option<RespFrame> {
+    for key in keys {
+        match backend.list_pop(key, count, end) {
+            Ok(Some(values)) if !values.is_empty() => {
+                let values = values
+                    .into_iter()
+                    .map(|v| BulkString::new(v).into())
+                    .collect::<Vec<RespFrame>>();
+                return Some(
+                    RespArray::new([
+                        BulkString::new(key.as_str()).into(),
+                        RespArray::new(values).into(),
+                    ])
+                    .into(),
+                );
+            }
+            Ok(_) => {}
+            Err(e) => return Some(e.into()),
+        }
+    }
+    None
+}
+
+// parses `numkeys key [key ...] <LEFT | RIGHT> [COUNT count]`
+fn parse_mpop_args(
+    mut args: impl Iterator<Item = RespFrame>,
+) -> Result<(Vec<String>, ListEnd, usize), CommandError> {
+    let numkeys = extract_integer(args.next())?;
+    if numkeys <= 0 {
+        return Err(CommandError::InvalidArgument(
+            "numkeys should be greater than 0".to_string(),
+        ));
+    }
+    let keys = (0..numkeys)
+        .map(|_| extract_string(args.next()))
+        .collect::<Result<Vec<String>, CommandError>>()?;
+    let end = extract_list_end(args.next())?;
+    let count = match args.next() {
+        Some(arg) => {
+            if !extract_string(Some(arg))?.eq_ignore_ascii_case("count") {
+                return Err(CommandError::InvalidArgument("syntax error".to_string()));
+            }
+            let count = extract_integer(args.next())?;
+            if count <= 0 {
+                return Err(CommandError::InvalidArgument(
+                    "count should be greater than 0".to_string(),
+                ));
+            }
+            count as usize
+        }
+        None => 1,
+    };
+    if args.next().is_some() {
+        return Err(CommandError::InvalidArgument("syntax error".to_string()));
+    }
+    Ok((keys, end, count))
+}
+
+impl TryFrom<RespArray> for LMPop {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "lmpop")?;
+        let args = extract_args(value, 1)?.into_iter();
+        let (keys, end, count) = parse_mpop_args(args)?;
+        Ok(LMPop { keys, end, count })
+    }
+}
+
+impl TryFrom<RespArray> for BLMPop {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "blmpop")?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        let timeout = extract_timeout(args.next())?;
+        let (keys, end, count) = parse_mpop_args(args)?;
+        Ok(BLMPop {
+            timeout,
+            keys,
+            end,
+            count,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bytes::Bytes;
+
+    #[test]
+    fn test_lmpop_command() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let values = ["one", "two", "three"].map(Bytes::from).to_vec();
+        backend.list_push("mylist", values, ListEnd::Right, false)?;
+        let cmd = LMPop::try_from(RespArray::new([
+            BulkString::new("lmpop").into(),
+            BulkString::new("2").into(),
+            BulkString::new("non1").into(),
+            BulkString::new("mylist").into(),
+            BulkString::new("LEFT").into(),
+            BulkString::new("COUNT").into(),
+            BulkString::new("2").into(),
+        ]))?;
+        let expected = RespArray::new([
+            BulkString::new("mylist").into(),
+            RespArray::new([BulkString::new("one").into(), BulkString::new("two").into()]).into(),
+        ]);
+        assert_eq!(cmd.execute(&backend), expected.into());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_blmpop_command() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let cmd = BLMPop::try_from(RespArray::new([
+            BulkString::new("blmpop").into(),
+            BulkString::new("0.01").into(),
+            BulkString::new("1").into(),
+            BulkString::new("mylist").into(),
+            BulkString::new("RIGHT").into(),
+        ]))?;
+        assert_eq!(cmd.count, 1);
+        assert_eq!(
+            cmd.execute_blocking(&backend, |_| {}).await,
+            RespFrame::NullArray(RespNullArray)
+        );
+        Ok(())
+    }
+}
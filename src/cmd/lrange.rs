@@ -0,0 +1,91 @@
+use crate::cmd::{
+    extract_args, extract_integer, extract_string, validate_command, CommandError, CommandExecutor,
+};
+use crate::resp::{BulkString, RespArray, RespFrame};
+use crate::Backend;
+
+// LRANGE key start stop
+// start and stop can be negative numbers indicating offsets from the end of the list
+
+// redis> RPUSH mylist "one" "two" "three"
+// (integer) 3
+// redis> LRANGE mylist -2 -1
+// 1) "two"
+// 2) "three"
+
+#[derive(Debug)]
+pub struct LRange {
+    key: String,
+    start: i64,
+    stop: i64,
+}
+
+impl CommandExecutor for LRange {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.lrange(&self.key, self.start, self.stop) {
+            Ok(values) => RespArray::new(
+                values
+                    .into_iter()
+                    .map(|v| BulkString::new(v).into())
+                    .collect::<Vec<RespFrame>>(),
+            )
+            .into(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for LRange {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "lrange")?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(LRange {
+            key: extract_string(args.next())?,
+            start: extract_integer(args.next())?,
+            stop: extract_integer(args.next())?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::backend::ListEnd;
+    use crate::resp::RespDecode;
+    use bytes::{Bytes, BytesMut};
+
+    #[test]
+    fn test_lrange_from_resp_array() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$6\r\nlrange\r\n$6\r\nmylist\r\n$2\r\n-2\r\n$2\r\n-1\r\n");
+
+        let frame = RespArray::decode(&mut buf)?;
+        let result: LRange = frame.try_into()?;
+        assert_eq!(result.key, "mylist");
+        assert_eq!(result.start, -2);
+        assert_eq!(result.stop, -1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_lrange_command() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let values = ["one", "two", "three"].map(Bytes::from).to_vec();
+        backend.list_push("mylist", values, ListEnd::Right, false)?;
+        let cmd = LRange {
+            key: "mylist".to_string(),
+            start: -2,
+            stop: -1,
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            RespArray::new([
+                BulkString::new("two").into(),
+                BulkString::new("three").into()
+            ])
+            .into()
+        );
+        Ok(())
+    }
+}
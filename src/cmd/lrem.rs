@@ -0,0 +1,65 @@
+use crate::cmd::{
+    extract_args, extract_bytes, extract_integer, extract_string, validate_command, CommandError,
+    CommandExecutor,
+};
+use crate::resp::{RespArray, RespFrame};
+use crate::Backend;
+use bytes::Bytes;
+
+// LREM key count element
+// count > 0: remove elements moving from head to tail
+// count < 0: remove elements moving from tail to head
+// count = 0: remove all elements equal to element
+
+#[derive(Debug)]
+pub struct LRem {
+    key: String,
+    count: i64,
+    value: Bytes,
+}
+
+impl CommandExecutor for LRem {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.lrem(&self.key, self.count, &self.value) {
+            Ok(n) => RespFrame::Integer(n as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for LRem {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "lrem")?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(LRem {
+            key: extract_string(args.next())?,
+            count: extract_integer(args.next())?,
+            value: extract_bytes(args.next())?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::backend::ListEnd;
+
+    #[test]
+    fn test_lrem_command() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let values = ["hello", "hello", "foo", "hello"].map(Bytes::from).to_vec();
+        backend.list_push("mylist", values, ListEnd::Right, false)?;
+        let cmd = LRem {
+            key: "mylist".to_string(),
+            count: -2,
+            value: Bytes::from("hello"),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(2));
+        assert_eq!(
+            backend.lrange("mylist", 0, -1)?,
+            vec![Bytes::from("hello"), Bytes::from("foo")]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,69 @@
+use crate::cmd::{
+    extract_args, extract_bytes, extract_integer, extract_string, validate_command, CommandError,
+    CommandExecutor, RESP_OK,
+};
+use crate::resp::{RespArray, RespFrame};
+use crate::Backend;
+use bytes::Bytes;
+
+// LSET key index element
+
+#[derive(Debug)]
+pub struct LSet {
+    key: String,
+    index: i64,
+    value: Bytes,
+}
+
+impl CommandExecutor for LSet {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.lset(&self.key, self.index, self.value) {
+            Ok(()) => RESP_OK.clone(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for LSet {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "lset")?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(LSet {
+            key: extract_string(args.next())?,
+            index: extract_integer(args.next())?,
+            value: extract_bytes(args.next())?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::backend::ListEnd;
+    use crate::resp::SimpleError;
+
+    #[test]
+    fn test_lset_command() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let cmd = LSet {
+            key: "mylist".to_string(),
+            index: 0,
+            value: Bytes::from("x"),
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            SimpleError::new("ERR no such key").into()
+        );
+
+        backend.list_push("mylist", vec![Bytes::from("a")], ListEnd::Right, false)?;
+        let cmd = LSet {
+            key: "mylist".to_string(),
+            index: -1,
+            value: Bytes::from("x"),
+        };
+        assert_eq!(cmd.execute(&backend), RESP_OK.clone());
+        assert_eq!(backend.lindex("mylist", 0)?, Some(Bytes::from("x")));
+        Ok(())
+    }
+}
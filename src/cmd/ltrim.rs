@@ -0,0 +1,62 @@
+use crate::cmd::{
+    extract_args, extract_integer, extract_string, validate_command, CommandError, CommandExecutor,
+    RESP_OK,
+};
+use crate::resp::{RespArray, RespFrame};
+use crate::Backend;
+
+// LTRIM key start stop
+
+#[derive(Debug)]
+pub struct LTrim {
+    key: String,
+    start: i64,
+    stop: i64,
+}
+
+impl CommandExecutor for LTrim {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.ltrim(&self.key, self.start, self.stop) {
+            Ok(()) => RESP_OK.clone(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for LTrim {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "ltrim")?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(LTrim {
+            key: extract_string(args.next())?,
+            start: extract_integer(args.next())?,
+            stop: extract_integer(args.next())?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::backend::ListEnd;
+    use bytes::Bytes;
+
+    #[test]
+    fn test_ltrim_command() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let values = ["one", "two", "three"].map(Bytes::from).to_vec();
+        backend.list_push("mylist", values, ListEnd::Right, false)?;
+        let cmd = LTrim {
+            key: "mylist".to_string(),
+            start: 1,
+            stop: -1,
+        };
+        assert_eq!(cmd.execute(&backend), RESP_OK.clone());
+        assert_eq!(
+            backend.lrange("mylist", 0, -1)?,
+            vec![Bytes::from("two"), Bytes::from("three")]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,507 @@
+use crate::backend::{library_callback, lua_sandbox, sha1_hex, FunctionLibrary};
+use crate::cmd::{aof_entry, Command, CommandError, CommandExecutor};
+use crate::resp::{BulkString, RespArray, RespFrame, RespNull, SimpleError, SimpleString};
+use crate::Backend;
+use bytes::Bytes;
+use mlua::{HookTriggers, IntoLuaMulti, Lua, MultiValue, Table, Value, Variadic};
+use tracing::{debug, info, warn};
+
+// redis.call raises the error replies that redis.pcall returns
+const CALL: &str = r#"
+local pcall_command = redis.pcall
+redis.call = function(...)
+    local reply = pcall_command(...)
+    if type(reply) == "table" and reply.err then
+        error(reply)
+    end
+    return reply
+end
+"#;
+
+// like redis 7, a script can't leave globals behind for the scripts that run after it
+const PROTECT_GLOBALS: &str = r#"
+setmetatable(_G, {
+    __newindex = function(_, name)
+        error("Script attempted to create global variable '" .. tostring(name) .. "'", 2)
+    end,
+})
+"#;
+
+// the registry flag of an interpreter the redis library was set up in
+const RUNTIME: &str = "runtime";
+
+// how many instructions run between two checks for SCRIPT KILL
+const KILL_CHECK_INSTRUCTIONS: u32 = 100_000;
+
+// what the redis library works with while a script runs, kept in the interpreter
+struct ScriptContext {
+    backend: Backend,
+    read_only: bool,
+    entries: Vec<RespArray>,
+}
+
+// runs a script with KEYS and ARGV set, returns its reply and the log entries of the writes it
+// made; the caller holds the locks that keep other clients out while it runs. Scripts share one
+// interpreter and are compiled once, by their SHA1
+pub fn run_script(
+    backend: &Backend,
+    body: &str,
+    keys: Vec<Bytes>,
+    args: Vec<Bytes>,
+) -> (RespFrame, Vec<RespArray>) {
+    let mut vm = backend.script_vm();
+    let lua = match script_vm(&mut vm) {
+        Ok(lua) => lua,
+        Err(e) => return (SimpleError::new(format!("ERR {}", e)).into(), Vec::new()),
+    };
+    let sha = sha1_hex(body.as_bytes());
+    run(backend, lua, false, false, |lua| {
+        let globals = lua.globals();
+        globals.raw_set("KEYS", string_sequence(lua, keys)?)?;
+        globals.raw_set("ARGV", string_sequence(lua, args)?)?;
+        let name = format!("f_{}", sha);
+        let function = match lua.named_registry_value::<Option<mlua::Function>>(&name)? {
+            Some(function) => function,
+            None => match lua.load(body).set_name("@user_script").into_function() {
+                Ok(function) => {
+                    lua.set_named_registry_value(&name, function.clone())?;
+                    function
+                }
+                Err(mlua::Error::SyntaxError { message, .. }) => {
+                    return Ok(SimpleError::new(format!(
+                        "ERR Error compiling script (new function): {}",
+                        message
+                    ))
+                    .into());
+                }
+                Err(e) => return Err(e),
+            },
+        };
+        call_protected(lua, function, (), &sha)
+    })
+}
+
+// runs a function of a library, it gets the keys and the arguments as its two parameters; a
+// read-only function can't call write commands. The library code ran once when it was loaded,
+// the callback is called in the interpreter it registered in
+pub fn run_function(
+    backend: &Backend,
+    library: &FunctionLibrary,
+    name: &str,
+    keys: Vec<Bytes>,
+    args: Vec<Bytes>,
+    read_only: bool,
+) -> (RespFrame, Vec<RespArray>) {
+    let lua = library.vm();
+    if let Err(e) = function_vm(&lua) {
+        return (SimpleError::new(format!("ERR {}", e)).into(), Vec::new());
+    }
+    run(backend, &lua, read_only, true, |lua| {
+        let callback = library_callback(lua, name)?;
+        let params = (string_sequence(lua, keys)?, string_sequence(lua, args)?);
+        call_protected(lua, callback, params, name)
+    })
+}
+
+fn script_vm(vm: &mut Option<Lua>) -> mlua::Result<&Lua> {
+    match vm {
+        Some(lua) => Ok(lua),
+        None => {
+            let lua = lua_sandbox()?;
+            runtime(&lua)?;
+            Ok(vm.insert(lua))
+        }
+    }
+}
+
+// the interpreter of a library gets the redis library on its first call
+fn function_vm(lua: &Lua) -> mlua::Result<()> {
+    if lua.named_registry_value::<bool>(RUNTIME)? {
+        return Ok(());
+    }
+    runtime(lua)?;
+    lua.set_named_registry_value(RUNTIME, true)
+}
+
+fn run(
+    backend: &Backend,
+    lua: &Lua,
+    read_only: bool,
+    function: bool,
+    body: impl FnOnce(&Lua) -> mlua::Result<RespFrame>,
+) -> (RespFrame, Vec<RespArray>) {
+    let _running = backend.start_script(function);
+    lua.set_app_data(ScriptContext {
+        backend: backend.clone(),
+        read_only,
+        entries: Vec::new(),
+    });
+    let mut reply = body(lua).unwrap_or_else(|e| SimpleError::new(format!("ERR {}", e)).into());
+    let entries = lua
+        .remove_app_data::<ScriptContext>()
+        .map(|context| context.entries)
+        .unwrap_or_default();
+    if backend.script_killed() {
+        let kill = if function {
+            "FUNCTION KILL"
+        } else {
+            "SCRIPT KILL"
+        };
+        reply = SimpleError::new(format!("ERR Script killed by user with {}...", kill)).into();
+    }
+    (reply, entries)
+}
+
+// the redis library, the check for SCRIPT KILL and the protection of the globals
+fn runtime(lua: &Lua) -> mlua::Result<()> {
+    lua.globals().set("redis", redis_table(lua)?)?;
+    lua.load(CALL).exec()?;
+    // a killed script gets an error at its next check, even from inside a pcall
+    let triggers = HookTriggers::new().every_nth_instruction(KILL_CHECK_INSTRUCTIONS);
+    lua.set_hook(triggers, |lua, _| {
+        let killed = lua
+            .app_data_ref::<ScriptContext>()
+            .is_some_and(|context| context.backend.script_killed());
+        if killed {
+            return Err(mlua::Error::RuntimeError(
+                "Script killed by user".to_string(),
+            ));
+        }
+        Ok(())
+    });
+    lua.load(PROTECT_GLOBALS).exec()
+}
+
+// an error reply raised by redis.call or error() goes to the client as it is, any other error
+// is reported along with the script that raised it
+fn call_protected<'lua>(
+    lua: &'lua Lua,
+    function: mlua::Function<'lua>,
+    params: impl IntoLuaMulti<'lua>,
+    script: &str,
+) -> mlua::Result<RespFrame> {
+    let pcall: mlua::Function = lua.globals().get("pcall")?;
+    let mut values = pcall.call::<_, MultiValue>((function, params))?.into_iter();
+    let ok = matches!(values.next(), Some(Value::Boolean(true)));
+    let value = values.next().unwrap_or(Value::Nil);
+    if ok {
+        return Ok(lua_to_resp(value));
+    }
+    let reply = match lua_to_resp(value.clone()) {
+        RespFrame::Error(e) => e.into(),
+        _ => {
+            let message = match value {
+                Value::String(s) => s.to_string_lossy().into_owned(),
+                _ => "unknown error".to_string(),
+            };
+            SimpleError::new(format!("ERR {} script: {}", message, script)).into()
+        }
+    };
+    Ok(reply)
+}
+
+fn string_sequence(lua: &Lua, items: Vec<Bytes>) -> mlua::Result<Table<'_>> {
+    let strings = items
+        .iter()
+        .map(|item| lua.create_string(item))
+        .collect::<mlua::Result<Vec<_>>>()?;
+    lua.create_sequence_from(strings)
+}
+
+fn redis_table(lua: &Lua) -> mlua::Result<Table<'_>> {
+    let redis = lua.create_table()?;
+    redis.set(
+        "pcall",
+        lua.create_function(|lua, args: Variadic<Value>| {
+            let reply = match (script_args(args), lua.app_data_mut::<ScriptContext>()) {
+                (Ok(args), Some(mut context)) => {
+                    let context = &mut *context;
+                    let (backend, read_only) = (&context.backend, context.read_only);
+                    call(backend, args, read_only, &mut context.entries)
+                }
+                (Err(e), _) => SimpleError::new(e).into(),
+                (_, None) => SimpleError::new("ERR no script is running").into(),
+            };
+            resp_to_lua(lua, reply)
+        })?,
+    )?;
+    redis.set(
+        "error_reply",
+        lua.create_function(|lua, message: mlua::String| {
+            lua.create_table_from([("err", message)])
+        })?,
+    )?;
+    redis.set(
+        "status_reply",
+        lua.create_function(|lua, message: mlua::String| lua.create_table_from([("ok", message)]))?,
+    )?;
+    redis.set(
+        "sha1hex",
+        lua.create_function(|_, data: mlua::String| Ok(sha1_hex(data.as_bytes())))?,
+    )?;
+    redis.set(
+        "log",
+        lua.create_function(|_, (level, message): (i64, mlua::String)| {
+            let message = message.to_string_lossy();
+            match level {
+                0 | 1 => debug!("[Simple-redis-server]script: {}", message),
+                2 => info!("[Simple-redis-server]script: {}", message),
+                _ => warn!("[Simple-redis-server]script: {}", message),
+            }
+            Ok(())
+        })?,
+    )?;
+    for (name, level) in [
+        ("LOG_DEBUG", 0),
+        ("LOG_VERBOSE", 1),
+        ("LOG_NOTICE", 2),
+        ("LOG_WARNING", 3),
+    ] {
+        redis.set(name, level)?;
+    }
+    Ok(redis)
+}
+
+// the arguments of redis.call, numbers are sent the way Lua prints them
+fn script_args(args: Variadic<Value>) -> Result<Vec<Bytes>, &'static str> {
+    if args.is_empty() {
+        return Err("ERR Please specify at least one argument for this redis lib call");
+    }
+    args.into_iter()
+        .map(|arg| match arg {
+            Value::String(s) => Ok(Bytes::copy_from_slice(s.as_bytes())),
+            Value::Integer(i) => Ok(Bytes::from(i.to_string())),
+            Value::Number(n) if n.fract() == 0.0 && n.abs() < 1e15 => {
+                Ok(Bytes::from((n as i64).to_string()))
+            }
+            Value::Number(n) => Ok(Bytes::from(n.to_string())),
+            _ => Err("ERR Lua redis lib command arguments must be strings or integers"),
+        })
+        .collect()
+}
+
+// runs one command of a script through the regular command path
+fn call(
+    backend: &Backend,
+    args: Vec<Bytes>,
+    read_only: bool,
+    entries: &mut Vec<RespArray>,
+) -> RespFrame {
+    let frame = RespArray::new(
+        args.into_iter()
+            .map(|arg| BulkString::from(arg).into())
+            .collect::<Vec<RespFrame>>(),
+    );
+    let logged = frame.clone();
+    let cmd = match Command::try_from(frame) {
+        Ok(cmd) => cmd,
+        Err(e) => return e.into(),
+    };
+    if !cmd.is_allowed_from_script() {
+        return SimpleError::new("ERR This Redis command is not allowed from script").into();
+    }
+    let is_write = cmd.is_write();
+    if read_only && is_write {
+        return SimpleError::new("ERR Write commands are not allowed from read-only scripts.")
+            .into();
+    }
+    if is_write && backend.is_replica() {
+        return CommandError::ReadOnly.into();
+    }
+    let reply = cmd.execute(backend);
+    if is_write {
+        backend.script_wrote();
+        backend.mark_dirty();
+        entries.extend(aof_entry(logged.into(), &reply));
+    }
+    reply
+}
+
+// the conversion rules of redis: status and error replies become tables with an `ok` or `err`
+// field, nulls become false, and the RESP3 types are seen as a RESP2 client gets them
+fn resp_to_lua(lua: &Lua, frame: RespFrame) -> mlua::Result<Value<'_>> {
+    let value = match frame {
+        RespFrame::SimpleString(s) => Value::Table(lua.create_table_from([("ok", s.0)])?),
+        RespFrame::Error(e) => Value::Table(lua.create_table_from([("err", e.0)])?),
+        RespFrame::BlobError(e) => Value::Table(lua.create_table_from([("err", e.0)])?),
+        RespFrame::Integer(i) => Value::Integer(i as mlua::Integer),
+        RespFrame::Boolean(b) => Value::Integer(b as mlua::Integer),
+        RespFrame::BulkString(s) => Value::String(lua.create_string(&s.0)?),
+        RespFrame::Double(d) => Value::String(lua.create_string(d.to_string())?),
+        RespFrame::BigNumber(n) => Value::String(lua.create_string(n.0)?),
+        RespFrame::VerbatimString(s) => Value::String(lua.create_string(s.data)?),
+        RespFrame::Null(_) | RespFrame::NullBulkString(_) | RespFrame::NullArray(_) => {
+            Value::Boolean(false)
+        }
+        RespFrame::Array(array) => sequence(lua, array.0)?,
+        RespFrame::Set(set) => sequence(lua, set.0)?,
+        RespFrame::Push(push) => sequence(lua, push.0)?,
+        RespFrame::Map(map) => sequence(
+            lua,
+            map.0
+                .into_iter()
+                .flat_map(|(key, value)| [BulkString::new(key).into(), value])
+                .collect(),
+        )?,
+        RespFrame::Attribute(attr) => resp_to_lua(lua, *attr.frame)?,
+    };
+    Ok(value)
+}
+
+fn sequence(lua: &Lua, frames: Vec<RespFrame>) -> mlua::Result<Value<'_>> {
+    let table = lua.create_table_with_capacity(frames.len(), 0)?;
+    for (i, frame) in frames.into_iter().enumerate() {
+        table.raw_set(i + 1, resp_to_lua(lua, frame)?)?;
+    }
+    Ok(Value::Table(table))
+}
+
+// numbers are truncated to integers and a table is read up to its first nil, unless it is a
+// status or an error reply
+fn lua_to_resp(value: Value) -> RespFrame {
+    match value {
+        Value::Boolean(true) => RespFrame::Integer(1),
+        Value::Integer(i) => RespFrame::Integer(i),
+        Value::Number(n) => RespFrame::Integer(n as i64),
+        Value::String(s) => BulkString::new(s.as_bytes()).into(),
+        Value::Table(table) => {
+            if let Ok(Value::String(e)) = table.raw_get("err") {
+                return SimpleError::new(e.to_string_lossy()).into();
+            }
+            if let Ok(Value::String(s)) = table.raw_get("ok") {
+                return SimpleString::new(s.to_string_lossy()).into();
+            }
+            RespArray::new(
+                table
+                    .sequence_values::<Value>()
+                    .map_while(Result::ok)
+                    .map(lua_to_resp)
+                    .collect::<Vec<RespFrame>>(),
+            )
+            .into()
+        }
+        _ => RespFrame::Null(RespNull),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn run(backend: &Backend, body: &str, keys: &[&str], args: &[&str]) -> RespFrame {
+        let bytes = |v: &[&str]| v.iter().map(|s| Bytes::from(s.to_string())).collect();
+        run_script(backend, body, bytes(keys), bytes(args)).0
+    }
+
+    #[test]
+    fn test_conversions() {
+        let backend = Backend::new();
+        assert_eq!(
+            run(&backend, "return 3.99", &[], &[]),
+            RespFrame::Integer(3)
+        );
+        assert_eq!(
+            run(&backend, "return true", &[], &[]),
+            RespFrame::Integer(1)
+        );
+        assert_eq!(
+            run(&backend, "return false", &[], &[]),
+            RespFrame::Null(RespNull)
+        );
+        assert_eq!(
+            run(
+                &backend,
+                "return {KEYS[1], ARGV[1], 2, nil, 5}",
+                &["k"],
+                &["a"]
+            ),
+            RespArray::new([
+                BulkString::new("k").into(),
+                BulkString::new("a").into(),
+                RespFrame::Integer(2),
+            ])
+            .into()
+        );
+        assert_eq!(
+            run(&backend, "return redis.status_reply('FINE')", &[], &[]),
+            SimpleString::new("FINE").into()
+        );
+        assert_eq!(
+            run(&backend, "return {err = 'MY error'}", &[], &[]),
+            SimpleError::new("MY error").into()
+        );
+        assert_eq!(
+            run(&backend, "return redis.sha1hex('')", &[], &[]),
+            BulkString::new("da39a3ee5e6b4b0d3255bfef95601890afd80709").into()
+        );
+    }
+
+    #[test]
+    fn test_redis_call() {
+        let backend = Backend::new();
+        let body = "redis.call('SET', KEYS[1], ARGV[1]) return redis.call('INCRBY', KEYS[1], 2)";
+        assert_eq!(run(&backend, body, &["k"], &["40"]), RespFrame::Integer(42));
+        assert_eq!(
+            run(&backend, "return redis.call('GET', 'nokey')", &[], &[]),
+            RespFrame::Null(RespNull)
+        );
+        assert_eq!(
+            run(&backend, "return redis.call('SET', 'k', 'v')", &[], &[]),
+            SimpleString::new("OK").into()
+        );
+        run(&backend, "redis.call('RPUSH', 'list', 'a')", &[], &[]);
+        assert_eq!(
+            run(&backend, "return redis.call('GET', 'list')", &[], &[]),
+            SimpleError::new("WRONGTYPE Operation against a key holding the wrong kind of value")
+                .into()
+        );
+        let body = "local reply = redis.pcall('GET', 'list') return type(reply.err)";
+        assert_eq!(
+            run(&backend, body, &[], &[]),
+            BulkString::new("string").into()
+        );
+        assert_eq!(
+            run(&backend, "return redis.call('SUBSCRIBE', 'news')", &[], &[]),
+            SimpleError::new("ERR This Redis command is not allowed from script").into()
+        );
+    }
+
+    #[test]
+    fn test_script_errors() {
+        let backend = Backend::new();
+        let RespFrame::Error(e) = run(&backend, "return (", &[], &[]) else {
+            panic!("expected an error");
+        };
+        assert!(e.starts_with("ERR Error compiling script (new function): "));
+        let RespFrame::Error(e) = run(&backend, "return nil + 1", &[], &[]) else {
+            panic!("expected an error");
+        };
+        assert!(e.starts_with("ERR user_script:1: attempt to perform arithmetic"));
+        // no way out of the sandbox
+        let RespFrame::Error(e) = run(&backend, "return os.time()", &[], &[]) else {
+            panic!("expected an error");
+        };
+        assert!(e.contains("attempt to index global 'os'"));
+    }
+
+    #[test]
+    fn test_scripts_share_one_interpreter() -> mlua::Result<()> {
+        let backend = Backend::new();
+        run(&backend, "return 1", &[], &[]);
+        let name = format!("f_{}", sha1_hex(b"return 1"));
+        let vm = backend.script_vm();
+        let lua = vm.as_ref().expect("the interpreter is kept");
+        let compiled = lua.named_registry_value::<Option<mlua::Function>>(&name)?;
+        assert!(compiled.is_some());
+        drop(compiled);
+        drop(vm);
+        // nothing is left behind for the next script
+        let RespFrame::Error(e) = run(&backend, "x = 1", &[], &[]) else {
+            panic!("expected an error");
+        };
+        assert!(e.contains("Script attempted to create global variable 'x'"));
+        backend.script_flush();
+        assert!(backend.script_vm().is_none());
+        Ok(())
+    }
+}
@@ -0,0 +1,62 @@
+use crate::cmd::{extract_args, extract_string, validate_command, CommandError, CommandExecutor};
+use crate::resp::{BulkString, RespArray, RespFrame, RespNull};
+use crate::Backend;
+
+// MGET key [key ...]
+// returns the values of all the keys, nil for a missing key or a key that does not hold a string
+
+#[derive(Debug)]
+pub struct MGet {
+    keys: Vec<String>,
+}
+
+impl CommandExecutor for MGet {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let values = self
+            .keys
+            .iter()
+            .map(|key| match backend.get(key) {
+                Ok(Some(value)) => BulkString::new(value).into(),
+                _ => RespFrame::Null(RespNull),
+            })
+            .collect::<Vec<RespFrame>>();
+        RespArray::new(values).into()
+    }
+}
+
+impl TryFrom<RespArray> for MGet {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "mget")?;
+        let keys = extract_args(value, 1)?
+            .into_iter()
+            .map(|arg| extract_string(Some(arg)))
+            .collect::<Result<Vec<String>, CommandError>>()?;
+        Ok(MGet { keys })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_mget_command() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        backend.set("key1".to_string(), "Hello");
+        backend.insert_set("set".to_string(), vec!["a".to_string()])?;
+        let cmd = MGet {
+            keys: vec!["key1".to_string(), "set".to_string(), "none".to_string()],
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            RespArray::new([
+                BulkString::new("Hello").into(),
+                RespFrame::Null(RespNull),
+                RespFrame::Null(RespNull)
+            ])
+            .into()
+        );
+        Ok(())
+    }
+}
@@ -1,100 +1,367 @@
+mod aof;
+mod append;
+mod bgrewriteaof;
+mod blmove;
+mod blpop;
+mod cluster;
 mod command;
+mod copy;
+mod del;
 mod echo;
+mod eval;
+mod exists;
+mod expire;
+mod fcall;
+mod function;
 mod get;
+mod getdel;
+mod getex;
+mod getrange;
+mod hdel;
+mod hello;
+mod hexists;
 mod hget;
 mod hgetall;
+mod hincrby;
+mod hkeys;
+mod hlen;
 mod hmget;
+mod hrandfield;
 mod hset;
+mod hsetnx;
+mod hstrlen;
+mod incr;
+mod incrbyfloat;
+mod key_type;
+mod lindex;
+mod linsert;
+mod llen;
+mod lmove;
+mod lmpop;
+mod lrange;
+mod lrem;
+mod lset;
+mod ltrim;
+mod lua;
+mod mget;
+mod mset;
+mod multi;
+mod persist;
+mod ping;
+mod pop;
+mod psync;
+mod publish;
+mod pubsub;
+mod push;
+mod rename;
+mod replicaof;
 mod sadd;
+mod save;
+mod scard;
+mod script;
 mod set;
+mod set_ops;
+mod setrange;
 mod sismember;
+mod smembers;
+mod spop;
+mod srem;
+mod strlen;
+mod subscribe;
+mod touch;
+mod ttl;
+mod wait;
+mod watch;
+mod zadd;
+mod zcard;
+mod zincrby;
+mod zrange;
+mod zrank;
+mod zrem;
+mod zscore;
 
 use crate::backend;
-use crate::backend::Backend;
-pub use crate::cmd::command::Command;
+pub use crate::cmd::command::{command_keys, Command};
 pub use crate::cmd::{
-    echo::Echo, get::Get, hget::HGet, hgetall::HGetAll, hmget::HMGet, hset::HSet, sadd::SAdd,
-    set::Set, sismember::SisMember,
+    aof::{aof_entry, load_aof, propagate_transaction},
+    append::Append,
+    bgrewriteaof::BgRewriteAof,
+    blmove::BLMove,
+    blpop::{BLPop, BRPop},
+    cluster::{Asking, Cluster},
+    copy::Copy,
+    del::{Del, Unlink},
+    echo::Echo,
+    eval::{Eval, EvalSha},
+    exists::Exists,
+    expire::{Expire, ExpireAt, PExpire, PExpireAt},
+    fcall::{FCall, FCallRo},
+    function::Function,
+    get::Get,
+    getdel::GetDel,
+    getex::GetEx,
+    getrange::GetRange,
+    hdel::HDel,
+    hello::Hello,
+    hexists::HExists,
+    hget::HGet,
+    hgetall::HGetAll,
+    hincrby::{HIncrBy, HIncrByFloat},
+    hkeys::{HKeys, HVals},
+    hlen::HLen,
+    hmget::HMGet,
+    hrandfield::HRandField,
+    hset::HSet,
+    hsetnx::HSetNx,
+    hstrlen::HStrLen,
+    incr::{Decr, DecrBy, Incr, IncrBy},
+    incrbyfloat::IncrByFloat,
+    key_type::Type,
+    lindex::LIndex,
+    linsert::LInsert,
+    llen::LLen,
+    lmove::LMove,
+    lmpop::{BLMPop, LMPop},
+    lrange::LRange,
+    lrem::LRem,
+    lset::LSet,
+    ltrim::LTrim,
+    lua::{run_function, run_script},
+    mget::MGet,
+    mset::{MSet, MSetNx},
+    multi::{Discard, Exec, Multi, MultiState},
+    persist::Persist,
+    ping::Ping,
+    pop::{LPop, RPop},
+    psync::{PSync, ReplConf},
+    publish::{Publish, SPublish},
+    pubsub::PubSub,
+    push::{LPush, LPushX, RPush, RPushX},
+    rename::{Rename, RenameNx},
+    replicaof::ReplicaOf,
+    sadd::SAdd,
+    save::{BgSave, LastSave, Save},
+    scard::SCard,
+    script::Script,
+    set::Set,
+    set_ops::{SDiff, SDiffStore, SInter, SInterStore, SUnion, SUnionStore},
+    setrange::SetRange,
+    sismember::SisMember,
+    smembers::SMembers,
+    spop::{SPop, SRandMember},
+    srem::SRem,
+    strlen::StrLen,
+    subscribe::{PSubscribe, PUnsubscribe, SSubscribe, SUnsubscribe, Subscribe, Unsubscribe},
+    touch::Touch,
+    ttl::{PTtl, Ttl},
+    wait::{Wait, WaitAof},
+    watch::{Unwatch, Watch},
+    zadd::ZAdd,
+    zcard::ZCard,
+    zincrby::ZIncrBy,
+    zrange::{ZRange, ZRangeByScore},
+    zrank::{ZRank, ZRevRank},
+    zrem::ZRem,
+    zscore::ZScore,
 };
-use crate::resp::{RespArray, RespError, RespFrame, SimpleString};
+use crate::resp::{RespArray, RespError, RespFrame, SimpleError, SimpleString};
+use bytes::Bytes;
 use enum_dispatch::enum_dispatch;
 use lazy_static::lazy_static;
+use std::time::Duration;
 use thiserror::Error;
 //also can use onecell
 lazy_static! {
     static ref RESP_OK: RespFrame = SimpleString::new("OK").into();
 }
 
+// the messages are the error replies sent back to the client
 #[derive(Error, Debug)]
 pub enum CommandError {
-    #[error("Invalid command: {0}")]
+    #[error("ERR {0}")]
     InvalidCommand(String),
-    #[error("Invalid argument: {0}")]
+    #[error("ERR {0}")]
     InvalidArgument(String),
+    #[error("ERR unknown command '{0}', with args beginning with: {1}")]
+    UnknownCommand(String, String),
+    #[error("ERR wrong number of arguments for '{0}' command")]
+    WrongArity(String),
+    #[error("ERR Can't execute '{0}': only (P|S)SUBSCRIBE / (P|S)UNSUBSCRIBE / PING / QUIT / RESET are allowed in this context")]
+    NotAllowedWhenSubscribed(String),
+    #[error("CROSSSLOT Keys in request don't hash to the same slot")]
+    CrossSlot,
+    #[error("READONLY You can't write against a read only replica.")]
+    ReadOnly,
+    #[error("MOVED {0} {1}")]
+    Moved(u16, String),
+    #[error("ASK {0} {1}")]
+    Ask(u16, String),
+    #[error("CLUSTERDOWN Hash slot not served")]
+    ClusterDown,
 
-    #[error("{0}")]
+    #[error("ERR {0}")]
     RespError(#[from] RespError),
-    #[error("Utf8 error: {0}")]
+    #[error("ERR invalid UTF-8 in argument: {0}")]
     Utf8Error(#[from] std::string::FromUtf8Error),
 }
-#[enum_dispatch]
-pub trait CommandExecutor {
-    fn execute(self, backend: &backend::Backend) -> RespFrame;
-}
-
-#[derive(Debug)]
-pub struct Unrecognized;
 
-impl CommandExecutor for Unrecognized {
-    fn execute(self, _: &Backend) -> RespFrame {
-        RESP_OK.clone()
+impl From<backend::ClusterRedirect> for CommandError {
+    fn from(redirect: backend::ClusterRedirect) -> Self {
+        match redirect {
+            backend::ClusterRedirect::CrossSlot => CommandError::CrossSlot,
+            backend::ClusterRedirect::Moved(slot, addr) => CommandError::Moved(slot, addr),
+            backend::ClusterRedirect::Ask(slot, addr) => CommandError::Ask(slot, addr),
+            backend::ClusterRedirect::Down => CommandError::ClusterDown,
+        }
     }
 }
 
-fn validate_command(
-    value: &RespArray,
-    names: &[&'static str],
-    n_args: usize,
-) -> Result<(), CommandError> {
-    if value.len() < n_args + names.len() {
-        return Err(CommandError::InvalidArgument(format!(
-            "{} command must have exactly {} argument",
-            names.join(" "),
-            n_args
-        )));
+impl From<CommandError> for RespFrame {
+    fn from(e: CommandError) -> Self {
+        SimpleError::new(e.to_string()).into()
     }
+}
+#[enum_dispatch]
+pub trait CommandExecutor {
+    fn execute(self, backend: &backend::Backend) -> RespFrame;
+}
 
-    for (i, name) in names.iter().enumerate() {
-        let name = name.to_lowercase();
-        match value[i] {
-            RespFrame::BulkString(ref cmd) => {
-                if cmd.0.to_ascii_lowercase() != name.as_bytes() {
-                    return Err(CommandError::InvalidCommand(format!(
-                        "Invalid command: expected {}, got {}",
-                        name,
-                        String::from_utf8_lossy(cmd.as_ref())
-                    )));
-                }
-            }
-            _ => {
-                return Err(CommandError::InvalidCommand(
-                    "Command must have a BulkString as the first argument".to_string(),
-                ))
+// checks the name of the command and its number of arguments against the arity table
+fn validate_command(value: &RespArray, name: &'static str) -> Result<(), CommandError> {
+    let name = name.to_ascii_lowercase();
+    match value.first() {
+        Some(RespFrame::BulkString(cmd)) => {
+            if cmd.0.to_ascii_lowercase() != name.as_bytes() {
+                return Err(CommandError::InvalidCommand(format!(
+                    "Invalid command: expected {}, got {}",
+                    name,
+                    String::from_utf8_lossy(cmd.as_ref())
+                )));
             }
         }
+        _ => {
+            return Err(CommandError::InvalidCommand(
+                "Command must have a BulkString as the first argument".to_string(),
+            ))
+        }
+    }
+    if !arity(name.as_bytes()).is_some_and(|arity| arity_matches(arity, value.len())) {
+        return Err(CommandError::WrongArity(name));
     }
     Ok(())
 }
 
+// arity of every supported command as redis defines it, counting the command name:
+// a positive arity is the exact number of arguments, a negative one the minimum
+fn arity(name: &[u8]) -> Option<i64> {
+    let arity = match name {
+        b"get" | b"hgetall" | b"echo" | b"ttl" | b"pttl" | b"persist" | b"type" | b"llen"
+        | b"zcard" | b"smembers" | b"scard" | b"hlen" | b"hkeys" | b"hvals" | b"strlen"
+        | b"incr" | b"decr" | b"getdel" => 2,
+        b"hget" | b"sismember" | b"rename" | b"renamenx" | b"lindex" | b"zscore" | b"hexists"
+        | b"hstrlen" | b"append" | b"incrby" | b"decrby" | b"incrbyfloat" | b"publish"
+        | b"spublish" | b"replicaof" | b"wait" => 3,
+        b"lrange" | b"lset" | b"lrem" | b"ltrim" | b"zincrby" | b"hincrby" | b"hincrbyfloat"
+        | b"hsetnx" | b"getrange" | b"setrange" | b"waitaof" => 4,
+        b"linsert" | b"lmove" => 5,
+        b"blmove" => 6,
+        b"save" | b"lastsave" | b"bgrewriteaof" | b"multi" | b"exec" | b"discard" | b"unwatch"
+        | b"asking" => 1,
+        b"bgsave" | b"hello" | b"ping" | b"replconf" | b"unsubscribe" | b"punsubscribe"
+        | b"sunsubscribe" => -1,
+        b"del" | b"unlink" | b"exists" | b"touch" | b"lpop" | b"rpop" | b"spop"
+        | b"srandmember" | b"sinter" | b"sunion" | b"sdiff" | b"hrandfield" | b"mget"
+        | b"getex" | b"subscribe" | b"psubscribe" | b"ssubscribe" | b"pubsub" | b"watch"
+        | b"script" | b"function" | b"cluster" => -2,
+        b"set" | b"hmget" | b"sadd" | b"expire" | b"pexpire" | b"expireat" | b"pexpireat"
+        | b"copy" | b"lpush" | b"rpush" | b"lpushx" | b"rpushx" | b"blpop" | b"brpop"
+        | b"zrank" | b"zrevrank" | b"zrem" | b"srem" | b"sinterstore" | b"sunionstore"
+        | b"sdiffstore" | b"hdel" | b"mset" | b"msetnx" | b"eval" | b"evalsha" | b"fcall"
+        | b"fcall_ro" | b"psync" => -3,
+        b"hset" | b"lmpop" | b"zadd" | b"zrange" | b"zrangebyscore" => -4,
+        b"blmpop" => -5,
+        _ => return None,
+    };
+    Some(arity)
+}
+
+fn arity_matches(arity: i64, len: usize) -> bool {
+    let len = len as i64;
+    if arity >= 0 {
+        len == arity
+    } else {
+        len >= -arity
+    }
+}
+
 fn extract_args(value: RespArray, start: usize) -> Result<Vec<RespFrame>, CommandError> {
     Ok(value.0.into_iter().skip(start).collect::<Vec<RespFrame>>())
 }
 
+fn extract_string(value: Option<RespFrame>) -> Result<String, CommandError> {
+    match value {
+        Some(RespFrame::BulkString(s)) => Ok(String::from_utf8(s.0.to_vec())?),
+        _ => Err(CommandError::InvalidArgument(
+            "Invalid argument, expect a BulkString".to_string(),
+        )),
+    }
+}
+
+fn extract_bytes(value: Option<RespFrame>) -> Result<Bytes, CommandError> {
+    match value {
+        Some(RespFrame::BulkString(s)) => Ok(s.0),
+        _ => Err(CommandError::InvalidArgument(
+            "Invalid argument, expect a BulkString".to_string(),
+        )),
+    }
+}
+
+fn extract_integer(value: Option<RespFrame>) -> Result<i64, CommandError> {
+    extract_string(value)?.parse::<i64>().map_err(|_| {
+        CommandError::InvalidArgument("value is not an integer or out of range".to_string())
+    })
+}
+
+fn extract_float(value: Option<RespFrame>) -> Result<f64, CommandError> {
+    extract_string(value)?
+        .parse::<f64>()
+        .ok()
+        .filter(|f| !f.is_nan())
+        .ok_or_else(|| CommandError::InvalidArgument("value is not a valid float".to_string()))
+}
+
+// timeout of blocking commands, in seconds with decimals, 0 blocks forever
+fn extract_timeout(value: Option<RespFrame>) -> Result<Duration, CommandError> {
+    let timeout = extract_string(value)?
+        .parse::<f64>()
+        .ok()
+        .filter(|t| t.is_finite())
+        .ok_or_else(|| {
+            CommandError::InvalidArgument("timeout is not a float or out of range".to_string())
+        })?;
+    if timeout < 0.0 {
+        return Err(CommandError::InvalidArgument(
+            "timeout is negative".to_string(),
+        ));
+    }
+    Ok(Duration::from_secs_f64(timeout))
+}
+
+// a request the way a client sends it, for the tests of the commands
+#[cfg(test)]
+pub(crate) fn request(args: &[impl AsRef<[u8]>]) -> RespArray {
+    RespArray::new(
+        args.iter()
+            .map(|arg| crate::resp::BulkString::new(arg.as_ref()).into())
+            .collect::<Vec<RespFrame>>(),
+    )
+}
+
 #[cfg(test)]
 mod test {
     use crate::cmd::command::Command;
-    use crate::cmd::CommandExecutor;
-    use crate::resp::{RespArray, RespDecode, RespFrame, RespNull};
+    use crate::cmd::{request, CommandError, CommandExecutor, LastSave};
+    use crate::resp::{BulkString, RespArray, RespDecode, RespFrame, RespNull, SimpleError};
     use crate::Backend;
     use anyhow::Result;
     use bytes::BytesMut;
@@ -115,4 +382,42 @@ mod test {
 
         Ok(())
     }
+
+    fn error_reply(args: &[&str]) -> RespFrame {
+        Command::try_from(request(args)).unwrap_err().into()
+    }
+
+    #[test]
+    fn test_command_error_reply() {
+        assert_eq!(
+            error_reply(&["foo", "a", "b"]),
+            SimpleError::new("ERR unknown command 'foo', with args beginning with: 'a' 'b' ")
+                .into()
+        );
+        assert_eq!(
+            error_reply(&["GET"]),
+            SimpleError::new("ERR wrong number of arguments for 'get' command").into()
+        );
+        assert_eq!(
+            error_reply(&["get", "a", "b"]),
+            SimpleError::new("ERR wrong number of arguments for 'get' command").into()
+        );
+        assert_eq!(
+            error_reply(&["set", "a"]),
+            SimpleError::new("ERR wrong number of arguments for 'set' command").into()
+        );
+        assert_eq!(
+            error_reply(&["incrby", "a", "x"]),
+            SimpleError::new("ERR value is not an integer or out of range").into()
+        );
+        // the parser of a command checks the arity of the same table
+        let frame = RespArray::new(vec![
+            BulkString::new("lastsave").into(),
+            BulkString::new("x").into(),
+        ]);
+        assert!(matches!(
+            LastSave::try_from(frame),
+            Err(CommandError::WrongArity(name)) if name == "lastsave"
+        ));
+    }
 }
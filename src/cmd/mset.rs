@@ -0,0 +1,115 @@
+use crate::cmd::{
+    extract_args, extract_bytes, extract_string, validate_command, CommandError, CommandExecutor,
+    RESP_OK,
+};
+use crate::resp::{RespArray, RespFrame};
+use crate::Backend;
+use bytes::Bytes;
+
+// MSET key value [key value ...]
+// MSETNX key value [key value ...]
+// MSETNX sets nothing if any of the keys exists, and returns 1 if all the keys were set, 0 otherwise
+
+#[derive(Debug)]
+pub struct MSet {
+    pairs: Vec<(String, Bytes)>,
+}
+
+#[derive(Debug)]
+pub struct MSetNx {
+    pairs: Vec<(String, Bytes)>,
+}
+
+impl CommandExecutor for MSet {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        backend.mset(self.pairs, false);
+        RESP_OK.clone()
+    }
+}
+
+impl CommandExecutor for MSetNx {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.mset(self.pairs, true) as i64)
+    }
+}
+
+fn parse_pairs(value: RespArray, name: &'static str) -> Result<Vec<(String, Bytes)>, CommandError> {
+    validate_command(&value, name)?;
+    if value.len().is_multiple_of(2) {
+        return Err(CommandError::WrongArity(name.to_string()));
+    }
+    let mut args = extract_args(value, 1)?.into_iter();
+    let mut pairs = Vec::with_capacity(args.len() / 2);
+    while let Some(key) = args.next() {
+        pairs.push((extract_string(Some(key))?, extract_bytes(args.next())?));
+    }
+    Ok(pairs)
+}
+
+impl TryFrom<RespArray> for MSet {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let pairs = parse_pairs(value, "mset")?;
+        Ok(MSet { pairs })
+    }
+}
+
+impl TryFrom<RespArray> for MSetNx {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let pairs = parse_pairs(value, "msetnx")?;
+        Ok(MSetNx { pairs })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::resp::RespDecode;
+    use bytes::BytesMut;
+
+    #[test]
+    fn test_mset_from_resp_array() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*5\r\n$4\r\nmset\r\n$4\r\nkey1\r\n$5\r\nHello\r\n$4\r\nkey2\r\n$5\r\nWorld\r\n",
+        );
+
+        let frame = RespArray::decode(&mut buf)?;
+        let result: MSet = frame.try_into()?;
+        assert_eq!(
+            result.pairs,
+            vec![
+                ("key1".to_string(), Bytes::from("Hello")),
+                ("key2".to_string(), Bytes::from("World"))
+            ]
+        );
+
+        buf.extend_from_slice(b"*4\r\n$4\r\nmset\r\n$4\r\nkey1\r\n$5\r\nHello\r\n$4\r\nkey2\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(MSet::try_from(frame).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_msetnx_command() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let cmd = MSetNx {
+            pairs: vec![
+                ("key1".to_string(), Bytes::from("Hello")),
+                ("key2".to_string(), Bytes::from("there")),
+            ],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        let cmd = MSetNx {
+            pairs: vec![
+                ("key2".to_string(), Bytes::from("new")),
+                ("key3".to_string(), Bytes::from("world")),
+            ],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+        assert!(!backend.exists("key3"));
+        assert_eq!(backend.get("key2")?, Some(Bytes::from("there")));
+        Ok(())
+    }
+}
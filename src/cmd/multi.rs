@@ -0,0 +1,294 @@
+use crate::cmd::{
+    aof_entry, propagate_transaction, validate_command, Command, CommandError, CommandExecutor,
+    RESP_OK,
+};
+use crate::network::ClientState;
+use crate::resp::{RespArray, RespFrame, RespNullArray, SimpleError, SimpleString};
+use crate::Backend;
+
+// MULTI
+// EXEC
+// DISCARD
+// after MULTI the commands of the client are queued, EXEC runs them with no other client
+// interleaving and replies with all their replies, DISCARD drops them. EXEC replies with a null
+// array when a watched key was written in between, and with an error when a command could not
+// be queued
+
+// redis> MULTI
+// OK
+// redis> INCR counter
+// QUEUED
+// redis> EXEC
+// 1) (integer) 1
+
+#[derive(Debug)]
+pub struct Multi;
+
+#[derive(Debug)]
+pub struct Exec;
+
+#[derive(Debug)]
+pub struct Discard;
+
+// commands queued by a client since MULTI
+#[derive(Debug, Default)]
+pub struct MultiState {
+    // each command with the request to log if it writes
+    commands: Vec<(Command, Option<RespFrame>)>,
+    // a command was refused while queueing, EXEC discards the transaction
+    aborted: bool,
+}
+
+impl MultiState {
+    pub fn queue(&mut self, cmd: Command, logged: Option<RespFrame>) -> RespFrame {
+        // their replies would not fit in the one of EXEC
+        if cmd.is_subscription() || matches!(cmd, Command::PSync(_)) {
+            self.aborted = true;
+            return SimpleError::new("ERR Command not allowed inside a transaction").into();
+        }
+        self.commands.push((cmd, logged));
+        SimpleString::new("QUEUED").into()
+    }
+
+    pub fn abort(&mut self) {
+        self.aborted = true;
+    }
+}
+
+impl CommandExecutor for Multi {
+    fn execute(self, _backend: &Backend) -> RespFrame {
+        self.execute_for(&mut ClientState::default())
+    }
+}
+
+impl CommandExecutor for Exec {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        self.execute_for(&mut ClientState::default(), backend)
+    }
+}
+
+impl CommandExecutor for Discard {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        self.execute_for(&mut ClientState::default(), backend)
+    }
+}
+
+impl Multi {
+    pub fn execute_for(self, client: &mut ClientState) -> RespFrame {
+        if client.multi.is_some() {
+            return SimpleError::new("ERR MULTI calls can not be nested").into();
+        }
+        client.multi = Some(MultiState::default());
+        RESP_OK.clone()
+    }
+}
+
+impl Exec {
+    pub fn execute_for(self, client: &mut ClientState, backend: &Backend) -> RespFrame {
+        let Some(multi) = client.multi.take() else {
+            return SimpleError::new("ERR EXEC without MULTI").into();
+        };
+        let watched = std::mem::take(&mut client.watched);
+        let reply = run_transaction(multi, &watched, client, backend);
+        for (key, _) in watched {
+            backend.unwatch(&key);
+        }
+        reply
+    }
+}
+
+impl Discard {
+    pub fn execute_for(self, client: &mut ClientState, backend: &Backend) -> RespFrame {
+        if client.multi.take().is_none() {
+            return SimpleError::new("ERR DISCARD without MULTI").into();
+        }
+        client.unwatch_all(backend);
+        RESP_OK.clone()
+    }
+}
+
+// the caller holds the transaction lock exclusively, so nothing interleaves with the commands
+fn run_transaction(
+    multi: MultiState,
+    watched: &[(String, u64)],
+    client: &mut ClientState,
+    backend: &Backend,
+) -> RespFrame {
+    if multi.aborted {
+        return SimpleError::new("EXECABORT Transaction discarded because of previous errors.")
+            .into();
+    }
+    if watched
+        .iter()
+        .any(|(key, version)| backend.watched_key_changed(key, *version))
+    {
+        return RespFrame::NullArray(RespNullArray);
+    }
+    let _barrier = backend.write_barrier();
+    let mut entries = Vec::new();
+    let replies = multi
+        .commands
+        .into_iter()
+        .map(|(cmd, logged)| {
+            let is_write = cmd.is_write();
+            let reply = match cmd {
+                // the writes of a script are logged inside the transaction
+                Command::Eval(eval) => {
+                    let (reply, writes) = eval.run(backend);
+                    entries.extend(writes);
+                    reply
+                }
+                Command::EvalSha(eval) => {
+                    let (reply, writes) = eval.run(backend);
+                    entries.extend(writes);
+                    reply
+                }
+                Command::FCall(fcall) => {
+                    let (reply, writes) = fcall.run(backend);
+                    entries.extend(writes);
+                    reply
+                }
+                Command::FCallRo(fcall) => fcall.run(backend).0,
+                Command::Hello(hello) => hello.execute_for(client, backend),
+                Command::Ping(ping) => ping.execute_for(client),
+                cmd => cmd.execute(backend),
+            };
+            if is_write {
+                backend.mark_dirty();
+                entries.extend(logged.and_then(|frame| aof_entry(frame, &reply)));
+            }
+            reply
+        })
+        .collect::<Vec<RespFrame>>();
+    propagate_transaction(backend, entries);
+    RespArray::new(replies).into()
+}
+
+impl TryFrom<RespArray> for Multi {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "multi")?;
+        Ok(Multi)
+    }
+}
+
+impl TryFrom<RespArray> for Exec {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "exec")?;
+        Ok(Exec)
+    }
+}
+
+impl TryFrom<RespArray> for Discard {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "discard")?;
+        Ok(Discard)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cmd::request;
+    use crate::cmd::Watch;
+    use crate::resp::BulkString;
+    use anyhow::Result;
+
+    fn command(args: &[&str]) -> Result<Command> {
+        Ok(Command::try_from(request(args))?)
+    }
+
+    fn queue(client: &mut ClientState, args: &[&str]) -> Result<RespFrame> {
+        let cmd = command(args)?;
+        Ok(client.multi.as_mut().unwrap().queue(cmd, None))
+    }
+
+    #[test]
+    fn test_multi_exec() -> Result<()> {
+        let backend = Backend::new();
+        let mut client = ClientState::default();
+
+        assert_eq!(
+            Exec.execute_for(&mut client, &backend),
+            SimpleError::new("ERR EXEC without MULTI").into()
+        );
+        assert_eq!(Multi.execute_for(&mut client), RESP_OK.clone());
+        assert_eq!(
+            Multi.execute_for(&mut client),
+            SimpleError::new("ERR MULTI calls can not be nested").into()
+        );
+        assert_eq!(
+            queue(&mut client, &["set", "k", "1"])?,
+            SimpleString::new("QUEUED").into()
+        );
+        queue(&mut client, &["incr", "k"])?;
+        queue(&mut client, &["lpush", "k", "x"])?;
+        // nothing runs before EXEC
+        assert_eq!(backend.get("k"), Ok(None));
+        assert_eq!(
+            Exec.execute_for(&mut client, &backend),
+            RespArray::new([
+                RESP_OK.clone(),
+                RespFrame::Integer(2),
+                SimpleError::new(
+                    "WRONGTYPE Operation against a key holding the wrong kind of value"
+                )
+                .into(),
+            ])
+            .into()
+        );
+        assert!(client.multi.is_none());
+
+        Multi.execute_for(&mut client);
+        queue(&mut client, &["set", "k", "3"])?;
+        assert_eq!(Discard.execute_for(&mut client, &backend), RESP_OK.clone());
+        assert_eq!(backend.get("k"), Ok(Some("2".into())));
+        assert_eq!(
+            Discard.execute_for(&mut client, &backend),
+            SimpleError::new("ERR DISCARD without MULTI").into()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_exec_aborted() -> Result<()> {
+        let backend = Backend::new();
+        let mut client = ClientState::default();
+
+        // a command refused while queueing discards the whole transaction
+        Multi.execute_for(&mut client);
+        queue(&mut client, &["set", "k", "1"])?;
+        queue(&mut client, &["subscribe", "news"])?;
+        assert_eq!(
+            Exec.execute_for(&mut client, &backend),
+            SimpleError::new("EXECABORT Transaction discarded because of previous errors.").into()
+        );
+        assert_eq!(backend.get("k"), Ok(None));
+
+        // a watched key written by someone else makes EXEC fail
+        let cmd = Watch::try_from(RespArray::new([
+            BulkString::new("watch").into(),
+            BulkString::new("k").into(),
+        ]))?;
+        assert_eq!(cmd.execute_for(&mut client, &backend), RESP_OK.clone());
+        Multi.execute_for(&mut client);
+        queue(&mut client, &["set", "k", "1"])?;
+        backend.set("k".to_string(), "other");
+        assert_eq!(
+            Exec.execute_for(&mut client, &backend),
+            RespFrame::NullArray(RespNullArray)
+        );
+        assert_eq!(backend.get("k"), Ok(Some("other".into())));
+        // EXEC drops the watches, the next transaction goes through
+        assert!(client.watched.is_empty());
+        Multi.execute_for(&mut client);
+        queue(&mut client, &["set", "k", "1"])?;
+        assert_eq!(
+            Exec.execute_for(&mut client, &backend),
+            RespArray::new([RESP_OK.clone()]).into()
+        );
+        Ok(())
+    }
+}
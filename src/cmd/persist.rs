@@ -0,0 +1,51 @@
+use crate::cmd::{extract_args, extract_string, validate_command, CommandError, CommandExecutor};
+use crate::resp::{RespArray, RespFrame};
+use crate::Backend;
+
+// PERSIST key
+// returns 1 if the timeout was removed, 0 if the key does not exist or has no timeout
+
+#[derive(Debug)]
+pub struct Persist {
+    key: String,
+}
+
+impl CommandExecutor for Persist {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.persist(&self.key) {
+            true => RespFrame::Integer(1),
+            false => RespFrame::Integer(0),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Persist {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "persist")?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(Persist {
+            key: extract_string(args.next())?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::backend::now_ms;
+
+    #[test]
+    fn test_persist_command() {
+        let backend = Backend::new();
+        backend.set("hello".to_string(), "world");
+        let persist = || Persist {
+            key: "hello".to_string(),
+        };
+        assert_eq!(persist().execute(&backend), RespFrame::Integer(0));
+
+        backend.expire_at("hello", now_ms() + 10_000);
+        assert_eq!(persist().execute(&backend), RespFrame::Integer(1));
+        assert_eq!(backend.expire_time("hello"), Some(None));
+    }
+}
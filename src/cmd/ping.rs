@@ -0,0 +1,68 @@
+use crate::cmd::{extract_args, validate_command, CommandError, CommandExecutor};
+use crate::network::ClientState;
+use crate::resp::{BulkString, RespArray, RespFrame, SimpleString};
+use crate::Backend;
+
+// PING [message]
+
+#[derive(Debug)]
+pub struct Ping {
+    message: Option<BulkString>,
+}
+
+impl CommandExecutor for Ping {
+    fn execute(self, _backend: &Backend) -> RespFrame {
+        self.execute_for(&ClientState::default())
+    }
+}
+
+impl Ping {
+    // a subscribed RESP2 client gets the reply in the shape of a message, to tell it apart from
+    // the replies of the subscription commands
+    pub fn execute_for(self, client: &ClientState) -> RespFrame {
+        match (client.in_subscribe_mode(), self.message) {
+            (true, message) => RespArray::new([
+                BulkString::new("pong").into(),
+                message.unwrap_or_else(|| BulkString::new("")).into(),
+            ])
+            .into(),
+            (false, Some(message)) => message.into(),
+            (false, None) => SimpleString::new("PONG").into(),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Ping {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "ping")?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next()) {
+            (None, _) => Ok(Ping { message: None }),
+            (Some(RespFrame::BulkString(message)), None) => Ok(Ping {
+                message: Some(message),
+            }),
+            _ => Err(CommandError::WrongArity("ping".to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use anyhow::Result;
+
+    #[test]
+    fn test_ping_command() -> Result<()> {
+        let backend = Backend::new();
+        let cmd = Ping::try_from(RespArray::new([BulkString::new("ping").into()]))?;
+        assert_eq!(cmd.execute(&backend), SimpleString::new("PONG").into());
+
+        let cmd = Ping::try_from(RespArray::new([
+            BulkString::new("ping").into(),
+            BulkString::new("hi").into(),
+        ]))?;
+        assert_eq!(cmd.execute(&backend), BulkString::new("hi").into());
+        Ok(())
+    }
+}
@@ -0,0 +1,151 @@
+use crate::backend::ListEnd;
+use crate::cmd::{
+    extract_args, extract_integer, extract_string, validate_command, CommandError, CommandExecutor,
+};
+use crate::resp::{BulkString, RespArray, RespFrame, RespNull, RespNullArray};
+use crate::Backend;
+
+// LPOP key [count]
+// RPOP key [count]
+// without count, returns the popped element; with count, returns an array of popped elements
+
+#[derive(Debug)]
+pub struct LPop {
+    key: String,
+    count: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct RPop {
+    key: String,
+    count: Option<usize>,
+}
+
+impl CommandExecutor for LPop {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        pop_generic(backend, &self.key, self.count, ListEnd::Left)
+    }
+}
+
+impl CommandExecutor for RPop {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        pop_generic(backend, &self.key, self.count, ListEnd::Right)
+    }
+}
+
+fn pop_generic(backend: &Backend, key: &str, count: Option<usize>, end: ListEnd) -> RespFrame {
+    match backend.list_pop(key, count.unwrap_or(1), end) {
+        Ok(Some(values)) => match count {
+            Some(_) => RespArray::new(
+                values
+                    .into_iter()
+                    .map(|v| BulkString::new(v).into())
+                    .collect::<Vec<RespFrame>>(),
+            )
+            .into(),
+            None => values
+                .into_iter()
+                .next()
+                .map(|v| BulkString::new(v).into())
+                .unwrap_or(RespFrame::Null(RespNull)),
+        },
+        // with a count the reply is an array, so a missing key is a null array
+        Ok(None) if count.is_some() => RespFrame::NullArray(RespNullArray),
+        Ok(None) => RespFrame::Null(RespNull),
+        Err(e) => e.into(),
+    }
+}
+
+fn parse_pop_args(
+    value: RespArray,
+    name: &'static str,
+) -> Result<(String, Option<usize>), CommandError> {
+    validate_command(&value, name)?;
+    let mut args = extract_args(value, 1)?.into_iter();
+    let key = extract_string(args.next())?;
+    let count = match args.next() {
+        Some(arg) => {
+            let count = extract_integer(Some(arg))?;
+            if count < 0 {
+                return Err(CommandError::InvalidArgument(
+                    "value is out of range, must be positive".to_string(),
+                ));
+            }
+            Some(count as usize)
+        }
+        None => None,
+    };
+    Ok((key, count))
+}
+
+impl TryFrom<RespArray> for LPop {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, count) = parse_pop_args(value, "lpop")?;
+        Ok(LPop { key, count })
+    }
+}
+
+impl TryFrom<RespArray> for RPop {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, count) = parse_pop_args(value, "rpop")?;
+        Ok(RPop { key, count })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bytes::Bytes;
+
+    #[test]
+    fn test_pop_from_resp_array() -> anyhow::Result<()> {
+        let cmd = RPop::try_from(RespArray::new([
+            BulkString::new("rpop").into(),
+            BulkString::new("mylist").into(),
+            BulkString::new("2").into(),
+        ]))?;
+        assert_eq!(cmd.key, "mylist");
+        assert_eq!(cmd.count, Some(2));
+
+        let ret = LPop::try_from(RespArray::new([
+            BulkString::new("lpop").into(),
+            BulkString::new("mylist").into(),
+            BulkString::new("-2").into(),
+        ]));
+        assert!(ret.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_pop_commands() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let values = ["a", "b", "c"].map(Bytes::from).to_vec();
+        backend.list_push("mylist", values, ListEnd::Right, false)?;
+        let cmd = LPop {
+            key: "mylist".to_string(),
+            count: None,
+        };
+        assert_eq!(cmd.execute(&backend), BulkString::new("a").into());
+        let cmd = RPop {
+            key: "mylist".to_string(),
+            count: Some(5),
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            RespArray::new([BulkString::new("c").into(), BulkString::new("b").into()]).into()
+        );
+        let cmd = RPop {
+            key: "mylist".to_string(),
+            count: None,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Null(RespNull));
+        let cmd = LPop {
+            key: "mylist".to_string(),
+            count: Some(2),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::NullArray(RespNullArray));
+        Ok(())
+    }
+}
@@ -0,0 +1,141 @@
+use crate::backend::{PsyncReply, ReplicaSender};
+use crate::cmd::{
+    extract_args, extract_integer, extract_string, validate_command, CommandError, CommandExecutor,
+    RESP_OK,
+};
+use crate::resp::{RespArray, RespFrame, SimpleError};
+use crate::Backend;
+
+// PSYNC replicationid offset
+// REPLCONF option value [option value ...]
+// sent by a replica to its master: REPLCONF describes it during the handshake and then reports
+// the offset it processed, PSYNC turns the connection into the replication stream, resumed from
+// `offset` when the master still has it or preceded by the whole dataset otherwise
+
+#[derive(Debug)]
+pub struct PSync {
+    replid: String,
+    // the first byte of the stream the replica misses, -1 when it has nothing
+    offset: i64,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ReplConf {
+    // the offset the replica processed, and the one it fsynced to its log
+    Ack { offset: u64, fsynced: u64 },
+    // asks a replica for an ACK right away
+    GetAck,
+    // what the replica tells about itself, nothing of it is used
+    Info,
+}
+
+impl CommandExecutor for PSync {
+    fn execute(self, _backend: &Backend) -> RespFrame {
+        // the stream needs the connection of the replica to itself
+        SimpleError::new("ERR PSYNC is only served on a connection of its own").into()
+    }
+}
+
+impl CommandExecutor for ReplConf {
+    fn execute(self, _backend: &Backend) -> RespFrame {
+        RESP_OK.clone()
+    }
+}
+
+impl PSync {
+    // registers the replica of connection `id`, the stream goes through `sender` after the reply
+    pub fn serve(self, id: u64, backend: &Backend, sender: ReplicaSender) -> PsyncReply {
+        backend.psync(id, &self.replid, self.offset, sender)
+    }
+}
+
+impl TryFrom<RespArray> for PSync {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "psync")?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        let replid = extract_string(args.next())?;
+        let offset = extract_integer(args.next())?;
+        Ok(PSync { replid, offset })
+    }
+}
+
+impl TryFrom<RespArray> for ReplConf {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "replconf")?;
+        let args = extract_args(value, 1)?
+            .into_iter()
+            .map(|arg| extract_string(Some(arg)))
+            .collect::<Result<Vec<String>, CommandError>>()?;
+        if args.is_empty() || args.len() % 2 != 0 {
+            return Err(CommandError::InvalidArgument("syntax error".to_string()));
+        }
+        let offset = |value: &str| {
+            value.parse::<u64>().map_err(|_| {
+                CommandError::InvalidArgument("value is not an integer or out of range".to_string())
+            })
+        };
+        let mut conf = ReplConf::Info;
+        let mut fsynced = 0;
+        for pair in args.chunks(2) {
+            match pair[0].to_ascii_lowercase().as_str() {
+                "listening-port" | "ip-address" | "capa" => {}
+                "ack" => {
+                    conf = ReplConf::Ack {
+                        offset: offset(&pair[1])?,
+                        fsynced: 0,
+                    }
+                }
+                // a replica without a log reports nothing as fsynced
+                "fack" => fsynced = offset(&pair[1])?,
+                "getack" => conf = ReplConf::GetAck,
+                option => {
+                    return Err(CommandError::InvalidArgument(format!(
+                        "Unrecognized REPLCONF option: {}",
+                        option
+                    )))
+                }
+            }
+        }
+        if let ReplConf::Ack { offset, .. } = conf {
+            conf = ReplConf::Ack { offset, fsynced };
+        }
+        Ok(conf)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cmd::request;
+    use anyhow::Result;
+
+    #[test]
+    fn test_replconf_command() -> Result<()> {
+        let conf = ReplConf::try_from(request(&["replconf", "listening-port", "6380"]))?;
+        assert_eq!(conf, ReplConf::Info);
+        assert_eq!(conf.execute(&Backend::new()), RESP_OK.clone());
+        let conf = ReplConf::try_from(request(&["replconf", "ACK", "42"]))?;
+        assert_eq!(
+            conf,
+            ReplConf::Ack {
+                offset: 42,
+                fsynced: 0
+            }
+        );
+        let conf = ReplConf::try_from(request(&["replconf", "ACK", "42", "FACK", "40"]))?;
+        assert_eq!(
+            conf,
+            ReplConf::Ack {
+                offset: 42,
+                fsynced: 40
+            }
+        );
+        let conf = ReplConf::try_from(request(&["replconf", "getack", "*"]))?;
+        assert_eq!(conf, ReplConf::GetAck);
+        assert!(ReplConf::try_from(request(&["replconf", "capa"])).is_err());
+        assert!(ReplConf::try_from(request(&["replconf", "color", "blue"])).is_err());
+        Ok(())
+    }
+}
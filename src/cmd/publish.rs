@@ -0,0 +1,89 @@
+use crate::cmd::{extract_args, extract_bytes, validate_command, CommandError, CommandExecutor};
+use crate::resp::{RespArray, RespFrame};
+use crate::Backend;
+use bytes::Bytes;
+
+// PUBLISH channel message
+// SPUBLISH shardchannel message
+// replies with the number of clients that received the message
+
+// redis> PUBLISH news hello
+// (integer) 1
+
+#[derive(Debug)]
+pub struct Publish {
+    channel: Bytes,
+    message: Bytes,
+}
+
+#[derive(Debug)]
+pub struct SPublish {
+    channel: Bytes,
+    message: Bytes,
+}
+
+impl CommandExecutor for Publish {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.publish(self.channel, self.message) as i64)
+    }
+}
+
+impl CommandExecutor for SPublish {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.spublish(self.channel, self.message) as i64)
+    }
+}
+
+impl TryFrom<RespArray> for Publish {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "publish")?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(Publish {
+            channel: extract_bytes(args.next())?,
+            message: extract_bytes(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for SPublish {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "spublish")?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(SPublish {
+            channel: extract_bytes(args.next())?,
+            message: extract_bytes(args.next())?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::backend::Subscriber;
+    use crate::resp::BulkString;
+    use anyhow::Result;
+
+    #[test]
+    fn test_publish_command() -> Result<()> {
+        let backend = Backend::new();
+        let cmd = Publish::try_from(RespArray::new([
+            BulkString::new("publish").into(),
+            BulkString::new("news").into(),
+            BulkString::new("hello").into(),
+        ]))?;
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+
+        let (subscriber, mut messages) = Subscriber::new(16);
+        backend.subscribe(Bytes::from("news"), 1, &subscriber);
+        let cmd = Publish::try_from(RespArray::new([
+            BulkString::new("publish").into(),
+            BulkString::new("news").into(),
+            BulkString::new("hello").into(),
+        ]))?;
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        assert!(messages.try_recv().is_ok());
+        Ok(())
+    }
+}
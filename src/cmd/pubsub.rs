@@ -0,0 +1,156 @@
+use crate::cmd::{
+    extract_args, extract_bytes, extract_string, validate_command, CommandError, CommandExecutor,
+};
+use crate::resp::{BulkString, RespArray, RespFrame};
+use crate::Backend;
+use bytes::Bytes;
+
+// PUBSUB CHANNELS [pattern]
+// PUBSUB NUMSUB [channel [channel ...]]
+// PUBSUB NUMPAT
+// PUBSUB SHARDCHANNELS [pattern]
+// PUBSUB SHARDNUMSUB [shardchannel [shardchannel ...]]
+// introspects the channels and patterns that have subscribers
+
+// redis> PUBSUB NUMSUB news sport
+// 1) "news"
+// 2) (integer) 1
+// 3) "sport"
+// 4) (integer) 0
+
+#[derive(Debug)]
+pub enum PubSub {
+    Channels(Option<Bytes>),
+    NumSub(Vec<Bytes>),
+    NumPat,
+    ShardChannels(Option<Bytes>),
+    ShardNumSub(Vec<Bytes>),
+}
+
+impl CommandExecutor for PubSub {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match self {
+            PubSub::Channels(pattern) => channel_list(backend.pubsub_channels(pattern.as_deref())),
+            PubSub::NumSub(channels) => {
+                subscriber_counts(channels, |channel| backend.pubsub_numsub(channel))
+            }
+            PubSub::NumPat => RespFrame::Integer(backend.pubsub_numpat() as i64),
+            PubSub::ShardChannels(pattern) => {
+                channel_list(backend.pubsub_shardchannels(pattern.as_deref()))
+            }
+            PubSub::ShardNumSub(channels) => {
+                subscriber_counts(channels, |channel| backend.pubsub_shardnumsub(channel))
+            }
+        }
+    }
+}
+
+fn channel_list(channels: Vec<Bytes>) -> RespFrame {
+    RespArray::new(
+        channels
+            .into_iter()
+            .map(|channel| BulkString::from(channel).into())
+            .collect::<Vec<RespFrame>>(),
+    )
+    .into()
+}
+
+// a flat list of the channels, each followed by its number of subscribers
+fn subscriber_counts(channels: Vec<Bytes>, count: impl Fn(&[u8]) -> usize) -> RespFrame {
+    RespArray::new(
+        channels
+            .into_iter()
+            .flat_map(|channel| {
+                let n = count(&channel) as i64;
+                [BulkString::from(channel).into(), RespFrame::Integer(n)]
+            })
+            .collect::<Vec<RespFrame>>(),
+    )
+    .into()
+}
+
+impl TryFrom<RespArray> for PubSub {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "pubsub")?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        let subcommand = extract_string(args.next())?.to_ascii_lowercase();
+        let args = args
+            .map(|arg| extract_bytes(Some(arg)))
+            .collect::<Result<Vec<Bytes>, CommandError>>()?;
+        match subcommand.as_str() {
+            "channels" if args.len() <= 1 => Ok(PubSub::Channels(args.into_iter().next())),
+            "numsub" => Ok(PubSub::NumSub(args)),
+            "numpat" if args.is_empty() => Ok(PubSub::NumPat),
+            "shardchannels" if args.len() <= 1 => {
+                Ok(PubSub::ShardChannels(args.into_iter().next()))
+            }
+            "shardnumsub" => Ok(PubSub::ShardNumSub(args)),
+            "channels" | "numpat" | "shardchannels" => {
+                Err(CommandError::WrongArity(format!("pubsub|{}", subcommand)))
+            }
+            _ => Err(CommandError::InvalidArgument(format!(
+                "unknown subcommand '{}'. Try PUBSUB HELP.",
+                subcommand
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::backend::Subscriber;
+    use crate::cmd::request;
+    use anyhow::Result;
+
+    #[test]
+    fn test_pubsub_command() -> Result<()> {
+        let backend = Backend::new();
+        let (subscriber, _messages) = Subscriber::new(16);
+        backend.subscribe(Bytes::from("news"), 1, &subscriber);
+        backend.subscribe(Bytes::from("sport"), 1, &subscriber);
+        backend.psubscribe(Bytes::from("n*"), 1, &subscriber);
+
+        let cmd = PubSub::try_from(request(&["pubsub", "channels", "n*"]))?;
+        assert_eq!(
+            cmd.execute(&backend),
+            RespArray::new([BulkString::new("news").into()]).into()
+        );
+        let cmd = PubSub::try_from(request(&["pubsub", "NUMSUB", "news", "other"]))?;
+        assert_eq!(
+            cmd.execute(&backend),
+            RespArray::new([
+                BulkString::new("news").into(),
+                RespFrame::Integer(1),
+                BulkString::new("other").into(),
+                RespFrame::Integer(0),
+            ])
+            .into()
+        );
+        let cmd = PubSub::try_from(request(&["pubsub", "numpat"]))?;
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+
+        backend.ssubscribe(Bytes::from("orders"), 1, &subscriber);
+        let cmd = PubSub::try_from(request(&["pubsub", "shardchannels"]))?;
+        assert_eq!(
+            cmd.execute(&backend),
+            RespArray::new([BulkString::new("orders").into()]).into()
+        );
+        let cmd = PubSub::try_from(request(&["pubsub", "shardnumsub", "orders", "news"]))?;
+        assert_eq!(
+            cmd.execute(&backend),
+            RespArray::new([
+                BulkString::new("orders").into(),
+                RespFrame::Integer(1),
+                BulkString::new("news").into(),
+                RespFrame::Integer(0),
+            ])
+            .into()
+        );
+
+        assert!(PubSub::try_from(request(&["pubsub", "numpat", "x"])).is_err());
+        assert!(PubSub::try_from(request(&["pubsub", "foo"])).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,171 @@
+use crate::backend::ListEnd;
+use crate::cmd::{
+    extract_args, extract_bytes, extract_string, validate_command, CommandError, CommandExecutor,
+};
+use crate::resp::{RespArray, RespFrame};
+use crate::Backend;
+use bytes::Bytes;
+
+// LPUSH key element [element ...]
+// RPUSH key element [element ...]
+// LPUSHX key element [element ...]
+// RPUSHX key element [element ...]
+// returns the length of the list after the push operations
+
+// redis> LPUSH mylist "world"
+// (integer) 1
+// redis> LPUSH mylist "hello"
+// (integer) 2
+// redis> LRANGE mylist 0 -1
+// 1) "hello"
+// 2) "world"
+
+#[derive(Debug)]
+pub struct LPush {
+    key: String,
+    values: Vec<Bytes>,
+}
+
+#[derive(Debug)]
+pub struct RPush {
+    key: String,
+    values: Vec<Bytes>,
+}
+
+#[derive(Debug)]
+pub struct LPushX {
+    key: String,
+    values: Vec<Bytes>,
+}
+
+#[derive(Debug)]
+pub struct RPushX {
+    key: String,
+    values: Vec<Bytes>,
+}
+
+impl CommandExecutor for LPush {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        push_generic(backend, &self.key, self.values, ListEnd::Left, false)
+    }
+}
+
+impl CommandExecutor for RPush {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        push_generic(backend, &self.key, self.values, ListEnd::Right, false)
+    }
+}
+
+impl CommandExecutor for LPushX {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        push_generic(backend, &self.key, self.values, ListEnd::Left, true)
+    }
+}
+
+impl CommandExecutor for RPushX {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        push_generic(backend, &self.key, self.values, ListEnd::Right, true)
+    }
+}
+
+fn push_generic(
+    backend: &Backend,
+    key: &str,
+    values: Vec<Bytes>,
+    end: ListEnd,
+    only_existing: bool,
+) -> RespFrame {
+    match backend.list_push(key, values, end, only_existing) {
+        Ok(len) => RespFrame::Integer(len as i64),
+        Err(e) => e.into(),
+    }
+}
+
+fn parse_push_args(
+    value: RespArray,
+    name: &'static str,
+) -> Result<(String, Vec<Bytes>), CommandError> {
+    validate_command(&value, name)?;
+    let mut args = extract_args(value, 1)?.into_iter();
+    let key = extract_string(args.next())?;
+    let values = args
+        .map(|arg| extract_bytes(Some(arg)))
+        .collect::<Result<Vec<Bytes>, CommandError>>()?;
+    Ok((key, values))
+}
+
+impl TryFrom<RespArray> for LPush {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, values) = parse_push_args(value, "lpush")?;
+        Ok(LPush { key, values })
+    }
+}
+
+impl TryFrom<RespArray> for RPush {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, values) = parse_push_args(value, "rpush")?;
+        Ok(RPush { key, values })
+    }
+}
+
+impl TryFrom<RespArray> for LPushX {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, values) = parse_push_args(value, "lpushx")?;
+        Ok(LPushX { key, values })
+    }
+}
+
+impl TryFrom<RespArray> for RPushX {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, values) = parse_push_args(value, "rpushx")?;
+        Ok(RPushX { key, values })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::resp::RespDecode;
+    use bytes::BytesMut;
+
+    #[test]
+    fn test_lpush_from_resp_array() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$5\r\nlpush\r\n$6\r\nmylist\r\n$1\r\na\r\n$1\r\nb\r\n");
+
+        let frame = RespArray::decode(&mut buf)?;
+        let result: LPush = frame.try_into()?;
+        assert_eq!(result.key, "mylist");
+        assert_eq!(result.values, vec![Bytes::from("a"), Bytes::from("b")]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_push_commands() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let cmd = RPushX {
+            key: "mylist".to_string(),
+            values: vec![Bytes::from("a")],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+        let cmd = LPush {
+            key: "mylist".to_string(),
+            values: vec![Bytes::from("a"), Bytes::from("b")],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(2));
+        let cmd = RPush {
+            key: "mylist".to_string(),
+            values: vec![Bytes::from("c")],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(3));
+        assert_eq!(
+            backend.lrange("mylist", 0, -1)?,
+            vec![Bytes::from("b"), Bytes::from("a"), Bytes::from("c")]
+        );
+        Ok(())
+    }
+}
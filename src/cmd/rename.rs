@@ -0,0 +1,99 @@
+use crate::cmd::{
+    extract_args, extract_string, validate_command, CommandError, CommandExecutor, RESP_OK,
+};
+use crate::resp::{RespArray, RespFrame};
+use crate::Backend;
+
+// RENAME key newkey
+// RENAMENX key newkey
+
+#[derive(Debug)]
+pub struct Rename {
+    key: String,
+    new_key: String,
+}
+
+#[derive(Debug)]
+pub struct RenameNx {
+    key: String,
+    new_key: String,
+}
+
+impl CommandExecutor for Rename {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.rename(&self.key, &self.new_key, false) {
+            Ok(_) => RESP_OK.clone(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for RenameNx {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.rename(&self.key, &self.new_key, true) {
+            Ok(true) => RespFrame::Integer(1),
+            Ok(false) => RespFrame::Integer(0),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+fn parse_rename_args(
+    value: RespArray,
+    name: &'static str,
+) -> Result<(String, String), CommandError> {
+    validate_command(&value, name)?;
+    let mut args = extract_args(value, 1)?.into_iter();
+    Ok((extract_string(args.next())?, extract_string(args.next())?))
+}
+
+impl TryFrom<RespArray> for Rename {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, new_key) = parse_rename_args(value, "rename")?;
+        Ok(Rename { key, new_key })
+    }
+}
+
+impl TryFrom<RespArray> for RenameNx {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, new_key) = parse_rename_args(value, "renamenx")?;
+        Ok(RenameNx { key, new_key })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::resp::SimpleError;
+    use bytes::Bytes;
+
+    #[test]
+    fn test_rename_commands() {
+        let backend = Backend::new();
+        let cmd = Rename {
+            key: "a".to_string(),
+            new_key: "b".to_string(),
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            SimpleError::new("ERR no such key").into()
+        );
+
+        backend.set("a".to_string(), "1");
+        backend.set("c".to_string(), "3");
+        let cmd = Rename {
+            key: "a".to_string(),
+            new_key: "b".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RESP_OK.clone());
+
+        let cmd = RenameNx {
+            key: "b".to_string(),
+            new_key: "c".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+        assert_eq!(backend.get("b"), Ok(Some(Bytes::from("1"))));
+    }
+}
@@ -0,0 +1,82 @@
+use crate::cmd::{
+    extract_args, extract_string, validate_command, CommandError, CommandExecutor, RESP_OK,
+};
+use crate::replication::replicate;
+use crate::resp::{RespArray, RespFrame, SimpleError, SimpleString};
+use crate::Backend;
+
+// REPLICAOF host port
+// REPLICAOF NO ONE
+// makes the server a read-only replica of another one, which it loads the dataset of and follows
+// the writes of, or promotes a replica back to a master keeping its dataset
+
+#[derive(Debug)]
+pub struct ReplicaOf {
+    // None for NO ONE
+    master: Option<(String, u16)>,
+}
+
+impl CommandExecutor for ReplicaOf {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        if backend.cluster_enabled() {
+            return SimpleError::new("ERR REPLICAOF not allowed in cluster mode.").into();
+        }
+        if self.master.is_some() && backend.master() == self.master {
+            return SimpleString::new("OK Already connected to specified master").into();
+        }
+        replicate(backend, self.master);
+        RESP_OK.clone()
+    }
+}
+
+impl TryFrom<RespArray> for ReplicaOf {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "replicaof")?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        let host = extract_string(args.next())?;
+        let port = extract_string(args.next())?;
+        if host.eq_ignore_ascii_case("no") && port.eq_ignore_ascii_case("one") {
+            return Ok(ReplicaOf { master: None });
+        }
+        let port = port
+            .parse::<u16>()
+            .map_err(|_| CommandError::InvalidArgument("Invalid master port".to_string()))?;
+        Ok(ReplicaOf {
+            master: Some((host, port)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::resp::BulkString;
+    use anyhow::Result;
+
+    fn replicaof(host: &str, port: &str) -> Result<ReplicaOf, CommandError> {
+        ReplicaOf::try_from(RespArray::new([
+            BulkString::new("replicaof").into(),
+            BulkString::new(host).into(),
+            BulkString::new(port).into(),
+        ]))
+    }
+
+    #[tokio::test]
+    async fn test_replicaof_command() -> Result<()> {
+        let backend = Backend::new();
+        assert!(replicaof("localhost", "port").is_err());
+        assert_eq!(
+            replicaof("127.0.0.1", "1")?.execute(&backend),
+            RESP_OK.clone()
+        );
+        assert_eq!(backend.master(), Some(("127.0.0.1".to_string(), 1)));
+        assert_eq!(
+            replicaof("127.0.0.1", "1")?.execute(&backend),
+            SimpleString::new("OK Already connected to specified master").into()
+        );
+        assert_eq!(replicaof("NO", "one")?.execute(&backend), RESP_OK.clone());
+        assert!(!backend.is_replica());
+        Ok(())
+    }
+}
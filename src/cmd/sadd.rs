@@ -1,4 +1,4 @@
-use crate::cmd::{extract_args, validate_command, CommandError, CommandExecutor, RESP_OK};
+use crate::cmd::{extract_args, validate_command, CommandError, CommandExecutor};
 use crate::resp::{RespArray, RespFrame};
 use crate::Backend;
 
@@ -10,21 +10,23 @@ pub struct SAdd {
 
 impl CommandExecutor for SAdd {
     fn execute(self, backend: &Backend) -> RespFrame {
-        backend.insert_set(self.name, self.values);
-        RESP_OK.clone()
+        match backend.insert_set(self.name, self.values) {
+            Ok(added) => RespFrame::Integer(added as i64),
+            Err(e) => e.into(),
+        }
     }
 }
 
 impl TryFrom<RespArray> for SAdd {
     type Error = CommandError;
     fn try_from(value: RespArray) -> Result<Self, Self::Error> {
-        validate_command(&value, &["sadd"], 2)?;
+        validate_command(&value, "sadd")?;
         let args = extract_args(value, 1)?;
         let mut data = Vec::new();
         for arg in args {
             match arg {
                 RespFrame::BulkString(s) => {
-                    data.push(String::from_utf8(s.0)?);
+                    data.push(String::from_utf8(s.0.to_vec())?);
                 }
                 _ => {
                     return Err(CommandError::InvalidArgument(
@@ -61,4 +63,19 @@ mod test {
         );
         Ok(())
     }
+
+    #[test]
+    fn test_sadd_command() {
+        let backend = Backend::new();
+        let cmd = SAdd {
+            name: "myset".to_string(),
+            values: vec!["hello".to_string(), "world".to_string()],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(2));
+        let cmd = SAdd {
+            name: "myset".to_string(),
+            values: vec!["hello".to_string(), "again".to_string()],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+    }
 }
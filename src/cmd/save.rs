@@ -0,0 +1,103 @@
+use crate::cmd::{validate_command, CommandError, CommandExecutor, RESP_OK};
+use crate::resp::{RespArray, RespFrame, SimpleString};
+use crate::Backend;
+
+// SAVE
+// BGSAVE
+// LASTSAVE
+
+#[derive(Debug)]
+pub struct Save;
+
+#[derive(Debug)]
+pub struct BgSave;
+
+#[derive(Debug)]
+pub struct LastSave;
+
+impl CommandExecutor for Save {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        if backend.bgsave_in_progress() {
+            return crate::backend::BackendError::SaveInProgress.into();
+        }
+        match backend.save() {
+            Ok(()) => RESP_OK.clone(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for BgSave {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.bgsave() {
+            Ok(()) => SimpleString::new("Background saving started").into(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for LastSave {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.last_save() as i64)
+    }
+}
+
+impl TryFrom<RespArray> for Save {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "save")?;
+        Ok(Save)
+    }
+}
+
+impl TryFrom<RespArray> for BgSave {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        // BGSAVE SCHEDULE is accepted and behaves like a plain BGSAVE
+        validate_command(&value, "bgsave")?;
+        Ok(BgSave)
+    }
+}
+
+impl TryFrom<RespArray> for LastSave {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "lastsave")?;
+        Ok(LastSave)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::config::Config;
+    use crate::resp::BulkString;
+
+    #[test]
+    fn test_save_commands() -> anyhow::Result<()> {
+        let dir =
+            std::env::temp_dir().join(format!("simple-redis-cmd-save-{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        let backend = Backend::with_config(Config {
+            dir: dir.clone(),
+            ..Config::default()
+        });
+        backend.set("k".to_string(), "v");
+
+        let cmd = Save::try_from(RespArray::new([BulkString::new("save").into()]))?;
+        assert_eq!(cmd.execute(&backend), RESP_OK.clone());
+        assert!(dir.join("dump.rdb").exists());
+
+        let ret = LastSave.execute(&backend);
+        assert!(matches!(ret, RespFrame::Integer(t) if t > 0));
+
+        assert!(Save::try_from(RespArray::new([
+            BulkString::new("save").into(),
+            BulkString::new("now").into(),
+        ]))
+        .is_err());
+
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,51 @@
+use crate::cmd::{extract_args, extract_string, validate_command, CommandError, CommandExecutor};
+use crate::resp::{RespArray, RespFrame};
+use crate::Backend;
+
+// SCARD key
+// returns the number of members in the set, 0 if the key does not exist
+
+#[derive(Debug)]
+pub struct SCard {
+    key: String,
+}
+
+impl CommandExecutor for SCard {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.scard(&self.key) {
+            Ok(len) => RespFrame::Integer(len as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for SCard {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "scard")?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(SCard {
+            key: extract_string(args.next())?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_scard_command() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let cmd = SCard {
+            key: "myset".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+        backend.insert_set("myset".to_string(), vec!["a".to_string(), "b".to_string()])?;
+        let cmd = SCard {
+            key: "myset".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(2));
+        Ok(())
+    }
+}
@@ -0,0 +1,127 @@
+use crate::cmd::{
+    extract_args, extract_string, validate_command, CommandError, CommandExecutor, RESP_OK,
+};
+use crate::resp::{BulkString, RespArray, RespFrame};
+use crate::Backend;
+
+// SCRIPT LOAD script
+// SCRIPT EXISTS sha1 [sha1 ...]
+// SCRIPT FLUSH [ASYNC | SYNC]
+// SCRIPT KILL
+// manages the cache of the scripts EVALSHA runs, and stops a script running for too long
+
+// redis> SCRIPT LOAD "return 1"
+// "e0e1f9fabfc9d4800c877a703b823ac0578ff8db"
+// redis> SCRIPT EXISTS e0e1f9fabfc9d4800c877a703b823ac0578ff8db ffff
+// 1) (integer) 1
+// 2) (integer) 0
+
+#[derive(Debug)]
+pub enum Script {
+    Load(String),
+    Exists(Vec<String>),
+    Flush,
+    Kill,
+}
+
+impl CommandExecutor for Script {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match self {
+            Script::Load(script) => BulkString::new(backend.script_load(&script)).into(),
+            Script::Exists(shas) => RespArray::new(
+                shas.iter()
+                    .map(|sha| RespFrame::Integer(backend.script_exists(sha) as i64))
+                    .collect::<Vec<RespFrame>>(),
+            )
+            .into(),
+            Script::Flush => {
+                backend.script_flush();
+                RESP_OK.clone()
+            }
+            Script::Kill => match backend.script_kill(false) {
+                Ok(()) => RESP_OK.clone(),
+                Err(e) => e.into(),
+            },
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Script {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "script")?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        let subcommand = extract_string(args.next())?.to_ascii_lowercase();
+        let args = args
+            .map(|arg| extract_string(Some(arg)))
+            .collect::<Result<Vec<String>, CommandError>>()?;
+        match subcommand.as_str() {
+            "load" if args.len() == 1 => Ok(Script::Load(args.into_iter().next().unwrap())),
+            "exists" if !args.is_empty() => Ok(Script::Exists(args)),
+            // the cache is dropped at once either way
+            "flush" if args.is_empty() => Ok(Script::Flush),
+            "flush" if args.len() == 1 => match args[0].to_ascii_lowercase().as_str() {
+                "async" | "sync" => Ok(Script::Flush),
+                _ => Err(CommandError::InvalidArgument(
+                    "SCRIPT FLUSH only support SYNC|ASYNC option".to_string(),
+                )),
+            },
+            "kill" if args.is_empty() => Ok(Script::Kill),
+            "load" | "exists" | "flush" | "kill" => {
+                Err(CommandError::WrongArity(format!("script|{}", subcommand)))
+            }
+            _ => Err(CommandError::InvalidArgument(format!(
+                "unknown subcommand '{}'. Try SCRIPT HELP.",
+                subcommand
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cmd::request;
+    use crate::resp::SimpleError;
+    use anyhow::Result;
+
+    #[test]
+    fn test_script_command() -> Result<()> {
+        let backend = Backend::new();
+        let cmd = Script::try_from(request(&["script", "load", "return 1"]))?;
+        assert_eq!(
+            cmd.execute(&backend),
+            BulkString::new("e0e1f9fabfc9d4800c877a703b823ac0578ff8db").into()
+        );
+        let cmd = Script::try_from(request(&[
+            "script",
+            "exists",
+            "E0E1F9FABFC9D4800C877A703B823AC0578FF8DB",
+            "ffff",
+        ]))?;
+        assert_eq!(
+            cmd.execute(&backend),
+            RespArray::new([RespFrame::Integer(1), RespFrame::Integer(0)]).into()
+        );
+        let cmd = Script::try_from(request(&["script", "flush", "async"]))?;
+        assert_eq!(cmd.execute(&backend), RESP_OK.clone());
+        assert!(!backend.script_exists("e0e1f9fabfc9d4800c877a703b823ac0578ff8db"));
+
+        let ret = Script::try_from(request(&["script", "load"]));
+        assert_eq!(
+            ret.unwrap_err().to_string(),
+            "ERR wrong number of arguments for 'script|load' command"
+        );
+        let cmd = Script::try_from(request(&["script", "kill"]))?;
+        assert_eq!(
+            cmd.execute(&backend),
+            SimpleError::new("NOTBUSY No scripts in execution right now.").into()
+        );
+        let ret = Script::try_from(request(&["script", "debug"]));
+        assert_eq!(
+            ret.unwrap_err().to_string(),
+            "ERR unknown subcommand 'debug'. Try SCRIPT HELP."
+        );
+        Ok(())
+    }
+}
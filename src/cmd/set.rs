@@ -1,41 +1,120 @@
-use crate::cmd::{extract_args, validate_command, CommandError, CommandExecutor, RESP_OK};
-use crate::resp::{RespArray, RespFrame};
+use crate::backend::{now_ms, SetCondition, SetExpiry};
+use crate::cmd::{
+    extract_args, extract_bytes, extract_integer, extract_string, validate_command, CommandError,
+    CommandExecutor, RESP_OK,
+};
+use crate::resp::{BulkString, RespArray, RespFrame, RespNull};
 use crate::Backend;
+use bytes::Bytes;
+
+// SET key value [NX | XX] [GET] [EX seconds | PX milliseconds |
+//   EXAT unix-time-seconds | PXAT unix-time-milliseconds | KEEPTTL]
 
 #[derive(Debug)]
 pub struct Set {
     pub(crate) key: String,
-    pub(crate) value: RespFrame,
+    pub(crate) value: Bytes,
+    pub(crate) condition: SetCondition,
+    pub(crate) expiration: Option<Expiration>,
+    pub(crate) get: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Expiration {
+    Ex(u64),
+    Px(u64),
+    ExAt(u64),
+    PxAt(u64),
+    KeepTtl,
+}
+
+impl Expiration {
+    pub(crate) fn to_expiry(self) -> SetExpiry {
+        match self {
+            Expiration::Ex(s) => SetExpiry::At(now_ms().saturating_add(s.saturating_mul(1000))),
+            Expiration::Px(ms) => SetExpiry::At(now_ms().saturating_add(ms)),
+            Expiration::ExAt(s) => SetExpiry::At(s.saturating_mul(1000)),
+            Expiration::PxAt(ms) => SetExpiry::At(ms),
+            Expiration::KeepTtl => SetExpiry::Keep,
+        }
+    }
 }
 
 impl CommandExecutor for Set {
     fn execute(self, backend: &Backend) -> RespFrame {
-        backend.set(self.key, self.value);
-        RESP_OK.clone()
+        let expiry = self
+            .expiration
+            .map(Expiration::to_expiry)
+            .unwrap_or(SetExpiry::Clear);
+        let (applied, old) =
+            match backend.set_with(self.key, self.value, self.condition, expiry, self.get) {
+                Ok(ret) => ret,
+                Err(e) => return e.into(),
+            };
+        if self.get {
+            match old {
+                Some(old) => BulkString::new(old).into(),
+                None => RespFrame::Null(RespNull),
+            }
+        } else if applied {
+            RESP_OK.clone()
+        } else {
+            RespFrame::Null(RespNull)
+        }
     }
 }
 
 impl TryFrom<RespArray> for Set {
     type Error = CommandError;
     fn try_from(value: RespArray) -> Result<Self, Self::Error> {
-        validate_command(&value, &["set"], 2)?;
+        validate_command(&value, "set")?;
         let mut args = extract_args(value, 1)?.into_iter();
-        match (args.next(), args.next()) {
-            (Some(RespFrame::BulkString(key)), Some(value)) => Ok(Set {
-                key: String::from_utf8(key.0)?,
-                value,
-            }),
-            _ => Err(CommandError::InvalidArgument(
-                "Invalid key or value!".to_string(),
-            )),
+        let key = extract_string(args.next())?;
+        let value = extract_bytes(args.next())?;
+
+        let mut condition = SetCondition::Always;
+        let mut expiration = None;
+        let mut get = false;
+        while let Some(arg) = args.next() {
+            let option = extract_string(Some(arg))?.to_ascii_uppercase();
+            match option.as_str() {
+                "NX" if condition == SetCondition::Always => condition = SetCondition::NotExists,
+                "XX" if condition == SetCondition::Always => condition = SetCondition::Exists,
+                "GET" => get = true,
+                "KEEPTTL" if expiration.is_none() => expiration = Some(Expiration::KeepTtl),
+                "EX" | "PX" | "EXAT" | "PXAT" if expiration.is_none() => {
+                    let time = extract_integer(args.next())?;
+                    if time <= 0 {
+                        return Err(CommandError::InvalidArgument(
+                            "invalid expire time in 'set' command".to_string(),
+                        ));
+                    }
+                    let time = time as u64;
+                    expiration = Some(match option.as_str() {
+                        "EX" => Expiration::Ex(time),
+                        "PX" => Expiration::Px(time),
+                        "EXAT" => Expiration::ExAt(time),
+                        _ => Expiration::PxAt(time),
+                    });
+                }
+                _ => return Err(CommandError::InvalidArgument("syntax error".to_string())),
+            }
         }
+
+        Ok(Set {
+            key,
+            value,
+            condition,
+            expiration,
+            get,
+        })
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::resp::RespDecode;
+    use crate::resp::{BulkString, RespDecode};
     use anyhow::Result;
     use bytes::BytesMut;
 
@@ -48,8 +127,79 @@ mod test {
 
         let result: Set = frame.try_into()?;
         assert_eq!(result.key, "hello");
-        assert_eq!(result.value, RespFrame::BulkString(b"world".into()));
+        assert_eq!(result.value, Bytes::from("world"));
 
         Ok(())
     }
+
+    #[test]
+    fn test_set_options_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*7\r\n$3\r\nset\r\n$5\r\nhello\r\n$5\r\nworld\r\n$2\r\nnx\r\n$3\r\nGET\r\n$2\r\nPX\r\n$3\r\n100\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Set = frame.try_into()?;
+        assert_eq!(result.condition, SetCondition::NotExists);
+        assert_eq!(result.expiration, Some(Expiration::Px(100)));
+        assert!(result.get);
+
+        let frame = RespArray::new([
+            BulkString::new("set").into(),
+            BulkString::new("hello").into(),
+            BulkString::new("world").into(),
+            BulkString::new("NX").into(),
+            BulkString::new("XX").into(),
+        ]);
+        assert!(Set::try_from(frame).is_err());
+
+        let frame = RespArray::new([
+            BulkString::new("set").into(),
+            BulkString::new("hello").into(),
+            BulkString::new("world").into(),
+            BulkString::new("EX").into(),
+            BulkString::new("0").into(),
+        ]);
+        assert!(Set::try_from(frame).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_command_options() {
+        let backend = Backend::new();
+        let set = |value: &str, condition, expiration, get| Set {
+            key: "hello".to_string(),
+            value: Bytes::from(value.to_string()),
+            condition,
+            expiration,
+            get,
+        };
+
+        let ret = set("v1", SetCondition::Exists, None, false).execute(&backend);
+        assert_eq!(ret, RespFrame::Null(RespNull));
+
+        let ret = set(
+            "v1",
+            SetCondition::NotExists,
+            Some(Expiration::Ex(100)),
+            false,
+        )
+        .execute(&backend);
+        assert_eq!(ret, RESP_OK.clone());
+        assert!(matches!(backend.expire_time("hello"), Some(Some(_))));
+
+        let ret =
+            set("v2", SetCondition::Always, Some(Expiration::KeepTtl), true).execute(&backend);
+        assert_eq!(ret, BulkString::new("v1").into());
+        assert!(matches!(backend.expire_time("hello"), Some(Some(_))));
+
+        let ret = set("v3", SetCondition::Always, None, false).execute(&backend);
+        assert_eq!(ret, RESP_OK.clone());
+        assert_eq!(backend.expire_time("hello"), Some(None));
+
+        let ret =
+            set("v4", SetCondition::Always, Some(Expiration::PxAt(1)), false).execute(&backend);
+        assert_eq!(ret, RESP_OK.clone());
+        assert_eq!(backend.get("hello"), Ok(None));
+    }
 }
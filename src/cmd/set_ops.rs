@@ -0,0 +1,238 @@
+use crate::backend::SetOp;
+use crate::cmd::{extract_args, extract_string, validate_command, CommandError, CommandExecutor};
+use crate::resp::{BulkString, RespArray, RespFrame, RespSet};
+use crate::Backend;
+
+// SINTER key [key ...]
+// SUNION key [key ...]
+// SDIFF key [key ...]
+// returns the members of the intersection, union or difference of the sets, a missing key is an empty set
+
+// SINTERSTORE destination key [key ...]
+// SUNIONSTORE destination key [key ...]
+// SDIFFSTORE destination key [key ...]
+// stores the result in destination, overwriting it, and returns the number of members
+
+// redis> SADD key1 "a" "b" "c"
+// (integer) 3
+// redis> SADD key2 "c" "d" "e"
+// (integer) 3
+// redis> SDIFF key1 key2
+// 1) "a"
+// 2) "b"
+// redis> SINTERSTORE key key1 key2
+// (integer) 1
+
+#[derive(Debug)]
+pub struct SInter {
+    keys: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct SUnion {
+    keys: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct SDiff {
+    keys: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct SInterStore {
+    destination: String,
+    keys: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct SUnionStore {
+    destination: String,
+    keys: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct SDiffStore {
+    destination: String,
+    keys: Vec<String>,
+}
+
+impl CommandExecutor for SInter {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        combine_generic(backend, &self.keys, SetOp::Inter)
+    }
+}
+
+impl CommandExecutor for SUnion {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        combine_generic(backend, &self.keys, SetOp::Union)
+    }
+}
+
+impl CommandExecutor for SDiff {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        combine_generic(backend, &self.keys, SetOp::Diff)
+    }
+}
+
+impl CommandExecutor for SInterStore {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        combine_store_generic(backend, &self.destination, &self.keys, SetOp::Inter)
+    }
+}
+
+impl CommandExecutor for SUnionStore {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        combine_store_generic(backend, &self.destination, &self.keys, SetOp::Union)
+    }
+}
+
+impl CommandExecutor for SDiffStore {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        combine_store_generic(backend, &self.destination, &self.keys, SetOp::Diff)
+    }
+}
+
+fn combine_generic(backend: &Backend, keys: &[String], op: SetOp) -> RespFrame {
+    match backend.set_combine(keys, op) {
+        Ok(set) => RespSet::new(
+            set.into_iter()
+                .map(|m| BulkString::new(m).into())
+                .collect::<Vec<RespFrame>>(),
+        )
+        .into(),
+        Err(e) => e.into(),
+    }
+}
+
+fn combine_store_generic(backend: &Backend, dst: &str, keys: &[String], op: SetOp) -> RespFrame {
+    match backend.set_combine_store(dst, keys, op) {
+        Ok(len) => RespFrame::Integer(len as i64),
+        Err(e) => e.into(),
+    }
+}
+
+fn parse_keys(value: RespArray, name: &'static str) -> Result<Vec<String>, CommandError> {
+    validate_command(&value, name)?;
+    extract_args(value, 1)?
+        .into_iter()
+        .map(|arg| extract_string(Some(arg)))
+        .collect()
+}
+
+fn parse_store_args(
+    value: RespArray,
+    name: &'static str,
+) -> Result<(String, Vec<String>), CommandError> {
+    validate_command(&value, name)?;
+    let mut keys = parse_keys(value, name)?;
+    let destination = keys.remove(0);
+    Ok((destination, keys))
+}
+
+impl TryFrom<RespArray> for SInter {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let keys = parse_keys(value, "sinter")?;
+        Ok(SInter { keys })
+    }
+}
+
+impl TryFrom<RespArray> for SUnion {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let keys = parse_keys(value, "sunion")?;
+        Ok(SUnion { keys })
+    }
+}
+
+impl TryFrom<RespArray> for SDiff {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let keys = parse_keys(value, "sdiff")?;
+        Ok(SDiff { keys })
+    }
+}
+
+impl TryFrom<RespArray> for SInterStore {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (destination, keys) = parse_store_args(value, "sinterstore")?;
+        Ok(SInterStore { destination, keys })
+    }
+}
+
+impl TryFrom<RespArray> for SUnionStore {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (destination, keys) = parse_store_args(value, "sunionstore")?;
+        Ok(SUnionStore { destination, keys })
+    }
+}
+
+impl TryFrom<RespArray> for SDiffStore {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (destination, keys) = parse_store_args(value, "sdiffstore")?;
+        Ok(SDiffStore { destination, keys })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::resp::RespDecode;
+    use bytes::BytesMut;
+
+    fn setup() -> anyhow::Result<Backend> {
+        let backend = Backend::new();
+        let members = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<String>>();
+        backend.insert_set("key1".to_string(), members(&["a", "b", "c"]))?;
+        backend.insert_set("key2".to_string(), members(&["c", "d", "e"]))?;
+        Ok(backend)
+    }
+
+    #[test]
+    fn test_sinterstore_from_resp_array() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*4\r\n$11\r\nsinterstore\r\n$3\r\nkey\r\n$4\r\nkey1\r\n$4\r\nkey2\r\n",
+        );
+
+        let frame = RespArray::decode(&mut buf)?;
+        let result: SInterStore = frame.try_into()?;
+        assert_eq!(result.destination, "key");
+        assert_eq!(result.keys, vec!["key1".to_string(), "key2".to_string()]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_sinter_command() -> anyhow::Result<()> {
+        let backend = setup()?;
+        let cmd = SInter {
+            keys: vec!["key1".to_string(), "key2".to_string()],
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            RespSet::new([BulkString::new("c").into()]).into()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_sunionstore_command() -> anyhow::Result<()> {
+        let backend = setup()?;
+        let cmd = SUnionStore {
+            destination: "key".to_string(),
+            keys: vec!["key1".to_string(), "key2".to_string()],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(5));
+        assert_eq!(backend.scard("key")?, 5);
+        let cmd = SDiffStore {
+            destination: "key".to_string(),
+            keys: vec!["key1".to_string(), "key1".to_string()],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+        assert!(!backend.exists("key"));
+        Ok(())
+    }
+}
@@ -0,0 +1,79 @@
+use crate::cmd::{
+    extract_args, extract_bytes, extract_integer, extract_string, validate_command, CommandError,
+    CommandExecutor,
+};
+use crate::resp::{RespArray, RespFrame};
+use crate::Backend;
+use bytes::Bytes;
+
+// SETRANGE key offset value
+// overwrites the string from offset, padding it with zero bytes, returns the new length
+
+// redis> SET key1 "Hello World"
+// "OK"
+// redis> SETRANGE key1 6 "Redis"
+// (integer) 11
+// redis> GET key1
+// "Hello Redis"
+
+#[derive(Debug)]
+pub struct SetRange {
+    key: String,
+    offset: usize,
+    value: Bytes,
+}
+
+impl CommandExecutor for SetRange {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.setrange(&self.key, self.offset, &self.value) {
+            Ok(len) => RespFrame::Integer(len as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for SetRange {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "setrange")?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let offset = extract_integer(args.next())?;
+        if offset < 0 {
+            return Err(CommandError::InvalidArgument(
+                "offset is out of range".to_string(),
+            ));
+        }
+        Ok(SetRange {
+            key,
+            offset: offset as usize,
+            value: extract_bytes(args.next())?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::backend::BackendError;
+
+    #[test]
+    fn test_setrange_command() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        backend.set("key1".to_string(), "Hello World");
+        let cmd = SetRange {
+            key: "key1".to_string(),
+            offset: 6,
+            value: Bytes::from("Redis"),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(11));
+        assert_eq!(backend.get("key1")?, Some(Bytes::from("Hello Redis")));
+        let cmd = SetRange {
+            key: "key1".to_string(),
+            offset: 512 * 1024 * 1024,
+            value: Bytes::from("x"),
+        };
+        assert_eq!(cmd.execute(&backend), BackendError::StringTooLong.into());
+        Ok(())
+    }
+}
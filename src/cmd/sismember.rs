@@ -10,23 +10,23 @@ pub struct SisMember {
 
 impl CommandExecutor for SisMember {
     fn execute(self, backend: &Backend) -> RespFrame {
-        let flag = backend.sismember(&self.key, &self.value);
-        match flag {
-            true => RespFrame::Integer(1),
-            false => RespFrame::Integer(0),
+        match backend.sismember(&self.key, &self.value) {
+            Ok(true) => RespFrame::Integer(1),
+            Ok(false) => RespFrame::Integer(0),
+            Err(e) => e.into(),
         }
     }
 }
 impl TryFrom<RespArray> for SisMember {
     type Error = CommandError;
     fn try_from(value: RespArray) -> Result<Self, Self::Error> {
-        validate_command(&value, &["SISMEMBER"], 2)?;
+        validate_command(&value, "SISMEMBER")?;
         let mut args = extract_args(value, 1)?.into_iter();
         match (args.next(), args.next()) {
             (Some(RespFrame::BulkString(name)), Some(RespFrame::BulkString(value))) => {
                 Ok(SisMember {
-                    key: String::from_utf8(name.0)?,
-                    value: String::from_utf8(value.0)?,
+                    key: String::from_utf8(name.0.to_vec())?,
+                    value: String::from_utf8(value.0.to_vec())?,
                 })
             }
             _ => Err(CommandError::InvalidArgument(
@@ -42,7 +42,7 @@ mod test {
     #[test]
     fn test_sismember_command() -> anyhow::Result<()> {
         let backend = Backend::new();
-        backend.insert_set("set".to_string(), vec!["a".to_string(), "b".to_string()]);
+        backend.insert_set("set".to_string(), vec!["a".to_string(), "b".to_string()])?;
         let command = SisMember {
             key: "set".to_string(),
             value: "a".to_string(),
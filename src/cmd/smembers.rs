@@ -0,0 +1,66 @@
+use crate::cmd::{extract_args, extract_string, validate_command, CommandError, CommandExecutor};
+use crate::resp::{BulkString, RespArray, RespFrame, RespSet};
+use crate::Backend;
+
+// SMEMBERS key
+// returns all the members of the set, as a RESP3 set
+
+// redis> SADD myset "Hello" "World"
+// (integer) 2
+// redis> SMEMBERS myset
+// 1) "Hello"
+// 2) "World"
+
+#[derive(Debug)]
+pub struct SMembers {
+    key: String,
+}
+
+impl CommandExecutor for SMembers {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.smembers(&self.key) {
+            Ok(members) => RespSet::new(
+                members
+                    .into_iter()
+                    .map(|m| BulkString::new(m).into())
+                    .collect::<Vec<RespFrame>>(),
+            )
+            .into(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for SMembers {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "smembers")?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(SMembers {
+            key: extract_string(args.next())?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_smembers_command() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        backend.insert_set("myset".to_string(), vec!["Hello".to_string()])?;
+        let cmd = SMembers {
+            key: "myset".to_string(),
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            RespSet::new([BulkString::new("Hello").into()]).into()
+        );
+        let cmd = SMembers {
+            key: "none".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespSet::new(vec![]).into());
+        Ok(())
+    }
+}
@@ -0,0 +1,160 @@
+use crate::cmd::{
+    extract_args, extract_integer, extract_string, validate_command, CommandError, CommandExecutor,
+};
+use crate::resp::{BulkString, RespArray, RespFrame, RespNull};
+use crate::Backend;
+
+// SPOP key [count]
+// SRANDMEMBER key [count]
+// without count a single member is returned, nil if the key does not exist
+// with count an array is returned, SRANDMEMBER allows repeated members for a negative count
+
+// redis> SADD myset "one" "two" "three"
+// (integer) 3
+// redis> SPOP myset
+// "one"
+// redis> SRANDMEMBER myset -3
+// 1) "two"
+// 2) "two"
+// 3) "three"
+
+#[derive(Debug)]
+pub struct SPop {
+    key: String,
+    count: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct SRandMember {
+    key: String,
+    count: Option<i64>,
+}
+
+impl CommandExecutor for SPop {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.spop(&self.key, self.count.unwrap_or(1)) {
+            Ok(members) => random_reply(members.unwrap_or_default(), self.count.is_some()),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for SRandMember {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.srandmember(&self.key, self.count.unwrap_or(1)) {
+            Ok(members) => random_reply(members, self.count.is_some()),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+fn random_reply(members: Vec<String>, with_count: bool) -> RespFrame {
+    let mut members = members.into_iter().map(|m| BulkString::new(m).into());
+    if with_count {
+        RespArray::new(members.collect::<Vec<RespFrame>>()).into()
+    } else {
+        members.next().unwrap_or(RespFrame::Null(RespNull))
+    }
+}
+
+fn parse_random_args(
+    value: RespArray,
+    name: &'static str,
+) -> Result<(String, Option<i64>), CommandError> {
+    validate_command(&value, name)?;
+    let mut args = extract_args(value, 1)?.into_iter();
+    let key = extract_string(args.next())?;
+    let count = match args.next() {
+        Some(arg) => Some(extract_integer(Some(arg))?),
+        None => None,
+    };
+    if args.next().is_some() {
+        return Err(CommandError::InvalidArgument("syntax error".to_string()));
+    }
+    Ok((key, count))
+}
+
+impl TryFrom<RespArray> for SPop {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, count) = parse_random_args(value, "spop")?;
+        let count = match count {
+            Some(n) if n < 0 => {
+                return Err(CommandError::InvalidArgument(
+                    "value is out of range, must be positive".to_string(),
+                ))
+            }
+            count => count.map(|n| n as usize),
+        };
+        Ok(SPop { key, count })
+    }
+}
+
+impl TryFrom<RespArray> for SRandMember {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, count) = parse_random_args(value, "srandmember")?;
+        Ok(SRandMember { key, count })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::resp::RespDecode;
+    use bytes::BytesMut;
+
+    #[test]
+    fn test_spop_from_resp_array() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$4\r\nspop\r\n$5\r\nmyset\r\n$1\r\n2\r\n");
+
+        let frame = RespArray::decode(&mut buf)?;
+        let result: SPop = frame.try_into()?;
+        assert_eq!(result.key, "myset");
+        assert_eq!(result.count, Some(2));
+
+        buf.extend_from_slice(b"*3\r\n$4\r\nspop\r\n$5\r\nmyset\r\n$2\r\n-2\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(SPop::try_from(frame).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_spop_command() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        backend.insert_set("myset".to_string(), vec!["one".to_string()])?;
+        let cmd = SPop {
+            key: "myset".to_string(),
+            count: None,
+        };
+        assert_eq!(cmd.execute(&backend), BulkString::new("one").into());
+        let cmd = SPop {
+            key: "myset".to_string(),
+            count: None,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Null(RespNull));
+        let cmd = SPop {
+            key: "myset".to_string(),
+            count: Some(1),
+        };
+        assert_eq!(cmd.execute(&backend), RespArray::new(vec![]).into());
+        Ok(())
+    }
+
+    #[test]
+    fn test_srandmember_command() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        backend.insert_set("myset".to_string(), vec!["one".to_string()])?;
+        let cmd = SRandMember {
+            key: "myset".to_string(),
+            count: Some(-2),
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            RespArray::new([BulkString::new("one").into(), BulkString::new("one").into()]).into()
+        );
+        assert_eq!(backend.scard("myset")?, 1);
+        Ok(())
+    }
+}
@@ -0,0 +1,51 @@
+use crate::cmd::{extract_args, extract_string, validate_command, CommandError, CommandExecutor};
+use crate::resp::{RespArray, RespFrame};
+use crate::Backend;
+
+// STRLEN key
+// returns the length of the string, 0 if the key does not exist
+
+#[derive(Debug)]
+pub struct StrLen {
+    key: String,
+}
+
+impl CommandExecutor for StrLen {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.strlen(&self.key) {
+            Ok(len) => RespFrame::Integer(len as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for StrLen {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "strlen")?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(StrLen {
+            key: extract_string(args.next())?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_strlen_command() {
+        let backend = Backend::new();
+        backend.set("mykey".to_string(), "Hello world");
+        let cmd = StrLen {
+            key: "mykey".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(11));
+        backend.set("counter".to_string(), "-100");
+        let cmd = StrLen {
+            key: "counter".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(4));
+    }
+}
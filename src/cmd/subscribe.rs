@@ -0,0 +1,420 @@
+use crate::backend::key_hash_slot;
+use crate::cmd::{extract_args, extract_bytes, validate_command, CommandError, CommandExecutor};
+use crate::network::ClientState;
+use crate::resp::{BulkString, RespArray, RespFrame, RespNullBulkString, RespPush, SimpleError};
+use crate::Backend;
+use bytes::Bytes;
+
+// SUBSCRIBE channel [channel ...]
+// UNSUBSCRIBE [channel [channel ...]]
+// PSUBSCRIBE pattern [pattern ...]
+// PUNSUBSCRIBE [pattern [pattern ...]]
+// SSUBSCRIBE shardchannel [shardchannel ...]
+// SUNSUBSCRIBE [shardchannel [shardchannel ...]]
+// every channel or pattern gets its own confirmation, with the number of subscriptions left to
+// the client; unsubscribing without arguments drops all of them. Sharded channels are counted
+// apart from the classic subscriptions and, like in a cluster, the channels of one command must
+// hash to the same slot
+
+// redis> SUBSCRIBE news sport
+// 1) "subscribe"
+// 2) "news"
+// 3) (integer) 1
+// 1) "subscribe"
+// 2) "sport"
+// 3) (integer) 2
+
+#[derive(Debug)]
+pub struct Subscribe {
+    channels: Vec<Bytes>,
+}
+
+#[derive(Debug)]
+pub struct Unsubscribe {
+    channels: Vec<Bytes>,
+}
+
+#[derive(Debug)]
+pub struct PSubscribe {
+    patterns: Vec<Bytes>,
+}
+
+#[derive(Debug)]
+pub struct PUnsubscribe {
+    patterns: Vec<Bytes>,
+}
+
+#[derive(Debug)]
+pub struct SSubscribe {
+    channels: Vec<Bytes>,
+}
+
+#[derive(Debug)]
+pub struct SUnsubscribe {
+    channels: Vec<Bytes>,
+}
+
+// subscriptions belong to a connection, without one there is nowhere to deliver the messages
+impl CommandExecutor for Subscribe {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        first_reply(self.execute_for(&mut ClientState::default(), backend))
+    }
+}
+
+impl CommandExecutor for Unsubscribe {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        first_reply(self.execute_for(&mut ClientState::default(), backend))
+    }
+}
+
+impl CommandExecutor for PSubscribe {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        first_reply(self.execute_for(&mut ClientState::default(), backend))
+    }
+}
+
+impl CommandExecutor for PUnsubscribe {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        first_reply(self.execute_for(&mut ClientState::default(), backend))
+    }
+}
+
+impl CommandExecutor for SSubscribe {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        first_reply(self.execute_for(&mut ClientState::default(), backend))
+    }
+}
+
+impl CommandExecutor for SUnsubscribe {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        first_reply(self.execute_for(&mut ClientState::default(), backend))
+    }
+}
+
+impl Subscribe {
+    pub fn execute_for(self, client: &mut ClientState, backend: &Backend) -> Vec<RespFrame> {
+        let Some(subscriber) = client.subscriber.clone() else {
+            return vec![not_allowed("subscribe")];
+        };
+        self.channels
+            .into_iter()
+            .map(|channel| {
+                if client.channels.insert(channel.clone()) {
+                    backend.subscribe(channel.clone(), client.id, &subscriber);
+                }
+                confirmation("subscribe", Some(channel), client.subscriptions())
+            })
+            .collect()
+    }
+}
+
+impl Unsubscribe {
+    pub fn execute_for(self, client: &mut ClientState, backend: &Backend) -> Vec<RespFrame> {
+        let channels = match self.channels.is_empty() {
+            true => client.channels.iter().cloned().collect(),
+            false => self.channels,
+        };
+        if channels.is_empty() {
+            return vec![confirmation("unsubscribe", None, client.subscriptions())];
+        }
+        channels
+            .into_iter()
+            .map(|channel| {
+                if client.channels.remove(&channel) {
+                    backend.unsubscribe(&channel, client.id);
+                }
+                confirmation("unsubscribe", Some(channel), client.subscriptions())
+            })
+            .collect()
+    }
+}
+
+impl PSubscribe {
+    pub fn execute_for(self, client: &mut ClientState, backend: &Backend) -> Vec<RespFrame> {
+        let Some(subscriber) = client.subscriber.clone() else {
+            return vec![not_allowed("psubscribe")];
+        };
+        self.patterns
+            .into_iter()
+            .map(|pattern| {
+                if client.patterns.insert(pattern.clone()) {
+                    backend.psubscribe(pattern.clone(), client.id, &subscriber);
+                }
+                confirmation("psubscribe", Some(pattern), client.subscriptions())
+            })
+            .collect()
+    }
+}
+
+impl PUnsubscribe {
+    pub fn execute_for(self, client: &mut ClientState, backend: &Backend) -> Vec<RespFrame> {
+        let patterns = match self.patterns.is_empty() {
+            true => client.patterns.iter().cloned().collect(),
+            false => self.patterns,
+        };
+        if patterns.is_empty() {
+            return vec![confirmation("punsubscribe", None, client.subscriptions())];
+        }
+        patterns
+            .into_iter()
+            .map(|pattern| {
+                if client.patterns.remove(&pattern) {
+                    backend.punsubscribe(&pattern, client.id);
+                }
+                confirmation("punsubscribe", Some(pattern), client.subscriptions())
+            })
+            .collect()
+    }
+}
+
+impl SSubscribe {
+    pub fn execute_for(self, client: &mut ClientState, backend: &Backend) -> Vec<RespFrame> {
+        let Some(subscriber) = client.subscriber.clone() else {
+            return vec![not_allowed("ssubscribe")];
+        };
+        self.channels
+            .into_iter()
+            .map(|channel| {
+                if client.shard_channels.insert(channel.clone()) {
+                    backend.ssubscribe(channel.clone(), client.id, &subscriber);
+                }
+                confirmation("ssubscribe", Some(channel), client.shard_channels.len())
+            })
+            .collect()
+    }
+}
+
+impl SUnsubscribe {
+    pub fn execute_for(self, client: &mut ClientState, backend: &Backend) -> Vec<RespFrame> {
+        let channels = match self.channels.is_empty() {
+            true => client.shard_channels.iter().cloned().collect(),
+            false => self.channels,
+        };
+        if channels.is_empty() {
+            return vec![confirmation(
+                "sunsubscribe",
+                None,
+                client.shard_channels.len(),
+            )];
+        }
+        channels
+            .into_iter()
+            .map(|channel| {
+                if client.shard_channels.remove(&channel) {
+                    backend.sunsubscribe(&channel, client.id);
+                }
+                confirmation("sunsubscribe", Some(channel), client.shard_channels.len())
+            })
+            .collect()
+    }
+}
+
+impl TryFrom<RespArray> for Subscribe {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(Subscribe {
+            channels: parse_names(value, "subscribe")?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for Unsubscribe {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(Unsubscribe {
+            channels: parse_names(value, "unsubscribe")?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for PSubscribe {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(PSubscribe {
+            patterns: parse_names(value, "psubscribe")?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for PUnsubscribe {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(PUnsubscribe {
+            patterns: parse_names(value, "punsubscribe")?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for SSubscribe {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let channels = parse_names(value, "ssubscribe")?;
+        check_same_slot(&channels)?;
+        Ok(SSubscribe { channels })
+    }
+}
+
+impl TryFrom<RespArray> for SUnsubscribe {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let channels = parse_names(value, "sunsubscribe")?;
+        check_same_slot(&channels)?;
+        Ok(SUnsubscribe { channels })
+    }
+}
+
+fn check_same_slot(channels: &[Bytes]) -> Result<(), CommandError> {
+    let mut slots = channels.iter().map(|channel| key_hash_slot(channel));
+    match slots.next() {
+        Some(slot) if slots.any(|other| other != slot) => Err(CommandError::CrossSlot),
+        _ => Ok(()),
+    }
+}
+
+fn parse_names(value: RespArray, name: &'static str) -> Result<Vec<Bytes>, CommandError> {
+    validate_command(&value, name)?;
+    extract_args(value, 1)?
+        .into_iter()
+        .map(|arg| extract_bytes(Some(arg)))
+        .collect()
+}
+
+// the confirmations are pushed like messages, so in RESP2 they read as arrays
+fn confirmation(kind: &'static str, name: Option<Bytes>, count: usize) -> RespFrame {
+    let name = match name {
+        Some(name) => BulkString::from(name).into(),
+        None => RespFrame::NullBulkString(RespNullBulkString),
+    };
+    RespPush::new([
+        BulkString::new(kind).into(),
+        name,
+        RespFrame::Integer(count as i64),
+    ])
+    .into()
+}
+
+fn not_allowed(name: &str) -> RespFrame {
+    SimpleError::new(format!("ERR {} isn't allowed in this context", name)).into()
+}
+
+fn first_reply(frames: Vec<RespFrame>) -> RespFrame {
+    frames
+        .into_iter()
+        .next()
+        .unwrap_or(RespFrame::NullBulkString(RespNullBulkString))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::backend::Subscriber;
+    use crate::cmd::request;
+    use anyhow::Result;
+
+    #[test]
+    fn test_subscribe_unsubscribe() -> Result<()> {
+        let backend = Backend::new();
+        let (subscriber, mut messages) = Subscriber::new(16);
+        let mut client = ClientState {
+            id: 1,
+            subscriber: Some(subscriber),
+            ..ClientState::default()
+        };
+
+        let cmd = Subscribe::try_from(request(&["subscribe", "a", "b", "a"]))?;
+        let replies = cmd.execute_for(&mut client, &backend);
+        assert_eq!(
+            replies,
+            vec![
+                confirmation("subscribe", Some(Bytes::from("a")), 1),
+                confirmation("subscribe", Some(Bytes::from("b")), 2),
+                confirmation("subscribe", Some(Bytes::from("a")), 2),
+            ]
+        );
+        let cmd = PSubscribe::try_from(request(&["psubscribe", "a*"]))?;
+        let replies = cmd.execute_for(&mut client, &backend);
+        assert_eq!(
+            replies,
+            vec![confirmation("psubscribe", Some(Bytes::from("a*")), 3)]
+        );
+
+        assert_eq!(backend.publish(Bytes::from("a"), Bytes::from("hi")), 2);
+        assert!(messages.try_recv().is_ok());
+        assert!(messages.try_recv().is_ok());
+
+        let cmd = Unsubscribe::try_from(request(&["unsubscribe", "a"]))?;
+        let replies = cmd.execute_for(&mut client, &backend);
+        assert_eq!(
+            replies,
+            vec![confirmation("unsubscribe", Some(Bytes::from("a")), 2)]
+        );
+        let cmd = Unsubscribe::try_from(request(&["unsubscribe"]))?;
+        assert_eq!(cmd.execute_for(&mut client, &backend).len(), 1);
+        let cmd = PUnsubscribe::try_from(request(&["punsubscribe"]))?;
+        assert_eq!(cmd.execute_for(&mut client, &backend).len(), 1);
+        let cmd = PUnsubscribe::try_from(request(&["punsubscribe"]))?;
+        assert_eq!(
+            cmd.execute_for(&mut client, &backend),
+            vec![confirmation("punsubscribe", None, 0)]
+        );
+        assert_eq!(backend.publish(Bytes::from("a"), Bytes::from("hi")), 0);
+
+        let cmd = Subscribe::try_from(request(&["subscribe", "a"]))?;
+        assert_eq!(
+            cmd.execute(&backend),
+            SimpleError::new("ERR subscribe isn't allowed in this context").into()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_ssubscribe_sunsubscribe() -> Result<()> {
+        let backend = Backend::new();
+        let (subscriber, mut messages) = Subscriber::new(16);
+        let mut client = ClientState {
+            id: 1,
+            subscriber: Some(subscriber),
+            ..ClientState::default()
+        };
+
+        let cmd = Subscribe::try_from(request(&["subscribe", "a"]))?;
+        cmd.execute_for(&mut client, &backend);
+        // sharded subscriptions are counted apart from the classic ones
+        let cmd = SSubscribe::try_from(request(&["ssubscribe", "{user}a", "{user}b"]))?;
+        assert_eq!(
+            cmd.execute_for(&mut client, &backend),
+            vec![
+                confirmation("ssubscribe", Some(Bytes::from("{user}a")), 1),
+                confirmation("ssubscribe", Some(Bytes::from("{user}b")), 2),
+            ]
+        );
+        assert_eq!(
+            backend.spublish(Bytes::from("{user}a"), Bytes::from("hi")),
+            1
+        );
+        assert!(messages.try_recv().is_ok());
+
+        assert!(matches!(
+            SSubscribe::try_from(request(&["ssubscribe", "a", "b"])),
+            Err(CommandError::CrossSlot)
+        ));
+        assert!(matches!(
+            SUnsubscribe::try_from(request(&["sunsubscribe", "a", "b"])),
+            Err(CommandError::CrossSlot)
+        ));
+
+        let cmd = SUnsubscribe::try_from(request(&["sunsubscribe"]))?;
+        assert_eq!(cmd.execute_for(&mut client, &backend).len(), 2);
+        let cmd = SUnsubscribe::try_from(request(&["sunsubscribe"]))?;
+        assert_eq!(
+            cmd.execute_for(&mut client, &backend),
+            vec![confirmation("sunsubscribe", None, 0)]
+        );
+        assert_eq!(client.subscriptions(), 1);
+        assert_eq!(
+            backend.spublish(Bytes::from("{user}a"), Bytes::from("hi")),
+            0
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,46 @@
+use crate::cmd::{extract_args, extract_string, validate_command, CommandError, CommandExecutor};
+use crate::resp::{RespArray, RespFrame};
+use crate::Backend;
+
+// TOUCH key [key ...]
+// returns the number of keys that were touched
+
+#[derive(Debug)]
+pub struct Touch {
+    keys: Vec<String>,
+}
+
+impl CommandExecutor for Touch {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        // there is no eviction policy yet, touching a key only triggers its lazy expiration
+        let count = self.keys.iter().filter(|key| backend.exists(key)).count();
+        RespFrame::Integer(count as i64)
+    }
+}
+
+impl TryFrom<RespArray> for Touch {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "touch")?;
+        let keys = extract_args(value, 1)?
+            .into_iter()
+            .map(|arg| extract_string(Some(arg)))
+            .collect::<Result<Vec<String>, CommandError>>()?;
+        Ok(Touch { keys })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_touch_command() {
+        let backend = Backend::new();
+        backend.set("a".to_string(), "1");
+        let cmd = Touch {
+            keys: vec!["a".to_string(), "b".to_string()],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+    }
+}
@@ -0,0 +1,89 @@
+use crate::backend::now_ms;
+use crate::cmd::{extract_args, extract_string, validate_command, CommandError, CommandExecutor};
+use crate::resp::{RespArray, RespFrame};
+use crate::Backend;
+
+// TTL key
+// PTTL key
+// returns -2 if the key does not exist, -1 if the key exists but has no associated expire
+
+#[derive(Debug)]
+pub struct Ttl {
+    key: String,
+}
+
+#[derive(Debug)]
+pub struct PTtl {
+    key: String,
+}
+
+impl CommandExecutor for Ttl {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match remaining_ms(backend, &self.key) {
+            // round to the closest second like redis does
+            Some(ms) if ms >= 0 => RespFrame::Integer((ms + 500) / 1000),
+            Some(ret) => RespFrame::Integer(ret),
+            None => RespFrame::Integer(-2),
+        }
+    }
+}
+
+impl CommandExecutor for PTtl {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(remaining_ms(backend, &self.key).unwrap_or(-2))
+    }
+}
+
+fn remaining_ms(backend: &Backend, key: &str) -> Option<i64> {
+    backend.expire_time(key).map(|at| match at {
+        Some(at) => at.saturating_sub(now_ms()) as i64,
+        None => -1,
+    })
+}
+
+impl TryFrom<RespArray> for Ttl {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "ttl")?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(Ttl {
+            key: extract_string(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for PTtl {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "pttl")?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(PTtl {
+            key: extract_string(args.next())?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_ttl_command() {
+        let backend = Backend::new();
+        let ttl = || Ttl {
+            key: "hello".to_string(),
+        };
+        assert_eq!(ttl().execute(&backend), RespFrame::Integer(-2));
+
+        backend.set("hello".to_string(), "world");
+        assert_eq!(ttl().execute(&backend), RespFrame::Integer(-1));
+
+        backend.expire_at("hello", now_ms() + 10_000);
+        assert_eq!(ttl().execute(&backend), RespFrame::Integer(10));
+        let ret = PTtl {
+            key: "hello".to_string(),
+        }
+        .execute(&backend);
+        assert!(matches!(ret, RespFrame::Integer(ms) if ms > 9_000 && ms <= 10_000));
+    }
+}
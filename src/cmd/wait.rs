@@ -0,0 +1,195 @@
+use crate::cmd::{extract_args, extract_integer, validate_command, CommandError, CommandExecutor};
+use crate::network::ClientState;
+use crate::resp::{RespArray, RespFrame, SimpleError};
+use crate::Backend;
+use std::time::Duration;
+
+// WAIT numreplicas timeout
+// WAITAOF numlocal numreplicas timeout
+// blocks the client until its last write reached `numreplicas` replicas, or WAITAOF until it was
+// fsynced to the local log and to the logs of `numreplicas` replicas, for at most `timeout`
+// milliseconds, 0 waits forever. WAIT replies with the number of replicas reached, WAITAOF with
+// whether the local log got it and the number of replicas reached
+
+// redis> SET k v
+// OK
+// redis> WAIT 1 100
+// (integer) 1
+
+#[derive(Debug)]
+pub struct Wait {
+    numreplicas: usize,
+    timeout: Option<Duration>,
+}
+
+#[derive(Debug)]
+pub struct WaitAof {
+    numlocal: usize,
+    numreplicas: usize,
+    timeout: Option<Duration>,
+}
+
+impl CommandExecutor for Wait {
+    // inside a transaction nothing can block, the replicas already reached are counted
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let offset = backend.master_repl_offset();
+        RespFrame::Integer(backend.replicas_acked(offset, false) as i64)
+    }
+}
+
+impl CommandExecutor for WaitAof {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let offset = backend.master_repl_offset();
+        aof_acks(backend, offset)
+    }
+}
+
+impl Wait {
+    pub async fn execute_for(self, client: &ClientState, backend: &Backend) -> RespFrame {
+        if backend.is_replica() {
+            return SimpleError::new("ERR WAIT cannot be used with replica instances.").into();
+        }
+        let offset = client.woff;
+        if backend.replicas_acked(offset, false) < self.numreplicas {
+            backend.request_acks();
+            backend
+                .wait_progress(self.timeout, || {
+                    backend.replicas_acked(offset, false) >= self.numreplicas
+                })
+                .await;
+        }
+        RespFrame::Integer(backend.replicas_acked(offset, false) as i64)
+    }
+}
+
+impl WaitAof {
+    pub async fn execute_for(self, client: &ClientState, backend: &Backend) -> RespFrame {
+        if backend.is_replica() {
+            return SimpleError::new("ERR WAITAOF cannot be used with replica instances.").into();
+        }
+        if self.numlocal > 0 && !backend.config().appendonly {
+            return SimpleError::new(
+                "ERR WAITAOF cannot be used when numlocal is set but appendonly is disabled.",
+            )
+            .into();
+        }
+        let offset = client.woff;
+        let reached = || {
+            let local = usize::from(backend.aof_fsynced_offset() >= offset);
+            local >= self.numlocal && backend.replicas_acked(offset, true) >= self.numreplicas
+        };
+        if !reached() {
+            backend.request_acks();
+            backend.wait_progress(self.timeout, reached).await;
+        }
+        aof_acks(backend, offset)
+    }
+}
+
+fn aof_acks(backend: &Backend, offset: u64) -> RespFrame {
+    let local = backend.config().appendonly && backend.aof_fsynced_offset() >= offset;
+    RespArray::new([
+        RespFrame::Integer(local as i64),
+        RespFrame::Integer(backend.replicas_acked(offset, true) as i64),
+    ])
+    .into()
+}
+
+fn extract_count(value: Option<RespFrame>) -> Result<usize, CommandError> {
+    usize::try_from(extract_integer(value)?).map_err(|_| {
+        CommandError::InvalidArgument("value is out of range, must be positive".to_string())
+    })
+}
+
+// in milliseconds, 0 is no timeout
+fn extract_timeout_ms(value: Option<RespFrame>) -> Result<Option<Duration>, CommandError> {
+    let timeout = extract_integer(value)?;
+    if timeout < 0 {
+        return Err(CommandError::InvalidArgument(
+            "timeout is negative".to_string(),
+        ));
+    }
+    Ok((timeout > 0).then(|| Duration::from_millis(timeout as u64)))
+}
+
+impl TryFrom<RespArray> for Wait {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "wait")?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(Wait {
+            numreplicas: extract_count(args.next())?,
+            timeout: extract_timeout_ms(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for WaitAof {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "waitaof")?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(WaitAof {
+            numlocal: extract_count(args.next())?,
+            numreplicas: extract_count(args.next())?,
+            timeout: extract_timeout_ms(args.next())?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::backend::ReplicaSender;
+    use crate::cmd::request;
+    use anyhow::Result;
+
+    #[tokio::test]
+    async fn test_wait_command() -> Result<()> {
+        let backend = Backend::new();
+        let (sender, _stream) = ReplicaSender::new(1024);
+        backend.psync(1, "?", -1, sender);
+        backend.feed_replication(b"*1\r\n$4\r\nPING\r\n");
+        let client = ClientState {
+            woff: backend.master_repl_offset(),
+            ..ClientState::default()
+        };
+
+        // the replica did not ack, the wait times out
+        let wait = Wait::try_from(request(&["wait", "1", "20"]))?;
+        assert_eq!(
+            wait.execute_for(&client, &backend).await,
+            RespFrame::Integer(0)
+        );
+
+        // an ack arriving while waiting wakes the client
+        let acker = backend.clone();
+        let offset = client.woff;
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            acker.replica_ack(1, offset + 100, 0);
+        });
+        let wait = Wait::try_from(request(&["wait", "1", "0"]))?;
+        assert_eq!(
+            wait.execute_for(&client, &backend).await,
+            RespFrame::Integer(1)
+        );
+
+        // the replica has no log, WAITAOF never counts it
+        let wait = WaitAof::try_from(request(&["waitaof", "0", "1", "20"]))?;
+        assert_eq!(
+            wait.execute_for(&client, &backend).await,
+            RespArray::new([RespFrame::Integer(0), RespFrame::Integer(0)]).into()
+        );
+        let wait = WaitAof::try_from(request(&["waitaof", "1", "0", "0"]))?;
+        assert_eq!(
+            wait.execute_for(&client, &backend).await,
+            SimpleError::new(
+                "ERR WAITAOF cannot be used when numlocal is set but appendonly is disabled."
+            )
+            .into()
+        );
+        assert!(Wait::try_from(request(&["wait", "1", "-1"])).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,98 @@
+use crate::cmd::{
+    extract_args, extract_string, validate_command, CommandError, CommandExecutor, RESP_OK,
+};
+use crate::network::ClientState;
+use crate::resp::{RespArray, RespFrame, SimpleError};
+use crate::Backend;
+
+// WATCH key [key ...]
+// UNWATCH
+// the next EXEC of the client fails if any watched key is written before it runs, EXEC and
+// DISCARD drop the watches
+
+#[derive(Debug)]
+pub struct Watch {
+    keys: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct Unwatch;
+
+impl CommandExecutor for Watch {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        self.execute_for(&mut ClientState::default(), backend)
+    }
+}
+
+impl CommandExecutor for Unwatch {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        self.execute_for(&mut ClientState::default(), backend)
+    }
+}
+
+impl Watch {
+    pub fn execute_for(self, client: &mut ClientState, backend: &Backend) -> RespFrame {
+        if client.multi.is_some() {
+            return SimpleError::new("ERR WATCH inside MULTI is not allowed").into();
+        }
+        for key in self.keys {
+            // watching a key twice keeps the first version
+            if client.watched.iter().any(|(watched, _)| *watched == key) {
+                continue;
+            }
+            let version = backend.watch(&key);
+            client.watched.push((key, version));
+        }
+        RESP_OK.clone()
+    }
+}
+
+impl Unwatch {
+    pub fn execute_for(self, client: &mut ClientState, backend: &Backend) -> RespFrame {
+        client.unwatch_all(backend);
+        RESP_OK.clone()
+    }
+}
+
+impl TryFrom<RespArray> for Watch {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "watch")?;
+        let keys = extract_args(value, 1)?
+            .into_iter()
+            .map(|arg| extract_string(Some(arg)))
+            .collect::<Result<Vec<String>, CommandError>>()?;
+        Ok(Watch { keys })
+    }
+}
+
+impl TryFrom<RespArray> for Unwatch {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "unwatch")?;
+        Ok(Unwatch)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_watch_command() {
+        let backend = Backend::new();
+        let mut client = ClientState::default();
+        let cmd = Watch {
+            keys: vec!["a".to_string(), "b".to_string(), "a".to_string()],
+        };
+        assert_eq!(cmd.execute_for(&mut client, &backend), RESP_OK.clone());
+        assert_eq!(
+            client.watched,
+            vec![("a".to_string(), 0), ("b".to_string(), 0)]
+        );
+        assert_eq!(Unwatch.execute_for(&mut client, &backend), RESP_OK.clone());
+        assert!(client.watched.is_empty());
+        // the backend stops tracking keys nobody watches
+        assert!(backend.watched_key_changed("a", 0));
+    }
+}
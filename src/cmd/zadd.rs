@@ -0,0 +1,153 @@
+use crate::backend::ZAddOptions;
+use crate::cmd::{
+    extract_args, extract_float, extract_string, validate_command, CommandError, CommandExecutor,
+};
+use crate::resp::{RespArray, RespFrame, RespNull};
+use crate::Backend;
+
+// ZADD key [NX | XX] [GT | LT] [CH] [INCR] score member [score member ...]
+// returns the number of added members, or added and updated members with CH
+// with INCR the score is incremented and the new score is returned, nil if the update was skipped
+
+// redis> ZADD myzset 1 "one"
+// (integer) 1
+// redis> ZADD myzset 1 "uno" 2 "two" 3 "three"
+// (integer) 3
+// redis> ZADD myzset XX CH 4 "one"
+// (integer) 1
+
+#[derive(Debug)]
+pub struct ZAdd {
+    key: String,
+    options: ZAddOptions,
+    members: Vec<(f64, String)>,
+}
+
+impl CommandExecutor for ZAdd {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let incr = self.options.incr;
+        let ch = self.options.ch;
+        match backend.zadd(&self.key, self.members, self.options) {
+            Ok(outcome) if incr => match outcome.score {
+                Some(score) => RespFrame::Double(score),
+                None => RespFrame::Null(RespNull),
+            },
+            Ok(outcome) if ch => RespFrame::Integer((outcome.added + outcome.updated) as i64),
+            Ok(outcome) => RespFrame::Integer(outcome.added as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for ZAdd {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "zadd")?;
+        let mut args = extract_args(value, 1)?.into_iter().peekable();
+        let key = extract_string(args.next())?;
+
+        let mut options = ZAddOptions::default();
+        while let Some(RespFrame::BulkString(arg)) = args.peek() {
+            let flag = match arg.to_ascii_lowercase().as_slice() {
+                b"nx" => &mut options.nx,
+                b"xx" => &mut options.xx,
+                b"gt" => &mut options.gt,
+                b"lt" => &mut options.lt,
+                b"ch" => &mut options.ch,
+                b"incr" => &mut options.incr,
+                _ => break,
+            };
+            *flag = true;
+            args.next();
+        }
+        if options.nx && options.xx {
+            return Err(CommandError::InvalidArgument(
+                "XX and NX options at the same time are not compatible".to_string(),
+            ));
+        }
+        if (options.gt && options.lt) || (options.nx && (options.gt || options.lt)) {
+            return Err(CommandError::InvalidArgument(
+                "GT, LT, and/or NX options at the same time are not compatible".to_string(),
+            ));
+        }
+
+        let rest = args.collect::<Vec<RespFrame>>();
+        if rest.is_empty() || rest.len() % 2 != 0 {
+            return Err(CommandError::InvalidArgument("syntax error".to_string()));
+        }
+        if options.incr && rest.len() > 2 {
+            return Err(CommandError::InvalidArgument(
+                "INCR option supports a single increment-element pair".to_string(),
+            ));
+        }
+        let mut members = Vec::with_capacity(rest.len() / 2);
+        let mut rest = rest.into_iter();
+        while let Some(score) = rest.next() {
+            members.push((extract_float(Some(score))?, extract_string(rest.next())?));
+        }
+        Ok(ZAdd {
+            key,
+            options,
+            members,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::resp::RespDecode;
+    use bytes::BytesMut;
+
+    #[test]
+    fn test_zadd_from_resp_array() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*6\r\n$4\r\nzadd\r\n$6\r\nmyzset\r\n$2\r\nxx\r\n$2\r\nCH\r\n$4\r\n-inf\r\n$3\r\none\r\n",
+        );
+
+        let frame = RespArray::decode(&mut buf)?;
+        let result: ZAdd = frame.try_into()?;
+        assert_eq!(result.key, "myzset");
+        assert!(result.options.xx && result.options.ch);
+        assert_eq!(result.members, vec![(f64::NEG_INFINITY, "one".to_string())]);
+
+        buf.extend_from_slice(
+            b"*5\r\n$4\r\nzadd\r\n$1\r\nz\r\n$2\r\nnx\r\n$2\r\ngt\r\n$1\r\n1\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(ZAdd::try_from(frame).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_zadd_command() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let cmd = ZAdd {
+            key: "myzset".to_string(),
+            options: ZAddOptions::default(),
+            members: vec![(1.0, "uno".to_string()), (2.0, "two".to_string())],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(2));
+        let cmd = ZAdd {
+            key: "myzset".to_string(),
+            options: ZAddOptions {
+                incr: true,
+                ..Default::default()
+            },
+            members: vec![(1.5, "two".to_string())],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Double(3.5));
+        let cmd = ZAdd {
+            key: "myzset".to_string(),
+            options: ZAddOptions {
+                nx: true,
+                incr: true,
+                ..Default::default()
+            },
+            members: vec![(1.0, "two".to_string())],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Null(RespNull));
+        Ok(())
+    }
+}
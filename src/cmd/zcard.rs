@@ -0,0 +1,53 @@
+use crate::cmd::{extract_args, extract_string, validate_command, CommandError, CommandExecutor};
+use crate::resp::{RespArray, RespFrame};
+use crate::Backend;
+
+// ZCARD key
+// returns the number of members in the sorted set, 0 if the key does not exist
+
+#[derive(Debug)]
+pub struct ZCard {
+    key: String,
+}
+
+impl CommandExecutor for ZCard {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.zcard(&self.key) {
+            Ok(len) => RespFrame::Integer(len as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for ZCard {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "zcard")?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(ZCard {
+            key: extract_string(args.next())?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::backend::ZAddOptions;
+
+    #[test]
+    fn test_zcard_command() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let cmd = ZCard {
+            key: "myzset".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+        let members = vec![(1.0, "one".to_string()), (1.0, "two".to_string())];
+        backend.zadd("myzset", members, ZAddOptions::default())?;
+        let cmd = ZCard {
+            key: "myzset".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(2));
+        Ok(())
+    }
+}
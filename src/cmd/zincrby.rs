@@ -0,0 +1,84 @@
+use crate::backend::ZAddOptions;
+use crate::cmd::{
+    extract_args, extract_float, extract_string, validate_command, CommandError, CommandExecutor,
+};
+use crate::resp::{RespArray, RespFrame};
+use crate::Backend;
+
+// ZINCRBY key increment member
+// returns the new score of the member, a missing member starts from 0
+
+// redis> ZADD myzset 1 "one"
+// (integer) 1
+// redis> ZINCRBY myzset 2 "one"
+// (double) 3
+
+#[derive(Debug)]
+pub struct ZIncrBy {
+    key: String,
+    increment: f64,
+    member: String,
+}
+
+impl CommandExecutor for ZIncrBy {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let options = ZAddOptions {
+            incr: true,
+            ..Default::default()
+        };
+        match backend.zadd(&self.key, vec![(self.increment, self.member)], options) {
+            Ok(outcome) => RespFrame::Double(outcome.score.unwrap_or(self.increment)),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for ZIncrBy {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "zincrby")?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(ZIncrBy {
+            key: extract_string(args.next())?,
+            increment: extract_float(args.next())?,
+            member: extract_string(args.next())?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::resp::RespDecode;
+    use bytes::BytesMut;
+
+    #[test]
+    fn test_zincrby_from_resp_array() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$7\r\nzincrby\r\n$6\r\nmyzset\r\n$3\r\n2.5\r\n$3\r\none\r\n");
+
+        let frame = RespArray::decode(&mut buf)?;
+        let result: ZIncrBy = frame.try_into()?;
+        assert_eq!(result.key, "myzset");
+        assert_eq!(result.increment, 2.5);
+        assert_eq!(result.member, "one");
+        Ok(())
+    }
+
+    #[test]
+    fn test_zincrby_command() {
+        let backend = Backend::new();
+        let cmd = ZIncrBy {
+            key: "myzset".to_string(),
+            increment: 2.0,
+            member: "one".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Double(2.0));
+        let cmd = ZIncrBy {
+            key: "myzset".to_string(),
+            increment: -0.5,
+            member: "one".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Double(1.5));
+    }
+}
@@ -0,0 +1,306 @@
+use crate::backend::{LexBound, ScoreBound, ZRangeBy};
+use crate::cmd::{
+    extract_args, extract_integer, extract_string, validate_command, CommandError, CommandExecutor,
+};
+use crate::resp::{BulkString, RespArray, RespFrame};
+use crate::Backend;
+
+// ZRANGE key start stop [BYSCORE | BYLEX] [REV] [LIMIT offset count] [WITHSCORES]
+// ZRANGEBYSCORE key min max [WITHSCORES] [LIMIT offset count]
+// with REV the order is reversed and BYSCORE/BYLEX take the max bound first
+// score bounds are inclusive unless prefixed with '(', lex bounds start with '[' or '(', or are '-'/'+'
+
+// redis> ZADD myzset 1 "one" 2 "two" 3 "three"
+// (integer) 3
+// redis> ZRANGE myzset (1 +inf BYSCORE WITHSCORES
+// 1) "two"
+// 2) (double) 2
+// 3) "three"
+// 4) (double) 3
+// redis> ZRANGE myzset 0 0 REV
+// 1) "three"
+
+#[derive(Debug)]
+pub struct ZRange {
+    key: String,
+    by: ZRangeBy,
+    rev: bool,
+    limit: Option<(i64, i64)>,
+    with_scores: bool,
+}
+
+#[derive(Debug)]
+pub struct ZRangeByScore {
+    key: String,
+    min: ScoreBound,
+    max: ScoreBound,
+    limit: Option<(i64, i64)>,
+    with_scores: bool,
+}
+
+impl CommandExecutor for ZRange {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        zrange_generic(
+            backend,
+            &self.key,
+            &self.by,
+            self.rev,
+            self.limit,
+            self.with_scores,
+        )
+    }
+}
+
+impl CommandExecutor for ZRangeByScore {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let by = ZRangeBy::Score(self.min, self.max);
+        zrange_generic(backend, &self.key, &by, false, self.limit, self.with_scores)
+    }
+}
+
+fn zrange_generic(
+    backend: &Backend,
+    key: &str,
+    by: &ZRangeBy,
+    rev: bool,
+    limit: Option<(i64, i64)>,
+    with_scores: bool,
+) -> RespFrame {
+    let limit = match limit {
+        // a negative offset selects nothing
+        Some((offset, _)) if offset < 0 => return RespArray::new(vec![]).into(),
+        Some((offset, count)) => Some((offset as usize, count)),
+        None => None,
+    };
+    match backend.zrange(key, by, rev, limit) {
+        Ok(range) => {
+            let mut frames = Vec::with_capacity(range.len() * if with_scores { 2 } else { 1 });
+            for (member, score) in range {
+                frames.push(BulkString::new(member).into());
+                if with_scores {
+                    frames.push(RespFrame::Double(score));
+                }
+            }
+            RespArray::new(frames).into()
+        }
+        Err(e) => e.into(),
+    }
+}
+
+fn parse_score_bound(value: Option<RespFrame>) -> Result<ScoreBound, CommandError> {
+    let s = extract_string(value)?;
+    let (s, exclusive) = match s.strip_prefix('(') {
+        Some(s) => (s, true),
+        None => (s.as_str(), false),
+    };
+    match s.parse::<f64>() {
+        Ok(value) if !value.is_nan() => Ok(ScoreBound { value, exclusive }),
+        _ => Err(CommandError::InvalidArgument(
+            "min or max is not a float".to_string(),
+        )),
+    }
+}
+
+fn parse_lex_bound(value: Option<RespFrame>) -> Result<LexBound, CommandError> {
+    let s = extract_string(value)?;
+    match s.as_str() {
+        "-" => Ok(LexBound::NegInf),
+        "+" => Ok(LexBound::PosInf),
+        _ if s.starts_with('[') => Ok(LexBound::Inclusive(s[1..].to_string())),
+        _ if s.starts_with('(') => Ok(LexBound::Exclusive(s[1..].to_string())),
+        _ => Err(CommandError::InvalidArgument(
+            "min or max not valid string range item".to_string(),
+        )),
+    }
+}
+
+impl TryFrom<RespArray> for ZRange {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "zrange")?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let (start, stop) = (args.next(), args.next());
+
+        let (mut by_score, mut by_lex, mut rev, mut with_scores) = (false, false, false, false);
+        let mut limit = None;
+        while let Some(arg) = args.next() {
+            match extract_string(Some(arg))?.to_ascii_lowercase().as_str() {
+                "byscore" => by_score = true,
+                "bylex" => by_lex = true,
+                "rev" => rev = true,
+                "withscores" => with_scores = true,
+                "limit" => {
+                    limit = Some((extract_integer(args.next())?, extract_integer(args.next())?))
+                }
+                _ => return Err(CommandError::InvalidArgument("syntax error".to_string())),
+            }
+        }
+        if by_score && by_lex {
+            return Err(CommandError::InvalidArgument("syntax error".to_string()));
+        }
+        if limit.is_some() && !by_score && !by_lex {
+            return Err(CommandError::InvalidArgument(
+                "syntax error, LIMIT is only supported in combination with either BYSCORE or BYLEX"
+                    .to_string(),
+            ));
+        }
+        if with_scores && by_lex {
+            return Err(CommandError::InvalidArgument(
+                "syntax error, WITHSCORES not supported in combination with BYLEX".to_string(),
+            ));
+        }
+
+        // in reverse order the bounds are given from max to min
+        let (start, stop) = if rev && (by_score || by_lex) {
+            (stop, start)
+        } else {
+            (start, stop)
+        };
+        let by = if by_score {
+            ZRangeBy::Score(parse_score_bound(start)?, parse_score_bound(stop)?)
+        } else if by_lex {
+            ZRangeBy::Lex(parse_lex_bound(start)?, parse_lex_bound(stop)?)
+        } else {
+            ZRangeBy::Rank(extract_integer(start)?, extract_integer(stop)?)
+        };
+        Ok(ZRange {
+            key,
+            by,
+            rev,
+            limit,
+            with_scores,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZRangeByScore {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "zrangebyscore")?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let min = parse_score_bound(args.next())?;
+        let max = parse_score_bound(args.next())?;
+
+        let (mut limit, mut with_scores) = (None, false);
+        while let Some(arg) = args.next() {
+            match extract_string(Some(arg))?.to_ascii_lowercase().as_str() {
+                "withscores" => with_scores = true,
+                "limit" => {
+                    limit = Some((extract_integer(args.next())?, extract_integer(args.next())?))
+                }
+                _ => return Err(CommandError::InvalidArgument("syntax error".to_string())),
+            }
+        }
+        Ok(ZRangeByScore {
+            key,
+            min,
+            max,
+            limit,
+            with_scores,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::backend::ZAddOptions;
+    use crate::resp::RespDecode;
+    use bytes::BytesMut;
+
+    fn setup() -> anyhow::Result<Backend> {
+        let backend = Backend::new();
+        let members = [(1.0, "one"), (2.0, "two"), (3.0, "three")]
+            .map(|(s, m)| (s, m.to_string()))
+            .to_vec();
+        backend.zadd("myzset", members, ZAddOptions::default())?;
+        Ok(backend)
+    }
+
+    #[test]
+    fn test_zrange_from_resp_array() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*9\r\n$6\r\nzrange\r\n$6\r\nmyzset\r\n$4\r\n+inf\r\n$2\r\n(1\r\n$7\r\nBYSCORE\r\n$3\r\nREV\r\n$5\r\nLIMIT\r\n$1\r\n0\r\n$1\r\n1\r\n");
+
+        let frame = RespArray::decode(&mut buf)?;
+        let result: ZRange = frame.try_into()?;
+        assert_eq!(result.key, "myzset");
+        assert_eq!(
+            result.by,
+            ZRangeBy::Score(
+                ScoreBound {
+                    value: 1.0,
+                    exclusive: true
+                },
+                ScoreBound {
+                    value: f64::INFINITY,
+                    exclusive: false
+                }
+            )
+        );
+        assert!(result.rev);
+        assert_eq!(result.limit, Some((0, 1)));
+
+        buf.extend_from_slice(b"*7\r\n$6\r\nzrange\r\n$1\r\nz\r\n$1\r\n0\r\n$1\r\n1\r\n$5\r\nLIMIT\r\n$1\r\n0\r\n$1\r\n1\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(ZRange::try_from(frame).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_zrange_command() -> anyhow::Result<()> {
+        let backend = setup()?;
+        let cmd = ZRange {
+            key: "myzset".to_string(),
+            by: ZRangeBy::Rank(0, 0),
+            rev: true,
+            limit: None,
+            with_scores: true,
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            RespArray::new([BulkString::new("three").into(), RespFrame::Double(3.0)]).into()
+        );
+        let cmd = ZRange {
+            key: "myzset".to_string(),
+            by: ZRangeBy::Lex(LexBound::Exclusive("one".to_string()), LexBound::PosInf),
+            rev: false,
+            limit: Some((0, -1)),
+            with_scores: false,
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            RespArray::new([
+                BulkString::new("two").into(),
+                BulkString::new("three").into()
+            ])
+            .into()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_zrangebyscore_command() -> anyhow::Result<()> {
+        let backend = setup()?;
+        let cmd = ZRangeByScore {
+            key: "myzset".to_string(),
+            min: ScoreBound {
+                value: f64::NEG_INFINITY,
+                exclusive: false,
+            },
+            max: ScoreBound {
+                value: 2.0,
+                exclusive: false,
+            },
+            limit: Some((1, 5)),
+            with_scores: false,
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            RespArray::new([BulkString::new("two").into()]).into()
+        );
+        Ok(())
+    }
+}
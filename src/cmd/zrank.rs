@@ -0,0 +1,157 @@
+use crate::cmd::{extract_args, extract_string, validate_command, CommandError, CommandExecutor};
+use crate::resp::{RespArray, RespFrame, RespNull};
+use crate::Backend;
+
+// ZRANK key member [WITHSCORE]
+// ZREVRANK key member [WITHSCORE]
+// returns the 0-based rank of the member ordered from the lowest (ZRANK) or highest (ZREVRANK) score
+
+// redis> ZADD myzset 1 "one" 2 "two" 3 "three"
+// (integer) 3
+// redis> ZRANK myzset "three"
+// (integer) 2
+// redis> ZREVRANK myzset "three" WITHSCORE
+// 1) (integer) 0
+// 2) (double) 3
+
+#[derive(Debug)]
+pub struct ZRank {
+    key: String,
+    member: String,
+    with_score: bool,
+}
+
+#[derive(Debug)]
+pub struct ZRevRank {
+    key: String,
+    member: String,
+    with_score: bool,
+}
+
+impl CommandExecutor for ZRank {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        zrank_generic(backend, &self.key, &self.member, false, self.with_score)
+    }
+}
+
+impl CommandExecutor for ZRevRank {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        zrank_generic(backend, &self.key, &self.member, true, self.with_score)
+    }
+}
+
+fn zrank_generic(
+    backend: &Backend,
+    key: &str,
+    member: &str,
+    rev: bool,
+    with_score: bool,
+) -> RespFrame {
+    match backend.zrank(key, member, rev) {
+        Ok(Some((rank, score))) if with_score => {
+            RespArray::new([RespFrame::Integer(rank as i64), RespFrame::Double(score)]).into()
+        }
+        Ok(Some((rank, _))) => RespFrame::Integer(rank as i64),
+        Ok(None) => RespFrame::Null(RespNull),
+        Err(e) => e.into(),
+    }
+}
+
+fn parse_rank_args(
+    value: RespArray,
+    name: &'static str,
+) -> Result<(String, String, bool), CommandError> {
+    validate_command(&value, name)?;
+    let mut args = extract_args(value, 1)?.into_iter();
+    let key = extract_string(args.next())?;
+    let member = extract_string(args.next())?;
+    let with_score = match args.next() {
+        Some(arg) => {
+            if !extract_string(Some(arg))?.eq_ignore_ascii_case("withscore") {
+                return Err(CommandError::InvalidArgument("syntax error".to_string()));
+            }
+            true
+        }
+        None => false,
+    };
+    if args.next().is_some() {
+        return Err(CommandError::InvalidArgument("syntax error".to_string()));
+    }
+    Ok((key, member, with_score))
+}
+
+impl TryFrom<RespArray> for ZRank {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, member, with_score) = parse_rank_args(value, "zrank")?;
+        Ok(ZRank {
+            key,
+            member,
+            with_score,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZRevRank {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, member, with_score) = parse_rank_args(value, "zrevrank")?;
+        Ok(ZRevRank {
+            key,
+            member,
+            with_score,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::backend::ZAddOptions;
+    use crate::resp::RespDecode;
+    use bytes::BytesMut;
+
+    #[test]
+    fn test_zrank_from_resp_array() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*4\r\n$5\r\nzrank\r\n$6\r\nmyzset\r\n$3\r\none\r\n$9\r\nWITHSCORE\r\n",
+        );
+
+        let frame = RespArray::decode(&mut buf)?;
+        let result: ZRank = frame.try_into()?;
+        assert_eq!(result.key, "myzset");
+        assert_eq!(result.member, "one");
+        assert!(result.with_score);
+        Ok(())
+    }
+
+    #[test]
+    fn test_zrank_command() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let members = vec![(1.0, "one".to_string()), (2.0, "two".to_string())];
+        backend.zadd("myzset", members, ZAddOptions::default())?;
+        let cmd = ZRank {
+            key: "myzset".to_string(),
+            member: "two".to_string(),
+            with_score: false,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        let cmd = ZRevRank {
+            key: "myzset".to_string(),
+            member: "two".to_string(),
+            with_score: true,
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            RespArray::new([RespFrame::Integer(0), RespFrame::Double(2.0)]).into()
+        );
+        let cmd = ZRank {
+            key: "myzset".to_string(),
+            member: "three".to_string(),
+            with_score: false,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Null(RespNull));
+        Ok(())
+    }
+}
@@ -0,0 +1,54 @@
+use crate::cmd::{extract_args, extract_string, validate_command, CommandError, CommandExecutor};
+use crate::resp::{RespArray, RespFrame};
+use crate::Backend;
+
+// ZREM key member [member ...]
+// returns the number of members removed, the key is deleted once the sorted set is empty
+
+#[derive(Debug)]
+pub struct ZRem {
+    key: String,
+    members: Vec<String>,
+}
+
+impl CommandExecutor for ZRem {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.zrem(&self.key, &self.members) {
+            Ok(removed) => RespFrame::Integer(removed as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for ZRem {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "zrem")?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let members = args
+            .map(|arg| extract_string(Some(arg)))
+            .collect::<Result<Vec<String>, CommandError>>()?;
+        Ok(ZRem { key, members })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::backend::ZAddOptions;
+
+    #[test]
+    fn test_zrem_command() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let members = vec![(1.0, "one".to_string()), (2.0, "two".to_string())];
+        backend.zadd("myzset", members, ZAddOptions::default())?;
+        let cmd = ZRem {
+            key: "myzset".to_string(),
+            members: vec!["one".to_string(), "three".to_string()],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        assert_eq!(backend.zcard("myzset")?, 1);
+        Ok(())
+    }
+}
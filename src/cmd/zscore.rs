@@ -0,0 +1,66 @@
+use crate::cmd::{extract_args, extract_string, validate_command, CommandError, CommandExecutor};
+use crate::resp::{RespArray, RespFrame, RespNull};
+use crate::Backend;
+
+// ZSCORE key member
+// returns the score of the member, nil if the member or the key does not exist
+
+// redis> ZADD myzset 1 "one"
+// (integer) 1
+// redis> ZSCORE myzset "one"
+// (double) 1
+
+#[derive(Debug)]
+pub struct ZScore {
+    key: String,
+    member: String,
+}
+
+impl CommandExecutor for ZScore {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.zscore(&self.key, &self.member) {
+            Ok(Some(score)) => RespFrame::Double(score),
+            Ok(None) => RespFrame::Null(RespNull),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for ZScore {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, "zscore")?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(ZScore {
+            key: extract_string(args.next())?,
+            member: extract_string(args.next())?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::backend::ZAddOptions;
+
+    #[test]
+    fn test_zscore_command() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        backend.zadd(
+            "myzset",
+            vec![(1.5, "one".to_string())],
+            ZAddOptions::default(),
+        )?;
+        let cmd = ZScore {
+            key: "myzset".to_string(),
+            member: "one".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Double(1.5));
+        let cmd = ZScore {
+            key: "myzset".to_string(),
+            member: "two".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Null(RespNull));
+        Ok(())
+    }
+}
@@ -0,0 +1,304 @@
+use crate::resp::RespLimits;
+use anyhow::{anyhow, Result};
+use std::path::PathBuf;
+
+// server settings, parsed from redis-server style arguments: --port 6380 --save "60 100"
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    pub bind: String,
+    pub port: u16,
+    // directory of the snapshot file
+    pub dir: PathBuf,
+    pub dbfilename: String,
+    // a snapshot is taken once `changes` writes happened and `seconds` elapsed since the last one
+    pub save: Vec<SavePoint>,
+    // every write is also appended to `appendfilename` in `dir`
+    pub appendonly: bool,
+    pub appendfilename: String,
+    pub appendfsync: AppendFsync,
+    // largest string a client may send or build with APPEND and SETRANGE
+    pub proto_max_bulk_len: usize,
+    // most arguments of a request, and how deep its frames may nest
+    pub proto_max_multibulk_len: usize,
+    pub proto_max_nesting: usize,
+    // the master to replicate at startup, the server is a master when None
+    pub replicaof: Option<(String, u16)>,
+    // bytes of the replication stream kept for the replicas resuming after a disconnection
+    pub repl_backlog_size: usize,
+    // bytes of the stream a replica may fall behind by before it is dropped
+    pub replica_queue_limit: usize,
+    // published messages a subscriber may fall behind by before it is disconnected
+    pub pubsub_queue_limit: usize,
+    // the keys are split among the nodes of a cluster, which talk over the bus port
+    pub cluster_enabled: bool,
+    // the port of the cluster bus, 0 is the client port plus 10000
+    pub cluster_port: u16,
+    // milliseconds without a reply before a node is suspected to be down
+    pub cluster_node_timeout: u64,
+    // milliseconds a script may run before the other clients are answered BUSY and it can be
+    // killed
+    pub busy_reply_threshold: u64,
+}
+
+// when the append only file is flushed to the disk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AppendFsync {
+    // after every write, before the reply is sent
+    Always,
+    // once per second in the background
+    EverySec,
+    // left to the operating system
+    No,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SavePoint {
+    pub seconds: u64,
+    pub changes: u64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            bind: "127.0.0.1".to_string(),
+            port: 6379,
+            dir: PathBuf::from("."),
+            dbfilename: "dump.rdb".to_string(),
+            save: vec![
+                SavePoint {
+                    seconds: 3600,
+                    changes: 1,
+                },
+                SavePoint {
+                    seconds: 300,
+                    changes: 100,
+                },
+                SavePoint {
+                    seconds: 60,
+                    changes: 10000,
+                },
+            ],
+            appendonly: false,
+            appendfilename: "appendonly.aof".to_string(),
+            appendfsync: AppendFsync::EverySec,
+            proto_max_bulk_len: RespLimits::default().max_bulk_len,
+            proto_max_multibulk_len: RespLimits::default().max_multibulk_len,
+            proto_max_nesting: RespLimits::default().max_nesting,
+            replicaof: None,
+            repl_backlog_size: 1024 * 1024,
+            replica_queue_limit: 256 * 1024 * 1024,
+            pubsub_queue_limit: 65536,
+            cluster_enabled: false,
+            cluster_port: 0,
+            cluster_node_timeout: 15000,
+            busy_reply_threshold: 5000,
+        }
+    }
+}
+
+impl Config {
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Result<Self> {
+        let mut config = Config::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let name = arg
+                .strip_prefix("--")
+                .ok_or_else(|| anyhow!("invalid argument '{}'", arg))?
+                .to_ascii_lowercase();
+            let value = args
+                .next()
+                .ok_or_else(|| anyhow!("missing value for '{}'", arg))?;
+            match name.as_str() {
+                "bind" => config.bind = value,
+                "port" => config.port = value.parse()?,
+                "dir" => config.dir = PathBuf::from(value),
+                "dbfilename" => config.dbfilename = value,
+                "save" => config.save = parse_save_points(&value)?,
+                "appendonly" => config.appendonly = parse_yes_no(&value)?,
+                "appendfilename" => config.appendfilename = value,
+                "appendfsync" => {
+                    config.appendfsync = match value.to_ascii_lowercase().as_str() {
+                        "always" => AppendFsync::Always,
+                        "everysec" => AppendFsync::EverySec,
+                        "no" => AppendFsync::No,
+                        _ => return Err(anyhow!("invalid appendfsync '{}'", value)),
+                    }
+                }
+                "proto-max-bulk-len" => config.proto_max_bulk_len = parse_memory(&value)?,
+                "proto-max-multibulk-len" => config.proto_max_multibulk_len = value.parse()?,
+                "proto-max-nesting" => config.proto_max_nesting = value.parse()?,
+                "replicaof" => config.replicaof = parse_replicaof(&value)?,
+                "repl-backlog-size" => config.repl_backlog_size = parse_memory(&value)?,
+                "replica-queue-limit" => config.replica_queue_limit = parse_memory(&value)?,
+                "pubsub-queue-limit" => config.pubsub_queue_limit = value.parse()?,
+                "cluster-enabled" => config.cluster_enabled = parse_yes_no(&value)?,
+                "cluster-port" => config.cluster_port = value.parse()?,
+                "cluster-node-timeout" => config.cluster_node_timeout = value.parse()?,
+                "busy-reply-threshold" | "lua-time-limit" => {
+                    config.busy_reply_threshold = value.parse()?
+                }
+                _ => return Err(anyhow!("unknown option '{}'", arg)),
+            }
+        }
+        if config.cluster_enabled && config.cluster_port == 0 && config.port > u16::MAX - 10000 {
+            return Err(anyhow!(
+                "port {} is too high for the cluster bus, set cluster-port",
+                config.port
+            ));
+        }
+        Ok(config)
+    }
+
+    pub fn rdb_path(&self) -> PathBuf {
+        self.dir.join(&self.dbfilename)
+    }
+
+    pub fn aof_path(&self) -> PathBuf {
+        self.dir.join(&self.appendfilename)
+    }
+
+    pub fn cluster_bus_port(&self) -> u16 {
+        match self.cluster_port {
+            0 => self.port.saturating_add(10000),
+            port => port,
+        }
+    }
+
+    pub fn resp_limits(&self) -> RespLimits {
+        RespLimits {
+            max_bulk_len: self.proto_max_bulk_len,
+            max_multibulk_len: self.proto_max_multibulk_len,
+            max_nesting: self.proto_max_nesting,
+        }
+    }
+}
+
+fn parse_yes_no(value: &str) -> Result<bool> {
+    match value.to_ascii_lowercase().as_str() {
+        "yes" => Ok(true),
+        "no" => Ok(false),
+        _ => Err(anyhow!("argument must be 'yes' or 'no', got '{}'", value)),
+    }
+}
+
+// "host port" of the master, or "no one"
+fn parse_replicaof(value: &str) -> Result<Option<(String, u16)>> {
+    match value.split_whitespace().collect::<Vec<_>>().as_slice() {
+        [no, one] if no.eq_ignore_ascii_case("no") && one.eq_ignore_ascii_case("one") => Ok(None),
+        [host, port] => Ok(Some((host.to_string(), port.parse()?))),
+        _ => Err(anyhow!("invalid replicaof '{}'", value)),
+    }
+}
+
+// a byte count with an optional unit: 1k is 1000 bytes, 1kb is 1024 bytes, likewise m, mb, g, gb
+fn parse_memory(value: &str) -> Result<usize> {
+    let lower = value.to_ascii_lowercase();
+    let digits = lower.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    let unit = match &lower[digits.len()..] {
+        "" | "b" => 1,
+        "k" => 1000,
+        "kb" => 1024,
+        "m" => 1000 * 1000,
+        "mb" => 1024 * 1024,
+        "g" => 1000 * 1000 * 1000,
+        "gb" => 1024 * 1024 * 1024,
+        _ => return Err(anyhow!("invalid memory value '{}'", value)),
+    };
+    digits
+        .parse::<usize>()?
+        .checked_mul(unit)
+        .ok_or_else(|| anyhow!("memory value '{}' is out of range", value))
+}
+
+// "3600 1 300 100" is two save points, an empty string disables snapshots
+fn parse_save_points(value: &str) -> Result<Vec<SavePoint>> {
+    let numbers = value
+        .split_whitespace()
+        .map(|n| n.parse::<u64>())
+        .collect::<Result<Vec<u64>, _>>()?;
+    if !numbers.len().is_multiple_of(2) {
+        return Err(anyhow!("invalid save parameters '{}'", value));
+    }
+    Ok(numbers
+        .chunks(2)
+        .map(|c| SavePoint {
+            seconds: c[0],
+            changes: c[1],
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn args(v: &[&str]) -> Vec<String> {
+        v.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_config_from_args() -> Result<()> {
+        let config = Config::from_args(args(&[
+            "--port",
+            "6380",
+            "--dir",
+            "/tmp",
+            "--save",
+            "60 1 10 5",
+        ]))?;
+        assert_eq!(config.port, 6380);
+        assert_eq!(config.rdb_path(), PathBuf::from("/tmp/dump.rdb"));
+        assert_eq!(
+            config.save,
+            vec![
+                SavePoint {
+                    seconds: 60,
+                    changes: 1
+                },
+                SavePoint {
+                    seconds: 10,
+                    changes: 5
+                }
+            ]
+        );
+
+        let config = Config::from_args(args(&["--save", ""]))?;
+        assert!(config.save.is_empty());
+        assert!(!config.appendonly);
+
+        let config = Config::from_args(args(&["--appendonly", "yes", "--appendfsync", "always"]))?;
+        assert!(config.appendonly);
+        assert_eq!(config.appendfsync, AppendFsync::Always);
+        assert_eq!(config.aof_path(), PathBuf::from("./appendonly.aof"));
+        assert!(Config::from_args(args(&["--appendonly", "maybe"])).is_err());
+        assert!(Config::from_args(args(&["--save", "60"])).is_err());
+        assert!(Config::from_args(args(&["--unknown", "1"])).is_err());
+        assert!(Config::from_args(args(&["--port"])).is_err());
+
+        let config = Config::from_args(args(&["--proto-max-bulk-len", "1mb"]))?;
+        assert_eq!(config.resp_limits().max_bulk_len, 1024 * 1024);
+        assert_eq!(parse_memory("2k")?, 2000);
+        assert_eq!(parse_memory("100")?, 100);
+        assert!(parse_memory("1tb").is_err());
+
+        let config = Config::from_args(args(&["--replicaof", "10.0.0.1 6379"]))?;
+        assert_eq!(config.replicaof, Some(("10.0.0.1".to_string(), 6379)));
+        assert!(Config::from_args(args(&["--replicaof", "10.0.0.1"])).is_err());
+
+        let config = Config::from_args(args(&["--replica-queue-limit", "1mb"]))?;
+        assert_eq!(config.replica_queue_limit, 1024 * 1024);
+        let config = Config::from_args(args(&["--pubsub-queue-limit", "100"]))?;
+        assert_eq!(config.pubsub_queue_limit, 100);
+
+        let config = Config::from_args(args(&["--cluster-enabled", "yes", "--port", "7000"]))?;
+        assert!(config.cluster_enabled);
+        assert_eq!(config.cluster_bus_port(), 17000);
+        let config = Config::from_args(args(&["--cluster-port", "7100"]))?;
+        assert_eq!(config.cluster_bus_port(), 7100);
+        assert!(Config::from_args(args(&["--cluster-enabled", "yes", "--port", "60000"])).is_err());
+
+        let config = Config::from_args(args(&["--lua-time-limit", "100"]))?;
+        assert_eq!(config.busy_reply_threshold, 100);
+        Ok(())
+    }
+}
@@ -1,7 +1,15 @@
 mod backend;
+mod cluster;
 mod cmd;
+mod config;
 mod network;
+mod replication;
 mod resp;
+mod server;
 
-pub use backend::Backend;
+pub use backend::{active_expire_cycle, aof_fsync_cycle, replication_cycle, save_cycle, Backend};
+pub use cluster::{cluster_bus, cluster_cycle};
+pub use config::{AppendFsync, Config, SavePoint};
 pub use network::stream_handle;
+pub use resp::{BulkString, RespArray, RespDecode, RespEncode, RespFrame, RespParser};
+pub use server::{run, serve};
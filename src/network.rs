@@ -1,61 +1,517 @@
-use crate::backend::Backend;
-use crate::cmd::{Command, CommandExecutor};
-use crate::resp::{RespDecode, RespEncode, RespError, RespFrame};
+use crate::backend::{Backend, PsyncReply, ReplicaSender, ReplicaStream, Subscriber};
+use crate::cmd::{
+    aof_entry, command_keys, Command, CommandError, CommandExecutor, MultiState, PSync, ReplConf,
+};
+use crate::resp::{RespArray, RespError, RespFrame, RespParser, RespVersion, SimpleError};
 use anyhow::Result;
-use bytes::BytesMut;
+use bytes::{Bytes, BytesMut};
 use futures::SinkExt;
+use std::collections::HashSet;
+use std::future::Future;
+use std::pin::pin;
+use std::time::Duration;
 use tokio::net::TcpStream;
+use tokio::sync::mpsc::Receiver;
 use tokio_stream::StreamExt;
 use tokio_util::codec::{Decoder, Encoder, Framed};
 use tracing::info;
 
+// how often a client waiting on the transaction lock checks whether a script became busy
+const BUSY_CHECK_INTERVAL: Duration = Duration::from_millis(10);
+
 #[derive(Debug)]
-struct RespFrameCodec;
+struct RespFrameCodec {
+    // replies are encoded in the protocol negotiated by the connection
+    version: RespVersion,
+    // keeps the progress on a request that has not fully arrived yet
+    parser: RespParser,
+}
+
+// state of one connection, owned by its stream_handle task
+#[derive(Debug, Default)]
+pub struct ClientState {
+    pub id: u64,
+    pub protocol: RespVersion,
+    pub name: Option<String>,
+    // where published messages are queued, None when there is no connection to deliver them to
+    pub subscriber: Option<Subscriber>,
+    pub channels: HashSet<Bytes>,
+    pub patterns: HashSet<Bytes>,
+    pub shard_channels: HashSet<Bytes>,
+    // the commands queued since MULTI, None outside of a transaction
+    pub multi: Option<MultiState>,
+    // the keys watched for the next EXEC, with the version they had when watched
+    pub watched: Vec<(String, u64)>,
+    // the offset of the replication stream after the last write of the client, WAIT waits for it
+    pub woff: u64,
+    // set by ASKING, lets the next command into a slot being imported
+    pub asking: bool,
+}
+
+impl ClientState {
+    pub fn subscriptions(&self) -> usize {
+        self.channels.len() + self.patterns.len()
+    }
+
+    // in RESP2 replies and messages look alike, so a subscribed client can only manage its
+    // subscriptions
+    pub fn in_subscribe_mode(&self) -> bool {
+        self.protocol == RespVersion::Resp2 && self.subscriptions() + self.shard_channels.len() > 0
+    }
+
+    pub(crate) fn unwatch_all(&mut self, backend: &Backend) {
+        for (key, _) in self.watched.drain(..) {
+            backend.unwatch(&key);
+        }
+    }
+
+    fn unsubscribe_all(&mut self, backend: &Backend) {
+        for channel in self.channels.drain() {
+            backend.unsubscribe(&channel, self.id);
+        }
+        for pattern in self.patterns.drain() {
+            backend.punsubscribe(&pattern, self.id);
+        }
+        for channel in self.shard_channels.drain() {
+            backend.sunsubscribe(&channel, self.id);
+        }
+    }
+}
 
 #[derive(Debug)]
 struct RedisRequest {
-    frame: RespFrame,
+    cmd: Command,
+    // the raw command of a write, for the log and the replicas
+    logged: Option<RespFrame>,
     backend: Backend,
 }
 #[derive(Debug)]
 struct RedisResponse {
-    frame: RespFrame,
+    // a command may reply with several frames, e.g. one confirmation per channel subscribed to
+    frames: Vec<RespFrame>,
+}
+
+impl RedisRequest {
+    fn new(
+        frame: RespFrame,
+        backend: &Backend,
+        client: &ClientState,
+    ) -> Result<Self, CommandError> {
+        let raw = frame.clone();
+        let name = client.in_subscribe_mode().then(|| command_name(&frame));
+        let cmd = Command::try_from(frame)?;
+        if let Some(name) = name {
+            if !cmd.allowed_when_subscribed() {
+                return Err(CommandError::NotAllowedWhenSubscribed(name));
+            }
+        }
+        // the dataset of a replica only follows its master
+        if cmd.is_write() && backend.is_replica() {
+            return Err(CommandError::ReadOnly);
+        }
+        // in a cluster the keys have to be in one slot, served by this node
+        if let (true, RespFrame::Array(array)) = (backend.cluster_enabled(), &raw) {
+            backend.cluster_route(&command_keys(array), client.asking)?;
+        }
+        let logged = cmd.is_write().then_some(raw);
+        Ok(RedisRequest {
+            cmd,
+            logged,
+            backend: backend.clone(),
+        })
+    }
 }
+
 pub async fn stream_handle(stream: TcpStream, backend: Backend) -> Result<()> {
-    let mut framed = Framed::new(stream, RespFrameCodec);
+    let codec = RespFrameCodec {
+        version: RespVersion::default(),
+        parser: RespParser::with_limits(backend.config().resp_limits()),
+    };
+    let mut framed = Framed::new(stream, codec);
+    let (subscriber, messages) = Subscriber::new(backend.config().pubsub_queue_limit);
+    let mut client = ClientState {
+        id: backend.next_client_id(),
+        subscriber: Some(subscriber.clone()),
+        ..ClientState::default()
+    };
+    let ret = serve_client(&mut framed, &mut client, &backend, subscriber, messages).await;
+    // the registry must stop queueing messages for a connection that is gone
+    client.unsubscribe_all(&backend);
+    client.unwatch_all(&backend);
+    ret
+}
+
+async fn serve_client(
+    framed: &mut Framed<TcpStream, RespFrameCodec>,
+    client: &mut ClientState,
+    backend: &Backend,
+    subscriber: Subscriber,
+    mut messages: Receiver<RespFrame>,
+) -> Result<()> {
     loop {
-        match framed.next().await {
-            Some(Ok(frame)) => {
-                info!("Received Frame: {:?}", frame);
-                let request = RedisRequest {
-                    frame,
-                    backend: backend.clone(),
-                };
-                let response = request_handle(request).await?;
-                framed.send(response.frame).await?;
+        // published messages are written out between requests, whenever they arrive; a client
+        // too slow to read them is dropped, even while its socket is full
+        let mut next = tokio::select! {
+            next = framed.next() => next,
+            Some(message) = messages.recv() => {
+                tokio::select! {
+                    ret = write_messages(framed, message, &mut messages) => ret?,
+                    _ = subscriber.overflowed() => return slow_subscriber(backend),
+                }
+                continue;
             }
-            Some(Err(e)) => {
-                return Err(e);
+            _ = subscriber.overflowed() => return slow_subscriber(backend),
+        };
+        // every request already in the read buffer is served before the replies are flushed, so
+        // a pipelining client costs one write per batch rather than one per command
+        loop {
+            match next {
+                Some(Ok(frame)) => {
+                    info!("Received Frame: {:?}", frame);
+                    let request = RedisRequest::new(frame, backend, client);
+                    // ASKING only covers the command right after it
+                    client.asking = false;
+                    let frames = match request {
+                        // the connection belongs to the replication stream from here on
+                        Ok(RedisRequest {
+                            cmd: Command::PSync(psync),
+                            ..
+                        }) if client.multi.is_none() => {
+                            framed.flush().await?;
+                            return serve_replica(framed, client, backend, psync).await;
+                        }
+                        // inside a transaction only the commands ending it run right away
+                        Ok(request)
+                            if client.multi.is_some() && !request.cmd.is_transaction_control() =>
+                        {
+                            let multi = client.multi.as_mut().expect("inside a transaction");
+                            vec![multi.queue(request.cmd, request.logged)]
+                        }
+                        // replies held back for the batch go out before the client parks, and
+                        // a parked client that hangs up must leave the wait queues right away
+                        Ok(request)
+                            if request.cmd.is_blocking()
+                                || matches!(
+                                    request.cmd,
+                                    Command::Wait(_) | Command::WaitAof(_)
+                                ) =>
+                        {
+                            framed.flush().await?;
+                            tokio::select! {
+                                biased;
+                                response = request_handle(request, client) => response?.frames,
+                                _ = wait_peer_closed(framed.get_ref()) => return Ok(()),
+                            }
+                        }
+                        // the others answer even a client that half-closed after sending them
+                        Ok(request) => request_handle(request, client).await?.frames,
+                        // a malformed command only fails itself, the connection keeps serving the
+                        // next ones
+                        Err(e) => {
+                            // EXEC would run the transaction without the command that failed
+                            if let Some(multi) = client.multi.as_mut() {
+                                multi.abort();
+                            }
+                            vec![e.into()]
+                        }
+                    };
+                    framed.codec_mut().version = client.protocol;
+                    for frame in frames {
+                        framed.feed(frame).await?;
+                    }
+                }
+                Some(Err(e)) => {
+                    // like redis, a malformed request is answered and the connection closed, there
+                    // is no way to find where the next request starts
+                    let Some(e) = e.downcast_ref::<RespError>() else {
+                        return Err(e);
+                    };
+                    let message = match e {
+                        RespError::Protocol(_) => format!("ERR {}", e),
+                        _ => format!("ERR Protocol error: {}", e),
+                    };
+                    info!("Closing connection: {}", message);
+                    framed.send(SimpleError::new(message).into()).await?;
+                    return Ok(());
+                }
+                None => {
+                    framed.flush().await?;
+                    return Ok(());
+                }
             }
-            None => {
-                return Ok(());
+            match buffered_frame(framed).transpose() {
+                Some(frame) => next = Some(frame),
+                None => break,
             }
         }
+        framed.flush().await?;
+    }
+}
+
+// streams the writes to a replica after answering its PSYNC, the replica only sends back the
+// offsets it processed
+async fn serve_replica(
+    framed: &mut Framed<TcpStream, RespFrameCodec>,
+    client: &ClientState,
+    backend: &Backend,
+    psync: PSync,
+) -> Result<()> {
+    let (sender, mut stream) = ReplicaSender::new(backend.config().replica_queue_limit);
+    match psync.serve(client.id, backend, sender) {
+        PsyncReply::FullResync {
+            replid,
+            offset,
+            snapshot,
+        } => {
+            info!(
+                "Full resync of replica {} from offset {}",
+                client.id, offset
+            );
+            // the writes go on meanwhile, the replica gets them from its stream after the dump
+            let rdb = tokio::task::spawn_blocking(move || snapshot.encode()).await?;
+            let buf = framed.write_buffer_mut();
+            buf.extend_from_slice(format!("+FULLRESYNC {} {}\r\n", replid, offset).as_bytes());
+            buf.extend_from_slice(format!("${}\r\n", rdb.len()).as_bytes());
+            buf.extend_from_slice(&rdb);
+        }
+        PsyncReply::Continue { replid, backlog } => {
+            info!("Partial resync of replica {}", client.id);
+            let buf = framed.write_buffer_mut();
+            buf.extend_from_slice(format!("+CONTINUE {}\r\n", replid).as_bytes());
+            buf.extend_from_slice(&backlog);
+        }
+    }
+    let ret = forward_stream(framed, client, backend, &mut stream).await;
+    backend.remove_replica(client.id);
+    ret
+}
+
+async fn forward_stream(
+    framed: &mut Framed<TcpStream, RespFrameCodec>,
+    client: &ClientState,
+    backend: &Backend,
+    stream: &mut ReplicaStream,
+) -> Result<()> {
+    loop {
+        // a replica too slow to read the stream is dropped, even while its socket is full
+        tokio::select! {
+            ret = framed.flush() => ret?,
+            _ = stream.dropped() => return lagging_replica(client, backend),
+        }
+        tokio::select! {
+            data = stream.recv() => match data {
+                Some(data) => {
+                    framed.write_buffer_mut().extend_from_slice(&data);
+                    while let Some(data) = stream.try_recv() {
+                        framed.write_buffer_mut().extend_from_slice(&data);
+                    }
+                }
+                // the server dropped the replica, e.g. to follow a master itself
+                None => return Ok(()),
+            },
+            frame = framed.next() => match frame {
+                Some(Ok(frame)) => {
+                    if let Ok(Command::ReplConf(ReplConf::Ack { offset, fsynced })) =
+                        Command::try_from(frame)
+                    {
+                        backend.replica_ack(client.id, offset, fsynced);
+                    }
+                }
+                Some(Err(e)) => return Err(e),
+                None => return Ok(()),
+            },
+        }
+    }
+}
+
+// writes out the message and every other one already queued, with a single flush
+async fn write_messages(
+    framed: &mut Framed<TcpStream, RespFrameCodec>,
+    message: RespFrame,
+    messages: &mut Receiver<RespFrame>,
+) -> Result<()> {
+    framed.feed(message).await?;
+    while let Ok(message) = messages.try_recv() {
+        framed.feed(message).await?;
     }
+    framed.flush().await
+}
+
+fn lagging_replica(client: &ClientState, backend: &Backend) -> Result<()> {
+    info!(
+        "Closing connection: more than {} bytes of the stream queued for replica {}",
+        backend.config().replica_queue_limit,
+        client.id
+    );
+    Ok(())
+}
+
+fn slow_subscriber(backend: &Backend) -> Result<()> {
+    info!(
+        "Closing connection: more than {} messages queued for the subscriber",
+        backend.config().pubsub_queue_limit
+    );
+    Ok(())
+}
+
+// decodes the next request out of what was already read, without waiting on the socket
+fn buffered_frame(framed: &mut Framed<TcpStream, RespFrameCodec>) -> Result<Option<RespFrame>> {
+    let mut buf = std::mem::take(framed.read_buffer_mut());
+    let ret = framed.codec_mut().decode(&mut buf);
+    *framed.read_buffer_mut() = buf;
+    ret
 }
 
-async fn request_handle(request: RedisRequest) -> Result<RedisResponse> {
-    let (frame, backend) = (request.frame, request.backend);
-    let cmd = Command::try_from(frame)?;
+async fn request_handle(request: RedisRequest, client: &mut ClientState) -> Result<RedisResponse> {
+    let (cmd, logged, backend) = (request.cmd, request.logged, request.backend);
     info!("Executing command: {:?}", cmd);
-    let frame = cmd.execute(&backend);
-    Ok(RedisResponse { frame })
+    if cmd.is_script_kill() {
+        return Ok(RedisResponse {
+            frames: vec![cmd.execute(&backend)],
+        });
+    }
+    if let Some(busy) = busy_reply(&backend) {
+        return Ok(RedisResponse { frames: vec![busy] });
+    }
+    let may_write = cmd.is_write()
+        || matches!(
+            cmd,
+            Command::Exec(_) | Command::Eval(_) | Command::EvalSha(_) | Command::FCall(_)
+        );
+    let frames = match cmd {
+        // connection commands act on the state of the client rather than on the keyspace
+        Command::Hello(hello) => vec![hello.execute_for(client, &backend)],
+        Command::Ping(ping) => vec![ping.execute_for(client)],
+        Command::Asking(asking) => vec![asking.execute_for(client, &backend)],
+        Command::Subscribe(cmd) => cmd.execute_for(client, &backend),
+        Command::Unsubscribe(cmd) => cmd.execute_for(client, &backend),
+        Command::PSubscribe(cmd) => cmd.execute_for(client, &backend),
+        Command::PUnsubscribe(cmd) => cmd.execute_for(client, &backend),
+        Command::SSubscribe(cmd) => cmd.execute_for(client, &backend),
+        Command::SUnsubscribe(cmd) => cmd.execute_for(client, &backend),
+        Command::Multi(cmd) => vec![cmd.execute_for(client)],
+        // EXEC holds the transaction lock exclusively, nothing runs while the commands do
+        Command::Exec(cmd) => match lock(&backend, backend.exclusive_guard()).await {
+            Ok(_exclusive) => vec![cmd.execute_for(client, &backend)],
+            Err(busy) => vec![busy],
+        },
+        Command::Discard(cmd) => vec![cmd.execute_for(client, &backend)],
+        Command::Watch(cmd) => vec![cmd.execute_for(client, &backend)],
+        Command::Unwatch(cmd) => vec![cmd.execute_for(client, &backend)],
+        Command::Wait(cmd) => vec![cmd.execute_for(client, &backend).await],
+        Command::WaitAof(cmd) => vec![cmd.execute_for(client, &backend).await],
+        // a script runs alone, like a transaction
+        // on a thread of its own, the other clients are answered BUSY while it runs for too long
+        cmd
+        @ (Command::Eval(_) | Command::EvalSha(_) | Command::FCall(_) | Command::FCallRo(_)) => {
+            let exclusive = match lock(&backend, backend.exclusive_guard()).await {
+                Ok(exclusive) => exclusive,
+                Err(busy) => return Ok(RedisResponse { frames: vec![busy] }),
+            };
+            let cloned = backend.clone();
+            let frame = tokio::task::spawn_blocking(move || {
+                let _exclusive = exclusive;
+                let _barrier = cloned.write_barrier();
+                cmd.execute(&cloned)
+            })
+            .await?;
+            vec![frame]
+        }
+        cmd if cmd.is_blocking() => {
+            // a parked client cannot hold the write barrier, every try takes it and the one that
+            // pops logs what it popped before releasing it
+            let mut logged = logged;
+            let frame = cmd
+                .execute_async(&backend, |frame| propagate(&backend, logged.take(), frame))
+                .await;
+            vec![frame]
+        }
+        // every other command holds the transaction lock shared, so none runs during an EXEC
+        cmd if !cmd.is_write() => match lock(&backend, backend.command_guard()).await {
+            Ok(_guard) => vec![cmd.execute(&backend)],
+            Err(busy) => vec![busy],
+        },
+        cmd => {
+            let _guard = match lock(&backend, backend.command_guard()).await {
+                Ok(guard) => guard,
+                Err(busy) => return Ok(RedisResponse { frames: vec![busy] }),
+            };
+            let _barrier = backend.write_barrier();
+            let frame = cmd.execute(&backend);
+            propagate(&backend, logged, &frame);
+            vec![frame]
+        }
+    };
+    if may_write {
+        client.woff = backend.master_repl_offset();
+    }
+    Ok(RedisResponse { frames })
+}
+
+// a script running for longer than busy-reply-threshold gets the other clients a BUSY error
+fn busy_reply(backend: &Backend) -> Option<RespFrame> {
+    let kill = match backend.busy_script()? {
+        true => "FUNCTION KILL",
+        false => "SCRIPT KILL",
+    };
+    Some(
+        SimpleError::new(format!(
+            "BUSY Redis is busy running a script. You can only call {} or SHUTDOWN NOSAVE.",
+            kill
+        ))
+        .into(),
+    )
+}
+
+// waits for a side of the transaction lock, a script or an EXEC may hold it; the client is
+// answered BUSY instead once the script ran for too long
+async fn lock<G>(backend: &Backend, guard: impl Future<Output = G>) -> Result<G, RespFrame> {
+    let mut guard = pin!(guard);
+    let mut busy_check = tokio::time::interval(BUSY_CHECK_INTERVAL);
+    loop {
+        tokio::select! {
+            biased;
+            guard = &mut guard => return Ok(guard),
+            _ = busy_check.tick() => {
+                if let Some(busy) = busy_reply(backend) {
+                    return Err(busy);
+                }
+            }
+        }
+    }
+}
+
+// the lowercase name of a request, for the errors that quote it
+fn command_name(frame: &RespFrame) -> String {
+    match frame {
+        RespFrame::Array(array) => match array.first() {
+            Some(RespFrame::BulkString(name)) => String::from_utf8_lossy(name).to_lowercase(),
+            _ => String::new(),
+        },
+        _ => String::new(),
+    }
+}
+
+fn propagate(backend: &Backend, logged: Option<RespFrame>, reply: &RespFrame) {
+    backend.mark_dirty();
+    if let Some(entry) = logged.and_then(|frame| aof_entry(frame, reply)) {
+        backend.propagate(entry);
+    }
+}
+
+// resolves once the peer closed the connection, stays pending when it sends more data
+async fn wait_peer_closed(stream: &TcpStream) {
+    let mut buf = [0u8; 1];
+    match stream.peek(&mut buf).await {
+        Ok(0) | Err(_) => {}
+        Ok(_) => std::future::pending().await,
+    }
 }
 
 impl Encoder<RespFrame> for RespFrameCodec {
     type Error = anyhow::Error;
     fn encode(&mut self, item: RespFrame, dst: &mut BytesMut) -> Result<()> {
-        let encoded = item.encode();
+        let encoded = item.encode_with(self.version);
         dst.extend_from_slice(&encoded);
         Ok(())
     }
@@ -66,10 +522,323 @@ impl Decoder for RespFrameCodec {
     type Error = anyhow::Error;
 
     fn decode(&mut self, src: &mut BytesMut) -> Result<Option<RespFrame>> {
-        match RespFrame::decode(src) {
-            Ok(frame) => Ok(Some(frame)),
-            Err(RespError::NotComplete) => Ok(None),
-            Err(e) => Err(e.into()),
+        loop {
+            // like redis, anything not starting as a multibulk request is an inline command
+            let ret = match src.first() {
+                None => return Ok(None),
+                Some(b'*') => self.parser.parse(src),
+                Some(_) => RespArray::decode_inline(src).map(|array| Some(array.into())),
+            };
+            match ret {
+                // empty lines and empty requests are skipped
+                Ok(Some(RespFrame::Array(array))) if array.is_empty() => continue,
+                Ok(frame) => return Ok(frame),
+                Err(RespError::NotComplete) => return Ok(None),
+                Err(e) => return Err(e.into()),
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::server::serve;
+    use std::net::SocketAddr;
+    use std::time::Duration;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    async fn start_server() -> Result<SocketAddr> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        tokio::spawn(serve(listener, Backend::new()));
+        Ok(addr)
+    }
+
+    async fn connect() -> Result<TcpStream> {
+        Ok(TcpStream::connect(start_server().await?).await?)
+    }
+
+    async fn read_exact(stream: &mut TcpStream, len: usize) -> Result<Vec<u8>> {
+        let mut buf = vec![0u8; len];
+        stream.read_exact(&mut buf).await?;
+        Ok(buf)
+    }
+
+    async fn expect_reply(stream: &mut TcpStream, expected: &[u8]) -> Result<()> {
+        let read = read_exact(stream, expected.len());
+        let reply = tokio::time::timeout(Duration::from_secs(1), read).await??;
+        assert_eq!(
+            String::from_utf8_lossy(&reply),
+            String::from_utf8_lossy(expected)
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_pipelined_replies_in_order() -> Result<()> {
+        let mut stream = connect().await?;
+        stream
+            .write_all(b"*3\r\n$3\r\nSET\r\n$1\r\nk\r\n$1\r\nv\r\nGET k\r\nFOO\r\nPING\r\n")
+            .await?;
+        let expected =
+            b"+OK\r\n$1\r\nv\r\n-ERR unknown command 'FOO', with args beginning with: \r\n+PONG\r\n";
+        assert_eq!(read_exact(&mut stream, expected.len()).await?, expected);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_pipelined_replies_flushed_before_blocking() -> Result<()> {
+        let mut stream = connect().await?;
+        stream.write_all(b"SET k v\r\nBLPOP list 0\r\n").await?;
+        // the client is parked on BLPOP, the reply to SET still has to arrive
+        let reply =
+            tokio::time::timeout(Duration::from_secs(1), read_exact(&mut stream, 5)).await??;
+        assert_eq!(reply, b"+OK\r\n");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_pipelined_replies_after_half_close() -> Result<()> {
+        let mut stream = connect().await?;
+        stream.write_all(b"SET k v\r\nGET k\r\nPING\r\n").await?;
+        stream.shutdown().await?;
+        expect_reply(&mut stream, b"+OK\r\n$1\r\nv\r\n+PONG\r\n").await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_pubsub_delivery() -> Result<()> {
+        let addr = start_server().await?;
+        let mut subscriber = TcpStream::connect(addr).await?;
+        let mut publisher = TcpStream::connect(addr).await?;
+
+        subscriber
+            .write_all(b"SUBSCRIBE news\r\nPSUBSCRIBE n*\r\n")
+            .await?;
+        expect_reply(
+            &mut subscriber,
+            b"*3\r\n$9\r\nsubscribe\r\n$4\r\nnews\r\n:1\r\n",
+        )
+        .await?;
+        expect_reply(
+            &mut subscriber,
+            b"*3\r\n$10\r\npsubscribe\r\n$2\r\nn*\r\n:2\r\n",
+        )
+        .await?;
+
+        publisher.write_all(b"PUBLISH news hi\r\n").await?;
+        expect_reply(&mut publisher, b":2\r\n").await?;
+        expect_reply(
+            &mut subscriber,
+            b"*3\r\n$7\r\nmessage\r\n$4\r\nnews\r\n$2\r\nhi\r\n",
+        )
+        .await?;
+        expect_reply(
+            &mut subscriber,
+            b"*4\r\n$8\r\npmessage\r\n$2\r\nn*\r\n$4\r\nnews\r\n$2\r\nhi\r\n",
+        )
+        .await?;
+
+        // in RESP2 a subscribed client can only manage its subscriptions
+        subscriber.write_all(b"GET k\r\nPING\r\n").await?;
+        expect_reply(&mut subscriber, b"-ERR Can't execute 'get': only (P|S)SUBSCRIBE / (P|S)UNSUBSCRIBE / PING / QUIT / RESET are allowed in this context\r\n").await?;
+        expect_reply(&mut subscriber, b"*2\r\n$4\r\npong\r\n$0\r\n\r\n").await?;
+
+        // in RESP3 messages are pushed and any command is allowed
+        let mut resp3 = TcpStream::connect(addr).await?;
+        resp3.write_all(b"HELLO 3\r\n").await?;
+        let mut buf = [0u8; 1];
+        resp3.read_exact(&mut buf).await?;
+        assert_eq!(&buf, b"%");
+        let _ = resp3.read(&mut [0u8; 1024]).await?;
+        resp3.write_all(b"SUBSCRIBE news\r\nGET k\r\n").await?;
+        expect_reply(
+            &mut resp3,
+            b">3\r\n$9\r\nsubscribe\r\n$4\r\nnews\r\n:1\r\n_\r\n",
+        )
+        .await?;
+        publisher
+            .write_all(b"PUBLISH other hi\r\nPUBLISH news hi\r\n")
+            .await?;
+        expect_reply(&mut publisher, b":0\r\n:3\r\n").await?;
+        expect_reply(
+            &mut resp3,
+            b">3\r\n$7\r\nmessage\r\n$4\r\nnews\r\n$2\r\nhi\r\n",
+        )
+        .await?;
+
+        // the subscriptions of a closed connection are dropped
+        drop(subscriber);
+        drop(resp3);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        publisher
+            .write_all(b"PUBSUB NUMSUB news\r\nPUBSUB NUMPAT\r\n")
+            .await?;
+        expect_reply(&mut publisher, b"*2\r\n$4\r\nnews\r\n:0\r\n:0\r\n").await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_transactions() -> Result<()> {
+        let addr = start_server().await?;
+        let mut client = TcpStream::connect(addr).await?;
+        let mut other = TcpStream::connect(addr).await?;
+
+        client
+            .write_all(b"MULTI\r\nSET k 1\r\nINCR k\r\nGET k\r\nEXEC\r\n")
+            .await?;
+        expect_reply(
+            &mut client,
+            b"+OK\r\n+QUEUED\r\n+QUEUED\r\n+QUEUED\r\n*3\r\n+OK\r\n:2\r\n$1\r\n2\r\n",
+        )
+        .await?;
+
+        // a command that fails to parse discards the transaction
+        client
+            .write_all(b"MULTI\r\nSET k 3\r\nINCR\r\nEXEC\r\nGET k\r\n")
+            .await?;
+        expect_reply(&mut client, b"+OK\r\n+QUEUED\r\n-ERR wrong number of arguments for 'incr' command\r\n-EXECABORT Transaction discarded because of previous errors.\r\n$1\r\n2\r\n").await?;
+
+        client
+            .write_all(b"MULTI\r\nSET k 3\r\nDISCARD\r\nGET k\r\n")
+            .await?;
+        expect_reply(&mut client, b"+OK\r\n+QUEUED\r\n+OK\r\n$1\r\n2\r\n").await?;
+
+        // a watched key written by another client makes EXEC fail
+        client.write_all(b"WATCH k\r\nMULTI\r\nINCR k\r\n").await?;
+        expect_reply(&mut client, b"+OK\r\n+OK\r\n+QUEUED\r\n").await?;
+        other.write_all(b"SET k 10\r\n").await?;
+        expect_reply(&mut other, b"+OK\r\n").await?;
+        client.write_all(b"EXEC\r\nGET k\r\n").await?;
+        expect_reply(&mut client, b"*-1\r\n$2\r\n10\r\n").await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_writes_logged_in_order() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("simple-redis-order-{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        let config = crate::config::Config {
+            dir,
+            appendonly: true,
+            ..Default::default()
+        };
+        let _ = std::fs::remove_file(config.aof_path());
+        let backend = Backend::with_config(config.clone());
+        backend.open_aof()?;
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        tokio::spawn(serve(listener, backend.clone()));
+
+        // every client appends to the same key, so any reordering in the log changes the value
+        const WRITES: usize = 200;
+        let mut handles = vec![];
+        for c in 0..8 {
+            handles.push(tokio::spawn(async move {
+                let mut stream = TcpStream::connect(addr).await?;
+                let requests = (0..WRITES)
+                    .map(|i| format!("APPEND k {}.{},\r\n", c, i))
+                    .collect::<String>();
+                stream.write_all(requests.as_bytes()).await?;
+                let mut replies = 0;
+                let mut buf = [0u8; 4096];
+                while replies < WRITES {
+                    let n = stream.read(&mut buf).await?;
+                    anyhow::ensure!(n > 0, "connection closed");
+                    replies += buf[..n].iter().filter(|b| **b == b'\n').count();
+                }
+                Ok::<_, anyhow::Error>(())
+            }));
+        }
+        for handle in handles {
+            handle.await??;
+        }
+
+        let replayed = Backend::with_config(config);
+        crate::cmd::load_aof(&replayed)?;
+        assert_eq!(replayed.get("k")?, backend.get("k")?);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_busy_script_killed() -> Result<()> {
+        let backend = Backend::with_config(crate::config::Config {
+            busy_reply_threshold: 50,
+            ..Default::default()
+        });
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        tokio::spawn(serve(listener, backend.clone()));
+        let mut script = TcpStream::connect(addr).await?;
+        let mut other = TcpStream::connect(addr).await?;
+
+        let body = "while true do end";
+        let eval = format!(
+            "*3\r\n$4\r\nEVAL\r\n${}\r\n{}\r\n$1\r\n0\r\n",
+            body.len(),
+            body
+        );
+        script.write_all(eval.as_bytes()).await?;
+        while backend.busy_script().is_none() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        other.write_all(b"GET k\r\n").await?;
+        expect_reply(
+            &mut other,
+            b"-BUSY Redis is busy running a script. You can only call SCRIPT KILL or SHUTDOWN NOSAVE.\r\n",
+        )
+        .await?;
+        other.write_all(b"SCRIPT KILL\r\n").await?;
+        expect_reply(&mut other, b"+OK\r\n").await?;
+        expect_reply(
+            &mut script,
+            b"-ERR Script killed by user with SCRIPT KILL...\r\n",
+        )
+        .await?;
+        other.write_all(b"GET k\r\nSCRIPT KILL\r\n").await?;
+        expect_reply(
+            &mut other,
+            b"$-1\r\n-NOTBUSY No scripts in execution right now.\r\n",
+        )
+        .await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn test_exec_waits_for_script() -> Result<()> {
+        let backend = Backend::new();
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        tokio::spawn(serve(listener, backend.clone()));
+        let mut script = TcpStream::connect(addr).await?;
+        let mut exec = TcpStream::connect(addr).await?;
+        let mut other = TcpStream::connect(addr).await?;
+
+        let body = "while true do end";
+        let eval = format!(
+            "*3\r\n$4\r\nEVAL\r\n${}\r\n{}\r\n$1\r\n0\r\n",
+            body.len(),
+            body
+        );
+        script.write_all(eval.as_bytes()).await?;
+        while !backend.script_running() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        exec.write_all(b"MULTI\r\nSET k v\r\n").await?;
+        expect_reply(&mut exec, b"+OK\r\n+QUEUED\r\n").await?;
+        exec.write_all(b"EXEC\r\n").await?;
+        // the EXEC waiting on the script leaves the only worker thread to the other clients
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        other.write_all(b"PING\r\nSCRIPT KILL\r\n").await?;
+        expect_reply(&mut other, b"+PONG\r\n+OK\r\n").await?;
+        expect_reply(
+            &mut script,
+            b"-ERR Script killed by user with SCRIPT KILL...\r\n",
+        )
+        .await?;
+        expect_reply(&mut exec, b"*1\r\n+OK\r\n").await?;
+        Ok(())
+    }
+}
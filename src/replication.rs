@@ -0,0 +1,374 @@
+use crate::cmd::{Command, CommandExecutor, ReplConf};
+use crate::resp::{BulkString, RespArray, RespEncode, RespFrame, RespParser};
+use crate::Backend;
+use anyhow::{anyhow, bail, Result};
+use bytes::{Bytes, BytesMut};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tracing::{info, warn};
+
+// how often a replica reports the offset it processed
+const REPL_ACK_INTERVAL: Duration = Duration::from_secs(1);
+// how long a replica waits before connecting again to a master it lost
+const REPL_RETRY_INTERVAL: Duration = Duration::from_secs(1);
+
+// makes the server a replica of `master`, or a master again when None
+pub fn replicate(backend: &Backend, master: Option<(String, u16)>) {
+    backend.set_master(master, |host, port| {
+        tokio::spawn(replica_link(backend.clone(), host, port)).abort_handle()
+    });
+}
+
+// keeps the replica in sync until REPLICAOF aborts it, reconnecting whenever the link drops
+async fn replica_link(backend: Backend, host: String, port: u16) {
+    loop {
+        if let Err(e) = sync_with_master(&backend, &host, port).await {
+            warn!(
+                "[Simple-redis-server]replication from {}:{} failed: {}",
+                host, port, e
+            );
+        }
+        tokio::time::sleep(REPL_RETRY_INTERVAL).await;
+    }
+}
+
+struct MasterConnection {
+    stream: TcpStream,
+    buf: BytesMut,
+    parser: RespParser,
+}
+
+impl MasterConnection {
+    async fn send(&mut self, args: &[&str]) -> Result<()> {
+        let command = RespArray::new(
+            args.iter()
+                .map(|arg| BulkString::new(*arg).into())
+                .collect::<Vec<RespFrame>>(),
+        );
+        self.stream.write_all(&command.encode()).await?;
+        Ok(())
+    }
+
+    async fn fill(&mut self) -> Result<()> {
+        if self.stream.read_buf(&mut self.buf).await? == 0 {
+            bail!("connection closed by the master");
+        }
+        Ok(())
+    }
+
+    async fn read_status(&mut self) -> Result<String> {
+        let frame = loop {
+            if let Some(frame) = self.parser.parse(&mut self.buf)? {
+                break frame;
+            }
+            self.fill().await?;
+        };
+        match frame {
+            RespFrame::SimpleString(status) => Ok(status.0),
+            RespFrame::Error(e) => bail!("master replied {}", e.0),
+            frame => bail!("unexpected reply from the master: {:?}", frame),
+        }
+    }
+
+    // the dataset comes as a bulk string without the CRLF at its end
+    async fn read_rdb(&mut self) -> Result<Bytes> {
+        let header = loop {
+            if let Some(end) = self.buf.windows(2).position(|w| w == b"\r\n") {
+                break self.buf.split_to(end + 2);
+            }
+            self.fill().await?;
+        };
+        let len = std::str::from_utf8(&header[..header.len() - 2])
+            .ok()
+            .and_then(|header| header.strip_prefix('$')?.parse::<usize>().ok())
+            .ok_or_else(|| anyhow!("invalid dataset header from the master"))?;
+        while self.buf.len() < len {
+            self.fill().await?;
+        }
+        Ok(self.buf.split_to(len).freeze())
+    }
+}
+
+async fn sync_with_master(backend: &Backend, host: &str, port: u16) -> Result<()> {
+    let mut master = MasterConnection {
+        stream: TcpStream::connect((host, port)).await?,
+        buf: BytesMut::new(),
+        parser: RespParser::with_limits(backend.config().resp_limits()),
+    };
+    master.send(&["PING"]).await?;
+    master.read_status().await?;
+    let listening_port = backend.config().port.to_string();
+    master
+        .send(&["REPLCONF", "listening-port", &listening_port])
+        .await?;
+    master.read_status().await?;
+    master.send(&["REPLCONF", "capa", "psync2"]).await?;
+    master.read_status().await?;
+
+    let (replid, offset) = backend.replication_offset();
+    let offset = (offset + 1).to_string();
+    master.send(&["PSYNC", &replid, &offset]).await?;
+    let reply = master.read_status().await?;
+    match reply.split_whitespace().collect::<Vec<_>>().as_slice() {
+        ["FULLRESYNC", replid, offset] => {
+            let offset = offset.parse::<u64>()?;
+            let rdb = master.read_rdb().await?;
+            let loaded = {
+                let _exclusive = backend.exclusive_guard().await;
+                let _barrier = backend.write_barrier();
+                let loaded = backend.load_rdb(&rdb)?;
+                backend.replica_full_sync(replid.to_string(), offset);
+                loaded
+            };
+            // the log has to start over from the new dataset, snapshotted before the next write
+            if backend.config().appendonly {
+                let _guard = backend.command_guard().await;
+                backend.bgrewrite_aof()?;
+            }
+            info!(
+                "[Simple-redis-server]full resync from {}:{}, loaded {} keys",
+                host, port, loaded
+            );
+        }
+        ["CONTINUE"] => {}
+        ["CONTINUE", replid] => backend.replica_continue(replid),
+        _ => bail!("unexpected reply to PSYNC: {}", reply),
+    }
+
+    let mut ack = tokio::time::interval(REPL_ACK_INTERVAL);
+    let mut transaction = None;
+    loop {
+        while let Some((frame, raw)) = master.parser.parse_raw(&mut master.buf)? {
+            if let Some(reply) = apply(backend, frame, &raw, &mut transaction).await {
+                master.stream.write_all(&reply.encode()).await?;
+            }
+        }
+        tokio::select! {
+            read = master.stream.read_buf(&mut master.buf) => {
+                if read? == 0 {
+                    bail!("connection closed by the master");
+                }
+            }
+            _ = ack.tick() => {
+                master.stream.write_all(&ack_command(backend).encode()).await?;
+            }
+        }
+    }
+}
+
+// the offsets processed and fsynced to the log
+fn ack_command(backend: &Backend) -> RespArray {
+    let offset = backend.master_repl_offset().to_string();
+    let fsynced = backend.aof_fsynced_offset().to_string();
+    RespArray::new(
+        ["REPLCONF", "ACK", &offset, "FACK", &fsynced]
+            .map(|arg| BulkString::new(arg).into())
+            .to_vec(),
+    )
+}
+
+// applies a command of the stream, only REPLCONF GETACK gets a reply. The raw command goes on to
+// the replicas of this replica, the log gets it once applied. Keys are not deleted on behalf of
+// the master, their deadlines are absolute so they expire here just the same. The transaction
+// lock is awaited, a script running on the replica never holds up the thread
+async fn apply(
+    backend: &Backend,
+    frame: RespFrame,
+    raw: &[u8],
+    transaction: &mut Option<Vec<(Command, RespArray)>>,
+) -> Option<RespArray> {
+    let RespFrame::Array(array) = frame else {
+        warn!(
+            "[Simple-redis-server]invalid frame from the master: {:?}",
+            frame
+        );
+        return None;
+    };
+    let cmd = match Command::try_from(array.clone()) {
+        Ok(cmd) => cmd,
+        Err(e) => {
+            warn!(
+                "[Simple-redis-server]invalid command from the master: {}",
+                e
+            );
+            let _guard = backend.command_guard().await;
+            let _barrier = backend.write_barrier();
+            backend.feed_replication(raw);
+            return None;
+        }
+    };
+    let mut reply = None;
+    match cmd {
+        // the ack is about what was processed before asking
+        Command::ReplConf(ReplConf::GetAck) => {
+            reply = Some(ack_command(backend));
+        }
+        Command::Multi(_) => *transaction = Some(Vec::new()),
+        Command::Exec(_) => {
+            let commands = transaction.take().unwrap_or_default();
+            let _exclusive = backend.exclusive_guard().await;
+            let _barrier = backend.write_barrier();
+            let entries = commands
+                .into_iter()
+                .map(|(cmd, entry)| {
+                    cmd.execute(backend);
+                    backend.mark_dirty();
+                    entry
+                })
+                .collect::<Vec<RespArray>>();
+            if !entries.is_empty() {
+                backend.feed_aof(RespArray::new([BulkString::new("MULTI").into()]));
+                entries
+                    .into_iter()
+                    .for_each(|entry| backend.feed_aof(entry));
+                backend.feed_aof(RespArray::new([BulkString::new("EXEC").into()]));
+            }
+            backend.feed_replication(raw);
+            return reply;
+        }
+        cmd if cmd.is_write() => {
+            if let Some(commands) = transaction.as_mut() {
+                commands.push((cmd, array));
+            } else {
+                let _guard = backend.command_guard().await;
+                let _barrier = backend.write_barrier();
+                cmd.execute(backend);
+                backend.mark_dirty();
+                backend.feed_aof(array);
+                backend.feed_replication(raw);
+                return reply;
+            }
+        }
+        // PING and the like only move the offset
+        _ => {}
+    }
+    let _guard = backend.command_guard().await;
+    let _barrier = backend.write_barrier();
+    backend.feed_replication(raw);
+    reply
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::server::serve;
+    use anyhow::ensure;
+    use std::net::SocketAddr;
+    use tokio::net::TcpListener;
+
+    async fn start_server(backend: Backend) -> Result<SocketAddr> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        tokio::spawn(serve(listener, backend));
+        Ok(addr)
+    }
+
+    async fn request(stream: &mut TcpStream, request: &[u8]) -> Result<String> {
+        stream.write_all(request).await?;
+        let mut buf = vec![0u8; 1024];
+        let n = stream.read(&mut buf).await?;
+        Ok(String::from_utf8_lossy(&buf[..n]).into_owned())
+    }
+
+    // polls the replica until it has the value, the stream is asynchronous
+    async fn wait_value(backend: &Backend, key: &str, value: &str) -> Result<()> {
+        for _ in 0..100 {
+            if backend.get(key)?.as_deref() == Some(value.as_bytes()) {
+                return Ok(());
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        bail!("{} never became {} on the replica", key, value)
+    }
+
+    #[tokio::test]
+    async fn test_replication() -> Result<()> {
+        let master = Backend::new();
+        let master_addr = start_server(master.clone()).await?;
+        let replica = Backend::new();
+        let replica_addr = start_server(replica.clone()).await?;
+        let mut client = TcpStream::connect(master_addr).await?;
+        assert_eq!(request(&mut client, b"SET a 1\r\n").await?, "+OK\r\n");
+
+        // the first sync transfers the dataset
+        let port = master_addr.port();
+        replicate(&replica, Some(("127.0.0.1".to_string(), port)));
+        wait_value(&replica, "a", "1").await?;
+        assert_eq!(master.sync_stats(), (1, 0));
+
+        // then the writes follow, transactions included
+        request(&mut client, b"SET b 2\r\n").await?;
+        request(&mut client, b"MULTI\r\nINCR b\r\nINCR b\r\nEXEC\r\n").await?;
+        wait_value(&replica, "b", "4").await?;
+        // the replica acks the last write of the client as soon as asked
+        request(&mut client, b"SET b 4\r\n").await?;
+        assert_eq!(request(&mut client, b"WAIT 1 0\r\n").await?, ":1\r\n");
+
+        let mut replica_client = TcpStream::connect(replica_addr).await?;
+        assert_eq!(
+            request(&mut replica_client, b"SET c 1\r\n").await?,
+            "-READONLY You can't write against a read only replica.\r\n"
+        );
+        assert_eq!(
+            request(&mut replica_client, b"GET b\r\n").await?,
+            "$1\r\n4\r\n"
+        );
+
+        // a new link resumes from the offset the replica reached
+        replicate(&replica, Some(("127.0.0.1".to_string(), port)));
+        request(&mut client, b"SET b 5\r\n").await?;
+        wait_value(&replica, "b", "5").await?;
+        assert_eq!(master.sync_stats(), (1, 1));
+        assert_eq!(replica.replication_offset(), master.replication_offset());
+
+        // once promoted the replica takes writes
+        replicate(&replica, None);
+        assert_eq!(
+            request(&mut replica_client, b"SET c 1\r\n").await?,
+            "+OK\r\n"
+        );
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_writes_replicated_in_order() -> Result<()> {
+        let master = Backend::new();
+        let master_addr = start_server(master.clone()).await?;
+        let replica = Backend::new();
+        replicate(
+            &replica,
+            Some(("127.0.0.1".to_string(), master_addr.port())),
+        );
+        let mut client = TcpStream::connect(master_addr).await?;
+        request(&mut client, b"SET k start\r\n").await?;
+        wait_value(&replica, "k", "start").await?;
+
+        // every client appends to the same key, any reordering of the stream changes the value
+        const WRITES: usize = 200;
+        let mut handles = vec![];
+        for c in 0..8 {
+            handles.push(tokio::spawn(async move {
+                let mut stream = TcpStream::connect(master_addr).await?;
+                let requests = (0..WRITES)
+                    .map(|i| format!("APPEND k {}.{},\r\n", c, i))
+                    .collect::<String>();
+                stream.write_all(requests.as_bytes()).await?;
+                let mut replies = 0;
+                let mut buf = [0u8; 4096];
+                while replies < WRITES {
+                    let n = stream.read(&mut buf).await?;
+                    ensure!(n > 0, "connection closed");
+                    replies += buf[..n].iter().filter(|b| **b == b'\n').count();
+                }
+                Ok::<_, anyhow::Error>(())
+            }));
+        }
+        for handle in handles {
+            handle.await??;
+        }
+        let expected = master.get("k")?.unwrap_or_default();
+        wait_value(&replica, "k", std::str::from_utf8(&expected)?).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,81 @@
+use crate::resp::{
+    extract_simple_frame_data, RespDecode, RespEncode, RespError, RespLimits, CRLF_LEN,
+};
+use bytes::BytesMut;
+use std::ops::Deref;
+
+// integers beyond the range of i64, kept as their decimal digits
+#[derive(Debug, Clone, PartialOrd, PartialEq, Eq)]
+pub struct BigNumber(pub(crate) String);
+
+impl BigNumber {
+    pub fn new(s: impl Into<String>) -> Result<Self, RespError> {
+        let s = s.into();
+        let digits = s.strip_prefix(['+', '-']).unwrap_or(&s);
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(RespError::InvalidFrame(format!(
+                "invalid big number: {}",
+                s
+            )));
+        }
+        Ok(BigNumber(s))
+    }
+}
+
+// - big number: "(<big number>\r\n"
+impl RespEncode for BigNumber {
+    fn encode(self) -> Vec<u8> {
+        format!("({}\r\n", self.0).into_bytes()
+    }
+}
+
+impl RespDecode for BigNumber {
+    const PREFIX: &'static str = "(";
+    fn decode_with_limits(buf: &mut BytesMut, _limits: &RespLimits) -> Result<Self, RespError> {
+        let end = extract_simple_frame_data(buf, Self::PREFIX)?;
+        let data = buf.split_to(end + CRLF_LEN);
+        let s = std::str::from_utf8(&data[Self::PREFIX.len()..end])?;
+        BigNumber::new(s)
+    }
+}
+
+impl Deref for BigNumber {
+    type Target = String;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::resp::RespFrame;
+
+    #[test]
+    fn test_big_number_encode() -> anyhow::Result<()> {
+        let frame: RespFrame =
+            BigNumber::new("3492890328409238509324850943850943825024385")?.into();
+        assert_eq!(
+            frame.encode(),
+            b"(3492890328409238509324850943850943825024385\r\n"
+        );
+        assert!(BigNumber::new("-").is_err());
+        assert!(BigNumber::new("12a").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_big_number_decode() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"(-3492890328409238509324850943850943825024385\r\n");
+        let frame = BigNumber::decode(&mut buf)?;
+        assert_eq!(
+            frame.as_str(),
+            "-3492890328409238509324850943850943825024385"
+        );
+
+        buf.extend_from_slice(b"(12.5\r\n");
+        assert!(BigNumber::decode(&mut buf).is_err());
+        Ok(())
+    }
+}
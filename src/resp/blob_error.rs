@@ -0,0 +1,63 @@
+use crate::resp::{decode_blob, RespDecode, RespEncode, RespError, RespLimits};
+use bytes::BytesMut;
+use std::ops::Deref;
+
+// an error that may be long or contain newlines
+#[derive(Debug, Clone, PartialOrd, PartialEq, Eq)]
+pub struct BlobError(pub(crate) String);
+
+impl BlobError {
+    pub fn new(s: impl Into<String>) -> Self {
+        BlobError(s.into())
+    }
+}
+
+// - blob error: "!<length>\r\n<error>\r\n"
+impl RespEncode for BlobError {
+    fn encode(self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(self.len() + 16);
+        buf.extend_from_slice(format!("!{}\r\n", self.len()).as_bytes());
+        buf.extend_from_slice(self.as_bytes());
+        buf.extend_from_slice(b"\r\n");
+        buf
+    }
+}
+
+impl RespDecode for BlobError {
+    const PREFIX: &'static str = "!";
+    fn decode_with_limits(buf: &mut BytesMut, limits: &RespLimits) -> Result<Self, RespError> {
+        let data = decode_blob(buf, Self::PREFIX, limits)?;
+        Ok(BlobError::new(String::from_utf8_lossy(&data)))
+    }
+}
+
+impl Deref for BlobError {
+    type Target = String;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::resp::RespFrame;
+
+    #[test]
+    fn test_blob_error_encode() {
+        let frame: RespFrame = BlobError::new("SYNTAX invalid syntax").into();
+        assert_eq!(frame.encode(), b"!21\r\nSYNTAX invalid syntax\r\n");
+    }
+
+    #[test]
+    fn test_blob_error_decode() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"!10\r\nERR a\r\nb c\r\n");
+        let frame = BlobError::decode(&mut buf)?;
+        assert_eq!(frame, BlobError::new("ERR a\r\nb c"));
+
+        buf.extend_from_slice(b"!12\r\nERR");
+        assert_eq!(BlobError::decode(&mut buf), Err(RespError::NotComplete));
+        Ok(())
+    }
+}
@@ -1,22 +1,19 @@
-use crate::resp::{extract_fixed_data, parse_length, RespDecode, RespEncode, RespError, CRLF_LEN};
-use bytes::{Buf, BytesMut};
+use crate::resp::{decode_blob, RespDecode, RespEncode, RespError, RespLimits};
+use bytes::{Bytes, BytesMut};
 use std::ops::Deref;
 
+// decoded bulk strings share the buffer they were read into instead of copying it
 #[derive(Debug, Clone, PartialOrd, PartialEq, Eq)]
-pub struct BulkString(pub(crate) Vec<u8>);
+pub struct BulkString(pub(crate) Bytes);
 impl BulkString {
     pub fn new(s: impl Into<Vec<u8>>) -> Self {
-        BulkString(s.into())
+        BulkString(Bytes::from(s.into()))
     }
 }
 
 // - bulk string: "$<length>\r\n<data>\r\n"
 impl RespEncode for BulkString {
     fn encode(self) -> Vec<u8> {
-        let len = self.len();
-        if len == 0 {
-            return b"$-1\r\n".to_vec();
-        }
         let mut buf = Vec::with_capacity(self.len() + 16);
         buf.extend_from_slice(&format!("${}\r\n", self.len()).into_bytes());
         buf.extend_from_slice(&self);
@@ -27,34 +24,13 @@ impl RespEncode for BulkString {
 
 impl RespDecode for BulkString {
     const PREFIX: &'static str = "$";
-    fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
-        let (end, len) = parse_length(buf, Self::PREFIX)?;
-        if len == -1 {
-            extract_fixed_data(buf, "$-1\r\n", "NullBulkString")?;
-            return Ok(BulkString(vec![]));
-        }
-        let len = len as usize;
-        let remained = &buf[end + CRLF_LEN..];
-        if remained.len() < len + CRLF_LEN {
-            return Err(RespError::NotComplete);
-        }
-        buf.advance(end + CRLF_LEN);
-        let data = buf.split_to(len + CRLF_LEN);
-        Ok(BulkString::new(data[..len].to_vec()))
-    }
-
-    fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
-        let (end, len) = parse_length(buf, Self::PREFIX)?;
-        if len == -1 {
-            return Ok(5);
-        }
-        let len = len as usize;
-        Ok(end + len + CRLF_LEN + CRLF_LEN)
+    fn decode_with_limits(buf: &mut BytesMut, limits: &RespLimits) -> Result<Self, RespError> {
+        Ok(BulkString::new(decode_blob(buf, Self::PREFIX, limits)?))
     }
 }
 
 impl Deref for BulkString {
-    type Target = Vec<u8>;
+    type Target = Bytes;
     fn deref(&self) -> &Self::Target {
         &self.0
     }
@@ -62,18 +38,24 @@ impl Deref for BulkString {
 
 impl From<String> for BulkString {
     fn from(value: String) -> Self {
-        BulkString(value.into_bytes())
+        BulkString(Bytes::from(value))
     }
 }
 impl From<&str> for BulkString {
     fn from(value: &str) -> Self {
-        BulkString(value.as_bytes().to_vec())
+        BulkString(Bytes::copy_from_slice(value.as_bytes()))
     }
 }
 
 impl From<&[u8]> for BulkString {
     fn from(value: &[u8]) -> Self {
-        BulkString(value.to_vec())
+        BulkString(Bytes::copy_from_slice(value))
+    }
+}
+
+impl From<Bytes> for BulkString {
+    fn from(value: Bytes) -> Self {
+        BulkString(value)
     }
 }
 
@@ -85,7 +67,7 @@ impl AsRef<[u8]> for BulkString {
 
 impl<const N: usize> From<&[u8; N]> for BulkString {
     fn from(value: &[u8; N]) -> Self {
-        BulkString(value.to_vec())
+        BulkString(Bytes::copy_from_slice(value))
     }
 }
 
@@ -123,19 +105,22 @@ mod test {
     }
 
     #[test]
-    fn test_null_bulk_string_encode() {
+    fn test_empty_bulk_string_encode() {
         let frame: RespFrame = BulkString::new(b"").into();
-        assert_eq!(frame.encode(), b"$-1\r\n");
+        assert_eq!(frame.encode(), b"$0\r\n\r\n");
     }
 
     #[test]
-    fn test_null_bulk_string_decode() -> anyhow::Result<()> {
+    fn test_empty_bulk_string_decode() -> anyhow::Result<()> {
         let mut buf = BytesMut::new();
-        buf.extend_from_slice(b"$-1\r\n");
+        buf.extend_from_slice(b"$0\r\n\r\n");
 
         let frame = BulkString::decode(&mut buf)?;
-        let expected = BulkString::new(b"");
-        assert_eq!(frame, expected);
+        assert_eq!(frame, BulkString::new(b""));
+
+        // the null bulk string is a frame of its own
+        buf.extend_from_slice(b"$-1\r\n");
+        assert!(BulkString::decode(&mut buf).is_err());
 
         Ok(())
     }
@@ -1,6 +1,7 @@
 use crate::resp::{
-    BulkString, RespArray, RespDecode, RespError, RespMap, RespNull, RespSet, SimpleError,
-    SimpleString,
+    encode_map_entries, BigNumber, BlobError, BulkString, RespArray, RespAttribute, RespDecode,
+    RespEncode, RespError, RespLimits, RespMap, RespNull, RespNullArray, RespNullBulkString,
+    RespParser, RespPush, RespSet, SimpleError, SimpleString, VerbatimString, BUF_CAP,
 };
 use bytes::BytesMut;
 use enum_dispatch::enum_dispatch;
@@ -18,75 +19,21 @@ pub enum RespFrame {
     Double(f64),
     Map(RespMap),
     Set(RespSet),
+    BigNumber(BigNumber),
+    VerbatimString(VerbatimString),
+    BlobError(BlobError),
+    Attribute(RespAttribute),
+    Push(RespPush),
+    NullBulkString(RespNullBulkString),
+    NullArray(RespNullArray),
 }
 
 impl RespDecode for RespFrame {
     const PREFIX: &'static str = "";
-    fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
-        let mut iter = buf.iter().peekable();
-        match iter.peek() {
-            Some(b'+') => {
-                let frame = SimpleString::decode(buf)?;
-                Ok(frame.into())
-            }
-            Some(b'-') => {
-                let frame = SimpleError::decode(buf)?;
-                Ok(frame.into())
-            }
-            Some(b':') => {
-                let frame = i64::decode(buf)?;
-                Ok(frame.into())
-            }
-            Some(b'$') => {
-                let frame = BulkString::decode(buf)?;
-                Ok(frame.into())
-            }
-            Some(b'*') => {
-                let frame = RespArray::decode(buf)?;
-                Ok(frame.into())
-            }
-            Some(b'_') => {
-                let frame = RespNull::decode(buf)?;
-                Ok(frame.into())
-            }
-            Some(b'#') => {
-                let frame = bool::decode(buf)?;
-                Ok(frame.into())
-            }
-            Some(b',') => {
-                let frame = f64::decode(buf)?;
-                Ok(frame.into())
-            }
-            Some(b'%') => {
-                let frame = RespMap::decode(buf)?;
-                Ok(frame.into())
-            }
-            Some(b'~') => {
-                let frame = RespSet::decode(buf)?;
-                Ok(frame.into())
-            }
-            None => Err(RespError::NotComplete),
-            _ => Err(RespError::InvalidFrameType(format!(
-                "expect_length: unknown frame type: {:?}",
-                buf
-            ))),
-        }
-    }
-    fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
-        let mut iter = buf.iter().peekable();
-        match iter.peek() {
-            Some(b'*') => RespArray::expect_length(buf),
-            Some(b'~') => RespSet::expect_length(buf),
-            Some(b'%') => RespMap::expect_length(buf),
-            Some(b'$') => BulkString::expect_length(buf),
-            Some(b':') => i64::expect_length(buf),
-            Some(b'+') => SimpleString::expect_length(buf),
-            Some(b'-') => SimpleError::expect_length(buf),
-            Some(b'#') => bool::expect_length(buf),
-            Some(b',') => f64::expect_length(buf),
-            Some(b'_') => RespNull::expect_length(buf),
-            _ => Err(RespError::NotComplete),
-        }
+    fn decode_with_limits(buf: &mut BytesMut, limits: &RespLimits) -> Result<Self, RespError> {
+        RespParser::with_limits(*limits)
+            .parse(buf)?
+            .ok_or(RespError::NotComplete)
     }
 }
 
@@ -98,12 +45,302 @@ impl From<&str> for RespFrame {
 
 impl From<&[u8]> for RespFrame {
     fn from(value: &[u8]) -> Self {
-        BulkString(value.to_vec()).into()
+        BulkString::from(value).into()
     }
 }
 
 impl<const N: usize> From<&[u8; N]> for RespFrame {
     fn from(value: &[u8; N]) -> Self {
-        BulkString(value.to_vec()).into()
+        BulkString::from(value).into()
+    }
+}
+
+// the protocol spoken on a connection, RESP2 until the client upgrades with HELLO 3
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RespVersion {
+    #[default]
+    Resp2,
+    Resp3,
+}
+
+impl RespFrame {
+    // `encode` keeps the exact wire type of every frame, this adapts them to the protocol of the
+    // connection: RESP3 has a single null, RESP2 lacks most types and gets their closest
+    // equivalents the way redis sends them
+    pub fn encode_with(self, version: RespVersion) -> Vec<u8> {
+        match (self, version) {
+            (RespFrame::Array(array), _) => encode_aggregate("*", array.0, version),
+            (RespFrame::Set(set), RespVersion::Resp3) => encode_aggregate("~", set.0, version),
+            (RespFrame::Push(push), RespVersion::Resp3) => encode_aggregate(">", push.0, version),
+            (RespFrame::Map(map), RespVersion::Resp3) => {
+                let mut buf = Vec::with_capacity(BUF_CAP);
+                buf.extend_from_slice(format!("%{}\r\n", map.len()).as_bytes());
+                encode_map_entries(&mut buf, map, |frame| frame.encode_with(version));
+                buf
+            }
+            (RespFrame::Attribute(attr), RespVersion::Resp3) => {
+                let mut buf = Vec::with_capacity(BUF_CAP);
+                buf.extend_from_slice(format!("|{}\r\n", attr.attributes.len()).as_bytes());
+                encode_map_entries(&mut buf, attr.attributes, |frame| {
+                    frame.encode_with(version)
+                });
+                buf.extend_from_slice(&attr.frame.encode_with(version));
+                buf
+            }
+            (RespFrame::NullBulkString(_) | RespFrame::NullArray(_), RespVersion::Resp3) => {
+                RespNull.encode()
+            }
+
+            (RespFrame::Set(set), RespVersion::Resp2) => encode_aggregate("*", set.0, version),
+            (RespFrame::Push(push), RespVersion::Resp2) => encode_aggregate("*", push.0, version),
+            (RespFrame::Map(map), RespVersion::Resp2) => {
+                let items = map
+                    .0
+                    .into_iter()
+                    .flat_map(|(key, value)| [BulkString::new(key).into(), value])
+                    .collect::<Vec<_>>();
+                encode_aggregate("*", items, version)
+            }
+            // a RESP2 client can't skip the attributes, it only gets the reply
+            (RespFrame::Attribute(attr), RespVersion::Resp2) => attr.frame.encode_with(version),
+            (RespFrame::Null(_), RespVersion::Resp2) => RespNullBulkString.encode(),
+            (RespFrame::Boolean(b), RespVersion::Resp2) => RespFrame::Integer(b as i64).encode(),
+            (RespFrame::Double(d), RespVersion::Resp2) => BulkString::new(d.to_string()).encode(),
+            (RespFrame::BigNumber(n), RespVersion::Resp2) => BulkString::new(n.0).encode(),
+            (RespFrame::VerbatimString(s), RespVersion::Resp2) => BulkString::new(s.data).encode(),
+            (RespFrame::BlobError(e), RespVersion::Resp2) => {
+                SimpleError::new(e.replace(['\r', '\n'], " ")).encode()
+            }
+            (frame, _) => frame.encode(),
+        }
+    }
+}
+
+fn encode_aggregate(prefix: &str, items: Vec<RespFrame>, version: RespVersion) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(BUF_CAP);
+    buf.extend_from_slice(format!("{}{}\r\n", prefix, items.len()).as_bytes());
+    for item in items {
+        buf.extend_from_slice(&item.encode_with(version));
+    }
+    buf
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::resp::{RespLimits, RespParser};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    // decodes until the buffer is empty or broken, every successful decode must consume bytes
+    fn decode_all(buf: &mut BytesMut) {
+        while !buf.is_empty() {
+            let len = buf.len();
+            match RespFrame::decode(buf) {
+                Ok(_) => assert!(buf.len() < len),
+                Err(_) => return,
+            }
+        }
+    }
+
+    fn sample_frames() -> Vec<RespFrame> {
+        let mut map = RespMap::new();
+        map.insert(
+            "k".to_string(),
+            RespSet::new([1.5.into(), true.into()]).into(),
+        );
+        vec![
+            RespArray::new([
+                BulkString::new("set").into(),
+                BulkString::new("key").into(),
+                BulkString::new("value").into(),
+            ])
+            .into(),
+            RespAttribute::new(map.clone(), RespPush::new([RespNullArray.into()])).into(),
+            RespArray::new([
+                map.into(),
+                BigNumber::new("123456789012345678901").unwrap().into(),
+            ])
+            .into(),
+            VerbatimString::new(*b"txt", "line\r\nline").into(),
+            BlobError::new("ERR x").into(),
+            SimpleError::new("ERR y").into(),
+            RespFrame::Integer(-42),
+            RespNullBulkString.into(),
+            RespNull.into(),
+        ]
+    }
+
+    #[test]
+    fn test_encode_resp2_downgrade() {
+        let mut map = RespMap::new();
+        map.insert("score".to_string(), 1.5.into());
+        map.insert("flag".to_string(), true.into());
+        let frame: RespFrame = RespArray::new([
+            map.into(),
+            RespSet::new([RespNull.into()]).into(),
+            SimpleString::new("OK").into(),
+        ])
+        .into();
+        assert_eq!(
+            frame.clone().encode_with(RespVersion::Resp2),
+            b"*3\r\n*4\r\n$4\r\nflag\r\n:1\r\n$5\r\nscore\r\n$3\r\n1.5\r\n*1\r\n$-1\r\n+OK\r\n"
+        );
+        assert_eq!(
+            frame.clone().encode_with(RespVersion::Resp3),
+            frame.encode()
+        );
+    }
+
+    #[test]
+    fn test_encode_resp3_types_for_each_version() -> anyhow::Result<()> {
+        let mut attributes = RespMap::new();
+        attributes.insert("ttl".to_string(), 10.into());
+        let frame: RespFrame = RespPush::new([
+            RespAttribute::new(attributes, BigNumber::new("12345678901234567890")?).into(),
+            VerbatimString::new(*b"txt", "hi").into(),
+            BlobError::new("ERR a\r\nb").into(),
+            RespNullBulkString.into(),
+            RespNullArray.into(),
+        ])
+        .into();
+        assert_eq!(
+            frame.clone().encode_with(RespVersion::Resp2),
+            b"*5\r\n$20\r\n12345678901234567890\r\n$2\r\nhi\r\n-ERR a  b\r\n$-1\r\n*-1\r\n"
+        );
+        assert_eq!(
+            frame.clone().encode_with(RespVersion::Resp3),
+            b">5\r\n|1\r\n+ttl\r\n:10\r\n(12345678901234567890\r\n=6\r\ntxt:hi\r\n\
+              !8\r\nERR a\r\nb\r\n_\r\n_\r\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_frames_round_trip() -> anyhow::Result<()> {
+        let mut map = RespMap::new();
+        map.insert("k".to_string(), RespNullBulkString.into());
+        let frames: Vec<RespFrame> = vec![
+            RespNullBulkString.into(),
+            RespNullArray.into(),
+            BulkString::new("").into(),
+            RespArray::new([]).into(),
+            BigNumber::new("-98765432109876543210")?.into(),
+            VerbatimString::new(*b"mkd", "# title").into(),
+            BlobError::new("SYNTAX invalid").into(),
+            RespAttribute::new(map.clone(), RespNullArray).into(),
+            RespPush::new([map.into(), f64::INFINITY.into()]).into(),
+        ];
+        let mut buf = BytesMut::new();
+        for frame in frames.clone() {
+            buf.extend_from_slice(&frame.encode());
+        }
+        for frame in frames {
+            let len = buf.len();
+            assert_eq!(RespFrame::decode(&mut buf)?, frame);
+            assert_eq!(len - buf.len(), frame.encode().len());
+        }
+        assert!(buf.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_fuzz_random_bytes() {
+        let mut rng = StdRng::seed_from_u64(0x5eed);
+        let alphabet = b"+-:$*_#,(!=%~>|0123456789-\r\nabc";
+        for _ in 0..20_000 {
+            let len = rng.gen_range(0..64);
+            let data = (0..len)
+                .map(|_| alphabet[rng.gen_range(0..alphabet.len())])
+                .collect::<Vec<u8>>();
+            decode_all(&mut BytesMut::from(&data[..]));
+        }
+    }
+
+    #[test]
+    fn test_decode_fuzz_mutated_frames() {
+        let mut rng = StdRng::seed_from_u64(0xf022);
+        let encoded = sample_frames()
+            .into_iter()
+            .map(|frame| frame.encode())
+            .collect::<Vec<_>>();
+        for data in &encoded {
+            // every strict prefix of a valid frame is incomplete, never an error
+            for end in 0..data.len() {
+                let mut buf = BytesMut::from(&data[..end]);
+                assert_eq!(RespFrame::decode(&mut buf), Err(RespError::NotComplete));
+                assert_eq!(buf.len(), end);
+            }
+        }
+        for _ in 0..20_000 {
+            let mut data = encoded[rng.gen_range(0..encoded.len())].clone();
+            for _ in 0..rng.gen_range(1..4) {
+                let i = rng.gen_range(0..data.len());
+                match rng.gen_range(0..3) {
+                    0 => data[i] = rng.gen(),
+                    1 => {
+                        data.remove(i);
+                    }
+                    _ => data.insert(i, rng.gen()),
+                }
+                if data.is_empty() {
+                    break;
+                }
+            }
+            decode_all(&mut BytesMut::from(&data[..]));
+        }
+    }
+
+    #[test]
+    fn test_decode_limits() {
+        let protocol_error = |data: &[u8]| match RespFrame::decode(&mut BytesMut::from(data)) {
+            Err(RespError::Protocol(e)) => e,
+            ret => panic!("expected a protocol error, got {:?}", ret),
+        };
+        assert_eq!(protocol_error(b"$-2\r\n"), "invalid bulk length");
+        assert_eq!(protocol_error(b"*1\r\n$x\r\n"), "invalid bulk length");
+        assert_eq!(protocol_error(b"$536870913\r\n"), "invalid bulk length");
+        assert_eq!(protocol_error(b"*-2\r\n"), "invalid multibulk length");
+        assert_eq!(
+            protocol_error(b"%99999999999\r\n"),
+            "invalid multibulk length"
+        );
+        assert_eq!(
+            protocol_error(&b"*1\r\n".repeat(100_000)),
+            "too deeply nested aggregate"
+        );
+        let mut long_header = b"*".to_vec();
+        long_header.resize(128 * 1024, b'1');
+        assert_eq!(protocol_error(&long_header), "too big mbulk count string");
+
+        let mut parser = RespParser::with_limits(RespLimits {
+            max_bulk_len: 3,
+            max_multibulk_len: 2,
+            max_nesting: 1,
+        });
+        let mut parse = |data: &[u8]| parser.parse(&mut BytesMut::from(data));
+        assert!(parse(b"*2\r\n$3\r\nget\r\n$1\r\nk\r\n").is_ok());
+        assert!(parse(b"$4\r\nabcd\r\n").is_err());
+        assert!(parse(b"*3\r\n").is_err());
+        assert!(parse(b"*1\r\n*1\r\n:1\r\n").is_err());
+
+        // the typed decoders are held to the limits they are given, not to the default ones
+        let limits = RespLimits {
+            max_bulk_len: 3,
+            ..RespLimits::default()
+        };
+        let decode =
+            |data: &[u8]| BulkString::decode_with_limits(&mut BytesMut::from(data), &limits);
+        assert!(decode(b"$3\r\nabc\r\n").is_ok());
+        assert!(matches!(
+            decode(b"$4\r\nabcd\r\n"),
+            Err(RespError::Protocol(_))
+        ));
+        let decode =
+            |data: &[u8]| RespArray::decode_with_limits(&mut BytesMut::from(data), &limits);
+        assert!(matches!(
+            decode(b"*1\r\n$4\r\nabcd\r\n"),
+            Err(RespError::Protocol(_))
+        ));
     }
 }
@@ -0,0 +1,149 @@
+use crate::resp::{BulkString, RespArray, RespError, RespFrame, PROTO_INLINE_MAX_SIZE};
+use bytes::BytesMut;
+
+// - inline command: "<arg> <arg> ...\n", what telnet or `echo PING | nc` send; arguments are
+//   split on whitespace and may be quoted the way redis-cli quotes them. An empty line decodes
+//   to an empty array
+impl RespArray {
+    pub fn decode_inline(buf: &mut BytesMut) -> Result<Self, RespError> {
+        let Some(end) = buf.iter().position(|&b| b == b'\n') else {
+            if buf.len() > PROTO_INLINE_MAX_SIZE {
+                return Err(RespError::Protocol("too big inline request".to_string()));
+            }
+            return Err(RespError::NotComplete);
+        };
+        let line = buf.split_to(end + 1);
+        let line = line[..end].strip_suffix(b"\r").unwrap_or(&line[..end]);
+        let args = split_args(line)?
+            .into_iter()
+            .map(|arg| BulkString::new(arg).into())
+            .collect::<Vec<RespFrame>>();
+        Ok(RespArray::new(args))
+    }
+}
+
+// the quoting rules of sdssplitargs: "..." understands \n, \r, \t, \b, \a and \xHH escapes,
+// '...' only \', and a closing quote must be followed by a space or the end of the line
+fn split_args(line: &[u8]) -> Result<Vec<Vec<u8>>, RespError> {
+    let mut args = Vec::new();
+    let mut iter = line.iter().copied().peekable();
+    loop {
+        while iter.next_if(|b| b.is_ascii_whitespace()).is_some() {}
+        if iter.peek().is_none() {
+            return Ok(args);
+        }
+
+        let mut arg = Vec::new();
+        while let Some(b) = iter.next() {
+            match b {
+                b'"' => loop {
+                    match iter.next().ok_or_else(unbalanced_quotes)? {
+                        b'\\' => {
+                            let escaped = iter.next().ok_or_else(unbalanced_quotes)?;
+                            arg.push(match escaped {
+                                b'x' => hex_byte(&mut iter).unwrap_or(b'x'),
+                                b'n' => b'\n',
+                                b'r' => b'\r',
+                                b't' => b'\t',
+                                b'b' => 0x08,
+                                b'a' => 0x07,
+                                c => c,
+                            });
+                        }
+                        b'"' => {
+                            closing_quote(&mut iter)?;
+                            break;
+                        }
+                        c => arg.push(c),
+                    }
+                },
+                b'\'' => loop {
+                    match iter.next().ok_or_else(unbalanced_quotes)? {
+                        b'\\' if iter.peek() == Some(&b'\'') => {
+                            arg.push(b'\'');
+                            iter.next();
+                        }
+                        b'\'' => {
+                            closing_quote(&mut iter)?;
+                            break;
+                        }
+                        c => arg.push(c),
+                    }
+                },
+                b if b.is_ascii_whitespace() => break,
+                b => arg.push(b),
+            }
+        }
+        args.push(arg);
+    }
+}
+
+// consumes "HH" when both are hex digits, leaves the input alone otherwise
+fn hex_byte(iter: &mut std::iter::Peekable<impl Iterator<Item = u8> + Clone>) -> Option<u8> {
+    let mut ahead = iter.clone();
+    let high = (ahead.next()? as char).to_digit(16)?;
+    let low = (ahead.next()? as char).to_digit(16)?;
+    iter.nth(1);
+    Some((high * 16 + low) as u8)
+}
+
+fn closing_quote(
+    iter: &mut std::iter::Peekable<impl Iterator<Item = u8>>,
+) -> Result<(), RespError> {
+    match iter.peek() {
+        None => Ok(()),
+        Some(b) if b.is_ascii_whitespace() => Ok(()),
+        Some(_) => Err(unbalanced_quotes()),
+    }
+}
+
+fn unbalanced_quotes() -> RespError {
+    RespError::Protocol("unbalanced quotes in request".to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn inline(line: &[u8]) -> Result<Vec<String>, RespError> {
+        let mut buf = BytesMut::from(line);
+        let array = RespArray::decode_inline(&mut buf)?;
+        Ok(array
+            .0
+            .into_iter()
+            .map(|frame| match frame {
+                RespFrame::BulkString(s) => String::from_utf8(s.0.to_vec()).unwrap(),
+                _ => unreachable!(),
+            })
+            .collect())
+    }
+
+    #[test]
+    fn test_decode_inline() -> anyhow::Result<()> {
+        assert_eq!(inline(b"PING\r\n")?, ["PING"]);
+        assert_eq!(inline(b"  set  key\tvalue \n")?, ["set", "key", "value"]);
+        assert_eq!(
+            inline(b"set k \"a b\\n\\x41\\\"\" 'it\\'s' \"\"\r\n")?,
+            ["set", "k", "a b\nA\"", "it's", ""]
+        );
+        assert_eq!(inline(b"\r\n")?, Vec::<String>::new());
+
+        assert_eq!(inline(b"PING"), Err(RespError::NotComplete));
+        assert!(inline(b"set k \"value\n").is_err());
+        assert!(inline(b"set k 'a'b\n").is_err());
+        let long = vec![b'a'; PROTO_INLINE_MAX_SIZE + 1];
+        assert_eq!(
+            inline(&long),
+            Err(RespError::Protocol("too big inline request".to_string()))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_inline_leaves_the_next_command() -> anyhow::Result<()> {
+        let mut buf = BytesMut::from(&b"PING\nECHO hi\n"[..]);
+        RespArray::decode_inline(&mut buf)?;
+        assert_eq!(buf.as_ref(), b"ECHO hi\n");
+        Ok(())
+    }
+}
@@ -1,20 +1,39 @@
+mod big_number;
+mod blob_error;
 mod bulk_string;
 mod frame;
+mod inline;
+mod parser;
 mod resp_array;
+mod resp_attribute;
 mod resp_bool;
 mod resp_double;
 mod resp_int;
 mod resp_map;
 mod resp_null;
+mod resp_push;
 mod resp_set;
 mod simple_error;
 mod simple_string;
+mod verbatim_string;
 
 pub use crate::resp::{
-    bulk_string::BulkString, frame::RespFrame, resp_array::RespArray, resp_map::RespMap,
-    resp_null::RespNull, resp_set::RespSet, simple_error::SimpleError, simple_string::SimpleString,
+    big_number::BigNumber,
+    blob_error::BlobError,
+    bulk_string::BulkString,
+    frame::{RespFrame, RespVersion},
+    parser::{RespLimits, RespParser, PROTO_INLINE_MAX_SIZE},
+    resp_array::RespArray,
+    resp_attribute::RespAttribute,
+    resp_map::RespMap,
+    resp_null::{RespNull, RespNullArray, RespNullBulkString},
+    resp_push::RespPush,
+    resp_set::RespSet,
+    simple_error::SimpleError,
+    simple_string::SimpleString,
+    verbatim_string::VerbatimString,
 };
-use bytes::{Buf, BytesMut};
+use bytes::{Buf, Bytes, BytesMut};
 use enum_dispatch::enum_dispatch;
 use thiserror::Error;
 
@@ -28,8 +47,13 @@ pub trait RespEncode {
 
 pub trait RespDecode: Sized {
     const PREFIX: &'static str;
-    fn decode(buf: &mut BytesMut) -> Result<Self, RespError>;
-    fn expect_length(buf: &[u8]) -> Result<usize, RespError>;
+    // a frame beyond `limits` is a protocol error, the lengths it announces are checked before
+    // anything is read or allocated for it
+    fn decode_with_limits(buf: &mut BytesMut, limits: &RespLimits) -> Result<Self, RespError>;
+
+    fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
+        Self::decode_with_limits(buf, &RespLimits::default())
+    }
 }
 
 #[allow(dead_code)]
@@ -43,6 +67,9 @@ pub enum RespError {
     InvalidFrameLength(isize),
     #[error("Frame is not complete")]
     NotComplete,
+    // the peer broke the protocol or one of its limits, the connection can't be trusted anymore
+    #[error("Protocol error: {0}")]
+    Protocol(String),
 
     #[error("Parse error: {0}")]
     ParseIntError(#[from] std::num::ParseIntError),
@@ -103,51 +130,42 @@ fn parse_length(buf: &[u8], prefix: &str) -> Result<(usize, isize), RespError> {
     Ok((end, s.parse()?))
 }
 
-fn calc_total_length(buf: &[u8], end: usize, len: usize, prefix: &str) -> Result<usize, RespError> {
-    let mut total = end + CRLF_LEN;
-    let mut data = &buf[total..];
-    match prefix {
-        "*" | "~" => {
-            for _ in 0..len {
-                let len = RespFrame::expect_length(data)?;
-                data = &data[len..];
-                total += len;
-            }
-            Ok(total)
-        }
-        "%" => {
-            for _ in 0..len {
-                let len = SimpleString::expect_length(data)?;
-
-                data = &data[len..];
-                total += len;
-
-                let len = RespFrame::expect_length(data)?;
-                data = &data[len..];
-                total += len;
-            }
-            Ok(total)
-        }
-        _ => Ok(len + CRLF_LEN),
+// the payload of a length prefixed frame ($, = and !), "$-1" is RespNullBulkString
+fn decode_blob(buf: &mut BytesMut, prefix: &str, limits: &RespLimits) -> Result<Bytes, RespError> {
+    let (end, len) = parse_length(buf, prefix)?;
+    let len = usize::try_from(len)
+        .ok()
+        .filter(|len| *len <= limits.max_bulk_len)
+        .ok_or_else(|| RespError::Protocol("invalid bulk length".to_string()))?;
+    let remained = &buf[end + CRLF_LEN..];
+    if remained.len() < len + CRLF_LEN {
+        return Err(RespError::NotComplete);
     }
+    buf.advance(end + CRLF_LEN);
+    let data = buf.split_to(len + CRLF_LEN).freeze();
+    Ok(data.slice(..len))
 }
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    #[test]
-    fn test_calc_array_length() -> anyhow::Result<()> {
-        let buf = b"*2\r\n$3\r\nset\r\n$5\r\nhello\r\n";
-        let (end, len) = parse_length(buf, "*")?;
-        let total_len = calc_total_length(buf, end, len as usize, "*")?;
-        assert_eq!(total_len, buf.len());
-
-        let buf = b"*2\r\n$3\r\nset\r\n";
-        let (end, len) = parse_length(buf, "*")?;
-        let len = len as usize;
-        let ret = calc_total_length(buf, end, len, "*");
-        assert_eq!(ret.unwrap_err(), RespError::NotComplete);
+// aggregates are decoded by RespParser in a single pass, `variant` unwraps the expected type
+fn decode_aggregate<T>(
+    buf: &mut BytesMut,
+    prefix: &str,
+    limits: &RespLimits,
+    variant: impl FnOnce(RespFrame) -> Option<T>,
+) -> Result<T, RespError> {
+    let (_, len) = parse_length(buf, prefix)?;
+    if len < 0 {
+        return Err(RespError::InvalidFrameLength(len));
+    }
+    let frame = RespParser::with_limits(*limits)
+        .parse(buf)?
+        .ok_or(RespError::NotComplete)?;
+    variant(frame).ok_or_else(|| RespError::InvalidFrameType(format!("expect: {}", prefix)))
+}
 
-        Ok(())
+fn encode_map_entries(buf: &mut Vec<u8>, map: RespMap, encode: impl Fn(RespFrame) -> Vec<u8>) {
+    for (key, value) in map.0 {
+        buf.extend_from_slice(&SimpleString::new(key).encode());
+        buf.extend_from_slice(&encode(value));
     }
 }
@@ -0,0 +1,401 @@
+use crate::resp::{
+    BigNumber, BlobError, BulkString, RespArray, RespAttribute, RespError, RespFrame, RespMap,
+    RespNull, RespNullArray, RespNullBulkString, RespPush, RespSet, SimpleError, SimpleString,
+    VerbatimString, CRLF_LEN,
+};
+use bytes::{Bytes, BytesMut};
+
+// an element of the frame being scanned, offsets are relative to the start of the frame
+#[derive(Debug, Clone, Copy)]
+enum Token {
+    // + - : # , ( _ with the line after the prefix
+    Line {
+        prefix: u8,
+        start: usize,
+        end: usize,
+    },
+    // $ = ! with their payload
+    Blob {
+        prefix: u8,
+        start: usize,
+        end: usize,
+    },
+    // * ~ % > |, the tokens of the elements follow it
+    Aggregate {
+        prefix: u8,
+        len: usize,
+    },
+    NullBulkString,
+    NullArray,
+}
+
+// a line without its CRLF longer than this is refused, like PROTO_INLINE_MAX_SIZE in redis
+pub const PROTO_INLINE_MAX_SIZE: usize = 64 * 1024;
+
+// what a peer is allowed to send, a frame beyond them is a protocol error
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RespLimits {
+    // payload of a bulk string, verbatim string or blob error
+    pub max_bulk_len: usize,
+    // elements of an aggregate
+    pub max_multibulk_len: usize,
+    // aggregates nested in one another, it bounds the recursion building the frame
+    pub max_nesting: usize,
+}
+
+impl Default for RespLimits {
+    fn default() -> Self {
+        Self {
+            max_bulk_len: 512 * 1024 * 1024,
+            max_multibulk_len: 1024 * 1024 * 1024,
+            max_nesting: 128,
+        }
+    }
+}
+
+// an incremental RESP decoder: a frame that is not complete yet is resumed after its last complete
+// element instead of being scanned again from the start, and once it is complete its bulk strings
+// are slices of the buffer rather than copies
+#[derive(Debug, Default)]
+pub struct RespParser {
+    limits: RespLimits,
+    // bytes of the pending frame scanned so far
+    pos: usize,
+    tokens: Vec<Token>,
+    // for each aggregate still open, the number of elements it is waiting for
+    open: Vec<usize>,
+}
+
+impl RespParser {
+    pub fn with_limits(limits: RespLimits) -> Self {
+        RespParser {
+            limits,
+            ..RespParser::default()
+        }
+    }
+
+    // returns Ok(None) until a whole frame is in the buffer, which is then split off of it
+    pub fn parse(&mut self, buf: &mut BytesMut) -> Result<Option<RespFrame>, RespError> {
+        Ok(self.parse_raw(buf)?.map(|(frame, _)| frame))
+    }
+
+    // like parse, along with the bytes the frame was decoded from
+    pub fn parse_raw(
+        &mut self,
+        buf: &mut BytesMut,
+    ) -> Result<Option<(RespFrame, Bytes)>, RespError> {
+        let len = match self.scan(buf) {
+            Ok(Some(len)) => len,
+            Ok(None) => return Ok(None),
+            Err(e) => {
+                self.reset();
+                return Err(e);
+            }
+        };
+        let data = buf.split_to(len).freeze();
+        let mut tokens = self.tokens.drain(..);
+        let ret = build(&mut tokens, &data);
+        drop(tokens);
+        self.reset();
+        ret.map(|frame| Some((frame, data)))
+    }
+
+    fn reset(&mut self) {
+        self.pos = 0;
+        self.tokens.clear();
+        self.open.clear();
+    }
+
+    // returns the length of the frame once its last element is scanned
+    fn scan(&mut self, buf: &[u8]) -> Result<Option<usize>, RespError> {
+        loop {
+            let Some((token, next)) = self.scan_element(buf)? else {
+                return Ok(None);
+            };
+            self.tokens.push(token);
+            self.pos = next;
+            let elements = match token {
+                Token::Aggregate { prefix: b'%', len } => len * 2,
+                // the attribute pairs and the reply they describe
+                Token::Aggregate { prefix: b'|', len } => len * 2 + 1,
+                Token::Aggregate { len, .. } => len,
+                _ => 0,
+            };
+            if elements > 0 {
+                if self.open.len() >= self.limits.max_nesting {
+                    return Err(RespError::Protocol(
+                        "too deeply nested aggregate".to_string(),
+                    ));
+                }
+                self.open.push(elements);
+                continue;
+            }
+            // the element is complete, and so may be the aggregates it closes
+            loop {
+                match self.open.last_mut() {
+                    None => return Ok(Some(self.pos)),
+                    Some(remaining) if *remaining > 1 => {
+                        *remaining -= 1;
+                        break;
+                    }
+                    Some(_) => {
+                        self.open.pop();
+                    }
+                }
+            }
+        }
+    }
+
+    // the token starting at `pos` and where the next one starts, None if it is not complete
+    fn scan_element(&self, buf: &[u8]) -> Result<Option<(Token, usize)>, RespError> {
+        let pos = self.pos;
+        // an unknown type is reported right away rather than once its line is complete
+        match buf.get(pos) {
+            None => return Ok(None),
+            Some(prefix) if !b"+-:#,(_$=!*~%>|".contains(prefix) => {
+                return Err(RespError::InvalidFrameType(format!(
+                    "unknown frame type: {:?}",
+                    *prefix as char
+                )))
+            }
+            Some(_) => {}
+        }
+        let Some(line_end) = find_crlf_from(buf, pos) else {
+            // the length of a header is a handful of digits, only a simple string may be long
+            if buf.len() - pos > PROTO_INLINE_MAX_SIZE {
+                return Err(RespError::Protocol(
+                    match buf[pos] {
+                        b'$' | b'=' | b'!' => "too big bulk count string",
+                        b'*' | b'~' | b'%' | b'>' | b'|' => "too big mbulk count string",
+                        _ => "too big inline request",
+                    }
+                    .to_string(),
+                ));
+            }
+            return Ok(None);
+        };
+        if line_end == pos {
+            return Err(RespError::InvalidFrame("empty line".to_string()));
+        }
+        let prefix = buf[pos];
+        let next = line_end + CRLF_LEN;
+        let token = match prefix {
+            b'+' | b'-' | b':' | b'#' | b',' | b'(' | b'_' => Token::Line {
+                prefix,
+                start: pos + 1,
+                end: line_end,
+            },
+            b'$' | b'=' | b'!' => {
+                let len = parse_len(&buf[pos + 1..line_end]);
+                if len == Some(-1) && prefix == b'$' {
+                    return Ok(Some((Token::NullBulkString, next)));
+                }
+                let len = len
+                    .and_then(|len| usize::try_from(len).ok())
+                    .filter(|len| *len <= self.limits.max_bulk_len)
+                    .ok_or_else(|| RespError::Protocol("invalid bulk length".to_string()))?;
+                let end = next + len;
+                match buf.get(end..end + CRLF_LEN) {
+                    None => return Ok(None),
+                    Some(b"\r\n") => {}
+                    Some(_) => {
+                        return Err(RespError::InvalidFrame(
+                            "payload is not terminated by CRLF".to_string(),
+                        ))
+                    }
+                }
+                return Ok(Some((
+                    Token::Blob {
+                        prefix,
+                        start: next,
+                        end,
+                    },
+                    end + CRLF_LEN,
+                )));
+            }
+            b'*' | b'~' | b'%' | b'>' | b'|' => {
+                let len = parse_len(&buf[pos + 1..line_end]);
+                if len == Some(-1) && prefix == b'*' {
+                    return Ok(Some((Token::NullArray, next)));
+                }
+                let len = len
+                    .and_then(|len| usize::try_from(len).ok())
+                    .filter(|len| *len <= self.limits.max_multibulk_len)
+                    .ok_or_else(|| RespError::Protocol("invalid multibulk length".to_string()))?;
+                Token::Aggregate { prefix, len }
+            }
+            _ => unreachable!("the prefix is checked above"),
+        };
+        Ok(Some((token, next)))
+    }
+}
+
+fn find_crlf_from(buf: &[u8], mut pos: usize) -> Option<usize> {
+    loop {
+        let cr = pos + buf.get(pos..)?.iter().position(|&b| b == b'\r')?;
+        match buf.get(cr + 1) {
+            Some(b'\n') => return Some(cr),
+            Some(_) => pos = cr + 1,
+            None => return None,
+        }
+    }
+}
+
+fn parse_len(line: &[u8]) -> Option<i64> {
+    std::str::from_utf8(line).ok()?.parse().ok()
+}
+
+fn build(tokens: &mut impl Iterator<Item = Token>, data: &Bytes) -> Result<RespFrame, RespError> {
+    let token = tokens.next().ok_or(RespError::NotComplete)?;
+    let frame = match token {
+        Token::Line { prefix, start, end } => {
+            let line = &data[start..end];
+            match prefix {
+                b'+' => SimpleString::new(String::from_utf8_lossy(line)).into(),
+                b'-' => SimpleError::new(String::from_utf8_lossy(line)).into(),
+                b':' => RespFrame::Integer(std::str::from_utf8(line)?.parse()?),
+                b',' => RespFrame::Double(std::str::from_utf8(line)?.parse()?),
+                b'(' => BigNumber::new(std::str::from_utf8(line)?)?.into(),
+                b'#' if line == b"t" => true.into(),
+                b'#' if line == b"f" => false.into(),
+                b'_' if line.is_empty() => RespNull.into(),
+                _ => {
+                    return Err(RespError::InvalidFrame(format!(
+                        "invalid {:?} frame: {:?}",
+                        prefix as char,
+                        String::from_utf8_lossy(line)
+                    )))
+                }
+            }
+        }
+        Token::Blob { prefix, start, end } => match prefix {
+            b'$' => BulkString(data.slice(start..end)).into(),
+            b'=' => VerbatimString::from_payload(&data[start..end])?.into(),
+            _ => BlobError::new(String::from_utf8_lossy(&data[start..end])).into(),
+        },
+        Token::NullBulkString => RespNullBulkString.into(),
+        Token::NullArray => RespNullArray.into(),
+        Token::Aggregate { prefix, len } => match prefix {
+            b'*' => RespArray::new(build_elements(tokens, data, len)?).into(),
+            b'~' => RespSet::new(build_elements(tokens, data, len)?).into(),
+            b'>' => RespPush::new(build_elements(tokens, data, len)?).into(),
+            b'%' => build_map(tokens, data, len)?.into(),
+            _ => {
+                let attributes = build_map(tokens, data, len)?;
+                RespAttribute::new(attributes, build(tokens, data)?).into()
+            }
+        },
+    };
+    Ok(frame)
+}
+
+fn build_elements(
+    tokens: &mut impl Iterator<Item = Token>,
+    data: &Bytes,
+    len: usize,
+) -> Result<Vec<RespFrame>, RespError> {
+    (0..len).map(|_| build(tokens, data)).collect()
+}
+
+// map keys may be simple or bulk strings
+fn build_map(
+    tokens: &mut impl Iterator<Item = Token>,
+    data: &Bytes,
+    len: usize,
+) -> Result<RespMap, RespError> {
+    let mut map = RespMap::new();
+    for _ in 0..len {
+        let key = match build(tokens, data)? {
+            RespFrame::SimpleString(key) => key.0,
+            RespFrame::BulkString(key) => std::str::from_utf8(&key)?.to_string(),
+            frame => {
+                return Err(RespError::InvalidFrameType(format!(
+                    "map key must be a string, got: {:?}",
+                    frame
+                )))
+            }
+        };
+        map.insert(key, build(tokens, data)?);
+    }
+    Ok(map)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_resumes_across_calls() -> anyhow::Result<()> {
+        let data = b"*3\r\n$3\r\nset\r\n%1\r\n+k\r\n~1\r\n:1\r\n$5\r\nhello\r\n";
+        let mut parser = RespParser::default();
+        let mut buf = BytesMut::new();
+        for (i, b) in data.iter().enumerate() {
+            buf.extend_from_slice(&[*b]);
+            let ret = parser.parse(&mut buf)?;
+            if i < data.len() - 1 {
+                assert_eq!(ret, None);
+                assert_eq!(buf.len(), i + 1);
+            } else {
+                let mut map = RespMap::new();
+                map.insert("k".to_string(), RespSet::new([1.into()]).into());
+                let expected = RespArray::new([
+                    BulkString::new("set").into(),
+                    map.into(),
+                    BulkString::new("hello").into(),
+                ]);
+                assert_eq!(ret, Some(expected.into()));
+            }
+        }
+        assert!(buf.is_empty());
+        assert_eq!(parser.pos, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_shares_the_buffer() -> anyhow::Result<()> {
+        let mut buf = BytesMut::from(&b"*1\r\n$5\r\nhello\r\n*1\r\n$2\r\nhi\r\n"[..]);
+        let start = buf.as_ptr() as usize;
+        let mut parser = RespParser::default();
+        let Some(RespFrame::Array(array)) = parser.parse(&mut buf)? else {
+            panic!("expected an array");
+        };
+        let RespFrame::BulkString(s) = &array[0] else {
+            panic!("expected a bulk string");
+        };
+        assert_eq!(s.as_ptr() as usize, start + 8);
+        // the next frame stays in the buffer
+        assert_eq!(buf.as_ref(), b"*1\r\n$2\r\nhi\r\n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for data in [
+            &b"?"[..],
+            b"*x\r\n",
+            b"$-2\r\n",
+            b"$3\r\nabcd\r\n",
+            b"*1\r\n#x\r\n",
+            b"%1\r\n:1\r\n:2\r\n",
+        ] {
+            let mut parser = RespParser::default();
+            let mut buf = BytesMut::from(data);
+            assert!(parser.parse(&mut buf).is_err(), "{:?}", data);
+            assert_eq!(parser.pos, 0);
+        }
+    }
+
+    #[test]
+    fn test_scan_frame_length() -> anyhow::Result<()> {
+        let scan = |buf: &[u8]| RespParser::default().scan(buf);
+        let buf = b"*2\r\n$3\r\nset\r\n$5\r\nhello\r\n";
+        assert_eq!(scan(buf)?, Some(buf.len()));
+        assert_eq!(scan(b"*2\r\n$3\r\nset\r\n")?, None);
+        assert_eq!(scan(b"*2\r\n$3\r\nset\r\n$5\r\nhel")?, None);
+
+        let buf = b"|1\r\n+ttl\r\n:1\r\n*2\r\n$-1\r\n*-1\r\n:1\r\n";
+        assert_eq!(scan(buf)?, Some(buf.len() - 4));
+        assert_eq!(scan(&buf[..12])?, None);
+        Ok(())
+    }
+}
@@ -1,8 +1,7 @@
 use crate::resp::{
-    calc_total_length, extract_fixed_data, parse_length, RespDecode, RespEncode, RespError,
-    RespFrame, BUF_CAP, CRLF_LEN,
+    decode_aggregate, RespDecode, RespEncode, RespError, RespFrame, RespLimits, BUF_CAP,
 };
-use bytes::{Buf, BytesMut};
+use bytes::BytesMut;
 use std::ops::Deref;
 
 #[derive(Debug, Clone, PartialOrd, PartialEq)]
@@ -17,9 +16,6 @@ impl RespArray {
 // - array: "*<number-of-elements>\r\n<element-1>...<element-n>"
 impl RespEncode for RespArray {
     fn encode(self) -> Vec<u8> {
-        if self.0.is_empty() {
-            return "*-1\r\n".into();
-        }
         let mut buf = Vec::with_capacity(BUF_CAP);
         buf.extend_from_slice(&format!("*{}\r\n", self.len()).into_bytes());
         for frame in self.0 {
@@ -31,40 +27,11 @@ impl RespEncode for RespArray {
 
 impl RespDecode for RespArray {
     const PREFIX: &'static str = "*";
-    fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
-        let (end, len) = parse_length(buf, Self::PREFIX)?;
-        if len == -1 {
-            extract_fixed_data(buf, "*-1\r\n", "NullArray")?;
-            return Ok(RespArray::new(vec![]));
-        }
-        if len < -1 {
-            return Err(RespError::InvalidFrameLength(len));
-        }
-        let len = len as usize;
-        let total_len = calc_total_length(buf, end, len, Self::PREFIX)?;
-        if buf.len() < total_len {
-            return Err(RespError::NotComplete);
-        }
-
-        buf.advance(end + CRLF_LEN);
-
-        let mut frames = Vec::with_capacity(len);
-        for _ in 0..len {
-            frames.push(RespFrame::decode(buf)?);
-        }
-        Ok(RespArray::new(frames))
-    }
-
-    fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
-        let (end, len) = parse_length(buf, Self::PREFIX)?;
-        if len == -1 {
-            return Ok(4);
-        }
-        if len < -1 {
-            return Err(RespError::InvalidFrameLength(len));
-        }
-        let len = len as usize;
-        calc_total_length(buf, end, len, Self::PREFIX)
+    fn decode_with_limits(buf: &mut BytesMut, limits: &RespLimits) -> Result<Self, RespError> {
+        decode_aggregate(buf, Self::PREFIX, limits, |frame| match frame {
+            RespFrame::Array(v) => Some(v),
+            _ => None,
+        })
     }
 }
 
@@ -113,19 +80,22 @@ mod test {
     }
 
     #[test]
-    fn test_null_array_encode() {
+    fn test_empty_array_encode() {
         let frame = RespArray::new(vec![]);
-        assert_eq!(frame.encode(), b"*-1\r\n");
+        assert_eq!(frame.encode(), b"*0\r\n");
     }
 
     #[test]
-    fn test_null_array_decode() -> anyhow::Result<()> {
+    fn test_empty_array_decode() -> anyhow::Result<()> {
         let mut buf = BytesMut::new();
-        buf.extend_from_slice(b"*-1\r\n");
+        buf.extend_from_slice(b"*0\r\n");
 
         let frame = RespArray::decode(&mut buf)?;
-        let expected = RespArray::new(vec![]);
-        assert_eq!(frame, expected);
+        assert_eq!(frame, RespArray::new(vec![]));
+
+        // the null array is a frame of its own
+        buf.extend_from_slice(b"*-1\r\n");
+        assert!(RespArray::decode(&mut buf).is_err());
 
         Ok(())
     }
@@ -0,0 +1,76 @@
+use crate::resp::{
+    decode_aggregate, encode_map_entries, RespDecode, RespEncode, RespError, RespFrame, RespLimits,
+    RespMap, BUF_CAP,
+};
+use bytes::BytesMut;
+
+// auxiliary data about the reply that follows it, clients that do not know about it skip it
+#[derive(Debug, Clone, PartialOrd, PartialEq)]
+pub struct RespAttribute {
+    pub(crate) attributes: RespMap,
+    pub(crate) frame: Box<RespFrame>,
+}
+
+impl RespAttribute {
+    pub fn new(attributes: RespMap, frame: impl Into<RespFrame>) -> Self {
+        RespAttribute {
+            attributes,
+            frame: Box::new(frame.into()),
+        }
+    }
+}
+
+// - attribute: "|<number-of-entries>\r\n<key-1><value-1>...<key-n><value-n><reply>"
+impl RespEncode for RespAttribute {
+    fn encode(self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(BUF_CAP);
+        buf.extend_from_slice(&format!("|{}\r\n", self.attributes.len()).into_bytes());
+        encode_map_entries(&mut buf, self.attributes, |frame| frame.encode());
+        buf.extend_from_slice(&self.frame.encode());
+        buf
+    }
+}
+
+impl RespDecode for RespAttribute {
+    const PREFIX: &'static str = "|";
+    fn decode_with_limits(buf: &mut BytesMut, limits: &RespLimits) -> Result<Self, RespError> {
+        decode_aggregate(buf, Self::PREFIX, limits, |frame| match frame {
+            RespFrame::Attribute(v) => Some(v),
+            _ => None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::resp::RespArray;
+
+    fn popularity() -> RespAttribute {
+        let mut attributes = RespMap::new();
+        attributes.insert("key-popularity".to_string(), 0.1923.into());
+        RespAttribute::new(attributes, RespArray::new([2039123.into(), 9543892.into()]))
+    }
+
+    #[test]
+    fn test_attribute_encode() {
+        let frame: RespFrame = popularity().into();
+        assert_eq!(
+            frame.encode(),
+            b"|1\r\n+key-popularity\r\n,+0.1923\r\n*2\r\n:2039123\r\n:9543892\r\n"
+        );
+    }
+
+    #[test]
+    fn test_attribute_decode() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"|1\r\n+key-popularity\r\n,0.1923\r\n*2\r\n:2039123\r\n");
+        assert_eq!(RespAttribute::decode(&mut buf), Err(RespError::NotComplete));
+
+        buf.extend_from_slice(b":9543892\r\n");
+        let frame = RespAttribute::decode(&mut buf)?;
+        assert_eq!(frame, popularity());
+        assert!(buf.is_empty());
+        Ok(())
+    }
+}
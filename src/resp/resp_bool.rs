@@ -1,4 +1,4 @@
-use crate::resp::{extract_fixed_data, RespDecode, RespEncode, RespError};
+use crate::resp::{extract_fixed_data, RespDecode, RespEncode, RespError, RespLimits};
 use bytes::BytesMut;
 // - boolean: "#<t|f>\r\n"
 impl RespEncode for bool {
@@ -13,7 +13,7 @@ impl RespEncode for bool {
 
 impl RespDecode for bool {
     const PREFIX: &'static str = "#";
-    fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
+    fn decode_with_limits(buf: &mut BytesMut, _limits: &RespLimits) -> Result<Self, RespError> {
         match extract_fixed_data(buf, "#t\r\n", "Bool") {
             Ok(_) => Ok(true),
             Err(_) => match extract_fixed_data(buf, "#f\r\n", "Bool") {
@@ -22,9 +22,6 @@ impl RespDecode for bool {
             },
         }
     }
-    fn expect_length(_buf: &[u8]) -> Result<usize, RespError> {
-        Ok(4)
-    }
 }
 
 #[cfg(test)]
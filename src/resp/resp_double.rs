@@ -1,10 +1,17 @@
-use crate::resp::{extract_simple_frame_data, RespDecode, RespEncode, RespError, CRLF_LEN};
+use crate::resp::{
+    extract_simple_frame_data, RespDecode, RespEncode, RespError, RespLimits, CRLF_LEN,
+};
 use bytes::BytesMut;
 // - double: ",[<+|->]<integral>[.<fractional>][<E|e>[sign]<exponent>]\r\n"
 impl RespEncode for f64 {
     fn encode(self) -> Vec<u8> {
         let mut buf = Vec::with_capacity(32);
-        let ret = if self.abs() > 1e+8 || self.abs() < 1e-8 {
+        let ret = if self.is_nan() {
+            ",nan\r\n".to_string()
+        } else if self.is_infinite() {
+            let sign = if self < 0.0 { "-" } else { "" };
+            format!(",{}inf\r\n", sign)
+        } else if self.abs() > 1e+8 || self.abs() < 1e-8 {
             format!(",{:+e}\r\n", self)
         } else {
             let sign = if self < 0.0 { "" } else { "+" };
@@ -17,16 +24,12 @@ impl RespEncode for f64 {
 }
 impl RespDecode for f64 {
     const PREFIX: &'static str = ",";
-    fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
+    fn decode_with_limits(buf: &mut BytesMut, _limits: &RespLimits) -> Result<Self, RespError> {
         let end = extract_simple_frame_data(buf, Self::PREFIX)?;
         let data = buf.split_to(end + CRLF_LEN);
         let s = String::from_utf8_lossy(&data[Self::PREFIX.len()..end]);
         Ok(s.parse()?)
     }
-    fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
-        let end = extract_simple_frame_data(buf, Self::PREFIX)?;
-        Ok(end + CRLF_LEN)
-    }
 }
 
 #[cfg(test)]
@@ -46,6 +49,11 @@ mod test {
 
         let frame: RespFrame = (-1.23456e-9).into();
         assert_eq!(&frame.encode(), b",-1.23456e-9\r\n");
+
+        let frame: RespFrame = f64::NEG_INFINITY.into();
+        assert_eq!(frame.encode(), b",-inf\r\n");
+        let frame: RespFrame = f64::NAN.into();
+        assert_eq!(frame.encode(), b",nan\r\n");
     }
 
     #[test]
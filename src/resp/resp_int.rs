@@ -1,4 +1,6 @@
-use crate::resp::{extract_simple_frame_data, RespDecode, RespEncode, RespError, CRLF_LEN};
+use crate::resp::{
+    extract_simple_frame_data, RespDecode, RespEncode, RespError, RespLimits, CRLF_LEN,
+};
 use bytes::BytesMut;
 
 // - integer: ":[<+|->]<value>\r\n"
@@ -10,16 +12,12 @@ impl RespEncode for i64 {
 
 impl RespDecode for i64 {
     const PREFIX: &'static str = ":";
-    fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
+    fn decode_with_limits(buf: &mut BytesMut, _limits: &RespLimits) -> Result<Self, RespError> {
         let end = extract_simple_frame_data(buf, Self::PREFIX)?;
         let data = buf.split_to(end + CRLF_LEN);
         let s = String::from_utf8_lossy(&data[1..end]);
         Ok(s.parse()?)
     }
-    fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
-        let end = extract_simple_frame_data(buf, Self::PREFIX)?;
-        Ok(end + CRLF_LEN)
-    }
 }
 
 #[cfg(test)]
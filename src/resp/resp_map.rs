@@ -1,8 +1,8 @@
 use crate::resp::{
-    calc_total_length, parse_length, RespDecode, RespEncode, RespError, RespFrame, SimpleString,
-    BUF_CAP, CRLF_LEN,
+    decode_aggregate, encode_map_entries, RespDecode, RespEncode, RespError, RespFrame, RespLimits,
+    BUF_CAP,
 };
-use bytes::{Buf, BytesMut};
+use bytes::BytesMut;
 use std::collections::BTreeMap;
 use std::ops::{Deref, DerefMut};
 
@@ -20,37 +20,18 @@ impl RespEncode for RespMap {
     fn encode(self) -> Vec<u8> {
         let mut buf = Vec::with_capacity(BUF_CAP);
         buf.extend_from_slice(&format!("%{}\r\n", self.len()).into_bytes());
-        for (key, value) in self.0 {
-            buf.extend_from_slice(&SimpleString::new(key).encode());
-            buf.extend_from_slice(&value.encode());
-        }
+        encode_map_entries(&mut buf, self, |frame| frame.encode());
         buf
     }
 }
 
 impl RespDecode for RespMap {
     const PREFIX: &'static str = "%";
-    fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
-        let (end, len) = parse_length(buf, Self::PREFIX)?;
-        let len = len as usize;
-        let total_len = calc_total_length(buf, end, len, Self::PREFIX)?;
-        if buf.len() < total_len {
-            return Err(RespError::NotComplete);
-        }
-        buf.advance(len + CRLF_LEN);
-
-        let mut map = RespMap::new();
-        for _ in 0..len {
-            let key = SimpleString::decode(buf)?;
-            let value = RespFrame::decode(buf)?;
-            map.insert(key.0, value);
-        }
-        Ok(map)
-    }
-    fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
-        let (end, len) = parse_length(buf, Self::PREFIX)?;
-        let len = len as usize;
-        calc_total_length(buf, end, len, Self::PREFIX)
+    fn decode_with_limits(buf: &mut BytesMut, limits: &RespLimits) -> Result<Self, RespError> {
+        decode_aggregate(buf, Self::PREFIX, limits, |frame| match frame {
+            RespFrame::Map(v) => Some(v),
+            _ => None,
+        })
     }
 }
 
@@ -105,6 +86,16 @@ mod test {
         map.insert("foo".to_string(), BulkString::new(b"bar".to_vec()).into());
         assert_eq!(frame, map);
 
+        // keys may also be sent as bulk strings, and the count may have several digits
+        let mut entries = b"%10\r\n".to_vec();
+        for i in 0..10 {
+            entries.extend_from_slice(format!("$4\r\nkey{}\r\n:{}\r\n", i, i).as_bytes());
+        }
+        buf.extend_from_slice(&entries);
+        let frame = RespMap::decode(&mut buf)?;
+        assert_eq!(frame.len(), 10);
+        assert_eq!(frame.get("key9"), Some(&RespFrame::Integer(9)));
+
         Ok(())
     }
 }
@@ -1,8 +1,16 @@
-use crate::resp::{extract_fixed_data, RespDecode, RespEncode, RespError};
+use crate::resp::{extract_fixed_data, RespDecode, RespEncode, RespError, RespLimits};
 use bytes::BytesMut;
 
 #[derive(Debug, Clone, PartialOrd, PartialEq, Eq)]
 pub struct RespNull;
+
+// the RESP2 nulls, RESP3 replaces both of them with RespNull
+#[derive(Debug, Clone, PartialOrd, PartialEq, Eq)]
+pub struct RespNullBulkString;
+
+#[derive(Debug, Clone, PartialOrd, PartialEq, Eq)]
+pub struct RespNullArray;
+
 // - null: "_\r\n"
 impl RespEncode for RespNull {
     fn encode(self) -> Vec<u8> {
@@ -10,22 +18,48 @@ impl RespEncode for RespNull {
     }
 }
 
+// - null bulk string: "$-1\r\n"
+impl RespEncode for RespNullBulkString {
+    fn encode(self) -> Vec<u8> {
+        b"$-1\r\n".to_vec()
+    }
+}
+
+// - null array: "*-1\r\n"
+impl RespEncode for RespNullArray {
+    fn encode(self) -> Vec<u8> {
+        b"*-1\r\n".to_vec()
+    }
+}
+
 impl RespDecode for RespNull {
     const PREFIX: &'static str = "_";
-    fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
+    fn decode_with_limits(buf: &mut BytesMut, _limits: &RespLimits) -> Result<Self, RespError> {
         extract_fixed_data(buf, "_\r\n", "Null")?;
         Ok(RespNull)
     }
+}
+
+impl RespDecode for RespNullBulkString {
+    const PREFIX: &'static str = "$";
+    fn decode_with_limits(buf: &mut BytesMut, _limits: &RespLimits) -> Result<Self, RespError> {
+        extract_fixed_data(buf, "$-1\r\n", "NullBulkString")?;
+        Ok(RespNullBulkString)
+    }
+}
 
-    fn expect_length(_buf: &[u8]) -> Result<usize, RespError> {
-        Ok(3)
+impl RespDecode for RespNullArray {
+    const PREFIX: &'static str = "*";
+    fn decode_with_limits(buf: &mut BytesMut, _limits: &RespLimits) -> Result<Self, RespError> {
+        extract_fixed_data(buf, "*-1\r\n", "NullArray")?;
+        Ok(RespNullArray)
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::resp::{RespFrame, RespNull};
+    use crate::resp::{RespFrame, RespNull, RespNullArray, RespNullBulkString};
     #[test]
     fn test_null_encode() {
         let frame: RespFrame = RespNull.into();
@@ -42,4 +76,18 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn test_resp2_nulls() -> anyhow::Result<()> {
+        let frame: RespFrame = RespNullBulkString.into();
+        assert_eq!(frame.encode(), b"$-1\r\n");
+        let frame: RespFrame = RespNullArray.into();
+        assert_eq!(frame.encode(), b"*-1\r\n");
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"$-1\r\n*-1\r\n");
+        assert_eq!(RespNullBulkString::decode(&mut buf)?, RespNullBulkString);
+        assert_eq!(RespNullArray::decode(&mut buf)?, RespNullArray);
+        Ok(())
+    }
 }
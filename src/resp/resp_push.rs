@@ -0,0 +1,79 @@
+use crate::resp::{
+    decode_aggregate, RespDecode, RespEncode, RespError, RespFrame, RespLimits, BUF_CAP,
+};
+use bytes::BytesMut;
+use std::ops::Deref;
+
+// out of band data sent by the server, e.g. pub/sub messages
+#[derive(Debug, Clone, PartialOrd, PartialEq)]
+pub struct RespPush(pub(crate) Vec<RespFrame>);
+
+impl RespPush {
+    pub fn new(v: impl Into<Vec<RespFrame>>) -> Self {
+        RespPush(v.into())
+    }
+}
+
+// - push: "><number-of-elements>\r\n<element-1>...<element-n>"
+impl RespEncode for RespPush {
+    fn encode(self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(BUF_CAP);
+        buf.extend_from_slice(&format!(">{}\r\n", self.len()).into_bytes());
+        for frame in self.0 {
+            buf.extend_from_slice(&frame.encode());
+        }
+        buf
+    }
+}
+
+impl RespDecode for RespPush {
+    const PREFIX: &'static str = ">";
+    fn decode_with_limits(buf: &mut BytesMut, limits: &RespLimits) -> Result<Self, RespError> {
+        decode_aggregate(buf, Self::PREFIX, limits, |frame| match frame {
+            RespFrame::Push(v) => Some(v),
+            _ => None,
+        })
+    }
+}
+
+impl Deref for RespPush {
+    type Target = Vec<RespFrame>;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::resp::BulkString;
+
+    #[test]
+    fn test_push_encode() {
+        let frame: RespFrame = RespPush::new([
+            BulkString::new("message").into(),
+            BulkString::new("channel").into(),
+            BulkString::new("hi").into(),
+        ])
+        .into();
+        assert_eq!(
+            frame.encode(),
+            b">3\r\n$7\r\nmessage\r\n$7\r\nchannel\r\n$2\r\nhi\r\n"
+        );
+    }
+
+    #[test]
+    fn test_push_decode() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b">2\r\n$7\r\nmessage\r\n:1\r\n");
+        let frame = RespPush::decode(&mut buf)?;
+        assert_eq!(
+            frame,
+            RespPush::new([BulkString::new("message").into(), 1.into()])
+        );
+
+        buf.extend_from_slice(b">2\r\n$7\r\nmessage\r\n");
+        assert_eq!(RespPush::decode(&mut buf), Err(RespError::NotComplete));
+        Ok(())
+    }
+}
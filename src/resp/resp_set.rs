@@ -1,8 +1,7 @@
 use crate::resp::{
-    calc_total_length, parse_length, RespDecode, RespEncode, RespError, RespFrame, BUF_CAP,
-    CRLF_LEN,
+    decode_aggregate, RespDecode, RespEncode, RespError, RespFrame, RespLimits, BUF_CAP,
 };
-use bytes::{Buf, BytesMut};
+use bytes::BytesMut;
 use std::ops::Deref;
 
 #[derive(Debug, Clone, PartialOrd, PartialEq)]
@@ -29,29 +28,11 @@ impl RespEncode for RespSet {
 
 impl RespDecode for RespSet {
     const PREFIX: &'static str = "~";
-    fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
-        let (end, len) = parse_length(buf, Self::PREFIX)?;
-        let len = len as usize;
-        let total_len = calc_total_length(buf, end, len, Self::PREFIX)?;
-
-        if buf.len() < total_len {
-            return Err(RespError::NotComplete);
-        }
-
-        buf.advance(end + CRLF_LEN);
-
-        let mut frames = Vec::new();
-        for _ in 0..len {
-            frames.push(RespFrame::decode(buf)?);
-        }
-
-        Ok(RespSet::new(frames))
-    }
-
-    fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
-        let (end, len) = parse_length(buf, Self::PREFIX)?;
-        let len = len as usize;
-        calc_total_length(buf, end, len, Self::PREFIX)
+    fn decode_with_limits(buf: &mut BytesMut, limits: &RespLimits) -> Result<Self, RespError> {
+        decode_aggregate(buf, Self::PREFIX, limits, |frame| match frame {
+            RespFrame::Set(v) => Some(v),
+            _ => None,
+        })
     }
 }
 
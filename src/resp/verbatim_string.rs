@@ -0,0 +1,74 @@
+use crate::resp::{decode_blob, RespDecode, RespEncode, RespError, RespLimits};
+use bytes::BytesMut;
+
+// a string meant to be shown as is, with a three letter format such as "txt" or "mkd"
+#[derive(Debug, Clone, PartialOrd, PartialEq, Eq)]
+pub struct VerbatimString {
+    pub(crate) format: [u8; 3],
+    pub(crate) data: Vec<u8>,
+}
+
+impl VerbatimString {
+    pub fn new(format: [u8; 3], data: impl Into<Vec<u8>>) -> Self {
+        VerbatimString {
+            format,
+            data: data.into(),
+        }
+    }
+
+    // splits "<format>:<data>"
+    pub(crate) fn from_payload(payload: &[u8]) -> Result<Self, RespError> {
+        if payload.len() < 4 || payload[3] != b':' {
+            return Err(RespError::InvalidFrame(
+                "verbatim string without format".to_string(),
+            ));
+        }
+        let format = [payload[0], payload[1], payload[2]];
+        Ok(VerbatimString::new(format, &payload[4..]))
+    }
+}
+
+// - verbatim string: "=<length>\r\n<format>:<data>\r\n", the length includes "<format>:"
+impl RespEncode for VerbatimString {
+    fn encode(self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(self.data.len() + 20);
+        buf.extend_from_slice(format!("={}\r\n", self.data.len() + 4).as_bytes());
+        buf.extend_from_slice(&self.format);
+        buf.push(b':');
+        buf.extend_from_slice(&self.data);
+        buf.extend_from_slice(b"\r\n");
+        buf
+    }
+}
+
+impl RespDecode for VerbatimString {
+    const PREFIX: &'static str = "=";
+    fn decode_with_limits(buf: &mut BytesMut, limits: &RespLimits) -> Result<Self, RespError> {
+        let data = decode_blob(buf, Self::PREFIX, limits)?;
+        VerbatimString::from_payload(&data)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::resp::RespFrame;
+
+    #[test]
+    fn test_verbatim_string_encode() {
+        let frame: RespFrame = VerbatimString::new(*b"txt", "Some string").into();
+        assert_eq!(frame.encode(), b"=15\r\ntxt:Some string\r\n");
+    }
+
+    #[test]
+    fn test_verbatim_string_decode() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"=15\r\ntxt:Some string\r\n");
+        let frame = VerbatimString::decode(&mut buf)?;
+        assert_eq!(frame, VerbatimString::new(*b"txt", "Some string"));
+
+        buf.extend_from_slice(b"=3\r\ntxt\r\n");
+        assert!(VerbatimString::decode(&mut buf).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,85 @@
+use crate::backend::{
+    active_expire_cycle, aof_fsync_cycle, replication_cycle, save_cycle, Backend,
+};
+use crate::cluster::{cluster_bus, cluster_cycle};
+use crate::cmd::load_aof;
+use crate::config::{AppendFsync, Config};
+use crate::network::stream_handle;
+use crate::replication::replicate;
+use anyhow::Result;
+use tokio::net::TcpListener;
+use tracing::{info, warn};
+
+// loads the data before binding, so no client ever sees a partially loaded keyspace
+pub async fn run(config: Config) -> Result<()> {
+    let backend = Backend::with_config(config.clone());
+    load(&backend)?;
+
+    let addr = format!("{}:{}", config.bind, config.port);
+    info!("[Simple-redis-server]listening on {}", addr);
+    let listener = TcpListener::bind(&addr).await?;
+
+    tokio::spawn(active_expire_cycle(backend.clone()));
+    tokio::spawn(save_cycle(backend.clone()));
+    if config.appendonly && config.appendfsync == AppendFsync::EverySec {
+        tokio::spawn(aof_fsync_cycle(backend.clone()));
+    }
+    tokio::spawn(replication_cycle(backend.clone()));
+    if config.replicaof.is_some() {
+        replicate(&backend, config.replicaof.clone());
+    }
+    if config.cluster_enabled {
+        let bus_addr = format!("{}:{}", config.bind, config.cluster_bus_port());
+        info!("[Simple-redis-server]cluster bus listening on {}", bus_addr);
+        tokio::spawn(cluster_bus(
+            TcpListener::bind(&bus_addr).await?,
+            backend.clone(),
+        ));
+        tokio::spawn(cluster_cycle(backend.clone()));
+    }
+    serve(listener, backend).await
+}
+
+pub async fn serve(listener: TcpListener, backend: Backend) -> Result<()> {
+    loop {
+        let (socket, raddr) = listener.accept().await?;
+        info!("[Simple-redis-server]accepted connection from {}", raddr);
+        let cloned_backend = backend.clone();
+        tokio::spawn(async move {
+            if let Err(e) = stream_handle(socket, cloned_backend).await {
+                warn!(
+                    "[Simple-redis-server]error processing connection from {}: {:?}",
+                    raddr, e
+                );
+            }
+        });
+    }
+}
+
+// the append-only file is the most complete copy, the snapshot is only used without it
+fn load(backend: &Backend) -> Result<()> {
+    let config = backend.config();
+    if config.appendonly && config.aof_path().exists() {
+        let commands = load_aof(backend)?;
+        info!(
+            "[Simple-redis-server]replayed {} commands from {}",
+            commands,
+            config.aof_path().display()
+        );
+    } else {
+        let loaded = backend.load_snapshot()?;
+        info!(
+            "[Simple-redis-server]loaded {} keys from {}",
+            loaded,
+            config.rdb_path().display()
+        );
+        if config.appendonly {
+            // seeds the new log with what the snapshot had
+            backend.rewrite_aof()?;
+        }
+    }
+    if config.appendonly {
+        backend.open_aof()?;
+    }
+    Ok(())
+}